@@ -85,15 +85,26 @@ bitfield! {
     pub has_nikumaru, set_nikumaru: 8; // 0x100 / 0256
     // for custom equips
     pub unused_1, set_unused_1: 9; // 0x200 / 0512
-    pub unused_2, set_unused_2: 10; // 0x400 / 1024
-    pub unused_3, set_unused_3: 11; // 0x800 / 2048
-    pub unused_4, set_unused_4: 12; // 0x1000 / 4096
-    pub unused_5, set_unused_5: 13; // 0x2000 / 8192
+    pub has_ground_slam, set_ground_slam: 10; // 0x400 / 1024
+    pub has_cape, set_cape: 11; // 0x800 / 2048
+    pub has_crouch, set_crouch: 12; // 0x1000 / 4096
+    pub has_guardian_charm, set_guardian_charm: 13; // 0x2000 / 8192
     // bit 14 and 15 aren't accessible via TSC without abusing overflows (won't work in strict mode)
     pub unused_6, set_unused_6: 14; // 0x4000 / @384
     pub unused_7, set_unused_7: 15; // 0x8000 / P768
 }
 
+bitfield! {
+    /// Player abilities set with `<AB+`/`<AB-`, saved in the upper half of the profile equipment field.
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PlayerAbility(u16);
+    impl Debug;
+
+    pub has_ceiling_walk, set_ceiling_walk: 0; // 0x01 / 0001
+    pub has_double_jump, set_double_jump: 1; // 0x02 / 0002
+}
+
 bitfield! {
     #[derive(Clone, Copy)]
     #[repr(C)]
@@ -149,7 +160,8 @@ bitfield! {
     pub check_block_hit, set_check_block_hit: 5;
     /// Corresponds to flag & 0x40. If set, bullet will destroy snack blocks on hit.
     pub can_destroy_snack, set_can_destroy_snack: 6;
-    pub flag_x80, set_flag_x80: 7; // 0x80, nowhere in code?
+    /// Corresponds to flag & 0x80. Unused by vanilla data, if set, bullet is not destroyed by colliding with other bullets.
+    pub deflect_immune, set_deflect_immune: 7;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -194,7 +206,8 @@ pub enum FadeState {
     FadeOut(i8, FadeDirection),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 #[repr(u8)]
 pub enum Direction {
     Left = 0,
@@ -393,6 +406,17 @@ pub fn interpolate_fix9_scale(old_val: i32, val: i32, frame_delta: f64) -> f32 {
     }
 }
 
+/// Turns `angle` towards `target` (both in radians) by at most `speed`, taking the shorter way around.
+/// The result is normalized to the -PI..PI range.
+pub fn approach_angle(angle: f32, target: f32, speed: f32) -> f32 {
+    use std::f32::consts::{PI, TAU};
+
+    let diff = (target - angle + PI).rem_euclid(TAU) - PI;
+    let angle = angle + diff.signum() * speed.min(diff.abs());
+
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
 pub fn get_timestamp() -> u64 {
     let now = SystemTime::now();
     now.duration_since(UNIX_EPOCH).unwrap().as_secs() as u64
@@ -566,3 +590,16 @@ impl<T> SliceExt for [T] {
         }
     }
 }
+
+#[test]
+fn test_approach_angle() {
+    use std::f32::consts::PI;
+
+    let approx_eq = |a: f32, b: f32| (a - b).abs() < 0.0001;
+
+    assert!(approx_eq(approach_angle(0.0, 1.0, 0.25), 0.25));
+    assert!(approx_eq(approach_angle(0.0, 0.1, 0.25), 0.1));
+    assert!(approx_eq(approach_angle(0.5, -1.0, 0.25), 0.25));
+    // crosses the -PI/PI boundary instead of turning all the way around
+    assert!(approx_eq(approach_angle(PI - 0.1, -PI + 0.1, 0.3), -PI + 0.1));
+}
@@ -44,6 +44,11 @@ impl BossLifeBar {
         self.prev_life = self.life;
     }
 
+    /// Returns true if a boss fight is in progress.
+    pub fn is_active(&self) -> bool {
+        self.max_life != 0 && self.target != BossLifeTarget::None
+    }
+
     fn draw_regular(&self, state: &mut SharedGameState, ctx: &mut Context, _frame: &Frame) -> GameResult {
         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
 
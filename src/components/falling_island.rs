@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use crate::common::{Color, Rect};
+use crate::common::{interpolate_fix9_scale, Color, Rect};
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
@@ -9,23 +9,41 @@ use crate::game::frame::Frame;
 use crate::game::shared_game_state::SharedGameState;
 use crate::game::scripting::tsc::text_script::TextScriptExecutionState;
 
-pub struct FallingIsland {}
+pub struct FallingIsland {
+    /// Island position from the previous and current tick, used for interpolation.
+    positions: Option<((i32, i32), (i32, i32))>,
+}
 
 impl FallingIsland {
     pub fn new() -> FallingIsland {
-        FallingIsland {}
+        FallingIsland { positions: None }
     }
 }
 
 impl GameEntity<()> for FallingIsland {
-    fn tick(&mut self, _state: &mut SharedGameState, _custom: ()) -> GameResult {
+    fn tick(&mut self, state: &mut SharedGameState, _custom: ()) -> GameResult {
+        self.positions =
+            if let TextScriptExecutionState::FallingIsland(_, _, pos_x, pos_y, _, _) = state.textscript_vm.state {
+                let prev_pos = self.positions.map_or((pos_x, pos_y), |(_, pos)| pos);
+
+                Some((prev_pos, (pos_x, pos_y)))
+            } else {
+                None
+            };
+
         Ok(())
     }
 
     fn draw(&self, state: &mut SharedGameState, ctx: &mut Context, _frame: &Frame) -> GameResult {
         let (pos_x, pos_y) =
             if let TextScriptExecutionState::FallingIsland(_, _, pos_x, pos_y, _, _) = state.textscript_vm.state {
-                (pos_x, pos_y)
+                match self.positions {
+                    Some(((prev_x, prev_y), (x, y))) => (
+                        interpolate_fix9_scale(prev_x, x, state.frame_time),
+                        interpolate_fix9_scale(prev_y, y, state.frame_time),
+                    ),
+                    None => (pos_x as f32 / 512.0, pos_y as f32 / 512.0),
+                }
             } else {
                 return Ok(());
             };
@@ -51,7 +69,7 @@ impl GameEntity<()> for FallingIsland {
             &state.npc_table.stage_textures.deref().borrow().npc1,
         )?;
         batch.add_rect(off_x + 80.0, 80.0, &RECT_BG);
-        batch.add_rect(off_x + (pos_x as f32 / 512.0) - 20.0, (pos_y as f32 / 512.0) - 12.0, &RECT_ISLAND);
+        batch.add_rect(off_x + pos_x - 20.0, pos_y - 12.0, &RECT_ISLAND);
         batch.add_rect(off_x + 80.0, 128.0, &RECT_TERRAIN);
         batch.draw(ctx)?;
 
@@ -1,8 +1,9 @@
-use crate::common::Rect;
+use crate::common::{Color, Rect};
 use crate::components::draw_common::{Alignment, draw_number};
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
+use crate::framework::graphics;
 use crate::framework::graphics::screen_insets_scaled;
 use crate::game::frame::Frame;
 use crate::game::inventory::Inventory;
@@ -10,6 +11,9 @@ use crate::game::shared_game_state::SharedGameState;
 use crate::game::player::Player;
 use crate::game::weapon::WeaponType;
 
+/// How long the status bars stay visible after a change with `Settings::minimal_hud` on.
+const MINIMAL_HUD_DISPLAY_TICKS: u16 = 150;
+
 pub struct HUD {
     pub alignment: Alignment,
     pub weapon_x_pos: usize,
@@ -21,6 +25,11 @@ pub struct HUD {
     max_xp: u16,
     xp_bar_counter: u8,
     max_level: bool,
+    charge_counter: u8,
+    heat: Option<(f32, bool)>,
+    heat_counter: u8,
+    /// Remaining booster fuel as a fraction of a full tank, only set while it isn't full.
+    booster_fuel: Option<f32>,
     life: u16,
     max_life: u16,
     life_bar: u16,
@@ -32,6 +41,7 @@ pub struct HUD {
     current_weapon: isize,
     weapon_types: [u8; 16],
     shock: bool,
+    display_timer: u16,
 }
 
 impl HUD {
@@ -47,6 +57,10 @@ impl HUD {
             max_xp: 0,
             xp_bar_counter: 0,
             max_level: false,
+            charge_counter: 0,
+            heat: None,
+            heat_counter: 0,
+            booster_fuel: None,
             life: 0,
             max_life: 0,
             life_bar: 0,
@@ -58,6 +72,7 @@ impl HUD {
             current_weapon: 0,
             weapon_types: [0; 16],
             shock: false,
+            display_timer: 0,
         }
     }
 }
@@ -66,6 +81,7 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
     fn tick(&mut self, state: &mut SharedGameState, (player, inventory): (&Player, &mut Inventory)) -> GameResult {
         let (ammo, max_ammo) = inventory.get_current_ammo();
         let (xp, max_xp, max_level) = inventory.get_current_max_exp(&state.constants);
+        let status = (self.life, self.max_life, self.xp, self.current_level, self.ammo, self.current_weapon);
 
         self.ammo = ammo;
         self.max_ammo = max_ammo;
@@ -73,12 +89,28 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
         self.max_xp = max_xp;
         self.xp_bar_counter = if player.xp_counter != 0 { self.xp_bar_counter.wrapping_add(1) } else { 0 };
         self.max_level = max_level;
+        self.charge_counter = if inventory.is_current_weapon_charged(&state.constants) {
+            self.charge_counter.wrapping_add(1)
+        } else {
+            0
+        };
+
+        self.heat = inventory.get_current_heat(&state.constants);
+        self.heat_counter = self.heat_counter.wrapping_add(1);
+
+        let max_fuel = state.constants.booster.fuel;
+        let has_booster = player.equip.has_booster_0_8() || player.equip.has_booster_2_0();
+        self.booster_fuel = if state.settings.booster_gauge && has_booster && player.booster_fuel < max_fuel {
+            Some(player.booster_fuel as f32 / max_fuel as f32)
+        } else {
+            None
+        };
 
         self.life = player.life;
         self.max_life = player.max_life;
         self.air = player.air;
         self.air_counter = player.air_counter;
-        self.shock = player.shock_counter / 2 % 2 != 0;
+        self.shock = player.is_shock_blink_hidden(state);
         self.weapon_count = inventory.get_weapon_count();
         self.current_weapon = inventory.get_current_weapon_idx() as isize;
 
@@ -102,6 +134,13 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
             self.life_bar_counter = 0;
         }
 
+        let new_status = (self.life, self.max_life, self.xp, self.current_level, self.ammo, self.current_weapon);
+        if status != new_status || self.life_bar != self.life || self.heat.map_or(false, |(heat, _)| heat > 0.0) {
+            self.display_timer = MINIMAL_HUD_DISPLAY_TICKS;
+        } else {
+            self.display_timer = self.display_timer.saturating_sub(1);
+        }
+
         if self.weapon_x_pos > 16 {
             self.weapon_x_pos -= 2;
         } else if self.weapon_x_pos < 16 {
@@ -181,6 +220,7 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
         }
 
         let (left, top, right, bottom) = screen_insets_scaled(ctx, state.scale);
+        let show_status = !state.settings.minimal_hud || self.display_timer > 0;
 
         // none
         let weap_x = self.weapon_x_pos as f32;
@@ -203,12 +243,12 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
             0.0
         };
 
-        if self.max_ammo == 0 {
+        if show_status && self.max_ammo == 0 {
             batch.add_rect(bar_offset + weap_x + 48.0, 16.0 + top, &Rect::new_size(80, 48, 16, 8));
             batch.add_rect(bar_offset + weap_x + 48.0, 24.0 + top, &Rect::new_size(80, 48, 16, 8));
         }
 
-        if !self.shock {
+        if show_status && !self.shock {
             // per
             batch.add_rect(bar_offset + weap_x + 32.0, 24.0 + top, &Rect::new_size(72, 48, 8, 8));
             // lv
@@ -216,7 +256,10 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
             // xp box
             batch.add_rect(bar_offset + weap_x + 24.0, 32.0 + top, &Rect::new_size(0, 72, 40, 8));
 
-            if self.max_level {
+            if self.max_level && (self.charge_counter & 0x04) != 0 {
+                // fully charged spur flashes
+                batch.add_rect(bar_offset + weap_x + 24.0, 32.0 + top, &Rect::new_size(40, 80, 40, 8));
+            } else if self.max_level {
                 batch.add_rect(bar_offset + weap_x + 24.0, 32.0 + top, &Rect::new_size(40, 72, 40, 8));
             } else if self.max_xp > 0 {
                 // xp bar
@@ -257,7 +300,7 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
         batch.draw(ctx)?;
         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "ArmsImage")?;
 
-        if self.weapon_count != 0 {
+        if show_status && self.weapon_count != 0 {
             let mut rect = Rect::new(0, 0, 0, 16);
 
             // First frame of animation is off by one weapon
@@ -300,6 +343,35 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
 
         batch.draw(ctx)?;
 
+        if let Some((heat, overheated)) = self.heat.filter(|_| show_status) {
+            // heat gauge along the bottom edge of the weapon icon
+            if !overheated || (self.heat_counter & 0x04) != 0 {
+                let color =
+                    if overheated || heat > 0.75 { Color::from_rgb(224, 32, 32) } else { Color::from_rgb(32, 96, 224) };
+                let gauge_rect = Rect::new_size(
+                    ((weapon_offset + weap_x) * state.scale) as isize,
+                    ((30.0 + top) * state.scale) as isize,
+                    (heat * 16.0 * state.scale) as isize,
+                    (2.0 * state.scale) as isize,
+                );
+
+                graphics::draw_rect(ctx, gauge_rect, color)?;
+            }
+        }
+
+        if let Some(fuel) = self.booster_fuel.filter(|_| self.max_life != 0) {
+            // fuel gauge right below the life bar
+            let color = if fuel < 0.25 { Color::from_rgb(224, 32, 32) } else { Color::from_rgb(224, 160, 32) };
+            let gauge_rect = Rect::new_size(
+                ((bar_offset + 40.0) * state.scale) as isize,
+                ((48.0 + top) * state.scale) as isize,
+                (fuel * 39.0 * state.scale) as isize,
+                (2.0 * state.scale) as isize,
+            );
+
+            graphics::draw_rect(ctx, gauge_rect, color)?;
+        }
+
         if self.air_counter > 0 && self.air_counter % 6 < 4 {
             draw_number(
                 left + ((state.canvas_size.0 - left - right) / 2.0).floor() + 8.0 + air_offset,
@@ -311,11 +383,11 @@ impl GameEntity<(&Player, &mut Inventory)> for HUD {
             )?;
         }
 
-        if self.max_ammo != 0 {
+        if show_status && self.max_ammo != 0 {
             draw_number(bar_offset + weap_x + 64.0, 16.0 + top, self.ammo as usize, Alignment::Right, state, ctx)?;
             draw_number(bar_offset + weap_x + 64.0, 24.0 + top, self.max_ammo as usize, Alignment::Right, state, ctx)?;
         }
-        if !self.shock {
+        if show_status && !self.shock {
             draw_number(num_offset + weap_x + 24.0, 32.0 + top, self.current_level, Alignment::Right, state, ctx)?;
             draw_number(num_offset + 40.0, 40.0 + top, self.life_bar as usize, Alignment::Right, state, ctx)?;
         }
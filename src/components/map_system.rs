@@ -40,6 +40,15 @@ impl MapSystem {
         }
     }
 
+    /// Returns the factor by which the map has to be shrunk to fit on the screen, 1 tile is 1 pixel otherwise.
+    fn fit_scale(state: &SharedGameState, stage: &Stage) -> f32 {
+        // leave space for the border and the map name bar
+        let max_width = state.canvas_size.0 - 16.0;
+        let max_height = state.canvas_size.1 - 2.0 * (state.font.line_height() + 16.0);
+
+        (max_width / stage.map.width.max(1) as f32).min(max_height / stage.map.height.max(1) as f32).clamp(0.0, 1.0)
+    }
+
     fn render_map(&self, state: &mut SharedGameState, ctx: &mut Context, stage: &Stage) -> GameResult {
         if self.texture.borrow().is_none() {
             *self.has_map_data.borrow_mut() = false;
@@ -212,11 +221,13 @@ impl MapSystem {
         )?;
 
         let mut map_rect = Rect::new(0.0, 0.0, self.last_size.0 as f32, self.last_size.1 as f32);
+        let fit = Self::fit_scale(state, stage);
+        let (map_width, map_height) = (stage.map.width as f32 * fit, stage.map.height as f32 * fit);
 
         match self.state {
             MapSystemState::FadeInBox(tick) | MapSystemState::FadeOutBox(tick) => {
-                let width = (state.scale * tick as f32 * stage.map.width as f32 / 16.0) as isize;
-                let height = (state.scale * tick as f32 * stage.map.height as f32 / 16.0) as isize;
+                let width = (state.scale * tick as f32 * map_width / 16.0) as isize;
+                let height = (state.scale * tick as f32 * map_height / 16.0) as isize;
 
                 let rect = Rect::new_size(
                     (scr_w / 2.0) as isize - width,
@@ -235,8 +246,8 @@ impl MapSystem {
             _ => (),
         }
 
-        let width_border = state.scale * (stage.map.width as f32 + 2.0);
-        let height_border = state.scale * (stage.map.height as f32 + 2.0);
+        let width_border = state.scale * (map_width + 2.0);
+        let height_border = state.scale * (map_height + 2.0);
 
         let rect = Rect::new_size(
             ((scr_w - width_border) / 2.0) as isize,
@@ -248,13 +259,18 @@ impl MapSystem {
         graphics::draw_rect(ctx, rect, Color::new(0.0, 0.0, 0.0, 1.0))?;
 
         if let Some(tex) = self.texture.borrow_mut().as_mut() {
-            let width = state.scale * stage.map.width as f32;
-            let height = state.scale * stage.map.height as f32;
+            let width = state.scale * map_width;
+            let height = state.scale * map_height;
 
             tex.clear();
             tex.add(SpriteBatchCommand::DrawRect(
                 map_rect,
-                Rect::new_size((scr_w - width) / 2.0, (scr_h - height) / 2.0, map_rect.width(), map_rect.height()),
+                Rect::new_size(
+                    (scr_w - width) / 2.0,
+                    (scr_h - height) / 2.0,
+                    map_rect.width() * fit,
+                    map_rect.height() * fit,
+                ),
             ));
             tex.draw()?;
         }
@@ -263,8 +279,8 @@ impl MapSystem {
             const PLAYER_RECT: Rect<u16> = Rect { left: 0, top: 57, right: 1, bottom: 58 };
 
             let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
-            let x_offset = (state.canvas_size.0 - stage.map.width as f32) / 2.0;
-            let y_offset = (state.canvas_size.1 - stage.map.height as f32) / 2.0;
+            let x_offset = (state.canvas_size.0 - map_width) / 2.0;
+            let y_offset = (state.canvas_size.1 - map_height) / 2.0;
             let tile_div = stage.map.tile_size.as_int() * 0x200;
 
             for player in &players {
@@ -272,8 +288,8 @@ impl MapSystem {
                     continue;
                 }
 
-                let plr_x = x_offset + (player.x / tile_div) as f32;
-                let plr_y = y_offset + (player.y / tile_div) as f32;
+                let plr_x = x_offset + (player.x / tile_div) as f32 * fit;
+                let plr_y = y_offset + (player.y / tile_div) as f32 * fit;
 
                 batch.add_rect(plr_x, plr_y, &PLAYER_RECT);
             }
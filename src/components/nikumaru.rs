@@ -1,4 +1,4 @@
-use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write};
 
 use crate::common::Rect;
 use crate::components::draw_common::{Alignment, draw_number, draw_number_zeros};
@@ -13,6 +13,9 @@ use crate::game::player::Player;
 use crate::game::scripting::tsc::text_script::TextScriptExecutionState;
 use crate::util::rng::RNG;
 
+/// Size of a 290.rec file.
+const REC_SIZE: usize = 20;
+
 #[derive(Clone, Copy)]
 pub struct NikumaruCounter {
     pub tick: usize,
@@ -24,29 +27,59 @@ impl NikumaruCounter {
         NikumaruCounter { tick: 0, shown: false }
     }
 
-    fn load_time(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult<u32> {
-        if let Ok(mut data) = filesystem::user_open(ctx, [state.get_rec_filename(), ".rec".to_string()].join("")) {
-            let mut ticks: [u32; 4] = [0; 4];
+    /// Decodes a 290.rec file, returning `None` if its copies of the time don't match.
+    ///
+    /// The record holds four copies of the time, each byte offset by one of the four keys stored after them
+    /// (the last byte by half of it).
+    pub fn decode_record(data: &[u8; REC_SIZE]) -> Option<u32> {
+        let keys = &data[16..20];
+        let mut times = [0u32; 4];
+
+        for (iter, time) in times.iter_mut().enumerate() {
+            let bytes = &data[iter * 4..iter * 4 + 4];
+            let key = keys[iter];
+
+            *time = u32::from_le_bytes([
+                bytes[0].wrapping_sub(key),
+                bytes[1].wrapping_sub(key),
+                bytes[2].wrapping_sub(key),
+                bytes[3].wrapping_sub(key / 2),
+            ]);
+        }
 
-            for iter in 0..=3 {
-                ticks[iter] = data.read_u32::<LE>()?;
-            }
+        if times.iter().all(|&time| time == times[0]) {
+            Some(times[0])
+        } else {
+            None
+        }
+    }
 
-            let random = data.read_u32::<LE>()?;
-            let random_list: [u8; 4] = random.to_le_bytes();
+    /// Encodes a time in the format used by 290.rec, see `decode_record`.
+    pub fn encode_record(time: u32, keys: [u8; 4]) -> [u8; REC_SIZE] {
+        let mut data = [0u8; REC_SIZE];
+        let bytes = time.to_le_bytes();
 
-            for iter in 0..=3 {
-                ticks[iter] = u32::from_le_bytes([
-                    ticks[iter].to_le_bytes()[0].wrapping_sub(random_list[iter]),
-                    ticks[iter].to_le_bytes()[1].wrapping_sub(random_list[iter]),
-                    ticks[iter].to_le_bytes()[2].wrapping_sub(random_list[iter]),
-                    ticks[iter].to_le_bytes()[3].wrapping_sub(random_list[iter] / 2),
-                ]);
-            }
+        for (iter, &key) in keys.iter().enumerate() {
+            data[iter * 4] = bytes[0].wrapping_add(key);
+            data[iter * 4 + 1] = bytes[1].wrapping_add(key);
+            data[iter * 4 + 2] = bytes[2].wrapping_add(key);
+            data[iter * 4 + 3] = bytes[3].wrapping_add(key / 2);
+        }
+        data[16..20].copy_from_slice(&keys);
+
+        data
+    }
+
+    fn load_time(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult<u32> {
+        if let Ok(mut file) = filesystem::user_open(ctx, [state.get_rec_filename(), ".rec".to_string()].join("")) {
+            let mut data = [0u8; REC_SIZE];
+            file.read_exact(&mut data)?;
 
-            if ticks[0] == ticks[1] && ticks[0] == ticks[2] {
-                return Ok(ticks[0]);
+            if let Some(time) = Self::decode_record(&data) {
+                return Ok(time);
             }
+
+            log::warn!("290 record is corrupted, ignoring it.");
         } else {
             log::warn!("Failed to open 290 record.");
         }
@@ -54,28 +87,17 @@ impl NikumaruCounter {
     }
 
     fn save_time(&mut self, new_time: u32, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
-        if let Ok(mut data) = filesystem::open_options(
+        if let Ok(mut file) = filesystem::open_options(
             ctx,
             [state.get_rec_filename(), ".rec".to_string()].join(""),
             OpenOptions::new().write(true).create(true),
         ) {
-            let mut ticks: [u32; 4] = [new_time; 4];
-            let mut random_list: [u8; 4] = [0; 4];
-
-            for iter in 0..=3 {
-                random_list[iter] = state.effect_rng.range(0..250) as u8 + iter as u8;
-
-                ticks[iter] = u32::from_le_bytes([
-                    ticks[iter].to_le_bytes()[0].wrapping_add(random_list[iter]),
-                    ticks[iter].to_le_bytes()[1].wrapping_add(random_list[iter]),
-                    ticks[iter].to_le_bytes()[2].wrapping_add(random_list[iter]),
-                    ticks[iter].to_le_bytes()[3].wrapping_add(random_list[iter] / 2),
-                ]);
-
-                data.write_u32::<LE>(ticks[iter])?;
+            let mut keys = [0u8; 4];
+            for (iter, key) in keys.iter_mut().enumerate() {
+                *key = state.effect_rng.range(0..250) as u8 + iter as u8;
             }
 
-            data.write_u32::<LE>(u32::from_le_bytes(random_list))?;
+            file.write_all(&Self::encode_record(new_time, keys))?;
         } else {
             log::warn!("Failed to write 290 record.");
         }
@@ -164,3 +186,31 @@ impl GameEntity<&Player> for NikumaruCounter {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1234 ticks, encoded with keys 10, 21, 32 and 43 the same way the original game does it
+    const RECORD: [u8; REC_SIZE] = [
+        0xdc, 0x0e, 0x0a, 0x05, 0xe7, 0x19, 0x15, 0x0a, 0xf2, 0x24, 0x20, 0x10, 0xfd, 0x2f, 0x2b, 0x15, 0x0a, 0x15,
+        0x20, 0x2b,
+    ];
+
+    #[test]
+    fn test_record_round_trip() {
+        assert_eq!(NikumaruCounter::encode_record(1234, [10, 21, 32, 43]), RECORD);
+        assert_eq!(NikumaruCounter::decode_record(&RECORD), Some(1234));
+
+        let record = NikumaruCounter::encode_record(299999, [249, 250, 251, 252]);
+        assert_eq!(NikumaruCounter::decode_record(&record), Some(299999));
+    }
+
+    #[test]
+    fn test_corrupted_record() {
+        let mut record = RECORD;
+        record[13] ^= 1;
+
+        assert_eq!(NikumaruCounter::decode_record(&record), None);
+    }
+}
@@ -12,13 +12,28 @@ pub struct NumberPopup {
     pub y: i32,
     pub prev_x: i32,
     pub prev_y: i32,
+    /// Tint applied to the digits, white keeps them as they are in the texture.
+    pub color: (u8, u8, u8, u8),
     counter: u16,
     value_display: i16,
 }
 
 impl NumberPopup {
     pub fn new() -> NumberPopup {
-        NumberPopup { value: 0, x: 0, y: 0, prev_x: 0, prev_y: 0, counter: 0, value_display: 0 }
+        NumberPopup {
+            value: 0,
+            x: 0,
+            y: 0,
+            prev_x: 0,
+            prev_y: 0,
+            color: (255, 255, 255, 255),
+            counter: 0,
+            value_display: 0,
+        }
+    }
+
+    pub fn with_color(color: (u8, u8, u8, u8)) -> NumberPopup {
+        NumberPopup { color, ..NumberPopup::new() }
     }
 
     pub fn set_value(&mut self, value: i16) {
@@ -75,24 +90,26 @@ impl GameEntity<()> for NumberPopup {
 
         let x = x - n.len() as f32 * 4.0;
 
+        if x + n.len() as f32 * 8.0 < 0.0 || x > state.canvas_size.0 || y + 8.0 < 0.0 || y > state.canvas_size.1 {
+            return Ok(());
+        }
+
         for (offset, chr) in n.chars().enumerate() {
-            match chr {
-                '+' => {
-                    batch.add_rect(x + offset as f32 * 8.0, y, &Rect::new_size(32, 48 + clip, 8, 8 - clip));
-                }
-                '-' => {
-                    batch.add_rect(x + offset as f32 * 8.0, y, &Rect::new_size(40, 48 + clip, 8, 8 - clip));
-                }
+            let rect = match chr {
+                '+' => Rect::new_size(32, 48 + clip, 8, 8 - clip),
+                '-' => Rect::new_size(40, 48 + clip, 8, 8 - clip),
                 '0'..='9' => {
                     let number_set = if self.value_display < 0 { 64 } else { 56 };
                     let idx = chr as u16 - '0' as u16;
-                    batch.add_rect(
-                        x + offset as f32 * 8.0,
-                        y,
-                        &Rect::new_size(idx * 8, number_set + clip, 8, 8 - clip),
-                    );
+                    Rect::new_size(idx * 8, number_set + clip, 8, 8 - clip)
                 }
-                _ => {}
+                _ => continue,
+            };
+
+            if self.color == (255, 255, 255, 255) {
+                batch.add_rect(x + offset as f32 * 8.0, y, &rect);
+            } else {
+                batch.add_rect_tinted(x + offset as f32 * 8.0, y, self.color, &rect);
             }
         }
 
@@ -0,0 +1,296 @@
+use std::cell::{Cell, RefCell};
+
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+
+use crate::common::{Color, Rect};
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::graphics::BlendMode;
+use crate::framework::keyboard::ScanCode;
+use crate::framework::{filesystem, graphics, keyboard};
+use crate::game::frame::Frame;
+use crate::game::shared_game_state::SharedGameState;
+use crate::graphics::font::Font;
+use crate::input::player_controller::PlayerController;
+
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 4.0;
+/// Zoom change per tick while a zoom key is held.
+const ZOOM_SPEED: f32 = 1.02;
+/// Camera movement per tick at 1x zoom, in fixed point units.
+const CAMERA_SPEED: f32 = 3.0 * 512.0;
+const MESSAGE_TICKS: u16 = 150;
+const VIGNETTE_BANDS: isize = 12;
+const SCREENSHOTS_DIR: &str = "/Screenshots";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhotoFilter {
+    None,
+    Sepia,
+    Vignette,
+}
+
+impl PhotoFilter {
+    const ALL: [PhotoFilter; 3] = [PhotoFilter::None, PhotoFilter::Sepia, PhotoFilter::Vignette];
+
+    fn cycle(self, delta: isize) -> PhotoFilter {
+        let idx = Self::ALL.iter().position(|&f| f == self).unwrap_or(0) as isize;
+        let len = Self::ALL.len() as isize;
+
+        Self::ALL[(idx + delta).rem_euclid(len) as usize]
+    }
+
+    fn locale_key(self) -> &'static str {
+        match self {
+            PhotoFilter::None => "menus.photo_mode.filter_none",
+            PhotoFilter::Sepia => "menus.photo_mode.filter_sepia",
+            PhotoFilter::Vignette => "menus.photo_mode.filter_vignette",
+        }
+    }
+}
+
+/// Freezes the game and lets the player move the camera around to take screenshots.
+pub struct PhotoMode {
+    active: bool,
+    /// Frame position at the moment photo mode was entered, restored when leaving it.
+    origin: (i32, i32),
+    /// Offset of the camera center from the original one, in fixed point units.
+    pub camera_offset: (i32, i32),
+    pub zoom: f32,
+    pub filter: PhotoFilter,
+    /// Canvas scale outside of photo mode, zoom is applied on top of it.
+    base_scale: f32,
+    filter_keys: (bool, bool),
+    screenshot_requested: Cell<bool>,
+    message: RefCell<Option<(String, u16)>>,
+}
+
+impl PhotoMode {
+    pub fn new() -> PhotoMode {
+        PhotoMode {
+            active: false,
+            origin: (0, 0),
+            camera_offset: (0, 0),
+            zoom: 1.0,
+            filter: PhotoFilter::None,
+            base_scale: 1.0,
+            filter_keys: (false, false),
+            screenshot_requested: Cell::new(false),
+            message: RefCell::new(None),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns true if both weapon switch buttons (L+R on a gamepad) are held and one of them was just pressed.
+    pub fn toggle_triggered(controller: &dyn PlayerController) -> bool {
+        controller.prev_weapon()
+            && controller.next_weapon()
+            && (controller.trigger_prev_weapon() || controller.trigger_next_weapon())
+    }
+
+    pub fn enter(&mut self, state: &mut SharedGameState, frame: &Frame) {
+        self.active = true;
+        self.origin = (frame.x, frame.y);
+        self.camera_offset = (0, 0);
+        self.zoom = 1.0;
+        self.filter = PhotoFilter::None;
+        self.base_scale = state.scale;
+        self.filter_keys = (true, true);
+        self.message.replace(None);
+
+        state.sound_manager.play_sfx(18);
+    }
+
+    pub fn exit(&mut self, state: &mut SharedGameState, ctx: &mut Context, frame: &mut Frame) -> GameResult {
+        self.active = false;
+
+        frame.x = self.origin.0;
+        frame.y = self.origin.1;
+        frame.prev_x = frame.x;
+        frame.prev_y = frame.y;
+
+        state.sound_manager.play_sfx(5);
+        state.handle_resize(ctx)
+    }
+
+    pub fn tick(
+        &mut self,
+        state: &mut SharedGameState,
+        ctx: &mut Context,
+        frame: &mut Frame,
+        controller: &dyn PlayerController,
+    ) -> GameResult {
+        if let Some((_, ticks)) = self.message.get_mut() {
+            *ticks = ticks.saturating_sub(1);
+        }
+        if matches!(*self.message.get_mut(), Some((_, 0))) {
+            self.message.replace(None);
+        }
+
+        let key = |code| keyboard::is_key_pressed(ctx, code);
+        let camera_keys = [key(ScanCode::W), key(ScanCode::A), key(ScanCode::S), key(ScanCode::D)];
+        let zoom_keys = (key(ScanCode::Q), key(ScanCode::E));
+        let filter_keys = (key(ScanCode::F), key(ScanCode::G));
+
+        // A and S are the default weapon switch keys, so don't treat walking diagonally as the exit combo.
+        let toggled = Self::toggle_triggered(controller) && !camera_keys.contains(&true);
+        if toggled || controller.trigger_menu_pause() {
+            return self.exit(state, ctx, frame);
+        }
+
+        let speed = (CAMERA_SPEED / self.zoom) as i32;
+        if controller.move_up() || camera_keys[0] {
+            self.camera_offset.1 -= speed;
+        }
+        if controller.move_left() || camera_keys[1] {
+            self.camera_offset.0 -= speed;
+        }
+        if controller.move_down() || camera_keys[2] {
+            self.camera_offset.1 += speed;
+        }
+        if controller.move_right() || camera_keys[3] {
+            self.camera_offset.0 += speed;
+        }
+
+        if zoom_keys.0 {
+            self.zoom = (self.zoom / ZOOM_SPEED).max(MIN_ZOOM);
+        }
+        if zoom_keys.1 {
+            self.zoom = (self.zoom * ZOOM_SPEED).min(MAX_ZOOM);
+        }
+
+        if filter_keys.0 && !self.filter_keys.0 {
+            self.filter = self.filter.cycle(-1);
+            state.sound_manager.play_sfx(1);
+        }
+        if filter_keys.1 && !self.filter_keys.1 {
+            self.filter = self.filter.cycle(1);
+            state.sound_manager.play_sfx(1);
+        }
+        self.filter_keys = filter_keys;
+
+        if controller.trigger_shoot() {
+            self.screenshot_requested.set(true);
+        }
+
+        self.apply_camera(state, frame);
+
+        Ok(())
+    }
+
+    /// Scales the canvas by current zoom and centers the frame on the camera.
+    fn apply_camera(&self, state: &mut SharedGameState, frame: &mut Frame) {
+        let base_canvas_size = (state.screen_size.0 / self.base_scale, state.screen_size.1 / self.base_scale);
+
+        state.scale = self.base_scale * self.zoom;
+        state.canvas_size = (state.screen_size.0 / state.scale, state.screen_size.1 / state.scale);
+
+        let center_x = self.origin.0 + (base_canvas_size.0 * 256.0) as i32 + self.camera_offset.0;
+        let center_y = self.origin.1 + (base_canvas_size.1 * 256.0) as i32 + self.camera_offset.1;
+
+        frame.x = center_x - (state.canvas_size.0 * 256.0) as i32;
+        frame.y = center_y - (state.canvas_size.1 * 256.0) as i32;
+        frame.prev_x = frame.x;
+        frame.prev_y = frame.y;
+    }
+
+    fn draw_filter(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let (width, height) = (state.screen_size.0 as isize, state.screen_size.1 as isize);
+
+        match self.filter {
+            PhotoFilter::None => (),
+            PhotoFilter::Sepia => {
+                graphics::set_blend_mode(ctx, BlendMode::Multiply)?;
+                graphics::draw_rect(ctx, Rect::new(0, 0, width + 1, height + 1), Color::from_rgb(255, 218, 160))?;
+                graphics::set_blend_mode(ctx, BlendMode::Alpha)?;
+            }
+            PhotoFilter::Vignette => {
+                let band = (width.min(height) / 4 / VIGNETTE_BANDS).max(1);
+
+                for i in 0..VIGNETTE_BANDS {
+                    let alpha = (200 * (VIGNETTE_BANDS - i) / VIGNETTE_BANDS) as u8;
+                    let offset = i * band;
+
+                    graphics::draw_outline_rect(
+                        ctx,
+                        Rect::new(offset, offset, width - offset, height - offset),
+                        band as usize,
+                        Color::from_rgba(0, 0, 0, alpha),
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn save_screenshot(ctx: &mut Context) -> GameResult<String> {
+        let (width, height, mut pixels) = graphics::read_pixels(ctx)?;
+
+        // the canvas isn't guaranteed to be opaque after alpha blending
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel[3] = 255;
+        }
+
+        let path = format!("{}/{}.png", SCREENSHOTS_DIR, chrono::Local::now().format("%Y-%m-%d_%H-%M-%S%.3f"));
+
+        filesystem::user_create_dir(ctx, SCREENSHOTS_DIR)?;
+        let file = filesystem::user_create(ctx, &path)?;
+        PngEncoder::new(file).write_image(&pixels, width, height, ColorType::Rgba8)?;
+
+        Ok(path)
+    }
+
+    pub fn draw(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        self.draw_filter(state, ctx)?;
+
+        // captured before drawing the overlay, so it doesn't end up in the picture
+        if self.screenshot_requested.replace(false) {
+            let message = match Self::save_screenshot(ctx) {
+                Ok(path) => {
+                    log::info!("Saved screenshot to {}", path);
+                    state.loc.tt("menus.photo_mode.saved", &[("path", &path)])
+                }
+                Err(err) => {
+                    log::error!("Failed to save screenshot: {}", err);
+                    state.loc.t("menus.photo_mode.save_failed").to_owned()
+                }
+            };
+
+            self.message.replace(Some((message, MESSAGE_TICKS)));
+        }
+
+        let info = state.loc.tt(
+            "menus.photo_mode.info",
+            &[("zoom", &format!("{:.1}", self.zoom)), ("filter", state.loc.t(self.filter.locale_key()))],
+        );
+
+        state.font.builder().position(4.0, 4.0).shadow(true).draw(
+            state.loc.t("menus.photo_mode.title"),
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+        state.font.builder().position(4.0, 16.0).shadow(true).color((160, 160, 176, 255)).draw(
+            &info,
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+
+        if let Some((message, _)) = &*self.message.borrow() {
+            state.font.builder().position(4.0, state.canvas_size.1 - 16.0).shadow(true).draw(
+                message,
+                ctx,
+                &state.constants,
+                &mut state.texture_set,
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -29,15 +29,19 @@ impl StageSelect {
         self.stage_select_text_y_pos = 54;
         self.tick = 0;
     }
+
+    /// Returns the teleporter slots shown in the menu, slots registered with index 0 are treated as empty.
+    pub fn visible_slots(state: &SharedGameState) -> Vec<(u16, u16)> {
+        state.teleporter_slots.iter().copied().filter(|&(index, _event_num)| index != 0).collect()
+    }
 }
 
 impl GameEntity<(&mut Context, &Player, &Player)> for StageSelect {
     fn tick(&mut self, state: &mut SharedGameState, (ctx, player1, player2): (&mut Context, &Player, &Player)) -> GameResult {
         state.touch_controls.control_type = TouchControlType::None;
 
-        let slot_count = state.teleporter_slots.iter()
-            .filter(|&&(index, _event_num)| index != 0)
-            .count();
+        let slots = Self::visible_slots(state);
+        let slot_count = slots.len();
 
         if slot_count <= self.current_teleport_slot as usize {
             self.current_teleport_slot = 0;
@@ -70,7 +74,7 @@ impl GameEntity<(&mut Context, &Player, &Player)> for StageSelect {
         if self.prev_teleport_slot != self.current_teleport_slot {
             self.prev_teleport_slot = self.current_teleport_slot;
             state.sound_manager.play_sfx(1);
-            if let Some(&(index, _event_num)) = state.teleporter_slots.get(self.current_teleport_slot as usize) {
+            if let Some(&(index, _event_num)) = slots.get(self.current_teleport_slot as usize) {
                 state.textscript_vm.start_script(1000 + index);
             } else {
                 state.textscript_vm.start_script(1000);
@@ -113,7 +117,7 @@ impl GameEntity<(&mut Context, &Player, &Player)> for StageSelect {
             state.control_flags.set_interactions_disabled(false);
 
             if ok_pressed {
-                if let Some(&(_index, event_num)) = state.teleporter_slots.get(self.current_teleport_slot as usize) {
+                if let Some(&(_index, event_num)) = slots.get(self.current_teleport_slot as usize) {
                     state.textscript_vm.start_script(event_num);
                 }
             }
@@ -127,15 +131,12 @@ impl GameEntity<(&mut Context, &Player, &Player)> for StageSelect {
     fn draw(&self, state: &mut SharedGameState, ctx: &mut Context, _frame: &Frame) -> GameResult {
         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "StageImage")?;
 
-        let slot_count = state.teleporter_slots.iter()
-            .filter(|&&(index, _event_num)| index != 0)
-            .count();
+        let slots = Self::visible_slots(state);
+        let slot_count = slots.len();
         let slot_offset = ((state.canvas_size.0 - 40.0 * slot_count as f32) / 2.0).floor();
         let mut slot_rect = Rect::new(0, 0, 0, 0);
 
-        for i in 0..slot_count {
-            let index = state.teleporter_slots[i].0;
-
+        for (i, &(index, _event_num)) in slots.iter().enumerate() {
             slot_rect.left = 32 * (index as u16 % 8);
             slot_rect.top = 16 * (index as u16 / 8);
             slot_rect.right = slot_rect.left + 32;
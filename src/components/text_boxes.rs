@@ -5,12 +5,15 @@ use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::graphics;
 use crate::game::frame::Frame;
-use crate::game::scripting::tsc::text_script::{ConfirmSelection, TextScriptExecutionState, TextScriptLine};
+use crate::game::scripting::tsc::text_script::{
+    ConfirmSelection, TextScriptExecutionState, TextScriptLine, TextScriptVM, VISIBLE_CHOICES,
+};
 use crate::game::shared_game_state::SharedGameState;
 use crate::graphics::font::{Font, Symbols};
 
 pub struct TextBoxes {
     pub slide_in: u8,
+    pub item_slide_in: u8,
     pub anim_counter: usize,
     animated_face: AnimatedFace,
 }
@@ -22,6 +25,7 @@ impl TextBoxes {
     pub fn new() -> TextBoxes {
         TextBoxes {
             slide_in: 7,
+            item_slide_in: 0,
             anim_counter: 0,
             animated_face: AnimatedFace { face_id: 0, anim_id: 0, anim_frames: vec![(0, 0)] },
         }
@@ -30,6 +34,10 @@ impl TextBoxes {
 
 impl GameEntity<()> for TextBoxes {
     fn tick(&mut self, state: &mut SharedGameState, _custom: ()) -> GameResult {
+        if state.textscript_vm.item != 0 {
+            self.item_slide_in = self.item_slide_in.saturating_sub(1);
+        }
+
         if state.textscript_vm.face != 0 {
             self.slide_in = self.slide_in.saturating_sub(1);
             self.anim_counter = self.anim_counter.wrapping_add(1);
@@ -73,6 +81,9 @@ impl GameEntity<()> for TextBoxes {
             state.canvas_size.1 as f32 - off_bottom - 66.0
         };
         let left_pos = off_left + center - 122.0;
+        // the item box goes above the message box, or below it if the message box is on top
+        let item_pos = if state.textscript_vm.flags.position_top() { top_pos + 78.0 } else { top_pos - 46.0 };
+        let item_icon_pos = item_pos + 8.0 - self.item_slide_in as f32;
 
         {
             let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
@@ -85,36 +96,12 @@ impl GameEntity<()> for TextBoxes {
             }
 
             if state.textscript_vm.item != 0 {
-                batch.add_rect(
-                    center - 40.0,
-                    state.canvas_size.1 - off_bottom - 112.0,
-                    &state.constants.textscript.get_item_top_left,
-                );
-                batch.add_rect(
-                    center - 40.0,
-                    state.canvas_size.1 - off_bottom - 96.0,
-                    &state.constants.textscript.get_item_bottom_left,
-                );
-                batch.add_rect(
-                    center + 32.0,
-                    state.canvas_size.1 - off_bottom - 112.0,
-                    &state.constants.textscript.get_item_top_right,
-                );
-                batch.add_rect(
-                    center + 32.0,
-                    state.canvas_size.1 - off_bottom - 104.0,
-                    &state.constants.textscript.get_item_right,
-                );
-                batch.add_rect(
-                    center + 32.0,
-                    state.canvas_size.1 - off_bottom - 96.0,
-                    &state.constants.textscript.get_item_right,
-                );
-                batch.add_rect(
-                    center + 32.0,
-                    state.canvas_size.1 - off_bottom - 88.0,
-                    &state.constants.textscript.get_item_bottom_right,
-                );
+                batch.add_rect(center - 40.0, item_pos, &state.constants.textscript.get_item_top_left);
+                batch.add_rect(center - 40.0, item_pos + 16.0, &state.constants.textscript.get_item_bottom_left);
+                batch.add_rect(center + 32.0, item_pos, &state.constants.textscript.get_item_top_right);
+                batch.add_rect(center + 32.0, item_pos + 8.0, &state.constants.textscript.get_item_right);
+                batch.add_rect(center + 32.0, item_pos + 16.0, &state.constants.textscript.get_item_right);
+                batch.add_rect(center + 32.0, item_pos + 24.0, &state.constants.textscript.get_item_bottom_right);
             }
 
             if let TextScriptExecutionState::WaitConfirmation(_, _, _, wait, selection) = state.textscript_vm.state {
@@ -191,7 +178,7 @@ impl GameEntity<()> for TextBoxes {
                 rect.bottom = rect.top + 16;
 
                 let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "ArmsImage")?;
-                batch.add_rect((center - 12.0).floor(), state.canvas_size.1 - off_bottom - 104.0, &rect);
+                batch.add_rect((center - 12.0).floor(), item_icon_pos, &rect);
                 batch.draw(ctx)?;
             } else {
                 let item_id = state.textscript_vm.item as u16 - 1000;
@@ -202,7 +189,7 @@ impl GameEntity<()> for TextBoxes {
                 rect.bottom = rect.top + 16;
 
                 let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "ItemImage")?;
-                batch.add_rect((center - 20.0).floor(), state.canvas_size.1 - off_bottom - 104.0, &rect);
+                batch.add_rect((center - 20.0).floor(), item_icon_pos, &rect);
                 batch.draw(ctx)?;
             }
         }
@@ -273,6 +260,78 @@ impl GameEntity<()> for TextBoxes {
             }
         }
 
+        if let TextScriptExecutionState::WaitPage(_, _, _, tick) = state.textscript_vm.state {
+            if tick > 10 {
+                // there are more pages to read
+                draw_arrow(state, ctx, left_pos + 244.0 - 22.0, top_pos + 52.0, true)?;
+            }
+        }
+
+        if let TextScriptExecutionState::WaitChoice(_, _, selected, scroll) = state.textscript_vm.state {
+            let (box_x, box_y) = TextScriptVM::choice_box_pos(state, ctx);
+            let count = state.textscript_vm.choices.len();
+            let rows = count.min(VISIBLE_CHOICES);
+
+            {
+                let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
+
+                batch.add_rect(box_x, box_y, &state.constants.textscript.textbox_rect_top);
+                for i in 1..=(rows * 2) {
+                    batch.add_rect(box_x, box_y + i as f32 * 8.0, &state.constants.textscript.textbox_rect_middle);
+                }
+                batch.add_rect(
+                    box_x,
+                    box_y + 8.0 + rows as f32 * 16.0,
+                    &state.constants.textscript.textbox_rect_bottom,
+                );
+                batch.add_rect(
+                    box_x + 10.0,
+                    box_y + 8.0 + selected.saturating_sub(scroll) as f32 * 16.0,
+                    &state.constants.textscript.textbox_rect_cursor,
+                );
+
+                batch.draw(ctx)?;
+            }
+
+            for (row, (text, _)) in state.textscript_vm.choices.iter().skip(scroll as usize).take(rows).enumerate() {
+                state
+                    .font
+                    .builder()
+                    .position(box_x + 30.0, box_y + 10.0 + row as f32 * 16.0)
+                    .shadow(state.constants.textscript.text_shadow)
+                    .draw_iter(text.iter().copied(), ctx, &state.constants, &mut state.texture_set)?;
+            }
+
+            if scroll > 0 {
+                draw_arrow(state, ctx, box_x + 244.0 - 22.0, box_y + 6.0, false)?;
+            }
+
+            if scroll as usize + rows < count {
+                draw_arrow(state, ctx, box_x + 244.0 - 22.0, box_y + 6.0 + rows as f32 * 16.0, true)?;
+            }
+        }
+
         Ok(())
     }
 }
+
+/// Draws a small arrow hinting that there's more content above or below.
+fn draw_arrow(state: &SharedGameState, ctx: &mut Context, x: f32, y: f32, down: bool) -> GameResult {
+    for row in 0..4 {
+        let width = if down { 7 - row * 2 } else { 1 + row * 2 };
+        let offset = (7 - width) / 2;
+
+        graphics::draw_rect(
+            ctx,
+            Rect::new_size(
+                ((x + offset as f32) * state.scale) as isize,
+                ((y + row as f32) * state.scale) as isize,
+                (width as f32 * state.scale) as isize,
+                state.scale as isize,
+            ),
+            Color::from_rgb(255, 255, 255),
+        )?;
+    }
+
+    Ok(())
+}
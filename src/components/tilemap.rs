@@ -231,4 +231,71 @@ impl Tilemap {
 
         Ok(())
     }
+
+    /// Redraws background tiles right above lava in thin horizontal strips shifted by a sine wave.
+    pub fn draw_heat_haze(
+        &self,
+        state: &mut SharedGameState,
+        ctx: &mut Context,
+        frame: &Frame,
+        textures: &StageTexturePaths,
+        stage: &Stage,
+    ) -> GameResult {
+        if stage.data.pxpack_data.is_some() || (state.tile_props.is_empty() && state.constants.lava_tile_ids.is_empty())
+        {
+            return Ok(());
+        }
+
+        let tile_size = state.tile_size.as_int();
+        let tile_sizef = state.tile_size.as_float();
+        let halftf = tile_sizef / 2.0;
+        let (frame_x, frame_y) = frame.xy_interpolated(state.frame_time);
+        let time = self.tick as f32 + (self.tick - self.prev_tick) as f32 * state.frame_time as f32;
+
+        let tile_start_x = (frame_x as i32 / tile_size).clamp(0, stage.map.width as i32) as usize;
+        let tile_start_y = (frame_y as i32 / tile_size).clamp(1, stage.map.height as i32) as usize;
+        let tile_end_x = ((frame_x as i32 + 8 + state.canvas_size.0 as i32) / tile_size + 1)
+            .clamp(0, stage.map.width as i32) as usize;
+        let tile_end_y = ((frame_y as i32 + tile_size + state.canvas_size.1 as i32) / tile_size + 1)
+            .clamp(0, stage.map.height as i32) as usize;
+
+        let world = &state.constants.world;
+        let default = (world.lava_damage, world.lava_tick_interval);
+        let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, &textures.tileset_bg)?;
+
+        for y in tile_start_y..tile_end_y {
+            for x in tile_start_x..tile_end_x {
+                let tile = stage.map.tiles[y * stage.map.width as usize + x];
+                let above = stage.map.tiles[(y - 1) * stage.map.width as usize + x];
+                if stage.map.attrib[above as usize] >= 0x20
+                    || state
+                        .tile_props
+                        .lava(&stage.data.tileset.name, tile as u16, &state.constants.lava_tile_ids, default)
+                        .is_none()
+                {
+                    continue;
+                }
+
+                let left = (above as u16 % 16) * tile_size as u16;
+                let top = (above as u16 / 16) * tile_size as u16;
+
+                for row in 0..tile_size as u16 {
+                    // the wave gets stronger closer to the lava
+                    let strength = (row + 1) as f32 / tile_sizef;
+                    let offset = (time * 0.15 + (y as f32 * tile_sizef + row as f32) * 0.5).sin() * strength;
+                    let rect = Rect::new(left, top + row, left + tile_size as u16, top + row + 1);
+
+                    batch.add_rect(
+                        (x as f32 * tile_sizef - halftf) - frame_x + offset,
+                        ((y - 1) as f32 * tile_sizef - halftf) - frame_y + row as f32,
+                        &rect,
+                    );
+                }
+            }
+        }
+
+        batch.draw(ctx)?;
+
+        Ok(())
+    }
 }
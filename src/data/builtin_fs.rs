@@ -112,6 +112,7 @@ impl BuiltinFS {
                         "builtin_data",
                         vec![
                             FSNode::File("buttons.png", include_bytes!("builtin/builtin_data/buttons.png")),
+                            FSNode::File("npc_barrels.tbl", include_bytes!("builtin/builtin_data/npc_barrels.tbl")),
                             FSNode::File("triangles.png", include_bytes!("builtin/builtin_data/triangles.png")),
                             FSNode::Directory(
                                 "headband",
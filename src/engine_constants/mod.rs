@@ -54,6 +54,27 @@ pub struct PlayerConsts {
     pub frames_left: [Rect<u16>; 12],
     pub frames_right: [Rect<u16>; 12],
     pub frames_bubble: [Rect<u16>; 2],
+    /// Falling speed of the ground slam.
+    pub slam_velocity: i32,
+    /// Distance from the landing point in which NPCs are damaged by the ground slam.
+    pub slam_radius: i32,
+    pub slam_damage: u16,
+    /// Multiplier applied to gravity while gliding with the cape.
+    pub glide_gravity_factor: f32,
+    pub glide_max_fall: i32,
+    /// Top of the player hitbox while crouching.
+    pub crouch_hit_top: u32,
+    pub crouch_speed_factor: f32,
+    /// Vertical offset applied to bullets fired while crouching.
+    pub crouch_barrel_offset_y: i32,
+    /// Length of invincibility after taking damage.
+    pub invincible_ticks: u8,
+    /// Multiplier applied to the invincibility length while the Whimsical Star is equipped.
+    pub spirit_invincible_multiplier: f32,
+    /// Length of invincibility while the Guardian Charm is equipped.
+    pub guardian_charm_invincible_ticks: u8,
+    /// Amount of jumps the player can do in the air with the double jump ability, before the booster takes over.
+    pub air_jumps: u8,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -65,11 +86,17 @@ pub struct GameConsts {
     pub new_game_event: u16,
     pub new_game_player_pos: (i16, i16),
     pub tile_offset_x: i32,
+    /// If set, the player is always asked for their name before a new game starts, see `<NAM`.
+    /// Otherwise it's up to `Settings::name_entry`.
+    pub name_entry: bool,
+    /// Horizontal distance from the closest player at which sounds played by NPCs are panned fully to one side,
+    /// in fixed point units. 0 keeps them centered like the original game.
+    pub sfx_pan_range: i32,
 }
 
 #[derive(Debug)]
 pub struct CaretConsts {
-    pub offsets: [(i32, i32); 18],
+    pub offsets: [(i32, i32); 19],
     pub bubble_left_rects: Vec<Rect<u16>>,
     pub bubble_right_rects: Vec<Rect<u16>>,
     pub projectile_dissipation_left_rects: Vec<Rect<u16>>,
@@ -97,6 +124,16 @@ pub struct TextureSizeTable {
     sizes: HashMap<String, (u16, u16)>,
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct CompletionFlagTable {
+    flags: HashMap<usize, usize>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct WeaponHeatTable {
+    heat: HashMap<usize, WeaponHeat>,
+}
+
 impl Clone for CaretConsts {
     fn clone(&self) -> Self {
         Self {
@@ -175,11 +212,28 @@ pub struct BulletRects {
     pub b042_spur_trail_l3: [Rect<u16>; 6],
 }
 
+/// Overheating parameters of a weapon type, a weapon with zero `heat_per_shot` never overheats.
+#[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WeaponHeat {
+    pub heat_per_shot: f32,
+    pub max_heat: f32,
+    /// Heat lost every tick.
+    pub cool_rate: f32,
+    /// Heat below which an overheated weapon can fire again.
+    pub cool_threshold: f32,
+}
+
+impl WeaponHeat {
+    pub const NONE: WeaponHeat = WeaponHeat { heat_per_shot: 0.0, max_heat: 0.0, cool_rate: 0.0, cool_threshold: 0.0 };
+}
+
 #[derive(Debug)]
 pub struct WeaponConsts {
     pub bullet_table: Vec<BulletData>,
     pub bullet_rects: BulletRects,
     pub level_table: [[u16; 3]; 14],
+    /// Empty unless a mod ships weapon_heat.json, see [EngineConstants::load_weapon_heat].
+    pub heat_table: [WeaponHeat; 14],
 }
 
 impl Clone for WeaponConsts {
@@ -188,6 +242,7 @@ impl Clone for WeaponConsts {
             bullet_table: self.bullet_table.clone(),
             bullet_rects: self.bullet_rects,
             level_table: self.level_table,
+            heat_table: self.heat_table,
         }
     }
 }
@@ -196,6 +251,16 @@ impl Clone for WeaponConsts {
 pub struct WorldConsts {
     pub snack_rect: Rect<u16>,
     pub water_push_rect: Rect<u16>,
+    /// Part of horizontal velocity kept each tick while sliding on ice.
+    pub ice_friction: f32,
+    /// Multiplier applied to walking acceleration on ice.
+    pub ice_accel_factor: f32,
+    /// Damage and amount of ticks between hits for tiles in `EngineConstants::lava_tile_ids`.
+    pub lava_damage: u8,
+    pub lava_tick_interval: u16,
+    /// Damage per hit and duration of poison dealt by NPCs marked with [crate::game::npc::NPCTable::load_poisoners].
+    pub poison_damage: u8,
+    pub poison_duration: u16,
 }
 
 #[derive(Debug, Clone)]
@@ -242,7 +307,20 @@ pub struct TextScriptConsts {
     pub text_shadow: bool,
     pub text_speed_normal: u8,
     pub text_speed_fast: u8,
+    /// Amount of characters printed at once, scaled by the text speed setting.
+    pub text_chars_per_tick: u8,
+    /// Amount of characters printed at once while the player holds jump or shoot.
+    pub text_chars_per_tick_fast: u8,
     pub fade_ticks: i8,
+    /// If set, `<TAM` keeps the level and experience of the traded weapon instead of resetting them.
+    /// Players can also turn it on with `Settings::trade_keeps_weapon_level`.
+    pub trade_keeps_weapon_level: bool,
+    /// If set, `<ML+` fully heals the player besides raising their max life.
+    /// Players can also turn it on with `Settings::max_life_up_heals`.
+    pub max_life_up_heals: bool,
+    /// If set, messages that don't fit in the text box wait for confirmation and continue on a clear box
+    /// instead of scrolling line by line. Players can also turn it on with `Settings::paginate_messages`.
+    pub paginate_messages: bool,
 }
 
 #[derive(Debug)]
@@ -343,6 +421,16 @@ pub struct EngineConstants {
     pub animated_face_table: Vec<AnimatedFace>,
     pub string_table: HashMap<String, String>,
     pub missile_flags: Vec<u16>,
+    /// Flags marking stages as completed, keyed by stage id. Setting one shows the stage summary. Only mods provide them.
+    pub completion_flags: HashMap<usize, usize>,
+    /// Tiles that are slippery in every tileset, on top of ones marked in tile properties.
+    pub ice_tile_ids: Vec<u16>,
+    /// Tiles that burn the player in every tileset, on top of ones marked in tile properties.
+    pub lava_tile_ids: Vec<u16>,
+    /// Part of the invincibility frames that lava damage ignores, 0.0 respects them fully.
+    pub lava_iframe_bypass_fraction: f32,
+    /// Tiles that act as spikes hurting from every side in every tileset, on top of ones marked in tile properties.
+    pub spike_tile_ids: Vec<u16>,
     pub locales: Vec<Locale>,
     pub gamepad: GamepadConsts,
 }
@@ -378,6 +466,11 @@ impl Clone for EngineConstants {
             animated_face_table: self.animated_face_table.clone(),
             string_table: self.string_table.clone(),
             missile_flags: self.missile_flags.clone(),
+            completion_flags: self.completion_flags.clone(),
+            ice_tile_ids: self.ice_tile_ids.clone(),
+            lava_tile_ids: self.lava_tile_ids.clone(),
+            lava_iframe_bypass_fraction: self.lava_iframe_bypass_fraction,
+            spike_tile_ids: self.spike_tile_ids.clone(),
             locales: self.locales.clone(),
             gamepad: self.gamepad.clone(),
         }
@@ -402,6 +495,8 @@ impl EngineConstants {
                 new_game_event: 200,
                 new_game_player_pos: (10, 8),
                 tile_offset_x: 0,
+                name_entry: false,
+                sfx_pan_range: 0x14000,
             },
             player: PlayerConsts {
                 life: 3,
@@ -459,6 +554,18 @@ impl EngineConstants {
                     Rect { left: 56, top: 96, right: 80, bottom: 120 },
                     Rect { left: 80, top: 96, right: 104, bottom: 120 },
                 ],
+                slam_velocity: 0xa00,
+                slam_radius: 0x3000,
+                slam_damage: 4,
+                glide_gravity_factor: 0.25,
+                glide_max_fall: 0x200,
+                crouch_hit_top: 0x400,
+                crouch_speed_factor: 0.5,
+                crouch_barrel_offset_y: 0x600,
+                invincible_ticks: 128,
+                spirit_invincible_multiplier: 1.0,
+                guardian_charm_invincible_ticks: 192,
+                air_jumps: 1,
             },
             booster: BoosterConsts {
                 fuel: 50,
@@ -488,6 +595,7 @@ impl EngineConstants {
                     (0x800, 0x800),
                     (20 * 0x200, 0x800),
                     (52 * 0x200, 0x800),
+                    (0x1000, 0x1000),
                 ],
                 bubble_left_rects: vec![
                     Rect { left: 0, top: 64, right: 8, bottom: 72 },
@@ -589,6 +697,12 @@ impl EngineConstants {
             world: WorldConsts {
                 snack_rect: Rect { left: 256, top: 48, right: 272, bottom: 64 },
                 water_push_rect: Rect { left: 224, top: 48, right: 240, bottom: 64 },
+                ice_friction: 0.97,
+                ice_accel_factor: 0.3,
+                lava_damage: 2,
+                lava_tick_interval: 30,
+                poison_damage: 1,
+                poison_duration: 500,
             },
             npc: serde_json::from_str("{}").unwrap(),
             weapon: WeaponConsts {
@@ -1371,6 +1485,7 @@ impl EngineConstants {
                     [1, 1, 1],
                     [40, 60, 200],
                 ],
+                heat_table: [WeaponHeat::NONE; 14],
             },
             tex_sizes: case_insensitive_hashmap! {
                 "ArmsImage" => (256, 16),
@@ -1563,7 +1678,12 @@ impl EngineConstants {
                 text_shadow: false,
                 text_speed_normal: 4,
                 text_speed_fast: 1,
+                text_chars_per_tick: 1,
+                text_chars_per_tick_fast: 64,
                 fade_ticks: 15,
+                trade_keeps_weapon_level: false,
+                max_life_up_heals: false,
+                paginate_messages: false,
             },
             title: TitleConsts {
                 intro_text: "Studio Pixel presents".to_owned(),
@@ -1678,6 +1798,11 @@ impl EngineConstants {
             animated_face_table: vec![AnimatedFace { face_id: 0, anim_id: 0, anim_frames: vec![(0, 0)] }],
             string_table: HashMap::new(),
             missile_flags: vec![200, 201, 202, 218, 550, 766, 880, 920, 1551],
+            completion_flags: HashMap::new(),
+            ice_tile_ids: Vec::new(),
+            lava_tile_ids: Vec::new(),
+            lava_iframe_bypass_fraction: 0.5,
+            spike_tile_ids: Vec::new(),
             locales: Vec::new(),
             gamepad: GamepadConsts {
                 button_rects: HashMap::from([
@@ -1930,6 +2055,42 @@ impl EngineConstants {
         Ok(())
     }
 
+    /// Loads stage completion flags from mods, later ones replace entries of the same stage. Vanilla has none.
+    pub fn load_completion_flags(&mut self, ctx: &mut Context) -> GameResult {
+        self.completion_flags.clear();
+
+        for path in self.base_paths.clone().iter().rev() {
+            if let Ok(file) = filesystem::open(ctx, [path, "completion_flags.json"].join("")) {
+                match serde_json::from_reader::<_, CompletionFlagTable>(file) {
+                    Ok(table) => self.completion_flags.extend(table.flags),
+                    Err(err) => log::warn!("Failed to deserialize completion flags: {}", err),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads overheating parameters keyed by weapon type from mods, no weapon overheats in vanilla.
+    pub fn load_weapon_heat(&mut self, ctx: &mut Context) -> GameResult {
+        self.weapon.heat_table = [WeaponHeat::NONE; 14];
+
+        for path in self.base_paths.clone().iter().rev() {
+            if let Ok(file) = filesystem::open(ctx, [path, "weapon_heat.json"].join("")) {
+                match serde_json::from_reader::<_, WeaponHeatTable>(file) {
+                    Ok(table) => {
+                        for (wtype, heat) in table.heat {
+                            if let Some(entry) = self.weapon.heat_table.get_mut(wtype) {
+                                *entry = heat;
+                            }
+                        }
+                    }
+                    Err(err) => log::warn!("Failed to deserialize weapon heat table: {}", err),
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Loads bullet.tbl and arms_level.tbl from CS+ files,
     /// even though they match vanilla 1:1, we should load them for completeness
     /// or if any crazy person uses it for a CS+ mod...
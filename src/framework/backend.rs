@@ -4,7 +4,7 @@ use imgui::DrawData;
 
 use crate::common::{Color, Rect};
 use crate::framework::context::Context;
-use crate::framework::error::GameResult;
+use crate::framework::error::{GameError, GameResult};
 use crate::framework::graphics::{BlendMode, VSyncMode};
 use crate::game::Game;
 
@@ -67,6 +67,11 @@ pub trait BackendRenderer {
 
     fn set_clip_rect(&mut self, rect: Option<Rect>) -> GameResult;
 
+    /// Reads back contents of the screen as tightly packed RGBA rows, top to bottom.
+    fn read_pixels(&mut self) -> GameResult<(u32, u32, Vec<u8>)> {
+        Err(GameError::RenderError("Reading pixels is not supported by this renderer.".to_string()))
+    }
+
     fn imgui(&self) -> GameResult<&mut imgui::Context>;
 
     fn imgui_texture_id(&self, texture: &Box<dyn BackendTexture>) -> GameResult<imgui::TextureId>;
@@ -105,6 +110,9 @@ pub trait BackendGamepad {
     fn set_rumble(&mut self, low_freq: u16, high_freq: u16, duration_ms: u32) -> GameResult;
 
     fn instance_id(&self) -> u32;
+
+    /// Identifier made of the device name and GUID, which stays the same across reconnects and restarts.
+    fn stable_id(&self) -> String;
 }
 
 #[allow(unreachable_code)]
@@ -137,4 +145,6 @@ pub enum SpriteBatchCommand {
     DrawRectFlip(Rect<f32>, Rect<f32>, bool, bool),
     DrawRectTinted(Rect<f32>, Rect<f32>, Color),
     DrawRectFlipTinted(Rect<f32>, Rect<f32>, bool, bool, Color),
+    /// Rotated by given angle in radians (clockwise) around a point in destination coordinates.
+    DrawRectRotated(Rect<f32>, Rect<f32>, f32, (f32, f32)),
 }
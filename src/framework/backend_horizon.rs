@@ -448,6 +448,10 @@ impl BackendGamepad for HorizonGamepad {
     fn instance_id(&self) -> u32 {
         self.id
     }
+
+    fn stable_id(&self) -> String {
+        format!("Horizon {}", self.id)
+    }
 }
 
 lazy_static! {
@@ -782,6 +786,49 @@ impl BackendTexture for Deko3DTexture {
                 ];
                 self.vertices.extend_from_slice(&vertices);
             }
+            SpriteBatchCommand::DrawRectRotated(src, dest, angle, (pivot_x, pivot_y)) => {
+                let (sin, cos) = angle.sin_cos();
+                let rotate = |x: f32, y: f32| {
+                    (
+                        pivot_x + (x - pivot_x) * cos - (y - pivot_y) * sin,
+                        pivot_y + (x - pivot_x) * sin + (y - pivot_y) * cos,
+                    )
+                };
+
+                let vertices = [
+                    VertexData {
+                        position: rotate(dest.left, dest.bottom),
+                        uv: (src.left * tex_scale_x, src.bottom * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                    VertexData {
+                        position: rotate(dest.left, dest.top),
+                        uv: (src.left * tex_scale_x, src.top * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                    VertexData {
+                        position: rotate(dest.right, dest.top),
+                        uv: (src.right * tex_scale_x, src.top * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                    VertexData {
+                        position: rotate(dest.left, dest.bottom),
+                        uv: (src.left * tex_scale_x, src.bottom * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                    VertexData {
+                        position: rotate(dest.right, dest.top),
+                        uv: (src.right * tex_scale_x, src.top * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                    VertexData {
+                        position: rotate(dest.right, dest.bottom),
+                        uv: (src.right * tex_scale_x, src.bottom * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                ];
+                self.vertices.extend_from_slice(&vertices);
+            }
         }
     }
 
@@ -342,7 +342,12 @@ impl BackendEventLoop for SDL2EventLoop {
                             log::info!("Connected gamepad: {} (ID: {})", controller.name(), id);
 
                             let axis_sensitivity = state.settings.get_gamepad_axis_sensitivity(which);
-                            ctx.gamepad_context.add_gamepad(SDL2Gamepad::new(controller), axis_sensitivity);
+                            let gamepad = SDL2Gamepad::new(controller);
+                            if state.settings.is_assigned_gamepad(&gamepad.stable_id()) {
+                                log::info!("Reattached gamepad assigned to a player: {}", gamepad.stable_id());
+                            }
+
+                            ctx.gamepad_context.add_gamepad(gamepad, axis_sensitivity);
 
                             unsafe {
                                 let controller_type =
@@ -537,6 +542,14 @@ impl BackendGamepad for SDL2Gamepad {
     fn instance_id(&self) -> u32 {
         self.inner.instance_id()
     }
+
+    fn stable_id(&self) -> String {
+        // mappings start with the GUID of the device
+        let mapping = self.inner.mapping();
+        let guid = mapping.split(',').next().unwrap_or_default();
+
+        format!("{} {}", self.inner.name(), guid)
+    }
 }
 
 struct SDL2Renderer {
@@ -833,6 +846,17 @@ impl BackendRenderer for SDL2Renderer {
         Ok(())
     }
 
+    fn read_pixels(&mut self) -> GameResult<(u32, u32, Vec<u8>)> {
+        let mut refs = self.refs.borrow_mut();
+        let canvas = refs.window.canvas();
+
+        let (width, height) = canvas.output_size().map_err(|e| GameError::RenderError(e.to_string()))?;
+        let pixels =
+            canvas.read_pixels(None, PixelFormatEnum::RGBA32).map_err(|e| GameError::RenderError(e.to_string()))?;
+
+        Ok((width, height, pixels))
+    }
+
     fn imgui(&self) -> GameResult<&mut imgui::Context> {
         unsafe { Ok(&mut *self.imgui.as_ptr()) }
     }
@@ -1084,6 +1108,36 @@ impl BackendTexture for SDL2Texture {
                                 )
                                 .map_err(|e| GameError::RenderError(e.to_string()))?;
                         }
+                        SpriteBatchCommand::DrawRectRotated(src, dest, angle, (pivot_x, pivot_y)) => {
+                            texture.set_color_mod(255, 255, 255);
+                            texture.set_alpha_mod(255);
+                            texture.set_blend_mode(blend);
+
+                            canvas
+                                .copy_ex(
+                                    texture,
+                                    Some(sdl2::rect::Rect::new(
+                                        src.left.round() as i32,
+                                        src.top.round() as i32,
+                                        src.width().round() as u32,
+                                        src.height().round() as u32,
+                                    )),
+                                    Some(sdl2::rect::Rect::new(
+                                        dest.left.round() as i32,
+                                        dest.top.round() as i32,
+                                        dest.width().round() as u32,
+                                        dest.height().round() as u32,
+                                    )),
+                                    angle.to_degrees() as f64,
+                                    Some(sdl2::rect::Point::new(
+                                        (pivot_x - dest.left).round() as i32,
+                                        (pivot_y - dest.top).round() as i32,
+                                    )),
+                                    false,
+                                    false,
+                                )
+                                .map_err(|e| GameError::RenderError(e.to_string()))?;
+                        }
                     }
                 }
 
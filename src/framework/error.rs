@@ -101,6 +101,20 @@ impl From<serde_json::Error> for GameError {
     }
 }
 
+impl From<toml::de::Error> for GameError {
+    fn from(e: toml::de::Error) -> Self {
+        let errstr = format!("TOML error: {}", e);
+        GameError::ParseError(errstr)
+    }
+}
+
+impl From<toml::ser::Error> for GameError {
+    fn from(e: toml::ser::Error) -> Self {
+        let errstr = format!("TOML error: {}", e);
+        GameError::ParseError(errstr)
+    }
+}
+
 #[cfg(target_os = "android")]
 impl From<jni::errors::Error> for GameError {
     fn from(e: jni::errors::Error) -> GameError {
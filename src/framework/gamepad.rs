@@ -160,6 +160,7 @@ impl PlayerControllerInputType {
 pub struct GamepadData {
     controller: Box<dyn BackendGamepad>,
     controller_type: GamepadType,
+    stable_id: String,
 
     left_x: f64,
     left_y: f64,
@@ -177,6 +178,7 @@ pub struct GamepadData {
 impl GamepadData {
     pub(crate) fn new(game_controller: Box<dyn BackendGamepad>, axis_sensitivity: f64) -> Self {
         GamepadData {
+            stable_id: game_controller.stable_id(),
             controller: game_controller,
             controller_type: GamepadType::Unknown,
 
@@ -214,6 +216,10 @@ impl GamepadData {
         self.controller_type.get_name().to_owned()
     }
 
+    pub fn get_stable_id(&self) -> &str {
+        &self.stable_id
+    }
+
     pub fn set_rumble(&mut self, state: &SharedGameState, low_freq: u16, hi_freq: u16, ticks: u32) -> GameResult {
         let duration_ms = (ticks as f32 / state.settings.timing_mode.get_tps() as f32 * 1000.0) as u32;
         self.controller.set_rumble(low_freq, hi_freq, duration_ms)
@@ -342,6 +348,18 @@ impl GamepadContext {
         &self.gamepads
     }
 
+    pub(crate) fn get_stable_id(&self, gamepad_index: usize) -> Option<&str> {
+        self.get_gamepad_by_index(gamepad_index).map(|gamepad| gamepad.stable_id.as_str())
+    }
+
+    pub(crate) fn find_gamepad_by_stable_id(&self, stable_id: &str, skip_index: Option<usize>) -> Option<usize> {
+        self.gamepads
+            .iter()
+            .enumerate()
+            .find(|&(index, gamepad)| Some(index) != skip_index && gamepad.stable_id == stable_id)
+            .map(|(index, _)| index)
+    }
+
     pub(crate) fn pressed_buttons(&self, gamepad_index: u32) -> HashSet<Button> {
         if let Some(gamepad) = self.get_gamepad_by_index(gamepad_index as usize) {
             return gamepad.pressed_buttons_set.clone();
@@ -428,6 +446,17 @@ pub fn get_gamepads(ctx: &Context) -> &Vec<GamepadData> {
     ctx.gamepad_context.get_gamepads()
 }
 
+/// Returns the [GamepadData::get_stable_id] of the gamepad at given index.
+pub fn get_stable_id(ctx: &Context, gamepad_index: u32) -> Option<&str> {
+    ctx.gamepad_context.get_stable_id(gamepad_index as usize)
+}
+
+/// Returns the current index of the gamepad with given [GamepadData::get_stable_id], if it's connected.
+/// Identical gamepads share a stable id, so the one at `skip_index` can be left out.
+pub fn find_gamepad_by_stable_id(ctx: &Context, stable_id: &str, skip_index: Option<usize>) -> Option<usize> {
+    ctx.gamepad_context.find_gamepad_by_stable_id(stable_id, skip_index)
+}
+
 pub fn pressed_buttons(ctx: &Context, gamepad_index: u32) -> HashSet<Button> {
     ctx.gamepad_context.pressed_buttons(gamepad_index)
 }
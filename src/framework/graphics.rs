@@ -135,6 +135,15 @@ pub fn set_clip_rect(ctx: &mut Context, rect: Option<Rect>) -> GameResult {
     Err(GameError::RenderError("Rendering backend hasn't been initialized yet.".to_string()))
 }
 
+/// Returns the current contents of the screen as width, height and RGBA pixel data.
+pub fn read_pixels(ctx: &mut Context) -> GameResult<(u32, u32, Vec<u8>)> {
+    if let Some(renderer) = &mut ctx.renderer {
+        return renderer.read_pixels();
+    }
+
+    Err(GameError::RenderError("Rendering backend hasn't been initialized yet.".to_string()))
+}
+
 
 pub fn imgui_context(ctx: &Context) -> GameResult<&mut imgui::Context> {
     if let Some(renderer) = ctx.renderer.as_ref() {
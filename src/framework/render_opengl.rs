@@ -209,6 +209,49 @@ impl BackendTexture for OpenGLTexture {
                 ];
                 self.vertices.extend_from_slice(&vertices);
             }
+            SpriteBatchCommand::DrawRectRotated(src, dest, angle, (pivot_x, pivot_y)) => {
+                let (sin, cos) = angle.sin_cos();
+                let rotate = |x: f32, y: f32| {
+                    (
+                        pivot_x + (x - pivot_x) * cos - (y - pivot_y) * sin,
+                        pivot_y + (x - pivot_x) * sin + (y - pivot_y) * cos,
+                    )
+                };
+
+                let vertices = [
+                    VertexData {
+                        position: rotate(dest.left, dest.bottom),
+                        uv: (src.left * tex_scale_x, src.bottom * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                    VertexData {
+                        position: rotate(dest.left, dest.top),
+                        uv: (src.left * tex_scale_x, src.top * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                    VertexData {
+                        position: rotate(dest.right, dest.top),
+                        uv: (src.right * tex_scale_x, src.top * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                    VertexData {
+                        position: rotate(dest.left, dest.bottom),
+                        uv: (src.left * tex_scale_x, src.bottom * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                    VertexData {
+                        position: rotate(dest.right, dest.top),
+                        uv: (src.right * tex_scale_x, src.top * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                    VertexData {
+                        position: rotate(dest.right, dest.bottom),
+                        uv: (src.right * tex_scale_x, src.bottom * tex_scale_y),
+                        color: (255, 255, 255, 255),
+                    },
+                ];
+                self.vertices.extend_from_slice(&vertices);
+            }
         }
     }
 
@@ -1053,6 +1096,27 @@ impl BackendRenderer for OpenGLRenderer {
         }
     }
 
+    fn read_pixels(&mut self) -> GameResult<(u32, u32, Vec<u8>)> {
+        if let Some((_, gl)) = self.get_context() {
+            let (width, height) = self.render_data.last_size;
+            let stride = width as usize * 4;
+            let mut pixels = vec![0u8; stride * height as usize];
+
+            unsafe {
+                gl.gl.BindFramebuffer(gl::FRAMEBUFFER, self.render_data.surf_framebuffer);
+                gl.gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+                gl.gl.ReadPixels(0, 0, width as _, height as _, gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as _);
+            }
+
+            // OpenGL returns rows bottom to top
+            let flipped = pixels.chunks_exact(stride).rev().flatten().copied().collect();
+
+            Ok((width, height, flipped))
+        } else {
+            Err(RenderError("No OpenGL context available!".to_string()))
+        }
+    }
+
     fn imgui(&self) -> GameResult<&mut imgui::Context> {
         unsafe { Ok(&mut *self.imgui.get()) }
     }
@@ -22,6 +22,8 @@ pub enum CaretType {
     SmallProjectileDissipation,
     EmptyText,
     PushJumpKey,
+    /// Blinking warning shown above NPCs about to attack, see `NPC::start_telegraph`.
+    TelegraphWarning,
 }
 
 impl CaretType {
@@ -45,6 +47,7 @@ impl CaretType {
             15 => Some(CaretType::SmallProjectileDissipation),
             16 => Some(CaretType::EmptyText),
             17 => Some(CaretType::PushJumpKey),
+            18 => Some(CaretType::TelegraphWarning),
             _ => None,
         }
     }
@@ -91,17 +94,40 @@ impl Caret {
         }
     }
 
+    /// Creates a [CaretType::TelegraphWarning] caret disappearing after given amount of ticks.
+    pub fn new_telegraph(x: i32, y: i32, ticks: u16, constants: &EngineConstants) -> Caret {
+        let mut caret = Caret::new(x, y, CaretType::TelegraphWarning, Direction::Left, constants);
+        caret.action_num = ticks;
+
+        caret
+    }
+
+    /// True while a blinking [CaretType::TelegraphWarning] should be drawn.
+    pub fn telegraph_visible(&self) -> bool {
+        (self.anim_counter / 2) % 2 == 0
+    }
+
     pub fn tick(&mut self, rng: &dyn RNG, constants: &EngineConstants) {
         match self.ctype {
             CaretType::None => {}
             CaretType::Bubble => {
+                // facing up, it's an air bubble slowly rising through water instead of a droplet
+                let rising = self.direction == Direction::Up;
+
                 if self.action_num == 0 {
                     self.action_num = 1;
-                    self.vel_x = rng.range(-0x400..0x400);
-                    self.vel_y = rng.range(-0x400..0);
+                    if rising {
+                        self.vel_x = rng.range(-0x40..0x40);
+                        self.vel_y = -0x100;
+                    } else {
+                        self.vel_x = rng.range(-0x400..0x400);
+                        self.vel_y = rng.range(-0x400..0);
+                    }
                 }
 
-                self.vel_y += 0x40;
+                if !rising {
+                    self.vel_y += 0x40;
+                }
                 self.x += self.vel_x;
                 self.y += self.vel_y;
 
@@ -116,7 +142,9 @@ impl Caret {
                 }
 
                 match self.direction {
-                    Direction::Left => self.anim_rect = constants.caret.bubble_left_rects[self.anim_num as usize],
+                    Direction::Left | Direction::Up => {
+                        self.anim_rect = constants.caret.bubble_left_rects[self.anim_num as usize]
+                    }
                     Direction::Right => self.anim_rect = constants.caret.bubble_right_rects[self.anim_num as usize],
                     _ => (),
                 }
@@ -238,6 +266,13 @@ impl Caret {
                     }
                 }
             }
+            CaretType::TelegraphWarning => {
+                // lifetime is kept in action_num, drawn by the game scene, see `Caret::telegraph_visible`
+                self.anim_counter += 1;
+                if self.anim_counter >= self.action_num {
+                    self.cond.set_alive(false);
+                }
+            }
             CaretType::QuestionMark => {
                 self.anim_counter += 1;
                 if self.anim_counter < 5 {
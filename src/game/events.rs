@@ -0,0 +1,60 @@
+use std::collections::VecDeque;
+
+/// What hurt the player, see [GameEvent::PlayerDamaged].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageSource {
+    /// Contact with an NPC of given type.
+    Npc(u16),
+    /// Spike tiles.
+    Tile,
+    /// Lava tiles, see `TileProperty::Lava`.
+    Lava,
+    /// Scripts, boss attacks and anything else without a specific source.
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    NpcDied { id: u16, npc_type: u16, x: isize, y: isize },
+    PlayerDamaged { amount: u16, source: DamageSource },
+    BossDefeated { boss_id: u8 },
+    FlagChanged { id: usize, val: bool },
+}
+
+/// Queue of things that happened during a tick, dispatched by `SharedGameState::drain_events`.
+///
+/// Lets entity code report events without knowing which systems are interested in them.
+#[derive(Debug, Default)]
+pub struct EventBus {
+    queue: VecDeque<GameEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus { queue: VecDeque::new() }
+    }
+
+    pub fn emit(&mut self, event: GameEvent) {
+        self.queue.push_back(event);
+    }
+
+    /// Removes and returns queued events in the order they were emitted.
+    pub fn take(&mut self) -> VecDeque<GameEvent> {
+        std::mem::take(&mut self.queue)
+    }
+
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+}
+
+#[test]
+fn test_event_bus_order() {
+    let mut bus = EventBus::new();
+    bus.emit(GameEvent::FlagChanged { id: 10, val: true });
+    bus.emit(GameEvent::BossDefeated { boss_id: 3 });
+
+    let events: Vec<GameEvent> = bus.take().into_iter().collect();
+    assert_eq!(events, vec![GameEvent::FlagChanged { id: 10, val: true }, GameEvent::BossDefeated { boss_id: 3 }]);
+    assert!(bus.take().is_empty());
+}
@@ -1,7 +1,9 @@
 use std::cmp::Ordering;
 
+use crate::common::Direction;
 use crate::engine_constants::EngineConstants;
-use crate::game::player::{Player, TargetPlayer};
+use crate::game::caret::CaretType;
+use crate::game::player::{ControlMode, Player, TargetPlayer};
 use crate::game::shared_game_state::SharedGameState;
 use crate::game::weapon::{Weapon, WeaponLevel, WeaponType};
 use crate::game::weapon::bullet::BulletManager;
@@ -123,11 +125,16 @@ impl Inventory {
         }
     }
 
-    pub fn trade_weapon(&mut self, old: Option<WeaponType>, new: WeaponType, max_ammo: u16) {
+    /// Replaces `old` weapon with `new` one in the same slot, or adds `new` if `old` isn't owned.
+    /// If `keep_level` is set, the new weapon inherits level and experience of the old one.
+    pub fn trade_weapon(&mut self, old: Option<WeaponType>, new: WeaponType, max_ammo: u16, keep_level: bool) {
         if let Some(wtype) = old {
             if let Some(weapon) = self.get_weapon_by_type_mut(wtype) {
                 let ammo = if max_ammo == 0 { weapon.max_ammo } else { max_ammo };
-                *weapon = Weapon::new(new, WeaponLevel::Level1, 0, ammo, ammo);
+                let (level, experience) =
+                    if keep_level { (weapon.level, weapon.experience) } else { (WeaponLevel::Level1, 0) };
+
+                *weapon = Weapon::new(new, level, experience, ammo, ammo);
             } else {
                 self.add_weapon(new, max_ammo);
             }
@@ -222,6 +229,18 @@ impl Inventory {
         result
     }
 
+    /// Takes experience from current weapon after the player has taken damage. Arms Barrier halves the loss.
+    pub fn take_damage_xp(&mut self, player: &Player, state: &mut SharedGameState) {
+        let xp_loss = player.damage * if player.equip.has_arms_barrier() { 1 } else { 2 };
+
+        match self.take_xp(xp_loss, state) {
+            TakeExperienceResult::LevelDown if player.life > 0 => {
+                state.create_caret(player.x, player.y, CaretType::LevelUp, Direction::Right);
+            }
+            _ => {}
+        }
+    }
+
     pub fn add_xp(&mut self, exp: u16, player: &mut Player, state: &mut SharedGameState) {
         if let Some(weapon) = self.get_current_weapon_mut() {
             weapon.add_xp(exp, player, state);
@@ -237,6 +256,22 @@ impl Inventory {
         }
     }
 
+    /// Get current charge state. Returns true if current weapon is being charged and reached max level/exp.
+    pub fn is_current_weapon_charged(&self, constants: &EngineConstants) -> bool {
+        if let Some(weapon) = self.weapons.get(self.current_weapon as usize) {
+            weapon.is_charging() && weapon.get_max_exp(constants).2
+        } else {
+            false
+        }
+    }
+
+    /// Get current weapon heat state. Returns a (heat fraction, overheated) tuple if the weapon can overheat.
+    pub fn get_current_heat(&self, constants: &EngineConstants) -> Option<(f32, bool)> {
+        let weapon = self.weapons.get(self.current_weapon as usize)?;
+
+        weapon.get_heat(constants).map(|heat| (heat, weapon.overheated))
+    }
+
     /// Get current ammunition state. Returns a (ammo, max ammo) tuple.
     pub fn get_current_ammo(&self) -> (u16, u16) {
         if let Some(weapon) = self.weapons.get(self.current_weapon as usize) {
@@ -267,6 +302,10 @@ impl Inventory {
     }
 
     pub fn tick_weapons(&mut self, state: &mut SharedGameState, player: &mut Player, player_id: TargetPlayer, bullet_manager: &mut BulletManager) {
+        if player.control_mode == ControlMode::Locked {
+            return;
+        }
+
         if let Some(weapon) = self.get_current_weapon_mut() {
             weapon.tick(state, player, player_id, bullet_manager);
         }
@@ -303,3 +342,44 @@ fn inventory_test() {
     assert!(inventory.has_item_amount(4, Ordering::Equal, 4));
     assert!(!inventory.has_item_amount(4, Ordering::Less, 2));
 }
+
+#[test]
+fn weapon_add_test() {
+    let mut inventory = Inventory::new();
+
+    inventory.add_weapon_data(WeaponType::MissileLauncher, 5, 5, 10, WeaponLevel::Level2);
+    inventory.add_weapon(WeaponType::MissileLauncher, 5);
+
+    assert_eq!(inventory.weapons.len(), 1);
+
+    let weapon = inventory.get_weapon(0).unwrap();
+    assert_eq!(weapon.level, WeaponLevel::Level2);
+    assert_eq!(weapon.experience, 10);
+    assert_eq!(weapon.max_ammo, 10);
+}
+
+#[test]
+fn weapon_trade_test() {
+    let mut inventory = Inventory::new();
+
+    inventory.add_weapon(WeaponType::Snake, 0);
+    inventory.add_weapon_data(WeaponType::PolarStar, 0, 0, 20, WeaponLevel::Level3);
+    inventory.add_weapon_data(WeaponType::MachineGun, 100, 100, 5, WeaponLevel::Level2);
+
+    inventory.trade_weapon(Some(WeaponType::PolarStar), WeaponType::Spur, 0, true);
+    inventory.trade_weapon(Some(WeaponType::MachineGun), WeaponType::Nemesis, 0, false);
+
+    assert!(!inventory.has_weapon(WeaponType::PolarStar));
+    assert!(!inventory.has_weapon(WeaponType::MachineGun));
+
+    let spur = inventory.get_weapon(1).unwrap();
+    assert_eq!(spur.wtype, WeaponType::Spur);
+    assert_eq!(spur.level, WeaponLevel::Level3);
+    assert_eq!(spur.experience, 20);
+
+    let nemesis = inventory.get_weapon(2).unwrap();
+    assert_eq!(nemesis.wtype, WeaponType::Nemesis);
+    assert_eq!(nemesis.level, WeaponLevel::Level1);
+    assert_eq!(nemesis.experience, 0);
+    assert_eq!(nemesis.max_ammo, 100);
+}
@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+use std::io;
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+
+use crate::framework::error::GameResult;
+
+/// Amount of dialogue entries kept in the journal, older ones are dropped.
+pub const MAX_JOURNAL_ENTRIES: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    /// Face shown next to the message, 0 if there was none.
+    pub face: u16,
+    pub text: String,
+    pub map_id: usize,
+    /// Play time at which the message was closed, in ticks.
+    pub tick: u64,
+}
+
+/// Log of dialogue the player has read, see the journal in the pause menu.
+#[derive(Debug, Clone, Default)]
+pub struct Journal {
+    pub entries: VecDeque<JournalEntry>,
+}
+
+impl Journal {
+    pub fn new() -> Journal {
+        Journal { entries: VecDeque::with_capacity(MAX_JOURNAL_ENTRIES) }
+    }
+
+    pub fn push(&mut self, entry: JournalEntry) {
+        if entry.text.trim().is_empty() {
+            return;
+        }
+
+        while self.entries.len() >= MAX_JOURNAL_ENTRIES {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(entry);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn write_to<W: io::Write>(&self, mut data: W) -> GameResult {
+        data.write_u8(self.entries.len() as u8)?;
+
+        for entry in &self.entries {
+            let text = entry.text.as_bytes();
+            let text = &text[..text.len().min(u16::MAX as usize)];
+
+            data.write_u16::<LE>(entry.face)?;
+            data.write_u16::<LE>(entry.map_id as u16)?;
+            data.write_u64::<LE>(entry.tick)?;
+            data.write_u16::<LE>(text.len() as u16)?;
+            data.write_all(text)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read_from<R: io::Read>(mut data: R) -> GameResult<Journal> {
+        let mut journal = Journal::new();
+        let count = data.read_u8()?;
+
+        for _ in 0..count {
+            let face = data.read_u16::<LE>()?;
+            let map_id = data.read_u16::<LE>()? as usize;
+            let tick = data.read_u64::<LE>()?;
+            let mut text = vec![0u8; data.read_u16::<LE>()? as usize];
+            data.read_exact(&mut text)?;
+
+            journal.push(JournalEntry { face, text: String::from_utf8_lossy(&text).into_owned(), map_id, tick });
+        }
+
+        Ok(journal)
+    }
+}
+
+#[test]
+fn test_journal_capacity() {
+    let mut journal = Journal::new();
+
+    for i in 0..(MAX_JOURNAL_ENTRIES + 5) {
+        journal.push(JournalEntry { face: 0, text: i.to_string(), map_id: 0, tick: i as u64 });
+    }
+    journal.push(JournalEntry { face: 0, text: " \n".to_owned(), map_id: 0, tick: 0 });
+
+    assert_eq!(journal.entries.len(), MAX_JOURNAL_ENTRIES);
+    assert_eq!(journal.entries.front().unwrap().text, "5");
+}
+
+#[test]
+fn test_journal_serialization() {
+    let mut journal = Journal::new();
+    journal.push(JournalEntry { face: 3, text: "Hello, Quote!".to_owned(), map_id: 12, tick: 4500 });
+
+    let mut data = Vec::new();
+    journal.write_to(&mut data).unwrap();
+
+    let loaded = Journal::read_from(data.as_slice()).unwrap();
+    assert_eq!(loaded.entries, journal.entries);
+}
@@ -19,18 +19,24 @@ use crate::scene::loading_scene::LoadingScene;
 use crate::scene::Scene;
 
 pub mod caret;
+pub mod events;
 pub mod filesystem_container;
 pub mod frame;
 pub mod inventory;
+pub mod journal;
 pub mod map;
 pub mod npc;
 pub mod physics;
 pub mod player;
 pub mod profile;
+#[cfg(feature = "profiler")]
+pub mod profiler;
 pub mod scripting;
 pub mod settings;
 pub mod shared_game_state;
 pub mod stage;
+pub mod stats;
+pub mod tile_props;
 pub mod weapon;
 
 pub struct LaunchOptions {
@@ -51,6 +57,8 @@ pub struct Game {
     next_tick: u128,
     pub(crate) loops: u32,
     next_tick_draw: u128,
+    next_frame: u128,
+    sleep_correction: i128,
     present: bool,
     fps: Fps,
 }
@@ -66,6 +74,8 @@ impl Game {
             next_tick: 0,
             loops: 0,
             next_tick_draw: 0,
+            next_frame: 0,
+            sleep_correction: 0,
             present: true,
             fps: Fps::new(),
         };
@@ -110,13 +120,21 @@ impl Game {
                         self.last_tick = last_tick;
                     }
 
+                    #[cfg(feature = "profiler")]
+                    state_ref.profiler.begin("tick");
                     for _ in 0..self.loops {
                         scene.tick(state_ref, ctx)?;
                     }
+                    #[cfg(feature = "profiler")]
+                    state_ref.profiler.end();
                     self.fps.tick_count = self.fps.tick_count.saturating_add(self.loops as u32);
                 }
                 TimingMode::FrameSynchronized => {
+                    #[cfg(feature = "profiler")]
+                    state_ref.profiler.begin("tick");
                     scene.tick(state_ref, ctx)?;
+                    #[cfg(feature = "profiler")]
+                    state_ref.profiler.end();
                 }
             }
         }
@@ -177,7 +195,10 @@ impl Game {
 
             let n1 = (elapsed - self.last_tick) as f64;
             let n2 = (self.next_tick - self.last_tick) as f64;
-            state_ref.frame_time = if state_ref.settings.motion_interpolation { n1 / n2 } else { 1.0 };
+            // a frame drawn after the next tick was due (before update caught up) would extrapolate past the
+            // current positions and make entities jump back on the next frame
+            state_ref.frame_time =
+                if state_ref.settings.motion_interpolation { (n1 / n2).clamp(0.0, 1.0) } else { 1.0 };
         }
         unsafe {
             G_MAG = if state_ref.settings.subpixel_coords { state_ref.scale } else { 1.0 };
@@ -189,13 +210,21 @@ impl Game {
         graphics::clear(ctx, [0.0, 0.0, 0.0, 1.0].into());
 
         if let Some(scene) = &mut self.scene {
+            #[cfg(feature = "profiler")]
+            state_ref.profiler.begin("draw");
             scene.draw(state_ref, ctx)?;
+            #[cfg(feature = "profiler")]
+            state_ref.profiler.end();
+
             if state_ref.settings.touch_controls {
                 state_ref.touch_controls.draw(
                     state_ref.canvas_size,
                     state_ref.scale,
+                    &state_ref.settings.touch_layout,
                     &state_ref.constants,
                     &mut state_ref.texture_set,
+                    &state_ref.font,
+                    &state_ref.loc,
                     ctx,
                 )?;
             }
@@ -204,13 +233,63 @@ impl Game {
                 self.fps.act(state_ref, ctx, self.start_time.elapsed().as_nanos())?;
             }
 
+            #[cfg(feature = "profiler")]
+            if state_ref.settings.show_profiler {
+                let profiler = std::mem::take(&mut state_ref.profiler);
+                let result = profiler.draw(state_ref, ctx);
+                state_ref.profiler = profiler;
+                result?;
+            }
+
             self.ui.draw(state_ref, ctx, scene)?;
         }
 
         graphics::present(ctx)?;
 
+        if let Some(fps_cap) = state_ref.settings.fps_cap {
+            self.wait_for_next_frame(fps_cap);
+        }
+
+        #[cfg(feature = "profiler")]
+        state_ref.profiler.end_frame();
+
         Ok(())
     }
+
+    /// Sleeps until the next frame deadline of the given frame rate cap.
+    ///
+    /// Most of the remaining time is spent in `thread::sleep`, the last millisecond is busy-waited
+    /// since the OS scheduler tends to oversleep. How much it oversleeps is tracked and subtracted
+    /// from the following sleeps.
+    fn wait_for_next_frame(&mut self, fps_cap: u32) {
+        const SPIN_NANOS: i128 = 1_000_000;
+
+        let frame_time = 1_000_000_000 / fps_cap.max(1) as u128;
+        let now = self.start_time.elapsed().as_nanos();
+
+        // we've fallen behind by more than a frame (or just started), resync the deadline
+        if self.next_frame + frame_time < now {
+            self.next_frame = now;
+        }
+
+        self.next_frame += frame_time;
+        if now >= self.next_frame {
+            return;
+        }
+
+        let sleep_nanos = (self.next_frame - now) as i128 - SPIN_NANOS - self.sleep_correction;
+        if sleep_nanos > 0 {
+            let before = Instant::now();
+            std::thread::sleep(Duration::from_nanos(sleep_nanos as u64));
+            let overslept = before.elapsed().as_nanos() as i128 - sleep_nanos;
+
+            self.sleep_correction = ((self.sleep_correction * 7 + overslept) / 8).clamp(0, SPIN_NANOS * 4);
+        }
+
+        while self.start_time.elapsed().as_nanos() < self.next_frame {
+            std::hint::spin_loop();
+        }
+    }
 }
 
 // For the most part this is just a copy-paste of the code from FilesystemContainer because it logs 
@@ -147,11 +147,12 @@ impl NPC {
                     self.action_counter = 0;
 
                     self.anim_num = 0;
+                    self.start_telegraph(state);
                 }
             }
             2 => {
                 self.action_counter += 1;
-                if self.action_counter > 8 {
+                if self.action_counter > 8 && !self.telegraph_active {
                     self.action_num = 3;
                     self.anim_num = 2;
 
@@ -555,7 +556,10 @@ impl NPC {
 
         if self.action_counter < 150 {
             self.action_counter += 1;
-        } else {
+            if self.action_counter == 150 && abs(self.x - player.x) < 0x14000 {
+                self.start_telegraph(state);
+            }
+        } else if !self.telegraph_active {
             self.action_counter2 += 1;
             if (self.action_counter2 % 8) == 0 && abs(self.x - player.x) < 0x14000 {
                 let angle = f64::atan2((self.y - player.y) as f64, (self.x - player.x) as f64)
@@ -1213,7 +1217,10 @@ impl NPC {
 
         if self.action_counter < 150 {
             self.action_counter += 1;
-        } else {
+            if self.action_counter == 150 && abs(self.x - player.x) < 0x14000 {
+                self.start_telegraph(state);
+            }
+        } else if !self.telegraph_active {
             self.action_counter2 += 1;
             if (self.action_counter2 % 8) == 0 && abs(self.x - player.x) < 0x14000 {
                 let angle = f64::atan2((self.y - player.y) as f64, (self.x - player.x) as f64)
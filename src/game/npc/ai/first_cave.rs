@@ -5,6 +5,7 @@ use crate::framework::error::GameResult;
 use crate::game::npc::NPC;
 use crate::game::player::Player;
 use crate::game::shared_game_state::SharedGameState;
+use crate::game::stage::Stage;
 use crate::util::rng::RNG;
 
 impl NPC {
@@ -73,6 +74,7 @@ impl NPC {
         &mut self,
         state: &mut SharedGameState,
         players: [&mut Player; 2],
+        stage: &Stage,
     ) -> GameResult {
         match self.action_num {
             0 | 1 => {
@@ -89,7 +91,10 @@ impl NPC {
                     self.target_x += 1;
                 }
 
+                let notices_player = self.notices_player(player, stage);
+
                 if self.action_counter >= 8
+                    && notices_player
                     && self.x - 0xe000 < player.x
                     && self.x + 0xe000 > player.x
                     && self.y - 0xa000 < player.y
@@ -113,6 +118,7 @@ impl NPC {
 
                 if self.action_counter >= 8
                     && self.target_x >= 100
+                    && notices_player
                     && self.x - 0x8000 < player.x
                     && self.x + 0x8000 > player.x
                     && self.y - 0xa000 < player.y
@@ -121,11 +127,12 @@ impl NPC {
                     self.action_num = 2;
                     self.action_counter = 0;
                     self.anim_num = 0;
+                    self.start_telegraph(state);
                 }
             }
             2 => {
                 self.action_counter += 1;
-                if self.action_counter > 8 {
+                if self.action_counter > 8 && !self.telegraph_active {
                     self.action_num = 3;
                     self.anim_num = 2;
 
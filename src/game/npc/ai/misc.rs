@@ -2694,4 +2694,161 @@ impl NPC {
 
         Ok(())
     }
+
+    /// Custom checkpoint, sets the respawn point to the position of the player touching it.
+    pub(crate) fn tick_n371_checkpoint(&mut self, state: &mut SharedGameState, players: [&mut Player; 2]) -> GameResult {
+        if self.action_num == 0 {
+            self.action_num = 1;
+            self.spritesheet_id = 20;
+            self.display_bounds = Rect::new(0x1000, 0x1000, 0x1000, 0x1000);
+        }
+
+        let tile_size = state.tile_size.as_int() * 0x200;
+        let touching = players.iter().find(|player| {
+            player.cond.alive()
+                && !player.cond.hidden()
+                && (player.x - self.x).abs() < 0x1000
+                && (player.y - self.y).abs() < 0x1000
+        });
+
+        if let Some(player) = touching {
+            let tile = (
+                ((player.x + tile_size / 2) / tile_size) as isize,
+                ((player.y + tile_size / 2) / tile_size) as isize,
+            );
+
+            if self.action_num != 2 {
+                state.set_checkpoint(state.current_stage, tile.0, tile.1);
+                if self.flag_num != 0 {
+                    state.set_flag(self.flag_num as usize, true);
+                }
+                state.sound_manager.play_sfx(18);
+
+                self.action_num = 2;
+                self.target_x = tile.0 as i32;
+                self.target_y = tile.1 as i32;
+            }
+        }
+
+        // another checkpoint took over
+        if self.action_num == 2
+            && (state.checkpoint != Some((self.target_x as isize, self.target_y as isize))
+                || state.checkpoint_map != Some(state.current_stage))
+        {
+            self.action_num = 1;
+        }
+
+        if self.action_num == 2 {
+            self.anim_counter = (self.anim_counter + 1) % 24;
+            self.anim_num = self.anim_counter / 3;
+        } else {
+            self.anim_counter = 0;
+            self.anim_num = 0;
+        }
+
+        self.anim_rect = state.constants.npc.n016_save_point[self.anim_num as usize];
+
+        Ok(())
+    }
+
+    /// Floats back and forth between its spawn tile and a second waypoint, going around walls using
+    /// pathfinding. The waypoint is an offset in tiles stored in the event number, low byte is
+    /// the horizontal and high byte the vertical offset, both signed.
+    pub(crate) fn tick_n372_pathfinding_patroller(&mut self, state: &mut SharedGameState, stage: &Stage) -> GameResult {
+        let tile_size = state.tile_size.as_int() * 0x200;
+
+        if self.action_num == 0 {
+            self.action_num = 1;
+            self.spritesheet_id = 23;
+            self.display_bounds = Rect::new(0x1000, 0x1000, 0x1000, 0x1000);
+            self.hit_bounds = Rect::new(0x1000, 0x1000, 0x1000, 0x1000);
+            self.target_x = (self.x + tile_size / 2).div_euclid(tile_size);
+            self.target_y = (self.y + tile_size / 2).div_euclid(tile_size);
+            self.path = None;
+        }
+
+        let spawn = (self.target_x, self.target_y);
+        let waypoint =
+            (spawn.0 + (self.event_num & 0xff) as u8 as i8 as i32, spawn.1 + (self.event_num >> 8) as u8 as i8 as i32);
+
+        match self.action_num {
+            1 | 2 => {
+                let goal = if self.action_num == 1 { waypoint } else { spawn };
+
+                if !self.follow_path(goal, 0x200, stage) {
+                    self.action_num += 2;
+                    self.action_counter = 0;
+                }
+            }
+            3 | 4 => {
+                // wait a bit at the waypoint before turning back
+                self.action_counter += 1;
+                if self.action_counter > 50 {
+                    self.action_num = if self.action_num == 3 { 2 } else { 1 };
+                    self.path = None;
+                }
+            }
+            _ => (),
+        }
+
+        if self.vel_x != 0 {
+            self.direction = if self.vel_x > 0 { Direction::Right } else { Direction::Left };
+        }
+
+        self.x += self.vel_x;
+        self.y += self.vel_y;
+
+        self.animate(8, 0, 1);
+
+        let dir_offset = if self.direction == Direction::Left { 0 } else { 8 };
+        self.anim_rect = state.constants.npc.n067_misery_floating[self.anim_num as usize + dir_offset];
+
+        Ok(())
+    }
+    /// Machine gun floating in place, shows off barrels from npc_barrels.tbl. The barrel turns towards
+    /// the closest player and fires along itself every two seconds while they're nearby.
+    pub(crate) fn tick_n375_turret(
+        &mut self,
+        state: &mut SharedGameState,
+        players: [&mut Player; 2],
+        npc_list: &NPCList,
+    ) -> GameResult {
+        if self.action_num == 0 {
+            self.action_num = 1;
+            self.spritesheet_id = 11;
+            self.display_bounds = Rect::new(0x1800, 0x1000, 0x1800, 0x1000);
+            self.anim_rect = Rect::new(0, 0, 0, 0);
+        }
+
+        let player = self.get_closest_player_ref(&players);
+        let in_range = (player.x - self.x).abs() < 0x14000 && (player.y - self.y).abs() < 0xa000;
+
+        match self.action_num {
+            1 => {
+                self.action_counter += 1;
+                if self.action_counter > 100 && in_range {
+                    self.action_num = 2;
+                    self.action_counter = 0;
+                    self.start_telegraph(state);
+                }
+            }
+            2 if !self.telegraph_active => {
+                self.action_num = 1;
+
+                let (sin, cos) = self.aim_angle_rad.sin_cos();
+                let mut npc = NPC::create(84, &state.npc_table);
+                npc.cond.set_alive(true);
+                npc.x = self.x + (cos * 6144.0) as i32;
+                npc.y = self.y + (sin * 6144.0) as i32;
+                npc.vel_x = (cos * 1024.0) as i32;
+                npc.vel_y = (sin * 1024.0) as i32;
+
+                let _ = npc_list.spawn(0x100, npc);
+                state.sound_manager.play_sfx(39);
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
 }
@@ -1,4 +1,4 @@
-use crate::common::Direction;
+use crate::common::{Direction, Rect};
 use crate::framework::error::GameResult;
 use crate::game::npc::NPC;
 use crate::game::shared_game_state::SharedGameState;
@@ -10,9 +10,11 @@ impl NPC {
         if stage.data.background_type == BackgroundType::Scrolling || stage.data.background_type == BackgroundType::OutsideWind {
             if self.action_num == 0 {
                 self.action_num = 1;
+                // already carried by the scrolling stage
+                self.ignore_forces = true;
 
-                self.vel_x = self.rng.range(-0x80..0x80) as i32;
-                self.vel_y = self.rng.range(-0x7f..0x100) as i32;
+                self.vel_y = self.rng.range(-0x80..0x80) as i32;
+                self.vel_x = self.rng.range(0x7f..0x100) as i32;
             }
 
             self.vel_x -= 0x8;
@@ -139,74 +141,58 @@ impl NPC {
     }
 
     pub(crate) fn tick_n086_missile_pickup(&mut self, state: &mut SharedGameState, stage: &mut Stage) -> GameResult {
-        if self.direction == Direction::Left {
-            self.anim_counter += 1;
-            if self.anim_counter > 2 {
-                self.anim_counter = 0;
-                self.anim_num += 1;
-                if self.anim_num > 1 {
-                    self.anim_num = 0;
-                }
-            }
-
-            self.action_counter2 += 1;
-        }
-
-        if stage.data.background_type == BackgroundType::Scrolling || stage.data.background_type == BackgroundType::OutsideWind {
-            if self.action_num == 0 {
-                self.action_num = 1;
-                self.vel_x = self.rng.range(0x7f..0x100) as i32;
-                self.vel_y = self.rng.range(-0x20..0x20) as i32;
-            }
-
-            self.vel_x -= 0x08;
-            if self.x < 0xa000 {
-                self.cond.set_alive(false)
-            }
-
-            if self.flags.hit_left_wall() {
-                self.vel_x = 0x100;
-            }
-
-            if self.flags.hit_top_wall() {
-                self.vel_y = 0x40;
-            }
+        let rects = state.constants.npc.n086_missile_pickup.0;
+        self.tick_pickup(stage, &rects, 1, 3)
+    }
 
-            if self.flags.hit_bottom_wall() {
-                self.vel_y = -0x40;
-            }
+    pub(crate) fn tick_n087_heart_pickup(&mut self, state: &mut SharedGameState, stage: &mut Stage) -> GameResult {
+        let rects = state.constants.npc.n087_heart_pickup.0;
+        self.tick_pickup(stage, &rects, 2, 6)
+    }
 
-            self.x += self.vel_x;
-            self.y += self.vel_y;
+    /// Cures poison when touched, drawn using the Life Pot item icon.
+    pub(crate) fn tick_n373_antidote(&mut self) -> GameResult {
+        if self.action_num == 0 {
+            self.action_num = 1;
+            self.spritesheet_id = 8;
+            self.display_bounds = Rect::new(0x2000, 0x1000, 0x2000, 0x1000);
+            self.hit_bounds = Rect::new(0x1000, 0x1000, 0x1000, 0x1000);
+            self.anim_rect = Rect::new(224, 16, 256, 32);
+            self.target_y = self.y;
         }
 
-        match self.exp {
-            1 => {
-                self.anim_rect = state.constants.npc.n086_missile_pickup[self.anim_num as usize];
-            }
-            3 => {
-                self.anim_rect = state.constants.npc.n086_missile_pickup[2 + self.anim_num as usize];
-            }
-            _ => (),
-        }
+        // bob up and down
+        self.action_counter = self.action_counter.wrapping_add(1);
+        self.y = self.target_y + if (self.action_counter / 20) % 2 == 0 { 0 } else { -0x200 };
 
-        if self.action_counter2 > 550 {
-            self.cond.set_alive(false);
-        }
+        Ok(())
+    }
 
-        if self.action_counter2 > 500 && self.action_counter2 & 0x02 != 0 {
-            self.anim_rect.right = self.anim_rect.left;
-            self.anim_rect.bottom = self.anim_rect.top;
+    /// Revives a ghost player 2 when player 1 touches it, drawn using the Life Capsule sprite.
+    /// The revive itself is done by the game scene, see `GameScene::tick_revive_orbs`.
+    pub(crate) fn tick_n374_revive_orb(&mut self, state: &mut SharedGameState) -> GameResult {
+        if self.action_num == 0 {
+            self.action_num = 1;
+            self.spritesheet_id = 20;
+            self.display_bounds = Rect::new(0x1000, 0x1000, 0x1000, 0x1000);
+            self.hit_bounds = Rect::new(0x1000, 0x1000, 0x1000, 0x1000);
+            self.target_y = self.y;
         }
 
-        if self.action_counter2 > 547 {
-            self.anim_rect = state.constants.npc.n086_missile_pickup[4];
-        }
+        self.anim_counter = (self.anim_counter + 1) % 6;
+        self.anim_num = self.anim_counter / 3;
+        self.anim_rect = state.constants.npc.n032_life_capsule[self.anim_num as usize];
+
+        self.action_counter = self.action_counter.wrapping_add(1);
+        self.y = self.target_y + if (self.action_counter / 20) % 2 == 0 { 0 } else { -0x200 };
 
         Ok(())
     }
 
-    pub(crate) fn tick_n087_heart_pickup(&mut self, state: &mut SharedGameState, stage: &mut Stage) -> GameResult {
+    /// Shared behavior of missile and heart pickups. Pickups dropped by enemies face left, they animate,
+    /// start blinking after 500 ticks and disappear after 550, map-placed ones stay forever.
+    /// `small_exp` and `large_exp` are the amounts picked from the first or second pair of sprites.
+    fn tick_pickup(&mut self, stage: &Stage, rects: &[Rect<u16>; 5], small_exp: u16, large_exp: u16) -> GameResult {
         if self.direction == Direction::Left {
             self.anim_counter += 1;
             if self.anim_counter > 2 {
@@ -248,14 +234,10 @@ impl NPC {
             self.y += self.vel_y;
         }
 
-        match self.exp {
-            2 => {
-                self.anim_rect = state.constants.npc.n087_heart_pickup[self.anim_num as usize];
-            }
-            6 => {
-                self.anim_rect = state.constants.npc.n087_heart_pickup[2 + self.anim_num as usize];
-            }
-            _ => (),
+        if self.exp == small_exp {
+            self.anim_rect = rects[self.anim_num as usize];
+        } else if self.exp == large_exp {
+            self.anim_rect = rects[2 + self.anim_num as usize];
         }
 
         if self.action_counter2 > 550 {
@@ -268,7 +250,7 @@ impl NPC {
         }
 
         if self.action_counter2 > 547 {
-            self.anim_rect = state.constants.npc.n087_heart_pickup[4];
+            self.anim_rect = rects[4];
         }
 
         Ok(())
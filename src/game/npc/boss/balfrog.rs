@@ -1,7 +1,7 @@
 use crate::common::{CDEG_RAD, Direction, Rect};
 use crate::framework::error::GameResult;
 use crate::game::caret::CaretType;
-use crate::game::npc::boss::BossNPC;
+use crate::game::npc::boss::{BossNPC, PHASE_TRANSITION_ACTION};
 use crate::game::npc::list::NPCList;
 use crate::game::npc::NPC;
 use crate::game::player::Player;
@@ -49,6 +49,10 @@ impl BossNPC {
                 self.parts[0].npc_flags.set_event_when_killed(true);
                 self.parts[0].npc_flags.set_show_damage(true);
                 self.parts[0].life = 300;
+
+                if state.settings.boss_phases {
+                    self.phase_thresholds = [150, 0, 0];
+                }
             }
             10 => {
                 self.parts[0].action_num = 11;
@@ -97,8 +101,11 @@ impl BossNPC {
                     self.parts[0].vel_x = 0;
                 }
 
+                // jumps more often once enraged
+                let wait_ticks = if self.current_phase > 0 { 30 } else { 50 };
+
                 self.parts[0].action_counter += 1;
-                if self.parts[0].action_counter > 50 {
+                if self.parts[0].action_counter > wait_ticks {
                     self.parts[0].action_num = 102;
                     self.parts[0].anim_counter = 0;
                     self.parts[0].anim_num = 2;
@@ -223,6 +230,7 @@ impl BossNPC {
                 }
 
                 self.parts[0].vel_x = self.parts[0].vel_x * 10 / 11;
+                self.shootable_closing[1] = self.parts[0].vel_x2 <= 3;
 
                 self.parts[0].action_counter += 1;
                 if self.parts[0].action_counter > 16 {
@@ -461,6 +469,22 @@ impl BossNPC {
                     state.quake_rumble_counter = 30;
                 }
             }
+            // enraged, croaks while shaking then goes back to jumping
+            PHASE_TRANSITION_ACTION => {
+                self.parts[0].vel_x = 0;
+                self.parts[0].anim_num = 3;
+                self.parts[0].action_num = PHASE_TRANSITION_ACTION + 1;
+
+                state.sound_manager.play_sfx(72);
+            }
+            201 => {
+                self.parts[0].action_counter += 1;
+                self.parts[0].anim_num = if (self.parts[0].action_counter / 2 % 2) != 0 { 3 } else { 0 };
+
+                if self.parts[0].action_counter > 60 {
+                    self.parts[0].action_num = 100;
+                }
+            }
             _ => {}
         }
 
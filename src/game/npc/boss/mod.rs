@@ -1,11 +1,14 @@
 use std::mem::{MaybeUninit, transmute};
 use std::ops::Deref;
 
-use crate::common::{Direction, interpolate_fix9_scale};
+use crate::common::{Color, Direction, interpolate_fix9_scale, Rect};
 use crate::components::flash::Flash;
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
+use crate::framework::graphics;
+use crate::framework::graphics::BlendMode;
+use crate::game::events::GameEvent;
 use crate::game::frame::Frame;
 use crate::game::npc::list::NPCList;
 use crate::game::npc::NPC;
@@ -24,11 +27,32 @@ pub mod omega;
 pub mod sisters;
 pub mod undead_core;
 
+/// Action number set on the main part when the boss enters a new phase.
+pub const PHASE_TRANSITION_ACTION: u16 = 200;
+/// How long a part blinks after becoming shootable, see `Settings::boss_weak_points`.
+const SHOOTABLE_BLINK_TICKS: u8 = 50;
+
 pub struct BossNPC {
     pub boss_type: u16,
     pub parts: [NPC; 20],
     pub hurt_sound: [u8; 20],
     pub death_sound: [u8; 20],
+    /// Current phase, 0 is the initial one.
+    pub current_phase: u8,
+    /// Life of the main part at which the boss enters phase n + 1, 0 if unused. Bosses only set these when
+    /// `Settings::boss_phases` is on, so they behave like in the original game by default.
+    pub phase_thresholds: [u16; 3],
+    /// Song played when the boss enters phase n + 1.
+    pub phase_music: [Option<u8>; 3],
+    /// Song to be played by the scene on the next tick.
+    pub pending_song: Option<u8>,
+    /// Set on parts which became shootable, cleared once they stop being shootable.
+    pub shootable_blink: [bool; 20],
+    /// Ticks since the part became shootable.
+    pub blink_timer: [u8; 20],
+    /// Set by the boss when the shootable window of a part is about to close, making it blink faster.
+    pub shootable_closing: [bool; 20],
+    prev_shootable: [bool; 20],
 }
 
 impl BossNPC {
@@ -50,7 +74,215 @@ impl BossNPC {
 
         parts[0].cond.set_alive(true);
 
-        BossNPC { boss_type: 0, parts, hurt_sound: [0; 20], death_sound: [0; 20] }
+        BossNPC {
+            boss_type: 0,
+            parts,
+            hurt_sound: [0; 20],
+            death_sound: [0; 20],
+            current_phase: 0,
+            phase_thresholds: [0; 3],
+            phase_music: [None; 3],
+            pending_song: None,
+            shootable_blink: [false; 20],
+            blink_timer: [0; 20],
+            shootable_closing: [false; 20],
+            prev_shootable: [false; 20],
+        }
+    }
+
+    /// Clears the phase state left over from the previous boss, called when a new stage is loaded.
+    pub fn reset_phases(&mut self) {
+        self.current_phase = 0;
+        self.phase_thresholds = [0; 3];
+        self.phase_music = [None; 3];
+        self.pending_song = None;
+    }
+
+    /// Switches the boss to given phase. The tick function of the boss is expected to handle
+    /// [PHASE_TRANSITION_ACTION] and following action numbers as the transition animation.
+    pub fn transition_phase(&mut self, next_phase: u8, state: &mut SharedGameState, flash: &mut Flash) {
+        self.current_phase = next_phase;
+        self.parts[0].action_num = PHASE_TRANSITION_ACTION;
+        self.parts[0].action_counter = 0;
+
+        flash.set_blink();
+        state.quake_counter = 30;
+
+        if let Some(&Some(song_id)) = self.phase_music.get(next_phase as usize - 1) {
+            self.pending_song = Some(song_id);
+        }
+    }
+
+    fn tick_phases(&mut self, state: &mut SharedGameState, flash: &mut Flash) {
+        let (action_num, life) = (self.parts[0].action_num, self.parts[0].life);
+        if action_num == 0 || action_num >= PHASE_TRANSITION_ACTION || life == 0 {
+            return;
+        }
+
+        let threshold = self.phase_thresholds.get(self.current_phase as usize).copied().unwrap_or(0);
+        if threshold != 0 && life <= threshold {
+            self.transition_phase(self.current_phase + 1, state, flash);
+        }
+    }
+
+    fn tick_shootable_blink(&mut self) {
+        for (i, part) in self.parts.iter().enumerate() {
+            let shootable = part.cond.alive() && part.npc_flags.shootable();
+
+            if shootable && !self.prev_shootable[i] {
+                self.shootable_blink[i] = true;
+                self.blink_timer[i] = 0;
+            } else if !shootable {
+                self.shootable_blink[i] = false;
+                self.shootable_closing[i] = false;
+            }
+
+            self.prev_shootable[i] = shootable;
+            self.blink_timer[i] = self.blink_timer[i].saturating_add(1);
+        }
+    }
+
+    /// Returns true if the part should be drawn highlighted this tick. Parts blink every 4 ticks
+    /// right after becoming shootable and every 2 ticks once their window is about to close.
+    fn is_highlighted(&self, i: usize) -> bool {
+        if !self.shootable_blink[i] {
+            return false;
+        }
+
+        if self.shootable_closing[i] {
+            (self.blink_timer[i] / 2) % 2 == 0
+        } else {
+            self.blink_timer[i] < SHOOTABLE_BLINK_TICKS && (self.blink_timer[i] / 4) % 2 == 0
+        }
+    }
+
+    /// Highlights parts which just became shootable, drawn over the regular sprites.
+    fn draw_shootable_blink(&self, state: &mut SharedGameState, ctx: &mut Context, frame: &Frame) -> GameResult {
+        let highlighted: Vec<(usize, &NPC, f32, f32)> = (0..self.parts.len())
+            .filter(|&i| self.is_highlighted(i) && !self.parts[i].cond.hidden())
+            .map(|i| {
+                let (x, y) = self.parts[i].draw_pos(state, frame);
+                (i, &self.parts[i], x, y)
+            })
+            .collect();
+        if highlighted.is_empty() {
+            return Ok(());
+        }
+
+        let batch = state.texture_set.get_or_load_batch(
+            ctx,
+            &state.constants,
+            &state.npc_table.stage_textures.deref().borrow().npc2,
+        )?;
+
+        for &(_, part, x, y) in &highlighted {
+            batch.add_rect_tinted(x, y, (96, 96, 96, 255), &part.anim_rect);
+        }
+
+        graphics::set_blend_mode(ctx, BlendMode::Add)?;
+        batch.draw(ctx)?;
+        graphics::set_blend_mode(ctx, BlendMode::Alpha)?;
+
+        for &(i, part, x, y) in &highlighted {
+            let off_x =
+                if part.direction == Direction::Left { part.display_bounds.left } else { part.display_bounds.right };
+            let center_x = x + off_x as f32 / 512.0;
+            let center_y = y + part.display_bounds.top as f32 / 512.0;
+            // pulses between dim and bright every 16 ticks
+            let alpha = 96 + (self.blink_timer[i] % 16) * 8;
+
+            graphics::draw_outline_rect(
+                ctx,
+                Rect::new(
+                    ((center_x - part.hit_bounds.left as f32 / 512.0) * state.scale) as isize,
+                    ((center_y - part.hit_bounds.top as f32 / 512.0) * state.scale) as isize,
+                    ((center_x + part.hit_bounds.right as f32 / 512.0) * state.scale) as isize,
+                    ((center_y + part.hit_bounds.bottom as f32 / 512.0) * state.scale) as isize,
+                ),
+                state.scale as usize,
+                Color::from_rgba(255, 255, 255, alpha),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Damages shootable parts within `radius` from given point, handled like a bullet hit. Parts passing damage
+    /// to the main part only hurt it once, however many of them are in range.
+    pub fn area_damage(
+        &mut self,
+        x: i32,
+        y: i32,
+        radius: i32,
+        damage: u16,
+        state: &mut SharedGameState,
+        npc_list: &NPCList,
+    ) {
+        let radius = radius as i64;
+        let mut main_part_hit = false;
+
+        for i in 0..self.parts.len() {
+            let part = &self.parts[i];
+            if !part.cond.alive() || !part.npc_flags.shootable() {
+                continue;
+            }
+
+            let (dx, dy) = ((part.x - x) as i64, (part.y - y) as i64);
+            if dx * dx + dy * dy > radius * radius {
+                continue;
+            }
+
+            let shock = part.shock;
+            let idx = if part.cond.damage_boss() { 0 } else { i };
+            if idx == 0 {
+                if main_part_hit {
+                    continue;
+                }
+                main_part_hit = true;
+            }
+
+            let npc = &mut self.parts[idx];
+            npc.life = npc.life.saturating_sub(damage);
+
+            if npc.life == 0 {
+                npc.life = npc.id;
+
+                if idx == 0 {
+                    state.event_bus.emit(GameEvent::BossDefeated { boss_id: self.boss_type as u8 });
+                }
+
+                if npc.npc_flags.event_when_killed() {
+                    state.control_flags.set_tick_world(true);
+                    state.control_flags.set_interactions_disabled(true);
+                    state.textscript_vm.start_script(npc.event_num);
+                } else {
+                    state.sound_manager.play_sfx(self.death_sound[idx]);
+
+                    let destroy_count = 4usize * (2usize).pow((npc.size as u32).saturating_sub(1));
+
+                    npc_list.create_death_smoke(
+                        npc.x,
+                        npc.y,
+                        npc.display_bounds.right as usize,
+                        destroy_count,
+                        state,
+                        &npc.rng,
+                    );
+                    npc.cond.set_alive(false);
+                }
+            } else {
+                if shock < 14 {
+                    state.sound_manager.play_sfx(self.hurt_sound[idx]);
+                }
+
+                npc.shock = 8;
+                if npc.npc_flags.show_damage() {
+                    npc.popup.add_value(-(damage.min(i16::MAX as u16) as i16));
+                }
+
+                self.parts[i].shock = 8;
+            }
+        }
     }
 
     pub fn init_rng(&mut self, seed: i32) {
@@ -86,6 +318,8 @@ impl GameEntity<([&mut Player; 2], &NPCList, &mut Stage, &BulletManager, &mut Fl
             return Ok(());
         }
 
+        self.tick_phases(state, flash);
+
         match self.boss_type {
             1 => self.tick_b01_omega(state, players, npc_list, bullet_manager, flash),
             2 => self.tick_b02_balfrog(state, players, npc_list),
@@ -99,6 +333,8 @@ impl GameEntity<([&mut Player; 2], &NPCList, &mut Stage, &BulletManager, &mut Fl
             _ => {}
         }
 
+        self.tick_shootable_blink();
+
         for part in &mut self.parts {
             if part.shock > 0 {
                 part.shock -= 1;
@@ -144,6 +380,10 @@ impl GameEntity<([&mut Player; 2], &NPCList, &mut Stage, &BulletManager, &mut Fl
 
         batch.draw(ctx)?;
 
+        if state.settings.boss_weak_points {
+            self.draw_shootable_blink(state, ctx, frame)?;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,149 @@
+use crate::common::Direction;
+use crate::framework::error::GameResult;
+use crate::game::caret::CaretType;
+use crate::game::npc::list::NPCList;
+use crate::game::npc::NPC;
+use crate::game::shared_game_state::SharedGameState;
+use crate::game::weapon::bullet::{Bullet, BulletManager};
+
+/// Default faction, hit by the player's bullets only.
+pub const FACTION_NEUTRAL: u8 = 0;
+/// Fights NPCs from [FACTION_ALLY].
+pub const FACTION_ENEMY: u8 = 1;
+/// Fights NPCs from [FACTION_ENEMY], can't be hurt by the player.
+pub const FACTION_ALLY: u8 = 2;
+
+/// Action number of the generic AI state in which an NPC chases and shoots the nearest hostile NPC.
+pub const FACTION_ATTACK_ACTION: u16 = 500;
+
+const ATTACK_SPEED: i32 = 0x200;
+const ATTACK_FIRE_RANGE: i32 = 0x8000;
+const ATTACK_FIRE_DELAY: u16 = 40;
+/// Machine Gun level 3 bullet, also used by Curly's AI.
+const ATTACK_BULLET_TYPE: u16 = 12;
+
+/// Returns true if NPCs from given factions fight each other.
+pub fn is_hostile(faction: u8, other: u8) -> bool {
+    matches!((faction, other), (FACTION_ENEMY, FACTION_ALLY) | (FACTION_ALLY, FACTION_ENEMY))
+}
+
+/// Returns true if a bullet shot by given faction can damage an NPC, player bullets are shot by [FACTION_NEUTRAL].
+pub fn bullet_can_hit(source_faction: u8, target_faction: u8) -> bool {
+    if source_faction == FACTION_NEUTRAL {
+        target_faction != FACTION_ALLY
+    } else {
+        is_hostile(source_faction, target_faction)
+    }
+}
+
+impl NPC {
+    /// Returns the nearest alive NPC hostile to this one within it's aggro range.
+    fn find_hostile_npc<'a>(&self, npc_list: &'a NPCList) -> Option<&'a mut NPC> {
+        let range = self.aggro_range as i64;
+
+        npc_list
+            .iter_alive()
+            .filter(|npc| npc.id != self.id && is_hostile(self.faction, npc.faction))
+            .map(|npc| {
+                let dx = (npc.x - self.x) as i64;
+                let dy = (npc.y - self.y) as i64;
+
+                (dx * dx + dy * dy, npc)
+            })
+            .filter(|&(dist_sq, _)| range == 0 || dist_sq <= range * range)
+            .min_by_key(|&(dist_sq, _)| dist_sq)
+            .map(|(_, npc)| npc)
+    }
+
+    /// Switches NPCs of a faction to [FACTION_ATTACK_ACTION] once a hostile NPC comes within their aggro range.
+    pub(crate) fn tick_faction_aggro(&mut self, npc_list: &NPCList) {
+        if self.faction == FACTION_NEUTRAL || self.action_num == FACTION_ATTACK_ACTION {
+            return;
+        }
+
+        if self.find_hostile_npc(npc_list).is_some() {
+            self.action_num = FACTION_ATTACK_ACTION;
+            self.action_counter = 0;
+        }
+    }
+
+    /// Chases and shoots the nearest hostile NPC, the NPC goes back to its own AI from action 0 once there's
+    /// nothing left to fight.
+    pub(crate) fn tick_faction_attack(
+        &mut self,
+        state: &mut SharedGameState,
+        npc_list: &NPCList,
+        bullet_manager: &mut BulletManager,
+    ) -> GameResult {
+        self.action_counter = self.action_counter.saturating_add(1);
+
+        let (target_x, target_y) = if let Some(target) = self.find_hostile_npc(npc_list) {
+            (target.x, target.y)
+        } else {
+            self.vel_x = 0;
+            self.vel_y = 0;
+            self.action_num = 0;
+            return Ok(());
+        };
+
+        let dx = target_x - self.x;
+        let dy = target_y - self.y;
+
+        self.direction = if dx < 0 { Direction::Left } else { Direction::Right };
+
+        if dx.abs() > ATTACK_FIRE_RANGE || dy.abs() > ATTACK_FIRE_RANGE {
+            let angle = f64::atan2(dy as f64, dx as f64);
+
+            self.vel_x = (angle.cos() * ATTACK_SPEED as f64) as i32;
+            self.vel_y = (angle.sin() * ATTACK_SPEED as f64) as i32;
+        } else {
+            self.vel_x = 0;
+            self.vel_y = 0;
+
+            if self.action_counter >= ATTACK_FIRE_DELAY {
+                self.action_counter = 0;
+
+                let direction = if dx.abs() >= dy.abs() {
+                    self.direction
+                } else if dy < 0 {
+                    Direction::Up
+                } else {
+                    Direction::Bottom
+                };
+
+                let bullet = Bullet::new_npc(self, ATTACK_BULLET_TYPE, direction, &state.constants);
+
+                bullet_manager.push_bullet(bullet);
+                state.create_caret(self.x, self.y, CaretType::Shoot, Direction::Left);
+            }
+        }
+
+        self.x += self.vel_x;
+        self.y += self.vel_y;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_hostile() {
+        assert!(is_hostile(FACTION_ENEMY, FACTION_ALLY));
+        assert!(is_hostile(FACTION_ALLY, FACTION_ENEMY));
+        assert!(!is_hostile(FACTION_ENEMY, FACTION_ENEMY));
+        assert!(!is_hostile(FACTION_NEUTRAL, FACTION_ENEMY));
+    }
+
+    #[test]
+    fn test_bullet_can_hit() {
+        assert!(bullet_can_hit(FACTION_NEUTRAL, FACTION_NEUTRAL));
+        assert!(bullet_can_hit(FACTION_NEUTRAL, FACTION_ENEMY));
+        assert!(!bullet_can_hit(FACTION_NEUTRAL, FACTION_ALLY));
+        assert!(bullet_can_hit(FACTION_ALLY, FACTION_ENEMY));
+        assert!(!bullet_can_hit(FACTION_ALLY, FACTION_NEUTRAL));
+        assert!(bullet_can_hit(FACTION_ENEMY, FACTION_ALLY));
+    }
+}
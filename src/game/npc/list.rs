@@ -1,12 +1,73 @@
 use std::cell::{Cell, UnsafeCell};
+use std::collections::HashMap;
 use std::mem::{MaybeUninit, transmute};
 
+use crate::common::Rect;
 use crate::framework::error::{GameError, GameResult};
 use crate::game::npc::NPC;
 
 /// Maximum capacity of NPCList
 const NPC_LIST_MAX_CAP: usize = 512;
 
+/// Size of a spatial grid cell, 64 pixels in fixed point units.
+const GRID_CELL_SIZE: i32 = 0x8000;
+
+/// Buckets NPCs by the grid cells their hitbox overlaps, so collision checks can skip NPCs which are far away.
+pub struct SpatialGrid {
+    pub cell_size: i32,
+    cells: HashMap<(i32, i32), Vec<u16>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: i32) -> SpatialGrid {
+        SpatialGrid { cell_size, cells: HashMap::new() }
+    }
+
+    /// Empties all cells, keeping their allocations around for the next rebuild.
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    /// Returns the range of cells (inclusive) overlapping `bounds` extended by `margin` cells.
+    fn cell_range(&self, bounds: &Rect<i32>, margin: i32) -> Rect<i32> {
+        Rect {
+            left: bounds.left.div_euclid(self.cell_size) - margin,
+            top: bounds.top.div_euclid(self.cell_size) - margin,
+            right: bounds.right.div_euclid(self.cell_size) + margin,
+            bottom: bounds.bottom.div_euclid(self.cell_size) + margin,
+        }
+    }
+
+    pub fn insert(&mut self, id: u16, bounds: &Rect<i32>) {
+        let range = self.cell_range(bounds, 0);
+
+        for y in range.top..=range.bottom {
+            for x in range.left..=range.right {
+                self.cells.entry((x, y)).or_default().push(id);
+            }
+        }
+    }
+
+    /// Collects IDs of NPCs in cells overlapping `bounds` extended by `margin` cells, sorted in ascending order.
+    pub fn query(&self, bounds: &Rect<i32>, margin: i32, ids: &mut Vec<u16>) {
+        let range = self.cell_range(bounds, margin);
+
+        for y in range.top..=range.bottom {
+            for x in range.left..=range.right {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    ids.extend_from_slice(cell);
+                }
+            }
+        }
+
+        // keep the order of a linear scan, the outcome of collisions depends on it
+        ids.sort_unstable();
+        ids.dedup();
+    }
+}
+
 /// A data structure for storing an NPC list for current stage.
 /// Provides multiple mutable references to NPC objects with internal sanity checks and lifetime bounds.
 pub struct NPCList {
@@ -15,6 +76,7 @@ pub struct NPCList {
     npcs: Box<UnsafeCell<[NPC; NPC_LIST_MAX_CAP]>>,
     max_npc: Cell<u16>,
     seed: i32,
+    grid: SpatialGrid,
 }
 
 #[allow(dead_code)]
@@ -33,6 +95,7 @@ impl NPCList {
             })),
             max_npc: Cell::new(0),
             seed: 0,
+            grid: SpatialGrid::new(GRID_CELL_SIZE),
         };
 
         unsafe {
@@ -59,7 +122,8 @@ impl NPCList {
         for id in min_id..(npc_len as u16) {
             let npc_ref = unsafe { self.npcs_mut().get_unchecked_mut(id as usize) };
 
-            if !npc_ref.cond.alive() {
+            // dead NPCs waiting to respawn keep their slot
+            if !npc_ref.cond.alive() && npc_ref.respawn_timer == 0 {
                 npc.id = id;
 
                 if npc.tsc_direction == 0 {
@@ -124,6 +188,34 @@ impl NPCList {
         NPCListMutableAliveIterator::new(self)
     }
 
+    /// Rebuilds the spatial grid from hitboxes of alive NPCs, see `iter_alive_near`.
+    pub fn rebuild_grid(&mut self) {
+        let mut grid = std::mem::replace(&mut self.grid, SpatialGrid::new(GRID_CELL_SIZE));
+        grid.clear();
+
+        for npc in self.iter_alive() {
+            let bounds = Rect {
+                left: npc.x - npc.hit_bounds.left as i32,
+                top: npc.y - npc.hit_bounds.top as i32,
+                right: npc.x + npc.hit_bounds.right as i32,
+                bottom: npc.y + npc.hit_bounds.bottom as i32,
+            };
+
+            grid.insert(npc.id, &bounds);
+        }
+
+        self.grid = grid;
+    }
+
+    /// Returns an iterator over alive NPCs whose hitbox was within a grid cell of `bounds`
+    /// when `rebuild_grid` was last called. NPCs are visited in the same order as `iter_alive`.
+    pub fn iter_alive_near<'a>(&'a self, bounds: &Rect<i32>) -> impl Iterator<Item = &'a mut NPC> + 'a {
+        let mut ids = Vec::new();
+        self.grid.query(bounds, 1, &mut ids);
+
+        ids.into_iter().filter_map(move |id| self.get_npc(id as usize)).filter(|npc| npc.cond.alive())
+    }
+
     /// Removes all NPCs from this list and resets it's capacity.
     pub fn clear(&self) {
         for (idx, npc) in self.iter_alive().enumerate() {
@@ -274,3 +366,33 @@ pub fn test_npc_list() -> GameResult {
 
     Ok(())
 }
+
+#[test]
+fn test_spatial_grid() -> GameResult {
+    let mut npc = NPC::empty();
+    npc.cond.set_alive(true);
+    npc.hit_bounds = Rect { left: 0x1000, top: 0x1000, right: 0x1000, bottom: 0x1000 };
+
+    let mut map = Box::new(NPCList::new());
+
+    for (id, x) in [(0, 0x10000), (1, 0x100000), (2, 0x12000), (3, -0x8000)] {
+        npc.x = x;
+        map.spawn_at_slot(id, npc.clone())?;
+    }
+
+    map.rebuild_grid();
+
+    let player = Rect { left: 0xf000, top: -0x1000, right: 0x11000, bottom: 0x1000 };
+    let near: Vec<u16> = map.iter_alive_near(&player).map(|npc| npc.id).collect();
+    assert_eq!(near, vec![0, 2]);
+
+    map.get_npc(2).unwrap().cond.set_alive(false);
+    let near: Vec<u16> = map.iter_alive_near(&player).map(|npc| npc.id).collect();
+    assert_eq!(near, vec![0]);
+
+    let player = Rect { left: -0xa000, top: -0x1000, right: -0x9000, bottom: 0x1000 };
+    let near: Vec<u16> = map.iter_alive_near(&player).map(|npc| npc.id).collect();
+    assert_eq!(near, vec![3]);
+
+    Ok(())
+}
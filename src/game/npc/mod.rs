@@ -18,16 +18,20 @@ use crate::framework::error::GameResult;
 use crate::game::frame::Frame;
 use crate::game::npc::boss::BossNPC;
 use crate::game::npc::list::NPCList;
-use crate::game::physics::PhysicalEntity;
+use crate::game::physics::{ice_velocity, PhysicalEntity, CLIMB_SPEED};
 use crate::game::player::Player;
 use crate::game::shared_game_state::SharedGameState;
 use crate::game::stage::{Stage, StageTexturePaths};
 use crate::game::weapon::bullet::BulletManager;
+use crate::graphics::texture_set::SpriteBatch;
+use crate::sound::SoundManager;
 use crate::util::rng::Xoroshiro32PlusPlus;
 
 pub mod ai;
 pub mod boss;
+pub mod faction;
 pub mod list;
+pub mod pathfinding;
 pub mod utils;
 
 bitfield! {
@@ -68,6 +72,32 @@ bitfield! {
     pub show_damage, set_show_damage: 15;
 }
 
+impl NPCFlag {
+    const NAMES: [&'static str; 16] = [
+        "solid_soft",
+        "ignore_tile_44",
+        "invulnerable",
+        "ignore_solidity",
+        "bouncy",
+        "shootable",
+        "solid_hard",
+        "rear_and_top_not_hurt",
+        "event_when_touched",
+        "event_when_killed",
+        "flag_x400",
+        "appear_when_flag_set",
+        "spawn_facing_right",
+        "interactable",
+        "hide_unless_flag_set",
+        "show_damage",
+    ];
+
+    /// Returns names of all set flags, used by debug overlays.
+    pub fn names(&self) -> Vec<&'static str> {
+        Self::NAMES.iter().enumerate().filter(|&(bit, _)| self.0 & (1 << bit) != 0).map(|(_, &name)| name).collect()
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialOrd, PartialEq)]
 #[repr(u8)]
 pub enum NPCLayer {
@@ -76,6 +106,17 @@ pub enum NPCLayer {
     Foreground = 2,
 }
 
+/// Ladder climbing state of NPC types listed as climbers, see [NPC::tick_climb].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NPCClimbState {
+    None,
+    Up,
+    /// Going back down a ladder which runs into a ceiling.
+    Down,
+    /// Let go at the bottom, the ladder isn't grabbed again until the NPC moves away from it.
+    Dismounted,
+}
+
 /// Represents an NPC object.
 #[derive(Debug, Clone)]
 #[repr(C)]
@@ -127,6 +168,37 @@ pub struct NPC {
     pub rng: Xoroshiro32PlusPlus,
     pub popup: NumberPopup,
     pub splash: bool,
+    /// Remaining waypoints (in tiles) of the path followed by [NPC::follow_path]
+    pub path: Option<Vec<(i32, i32)>>,
+    pub path_tick: u64,
+    /// Maximum distance at which [NPC::has_los_to_player] can see the player, 0 means no limit.
+    /// None if the NPC notices the player through walls like in the original game, see [NPC::notices_player].
+    pub sight_range: Option<u32>,
+    /// Position the NPC was placed at by the map, used when respawning.
+    pub spawn_x: i32,
+    pub spawn_y: i32,
+    /// Amount of ticks spent away from players after which a dead NPC comes back, 0 disables respawning.
+    pub respawn_ticks: u16,
+    pub respawn_timer: u16,
+    /// See [faction::FACTION_NEUTRAL], [faction::FACTION_ENEMY] and [faction::FACTION_ALLY].
+    pub faction: u8,
+    /// Distance at which NPCs of hostile factions are noticed, 0 means no limit.
+    pub aggro_range: u32,
+    /// Opts the NPC out of being pushed by wind and water current tiles, set for all but a few types.
+    pub ignore_forces: bool,
+    /// Set while the NPC shows a warning before attacking, see [NPC::start_telegraph].
+    pub telegraph_active: bool,
+    pub telegraph_timer: u8,
+    /// Set for NPCs listed as tile aware in the NPC table while they stand on ice, see [ice_velocity].
+    pub on_ice: bool,
+    /// Speed of the conveyor belt a tile aware NPC stands on, added to its position each tick.
+    pub conveyor_vel_x: i32,
+    /// Ladder climbing state of NPCs listed as climbers in the NPC table.
+    pub climb_state: NPCClimbState,
+    /// Direction the barrel drawn over the NPC points at, see [NPCTableEntry::barrel_sprite].
+    pub aim_angle_rad: f32,
+    /// How fast the barrel turns towards the closest player, 0 disables aiming.
+    pub aim_speed_rad_per_tick: f32,
 }
 
 impl NPC {
@@ -171,6 +243,23 @@ impl NPC {
             rng: Xoroshiro32PlusPlus::new(0),
             popup: NumberPopup::new(),
             splash: false,
+            path: None,
+            path_tick: 0,
+            sight_range: None,
+            spawn_x: 0,
+            spawn_y: 0,
+            respawn_ticks: 0,
+            respawn_timer: 0,
+            faction: 0,
+            aggro_range: 0,
+            ignore_forces: true,
+            telegraph_active: false,
+            telegraph_timer: 0,
+            on_ice: false,
+            conveyor_vel_x: 0,
+            climb_state: NPCClimbState::None,
+            aim_angle_rad: 0.0,
+            aim_speed_rad_per_tick: 0.0,
         }
     }
 
@@ -219,6 +308,69 @@ impl NPC {
         Ok(())
     }
 
+    /// Adds the sprite to the batch of its spritesheet without drawing it, so consecutive NPCs sharing
+    /// a spritesheet can be drawn at once with `draw_batch`. Returns false if the NPC has to be drawn on its own.
+    pub fn queue_draw(&self, state: &mut SharedGameState, ctx: &mut Context, frame: &Frame) -> GameResult<bool> {
+        if self.is_sue() && state.more_rust {
+            return Ok(false);
+        }
+
+        if !self.cond.alive() || self.cond.hidden() {
+            return Ok(true);
+        }
+
+        let (final_x, final_y) = self.draw_pos(state, frame);
+        let texture_ref = state.npc_table.get_texture_ref(self.spritesheet_id);
+        let barrel = state.npc_table.get_barrel(self.npc_type);
+        let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, &*texture_ref)?;
+        batch.add_rect(final_x, final_y, &self.anim_rect);
+        self.add_barrel(batch, barrel, final_x, final_y);
+
+        Ok(true)
+    }
+
+    /// Draws sprites queued with `queue_draw` for given spritesheet.
+    pub fn draw_batch(spritesheet_id: u16, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let texture_ref = state.npc_table.get_texture_ref(spritesheet_id);
+
+        state.texture_set.get_or_load_batch(ctx, &state.constants, &*texture_ref)?.draw(ctx)
+    }
+
+    fn draw_pos(&self, state: &SharedGameState, frame: &Frame) -> (f32, f32) {
+        let off_x =
+            if self.direction == Direction::Left { self.display_bounds.left } else { self.display_bounds.right } as i32;
+        let shock = if self.shock > 0 { (2 * ((self.shock as i32 / 2) % 2) - 1) as f32 } else { 0.0 };
+
+        let (frame_x, frame_y) = frame.xy_interpolated(state.frame_time);
+
+        let final_x = interpolate_fix9_scale(self.prev_x - off_x, self.x - off_x, state.frame_time) + shock - frame_x;
+        let final_y = interpolate_fix9_scale(
+            self.prev_y - self.display_bounds.top as i32,
+            self.y - self.display_bounds.top as i32,
+            state.frame_time,
+        ) - frame_y;
+
+        (final_x, final_y)
+    }
+
+    /// Adds the barrel sprite rotated by `aim_angle_rad`, with its pivot placed at the center of the NPC.
+    fn add_barrel(&self, batch: &mut dyn SpriteBatch, barrel: Option<(Rect<u16>, (isize, isize))>, x: f32, y: f32) {
+        if let Some((rect, (pivot_x, pivot_y))) = barrel {
+            let off_x =
+                if self.direction == Direction::Left { self.display_bounds.left } else { self.display_bounds.right };
+            let center_x = x + off_x as f32 / 512.0;
+            let center_y = y + self.display_bounds.top as f32 / 512.0;
+
+            batch.add_rect_rotated(
+                center_x - pivot_x as f32,
+                center_y - pivot_y as f32,
+                self.aim_angle_rad,
+                (pivot_x as f32, pivot_y as f32),
+                &rect,
+            );
+        }
+    }
+
     fn is_sue(&self) -> bool {
         [42, 92, 280, 284].contains(&self.npc_type)
     }
@@ -249,8 +401,28 @@ impl GameEntity<([&mut Player; 2], &NPCList, &mut Stage, &mut BulletManager, &mu
                 npc_hook_ran = state.lua.try_run_npc_hook(self.id, self.npc_type);
             }
 
-        match self.npc_type {
+        // sounds played by the NPC come from its side of the screen
+        let pan_range = state.constants.game.sfx_pan_range;
+        if pan_range != 0 {
+            let player = self.get_closest_player_ref(&players);
+            state.sound_manager.set_sfx_pan(SoundManager::pan(self.x, player.x, pan_range));
+        }
+
+        if self.aim_speed_rad_per_tick > 0.0 {
+            self.tick_aim(&players);
+        }
+
+        let vel_x_before_ai = self.vel_x;
+
+        if !npc_hook_ran {
+            self.tick_faction_aggro(npc_list);
+        }
+
+        let result = match self.npc_type {
             _ if npc_hook_ran => Ok(()),
+            _ if self.action_num == faction::FACTION_ATTACK_ACTION && self.faction != faction::FACTION_NEUTRAL => {
+                self.tick_faction_attack(state, npc_list, bullet_manager)
+            }
             0 => self.tick_n000_null(),
             1 => self.tick_n001_experience(state, stage),
             2 => self.tick_n002_behemoth(state, npc_list),
@@ -315,7 +487,7 @@ impl GameEntity<([&mut Player; 2], &NPCList, &mut Stage, &mut BulletManager, &mu
             61 => self.tick_n061_king(state, npc_list),
             62 => self.tick_n062_kazuma_computer(state),
             63 => self.tick_n063_toroko_stick(state),
-            64 => self.tick_n064_first_cave_critter(state, players),
+            64 => self.tick_n064_first_cave_critter(state, players, stage),
             65 => self.tick_n065_first_cave_bat(state, players),
             66 => self.tick_n066_misery_bubble(state, npc_list),
             67 => self.tick_n067_misery_floating(state, npc_list, flash),
@@ -622,8 +794,34 @@ impl GameEntity<([&mut Player; 2], &NPCList, &mut Stage, &mut BulletManager, &mu
             368 => self.tick_n368_gclone(state, players, npc_list),
             369 => self.tick_n369_gclone_curly_clone(state, players, npc_list),
             370 => self.tick_n370_second_quote(state, players, npc_list),
+            371 => self.tick_n371_checkpoint(state, players),
+            372 => self.tick_n372_pathfinding_patroller(state, stage),
+            373 => self.tick_n373_antidote(),
+            374 => self.tick_n374_revive_orb(state),
+            375 => self.tick_n375_turret(state, players, npc_list),
             _ => Ok(()),
-        }?;
+        };
+
+        if pan_range != 0 {
+            state.sound_manager.set_sfx_pan(0.0);
+        }
+        result?;
+
+        if self.on_ice {
+            self.vel_x = ice_velocity(vel_x_before_ai, self.vel_x, &state.constants.world);
+        }
+        self.x += self.conveyor_vel_x;
+
+        match self.climb_state {
+            NPCClimbState::Up => self.vel_y = -CLIMB_SPEED,
+            NPCClimbState::Down => self.vel_y = CLIMB_SPEED,
+            NPCClimbState::None | NPCClimbState::Dismounted => {}
+        }
+
+        if self.telegraph_active {
+            self.telegraph_timer = self.telegraph_timer.saturating_sub(1);
+            self.telegraph_active = self.telegraph_timer != 0;
+        }
 
         // I don't know where the best place to put this is, but let's try putting it here
         if self.shock == 0 && self.npc_flags.show_damage() && self.popup.value != 0 {
@@ -653,29 +851,19 @@ impl GameEntity<([&mut Player; 2], &NPCList, &mut Stage, &mut BulletManager, &mu
             return Ok(());
         }
 
+        let (final_x, final_y) = self.draw_pos(state, frame);
         let texture_ref = state.npc_table.get_texture_ref(self.spritesheet_id);
+        let barrel = state.npc_table.get_barrel(self.npc_type);
 
         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, &*texture_ref)?;
 
-        let off_x =
-            if self.direction == Direction::Left { self.display_bounds.left } else { self.display_bounds.right } as i32;
-        let shock = if self.shock > 0 { (2 * ((self.shock as i32 / 2) % 2) - 1) as f32 } else { 0.0 };
-
-        let (frame_x, frame_y) = frame.xy_interpolated(state.frame_time);
-
-        let final_x = interpolate_fix9_scale(self.prev_x - off_x, self.x - off_x, state.frame_time) + shock - frame_x;
-        let final_y = interpolate_fix9_scale(
-            self.prev_y - self.display_bounds.top as i32,
-            self.y - self.display_bounds.top as i32,
-            state.frame_time,
-        ) - frame_y;
-
         if self.is_sue() && state.more_rust {
             // tint sue blue
             batch.add_rect_tinted(final_x, final_y, (200, 200, 255, 255), &self.anim_rect);
             batch.draw(ctx)?;
         } else {
             batch.add_rect(final_x, final_y, &self.anim_rect);
+            self.add_barrel(batch, barrel, final_x, final_y);
             batch.draw(ctx)?;
         }
 
@@ -797,6 +985,11 @@ impl PhysicalEntity for NPC {
     fn ignore_tile_44(&self) -> bool {
         self.npc_flags.ignore_tile_44()
     }
+
+    #[inline(always)]
+    fn blown_by_forces(&self) -> bool {
+        !self.ignore_forces
+    }
 }
 
 pub struct NPCTableEntry {
@@ -810,17 +1003,105 @@ pub struct NPCTableEntry {
     pub damage: u32,
     pub display_bounds: Rect<u8>,
     pub hit_bounds: Rect<u8>,
+    pub faction: u8,
+    /// In pixels.
+    pub aggro_range: u16,
+    /// In pixels, see [NPC::sight_range].
+    pub sight_range: Option<u16>,
+    /// Initial [NPC::respawn_ticks] of NPCs of this type placed by the map.
+    pub respawn_ticks: u16,
+    /// Sprite drawn over the NPC and rotated towards the player, see [NPC::aim_angle_rad].
+    pub barrel_sprite: Option<Rect<u16>>,
+    /// Point of the barrel sprite it's rotated around, relative to its top left corner.
+    pub barrel_pivot: (isize, isize),
+    /// Initial [NPC::aim_speed_rad_per_tick].
+    pub barrel_speed: f32,
+    /// Makes the NPC react to special floor tiles like ice, see [NPCTable::load_tile_aware].
+    pub ai_physics_tile_aware: bool,
+    /// Makes water zones slow the NPC down and push it up, see [NPCTable::load_swimmers].
+    pub ai_swims: bool,
+    /// Makes the NPC climb up ladders it walks into, see [NPCTable::load_climbers].
+    pub ai_can_climb: bool,
+    /// Makes spike tiles hurt the NPC, see [NPCTable::load_tile_damage_takers].
+    pub ai_takes_tile_damage: bool,
+    /// Makes the NPC poison the player it hits, see [NPCTable::load_poisoners].
+    pub ai_poisons_on_hit: bool,
+}
+
+impl NPCTableEntry {
+    fn blank() -> NPCTableEntry {
+        NPCTableEntry {
+            npc_flags: NPCFlag(0),
+            life: 0,
+            spritesheet_id: 0,
+            death_sound: 0,
+            hurt_sound: 0,
+            size: 0,
+            experience: 0,
+            damage: 0,
+            display_bounds: Rect::new(0, 0, 0, 0),
+            hit_bounds: Rect::new(0, 0, 0, 0),
+            faction: 0,
+            aggro_range: 0,
+            sight_range: None,
+            respawn_ticks: 0,
+            barrel_sprite: None,
+            barrel_pivot: (0, 0),
+            barrel_speed: 0.0,
+            ai_physics_tile_aware: false,
+            ai_swims: false,
+            ai_can_climb: false,
+            ai_takes_tile_damage: false,
+            ai_poisons_on_hit: false,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DropEntry {
+    pub npc_type: u16,
+    pub weight: u8,
+}
+
+/// Weighted list of NPCs one of which is spawned when an NPC of given type dies.
+#[derive(Debug, Clone, Default)]
+pub struct DropTable {
+    pub entries: Vec<DropEntry>,
+}
+
+impl DropTable {
+    pub fn total_weight(&self) -> u32 {
+        self.entries.iter().map(|e| e.weight as u32).sum()
+    }
+
+    /// Returns the NPC type picked by a roll in range 0..total_weight, entries with weight 0 are never picked.
+    pub fn pick(&self, mut roll: u32) -> Option<u16> {
+        for entry in &self.entries {
+            if roll < entry.weight as u32 {
+                return Some(entry.npc_type);
+            }
+
+            roll -= entry.weight as u32;
+        }
+
+        None
+    }
 }
 
 pub struct NPCTable {
     entries: Vec<NPCTableEntry>,
+    drop_tables: Vec<DropTable>,
     pub stage_textures: Rc<RefCell<StageTexturePaths>>,
 }
 
 impl NPCTable {
     #[allow(clippy::new_without_default)]
     pub fn new() -> NPCTable {
-        NPCTable { entries: Vec::new(), stage_textures: Rc::new(RefCell::new(StageTexturePaths::new())) }
+        NPCTable {
+            entries: Vec::new(),
+            drop_tables: Vec::new(),
+            stage_textures: Rc::new(RefCell::new(StageTexturePaths::new())),
+        }
     }
 
     pub fn load_from<R: io::Read>(mut data: R) -> GameResult<NPCTable> {
@@ -833,18 +1114,7 @@ impl NPCTable {
         let mut f = Cursor::new(buf);
 
         for _ in 0..count {
-            table.entries.push(NPCTableEntry {
-                npc_flags: NPCFlag(0),
-                life: 0,
-                spritesheet_id: 0,
-                death_sound: 0,
-                hurt_sound: 0,
-                size: 0,
-                experience: 0,
-                damage: 0,
-                display_bounds: Rect::new(0, 0, 0, 0),
-                hit_bounds: Rect::new(0, 0, 0, 0),
-            });
+            table.entries.push(NPCTableEntry::blank());
         }
 
         for npc in &mut table.entries {
@@ -896,10 +1166,195 @@ impl NPCTable {
         Ok(table)
     }
 
+    /// Loads the drop table column. Each type can have any amount of drops, which doesn't fit the fixed-size columns
+    /// of npc.tbl, so it's kept in a separate file ordered by NPC type: an u8 entry count followed by
+    /// (u16 type, u8 weight) pairs.
+    pub fn load_drop_tables<R: io::Read>(&mut self, mut data: R) -> GameResult {
+        let mut buf = Vec::new();
+        data.read_to_end(&mut buf)?;
+
+        let mut f = Cursor::new(buf);
+        self.drop_tables.clear();
+
+        'tables: while let Ok(count) = f.read_u8() {
+            let mut drop_table = DropTable::default();
+
+            for _ in 0..count {
+                match (f.read_u16::<LE>(), f.read_u8()) {
+                    (Ok(npc_type), Ok(weight)) => drop_table.entries.push(DropEntry { npc_type, weight }),
+                    _ => {
+                        log::warn!("Drop table {} is truncated, ignoring it.", self.drop_tables.len());
+                        break 'tables;
+                    }
+                }
+            }
+
+            self.drop_tables.push(drop_table);
+        }
+
+        Ok(())
+    }
+
+    /// Loads the faction and aggro range columns, laid out column by column like npc.tbl. They're kept in a separate
+    /// file since npc.tbl's record count is derived from its size, extra columns would break loading it.
+    pub fn load_factions<R: io::Read>(&mut self, mut data: R) -> GameResult {
+        let mut buf = Vec::new();
+        data.read_to_end(&mut buf)?;
+
+        let count = (buf.len() / 3).min(self.entries.len());
+        let mut f = Cursor::new(buf);
+
+        for npc in self.entries.iter_mut().take(count) {
+            npc.faction = f.read_u8()?;
+        }
+
+        for npc in self.entries.iter_mut().take(count) {
+            npc.aggro_range = f.read_u16::<LE>()?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads barrels of NPCs aiming at the player. Only a few NPC types have one, so instead of another column
+    /// they're listed in a separate file, each as an u16 type, u16 left, top, right and bottom of the sprite,
+    /// i16 pivot x and y and u16 turning speed in milliradians per tick. Types past the end of npc.tbl, like
+    /// the turret from the built-in list, get a blank entry.
+    pub fn load_barrels<R: io::Read>(&mut self, mut data: R) -> GameResult {
+        let mut buf = Vec::new();
+        data.read_to_end(&mut buf)?;
+
+        let mut f = Cursor::new(buf);
+
+        while let Ok(npc_type) = f.read_u16::<LE>() {
+            let sprite = Rect::new(f.read_u16::<LE>()?, f.read_u16::<LE>()?, f.read_u16::<LE>()?, f.read_u16::<LE>()?);
+            let pivot = (f.read_i16::<LE>()? as isize, f.read_i16::<LE>()? as isize);
+            let speed = f.read_u16::<LE>()? as f32 / 1000.0;
+
+            if self.entries.len() <= npc_type as usize {
+                self.entries.resize_with(npc_type as usize + 1, NPCTableEntry::blank);
+            }
+
+            if let Some(npc) = self.entries.get_mut(npc_type as usize) {
+                npc.barrel_sprite = Some(sprite);
+                npc.barrel_pivot = pivot;
+                npc.barrel_speed = speed;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads sight ranges of NPC types which need to see the player to notice them, kept in a separate file for
+    /// the same reason as drop tables. Only such NPC types are listed, each as an u16 type and u16 range in pixels.
+    pub fn load_sight_ranges<R: io::Read>(&mut self, mut data: R) -> GameResult {
+        let mut buf = Vec::new();
+        data.read_to_end(&mut buf)?;
+
+        let mut f = Cursor::new(buf);
+
+        while let (Ok(npc_type), Ok(range)) = (f.read_u16::<LE>(), f.read_u16::<LE>()) {
+            if let Some(npc) = self.entries.get_mut(npc_type as usize) {
+                npc.sight_range = Some(range);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads respawn delays of map NPCs, stored as u16 type and u16 amount of ticks pairs. Only NPC types that
+    /// respawn are listed, the original table has no room for this column and most NPCs must stay dead.
+    pub fn load_respawn_ticks<R: io::Read>(&mut self, mut data: R) -> GameResult {
+        let mut buf = Vec::new();
+        data.read_to_end(&mut buf)?;
+
+        let mut f = Cursor::new(buf);
+
+        while let (Ok(npc_type), Ok(ticks)) = (f.read_u16::<LE>(), f.read_u16::<LE>()) {
+            if let Some(npc) = self.entries.get_mut(npc_type as usize) {
+                npc.respawn_ticks = ticks;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads the list of NPC types affected by special floor tiles, stored as u16 types one after another.
+    /// Most NPCs keep their original movement, since their AI wasn't written with slippery floors in mind.
+    pub fn load_tile_aware<R: io::Read>(&mut self, data: R) -> GameResult {
+        self.load_type_list(data, |npc| npc.ai_physics_tile_aware = true)
+    }
+
+    /// Loads the list of NPC types affected by water zone tiles, in the same format as [NPCTable::load_tile_aware].
+    pub fn load_swimmers<R: io::Read>(&mut self, data: R) -> GameResult {
+        self.load_type_list(data, |npc| npc.ai_swims = true)
+    }
+
+    /// Loads the list of NPC types climbing ladders, in the same format as [NPCTable::load_tile_aware].
+    pub fn load_climbers<R: io::Read>(&mut self, data: R) -> GameResult {
+        self.load_type_list(data, |npc| npc.ai_can_climb = true)
+    }
+
+    /// Loads the list of NPC types hurt by spike tiles, in the same format as [NPCTable::load_tile_aware].
+    pub fn load_tile_damage_takers<R: io::Read>(&mut self, data: R) -> GameResult {
+        self.load_type_list(data, |npc| npc.ai_takes_tile_damage = true)
+    }
+
+    /// Loads the list of NPC types poisoning the player on hit, in the same format as [NPCTable::load_tile_aware].
+    pub fn load_poisoners<R: io::Read>(&mut self, data: R) -> GameResult {
+        self.load_type_list(data, |npc| npc.ai_poisons_on_hit = true)
+    }
+
+    fn load_type_list<R: io::Read>(&mut self, mut data: R, mark: impl Fn(&mut NPCTableEntry)) -> GameResult {
+        let mut buf = Vec::new();
+        data.read_to_end(&mut buf)?;
+
+        let mut f = Cursor::new(buf);
+
+        while let Ok(npc_type) = f.read_u16::<LE>() {
+            if let Some(npc) = self.entries.get_mut(npc_type as usize) {
+                mark(npc);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_tile_aware(&self, npc_type: u16) -> bool {
+        self.entries.get(npc_type as usize).map_or(false, |npc| npc.ai_physics_tile_aware)
+    }
+
+    pub fn swims(&self, npc_type: u16) -> bool {
+        self.entries.get(npc_type as usize).map_or(false, |npc| npc.ai_swims)
+    }
+
+    pub fn can_climb(&self, npc_type: u16) -> bool {
+        self.entries.get(npc_type as usize).map_or(false, |npc| npc.ai_can_climb)
+    }
+
+    pub fn takes_tile_damage(&self, npc_type: u16) -> bool {
+        self.entries.get(npc_type as usize).map_or(false, |npc| npc.ai_takes_tile_damage)
+    }
+
+    pub fn poisons_on_hit(&self, npc_type: u16) -> bool {
+        self.entries.get(npc_type as usize).map_or(false, |npc| npc.ai_poisons_on_hit)
+    }
+
+    /// Returns the barrel sprite and its pivot for given NPC type, if it has one.
+    pub fn get_barrel(&self, npc_type: u16) -> Option<(Rect<u16>, (isize, isize))> {
+        let npc = self.entries.get(npc_type as usize)?;
+
+        npc.barrel_sprite.map(|sprite| (sprite, npc.barrel_pivot))
+    }
+
     pub fn get_entry(&self, npc_type: u16) -> Option<&NPCTableEntry> {
         self.entries.get(npc_type as usize)
     }
 
+    /// Returns the drop table for given NPC type, if it has any droppable entries.
+    pub fn get_drop_table(&self, npc_type: u16) -> Option<&DropTable> {
+        self.drop_tables.get(npc_type as usize).filter(|t| t.total_weight() != 0)
+    }
+
     pub fn get_display_bounds(&self, npc_type: u16) -> Rect<u32> {
         if let Some(npc) = self.entries.get(npc_type as usize) {
             Rect {
@@ -981,3 +1436,38 @@ impl Deref for TexRef<'_> {
         }
     }
 }
+
+#[test]
+fn test_drop_table_pick() {
+    let table = DropTable {
+        entries: vec![
+            DropEntry { npc_type: 87, weight: 2 },
+            DropEntry { npc_type: 86, weight: 0 },
+            DropEntry { npc_type: 1, weight: 3 },
+        ],
+    };
+
+    assert_eq!(table.total_weight(), 5);
+    assert_eq!(table.pick(0), Some(87));
+    assert_eq!(table.pick(1), Some(87));
+    assert_eq!(table.pick(2), Some(1));
+    assert_eq!(table.pick(4), Some(1));
+    assert_eq!(table.pick(5), None);
+}
+
+#[test]
+fn test_npc_flag_names() {
+    let flags = NPCFlag(0x0001 | 0x0020 | 0x8000);
+
+    assert_eq!(flags.names(), vec!["solid_soft", "shootable", "show_damage"]);
+    assert!(NPCFlag(0).names().is_empty());
+}
+
+#[test]
+fn test_load_respawn_ticks() {
+    let mut table = NPCTable::load_from(Cursor::new(vec![0u8; 0x18 * 4])).unwrap();
+    table.load_respawn_ticks(Cursor::new(vec![2, 0, 0x2c, 0x01, 9, 0, 1, 0])).unwrap();
+
+    assert_eq!(table.get_entry(1).unwrap().respawn_ticks, 0);
+    assert_eq!(table.get_entry(2).unwrap().respawn_ticks, 300);
+}
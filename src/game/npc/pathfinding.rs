@@ -0,0 +1,243 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::game::map::Map;
+use crate::game::npc::NPC;
+use crate::game::stage::Stage;
+
+/// Amount of ticks after which an NPC following a path recomputes it.
+pub const PATH_REFRESH_TICKS: u64 = 30;
+
+/// Maximum amount of tiles explored by [NPC::follow_path] before giving up.
+const FOLLOW_PATH_MAX_NODES: usize = 512;
+
+const NEIGHBOURS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct OpenNode {
+    estimate: u32,
+    cost: u32,
+    pos: (i32, i32),
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, reverse the ordering to pop the cheapest node first.
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+            .then_with(|| other.pos.cmp(&self.pos))
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Returns true if tile at given position is not solid for NPCs.
+pub(crate) fn is_passable(map: &Map, x: i32, y: i32) -> bool {
+    if x < 0 || y < 0 || x >= map.width as i32 || y >= map.height as i32 {
+        return false;
+    }
+
+    !matches!(map.get_attribute(x as usize, y as usize), 0x03 | 0x04 | 0x05 | 0x41 | 0x43 | 0x44 | 0x61 | 0x64)
+}
+
+/// Returns true if tile at given position is a one-way platform, which can only be passed through downwards.
+fn is_platform(map: &Map, x: i32, y: i32) -> bool {
+    x >= 0 && y >= 0 && map.get_attribute(x as usize, y as usize) == 0x4a
+}
+
+#[inline]
+fn manhattan(a: (i32, i32), b: (i32, i32)) -> u32 {
+    ((a.0 - b.0).abs() + (a.1 - b.1).abs()) as u32
+}
+
+/// Finds the shortest path between two tiles using A*, with solid tiles being impassable.
+///
+/// The returned path is a list of tile coordinates excluding the start tile and including the goal.
+/// Returns None if the goal is unreachable or more than `max_nodes` tiles had to be explored.
+pub fn find_path(start: (i32, i32), goal: (i32, i32), stage: &Stage, max_nodes: usize) -> Option<Vec<(i32, i32)>> {
+    find_path_on_map(start, goal, &stage.map, max_nodes)
+}
+
+fn find_path_on_map(start: (i32, i32), goal: (i32, i32), map: &Map, max_nodes: usize) -> Option<Vec<(i32, i32)>> {
+    if start == goal {
+        return Some(Vec::new());
+    }
+
+    if !is_passable(map, goal.0, goal.1) {
+        return None;
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+    let mut costs: HashMap<(i32, i32), u32> = HashMap::new();
+    let mut explored = 0;
+
+    costs.insert(start, 0);
+    open.push(OpenNode { estimate: manhattan(start, goal), cost: 0, pos: start });
+
+    while let Some(OpenNode { cost, pos, .. }) = open.pop() {
+        if pos == goal {
+            let mut path = vec![pos];
+            let mut current = pos;
+            while let Some(&prev) = came_from.get(&current) {
+                if prev == start {
+                    break;
+                }
+
+                path.push(prev);
+                current = prev;
+            }
+            path.reverse();
+
+            return Some(path);
+        }
+
+        // stale entry, a cheaper way to this tile has been found already
+        if costs.get(&pos).map_or(false, |&best| cost > best) {
+            continue;
+        }
+
+        explored += 1;
+        if explored > max_nodes {
+            return None;
+        }
+
+        for (ox, oy) in NEIGHBOURS {
+            let next = (pos.0 + ox, pos.1 + oy);
+            if !is_passable(map, next.0, next.1) {
+                continue;
+            }
+
+            if oy == -1 && (is_platform(map, pos.0, pos.1) || is_platform(map, next.0, next.1)) {
+                continue;
+            }
+
+            let next_cost = cost + 1;
+            if costs.get(&next).map_or(true, |&best| next_cost < best) {
+                costs.insert(next, next_cost);
+                came_from.insert(next, pos);
+                open.push(OpenNode { estimate: next_cost + manhattan(next, goal), cost: next_cost, pos: next });
+            }
+        }
+    }
+
+    None
+}
+
+impl NPC {
+    /// Sets the velocity of this NPC to move along a path towards given tile, recomputing the path
+    /// every [PATH_REFRESH_TICKS] ticks. Waypoints are removed as they're reached.
+    ///
+    /// Returns false if there's no path to the goal or it has been reached.
+    pub fn follow_path(&mut self, goal: (i32, i32), speed: i32, stage: &Stage) -> bool {
+        let tile_size = stage.map.tile_size.as_int() * 0x200;
+        let pos = ((self.x + tile_size / 2).div_euclid(tile_size), (self.y + tile_size / 2).div_euclid(tile_size));
+
+        if self.path.is_none() || self.path_tick % PATH_REFRESH_TICKS == 0 {
+            self.path = find_path(pos, goal, stage, FOLLOW_PATH_MAX_NODES);
+        }
+        self.path_tick = self.path_tick.wrapping_add(1);
+
+        let path = match &mut self.path {
+            Some(path) => path,
+            None => {
+                self.vel_x = 0;
+                self.vel_y = 0;
+                return false;
+            }
+        };
+
+        if let Some(&(wx, wy)) = path.first() {
+            let (dx, dy) = (wx * tile_size - self.x, wy * tile_size - self.y);
+
+            if dx.abs() <= speed && dy.abs() <= speed {
+                path.remove(0);
+            }
+
+            self.vel_x = dx.clamp(-speed, speed);
+            self.vel_y = dy.clamp(-speed, speed);
+
+            return true;
+        }
+
+        self.vel_x = 0;
+        self.vel_y = 0;
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::map::Map;
+    use crate::game::shared_game_state::TileSize;
+
+    use super::find_path_on_map;
+
+    fn map_from(rows: &[&str]) -> Map {
+        let mut attrib = [0u8; 0x100];
+        attrib[1] = 0x41;
+        attrib[2] = 0x4a;
+
+        let tiles = rows
+            .iter()
+            .flat_map(|row| {
+                row.chars().map(|c| match c {
+                    '#' => 1u8,
+                    '=' => 2u8,
+                    _ => 0u8,
+                })
+            })
+            .collect::<Vec<u8>>();
+
+        Map { width: rows[0].len() as u16, height: rows.len() as u16, tiles, attrib, tile_size: TileSize::Tile16x16 }
+    }
+
+    #[test]
+    fn test_straight_path() {
+        let map = map_from(&["....", "....", "...."]);
+
+        assert_eq!(find_path_on_map((0, 1), (3, 1), &map, 64), Some(vec![(1, 1), (2, 1), (3, 1)]));
+        assert_eq!(find_path_on_map((2, 2), (2, 2), &map, 64), Some(vec![]));
+    }
+
+    #[test]
+    fn test_path_around_wall() {
+        let map = map_from(&[".....", "..#..", "..#..", "....."]);
+
+        let path = find_path_on_map((0, 1), (4, 1), &map, 64).unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(path.last(), Some(&(4, 1)));
+        assert!(path.iter().all(|&(x, y)| !(x == 2 && (y == 1 || y == 2))));
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let map = map_from(&["..#..", "..#..", "..#.."]);
+
+        assert_eq!(find_path_on_map((0, 0), (4, 0), &map, 64), None);
+        assert_eq!(find_path_on_map((0, 0), (2, 0), &map, 64), None);
+    }
+
+    #[test]
+    fn test_one_way_platform() {
+        let map = map_from(&["#.#", "#=#", "#.#"]);
+
+        assert_eq!(find_path_on_map((1, 0), (1, 2), &map, 64), Some(vec![(1, 1), (1, 2)]));
+        assert_eq!(find_path_on_map((1, 2), (1, 0), &map, 64), None);
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let map = map_from(&["..........", "..........", ".........."]);
+
+        assert_eq!(find_path_on_map((0, 0), (9, 2), &map, 4), None);
+        assert!(find_path_on_map((0, 0), (9, 2), &map, 64).is_some());
+    }
+}
@@ -1,18 +1,29 @@
 ///! Various utility functions for NPC-related objects
 use num_traits::abs;
 
-use crate::common::{Condition, Direction, Flag, Rect};
+use crate::common::{approach_angle, Condition, Direction, Flag, Rect};
 use crate::components::number_popup::NumberPopup;
-use crate::game::caret::CaretType;
+use crate::game::caret::{Caret, CaretType};
+use crate::game::events::GameEvent;
 use crate::game::map::NPCData;
-use crate::game::npc::{NPC, NPCFlag, NPCLayer, NPCTable};
+use crate::game::npc::{NPC, NPCClimbState, NPCFlag, NPCLayer, NPCTable};
 use crate::game::npc::list::NPCList;
+use crate::game::npc::pathfinding::is_passable;
+use crate::game::physics::{PhysicalEntity, CLIMB_HOP};
 use crate::game::player::Player;
-use crate::game::shared_game_state::{SharedGameState, TileSize};
+use crate::game::shared_game_state::{GameDifficulty, SharedGameState, TileSize};
+use crate::game::stage::Stage;
+use crate::game::tile_props::SPIKE_DAMAGE;
 use crate::game::weapon::bullet::Bullet;
 use crate::util::rng::{RNG, Xoroshiro32PlusPlus};
 
 const MAX_FALL_SPEED: i32 = 0x5FF;
+/// Distance players need to keep from the spawn point of a dead NPC for its respawn timer to count down.
+const RESPAWN_DISTANCE: i64 = 256 * 0x200;
+/// NPC types blown around by wind and water current tiles: experience orbs.
+const FORCE_AFFECTED_NPCS: [u16; 1] = [1];
+/// Ticks between the attack warning and the attack itself, halved on Hard.
+pub const TELEGRAPH_DURATION: u8 = 30;
 
 impl NPC {
     /// Initializes the RNG. Called when the [NPC] is being added to an [NPCList].
@@ -35,17 +46,22 @@ impl NPC {
     pub fn create(npc_type: u16, table: &NPCTable) -> NPC {
         let display_bounds = table.get_display_bounds(npc_type);
         let hit_bounds = table.get_hit_bounds(npc_type);
-        let (size, life, damage, flags, exp, spritesheet_id) = match table.get_entry(npc_type) {
-            Some(entry) => (
-                entry.size,
-                entry.life,
-                entry.damage as u16,
-                entry.npc_flags,
-                entry.experience as u16,
-                entry.spritesheet_id as u16,
-            ),
-            None => (2, 0, 0, NPCFlag(0), 0, 0),
-        };
+        let (size, life, damage, flags, exp, spritesheet_id, faction, aggro_range, sight_range, aim_speed) =
+            match table.get_entry(npc_type) {
+                Some(entry) => (
+                    entry.size,
+                    entry.life,
+                    entry.damage as u16,
+                    entry.npc_flags,
+                    entry.experience as u16,
+                    entry.spritesheet_id as u16,
+                    entry.faction,
+                    entry.aggro_range as u32 * 0x200,
+                    entry.sight_range.map(|range| range as u32 * 0x200),
+                    entry.barrel_speed,
+                ),
+                None => (2, 0, 0, NPCFlag(0), 0, 0, 0, 0, None, 0.0),
+            };
         let npc_flags = NPCFlag(flags.0);
 
         NPC {
@@ -88,6 +104,23 @@ impl NPC {
             rng: Xoroshiro32PlusPlus::new(0),
             popup: NumberPopup::new(),
             splash: false,
+            path: None,
+            path_tick: 0,
+            sight_range,
+            spawn_x: 0,
+            spawn_y: 0,
+            respawn_ticks: 0,
+            respawn_timer: 0,
+            faction,
+            aggro_range,
+            ignore_forces: !FORCE_AFFECTED_NPCS.contains(&npc_type),
+            telegraph_active: false,
+            telegraph_timer: 0,
+            on_ice: false,
+            conveyor_vel_x: 0,
+            climb_state: NPCClimbState::None,
+            aim_angle_rad: 0.0,
+            aim_speed_rad_per_tick: aim_speed,
         }
     }
 
@@ -99,6 +132,9 @@ impl NPC {
         npc.id = data.id;
         npc.x = data.x as i32 * ti;
         npc.y = data.y as i32 * ti;
+        npc.spawn_x = npc.x;
+        npc.spawn_y = npc.y;
+        npc.respawn_ticks = table.get_entry(data.npc_type).map_or(0, |entry| entry.respawn_ticks);
         npc.flag_num = data.flag_num;
         npc.event_num = data.event_num;
         npc.npc_flags = NPCFlag(data.flags | npc.npc_flags.0);
@@ -165,11 +201,117 @@ impl NPC {
         &players[idx]
     }
 
+    /// Shows a warning above the NPC if `Settings::attack_telegraphs` is on, setting `telegraph_active`
+    /// for [TELEGRAPH_DURATION] ticks. AI code waits for it to clear before attacking.
+    pub fn start_telegraph(&mut self, state: &mut SharedGameState) {
+        if !state.settings.attack_telegraphs {
+            return;
+        }
+
+        let duration =
+            if state.difficulty == GameDifficulty::Hard { TELEGRAPH_DURATION / 2 } else { TELEGRAPH_DURATION };
+        let y = self.y - self.display_bounds.top as i32 - 0x1000;
+
+        self.telegraph_active = true;
+        self.telegraph_timer = duration;
+        state.carets.push(Caret::new_telegraph(self.x, y, duration as u16, &state.constants));
+    }
+
+    /// Turns the barrel towards the closest player by at most `aim_speed_rad_per_tick`.
+    pub fn tick_aim(&mut self, players: &[&mut Player; 2]) {
+        let player = self.get_closest_player_ref(players);
+        let target = ((player.y - self.y) as f32).atan2((player.x - self.x) as f32);
+
+        self.aim_angle_rad = approach_angle(self.aim_angle_rad, target, self.aim_speed_rad_per_tick);
+    }
+
+    /// Updates the ladder climbing state after map collisions. Climbers grab a ladder overlapping their center,
+    /// or one right above them while standing on the ground, and hop off its top. A ladder running into a ceiling
+    /// is climbed back down and let go of at the bottom.
+    pub fn tick_climb(&mut self, state: &SharedGameState, stage: &Stage) {
+        let on_ladder = self.test_ladder(state, stage, 0);
+        let ladder_above = self.test_ladder(state, stage, -1);
+
+        match self.climb_state {
+            NPCClimbState::None => {
+                if on_ladder || (ladder_above && self.flags.hit_bottom_wall()) {
+                    self.climb_state = NPCClimbState::Up;
+                }
+            }
+            NPCClimbState::Up => {
+                if self.flags.hit_top_wall() {
+                    self.climb_state = NPCClimbState::Down;
+                } else if !on_ladder && !ladder_above {
+                    self.climb_state = NPCClimbState::None;
+                    self.vel_y = -CLIMB_HOP;
+                }
+            }
+            NPCClimbState::Down => {
+                if self.flags.hit_bottom_wall() || (!on_ladder && !ladder_above) {
+                    self.climb_state = NPCClimbState::Dismounted;
+                }
+            }
+            NPCClimbState::Dismounted => {
+                if !on_ladder && !ladder_above {
+                    self.climb_state = NPCClimbState::None;
+                }
+            }
+        }
+    }
+
     /// Sets direction of NPC to face towards Player.
     pub fn face_player(&mut self, player: &Player) {
         self.direction = if self.x > player.x { Direction::Left } else { Direction::Right };
     }
 
+    /// Returns true if there are no tiles solid to NPCs on the line between this [NPC] and the [Player],
+    /// and the player is within `sight_range` (unlimited if 0 or None).
+    pub fn has_los_to_player(&self, player: &Player, stage: &Stage) -> bool {
+        if let Some(range) = self.sight_range.filter(|&range| range != 0) {
+            let (dx, dy) = ((player.x - self.x) as i64, (player.y - self.y) as i64);
+            let range = range as i64;
+
+            if dx * dx + dy * dy > range * range {
+                return false;
+            }
+        }
+
+        // Bresenham's line algorithm over the tile grid
+        let tile_size = stage.map.tile_size.as_int() * 0x200;
+        let to_tile = |v: i32| (v + tile_size / 2).div_euclid(tile_size);
+        let (mut x, mut y) = (to_tile(self.x), to_tile(self.y));
+        let (end_x, end_y) = (to_tile(player.x), to_tile(player.y));
+
+        let (dx, dy) = ((end_x - x).abs(), -(end_y - y).abs());
+        let (step_x, step_y) = ((end_x - x).signum(), (end_y - y).signum());
+        let mut error = dx + dy;
+
+        loop {
+            if !is_passable(&stage.map, x, y) {
+                return false;
+            }
+
+            if x == end_x && y == end_y {
+                return true;
+            }
+
+            let error2 = error * 2;
+            if error2 >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if error2 <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Returns true if the player can be noticed, NPC types without a sight range notice them through walls.
+    pub fn notices_player(&self, player: &Player, stage: &Stage) -> bool {
+        self.sight_range.is_none() || self.has_los_to_player(player, stage)
+    }
+
     /// Clamps +Y velocity if above `MAX_FALL_SPEED`.
     pub fn clamp_fall_speed(&mut self) {
         if self.vel_y > MAX_FALL_SPEED {
@@ -225,6 +367,61 @@ impl NPC {
         }
     }
 
+    /// Applies direction argument of a TSC command, 4 turns the NPC towards given player
+    /// and 5 keeps the current direction.
+    pub fn set_tsc_direction(&mut self, tsc_direction: u16, player: &Player) {
+        let direction = Direction::from_int_facing(tsc_direction as usize).unwrap_or(Direction::Left);
+        self.tsc_direction = tsc_direction;
+
+        if direction == Direction::FacingPlayer {
+            self.direction = if player.x < self.x { Direction::Left } else { Direction::Right };
+        } else if tsc_direction != 5 {
+            self.direction = direction;
+        }
+    }
+
+    /// Hurts the NPC touching a spike tile, spikes hit it again once it recovers from the previous hit.
+    pub fn hit_by_spike(&mut self, instant_kill: bool, state: &mut SharedGameState) {
+        // already dead, its death event or explosion is pending
+        if self.life == 0 {
+            return;
+        }
+
+        if !self.npc_flags.shootable() || self.npc_flags.invulnerable() || (self.shock > 0 && !instant_kill) {
+            return;
+        }
+
+        let damage = if instant_kill { self.life } else { SPIKE_DAMAGE.min(self.life) };
+        self.life -= damage;
+
+        if self.npc_flags.show_damage() {
+            self.popup.add_value(-(damage.min(i16::MAX as u16) as i16));
+        }
+
+        if self.life == 0 {
+            state.event_bus.emit(GameEvent::NpcDied {
+                id: self.id,
+                npc_type: self.npc_type,
+                x: self.x as isize,
+                y: self.y as isize,
+            });
+
+            if self.npc_flags.event_when_killed() {
+                state.control_flags.set_tick_world(true);
+                state.control_flags.set_interactions_disabled(true);
+                state.textscript_vm.start_script(self.event_num);
+            } else {
+                self.cond.set_explode_die(true);
+            }
+        } else {
+            if let Some(table_entry) = state.npc_table.get_entry(self.npc_type) {
+                state.sound_manager.play_sfx(table_entry.hurt_sound);
+            }
+
+            self.shock = 16;
+        }
+    }
+
     /// Makes the NPC disappear and turns it into damage value holder.
     pub fn vanish(&mut self, state: &SharedGameState) {
         let mut npc = NPC::create(3, &state.npc_table);
@@ -253,6 +450,12 @@ impl NPCList {
         self.iter_alive().any(|npc| npc.event_num == event_num)
     }
 
+    /// Returns an iterator over alive NPCs with specified event.
+    #[inline]
+    pub fn iter_alive_by_event(&self, event_num: u16) -> impl Iterator<Item = &mut NPC> {
+        self.iter_alive().filter(move |npc| npc.event_num == event_num)
+    }
+
     /// Deletes NPCs with specified type.
     pub fn kill_npcs_by_type(&self, npc_type: u16, smoke: bool, state: &mut SharedGameState) {
         for npc in self.iter_alive().filter(|n| n.npc_type == npc_type) {
@@ -280,6 +483,151 @@ impl NPCList {
         }
     }
 
+    /// Damages shootable NPCs within `radius` from given point, like a weapon hit would. Killed NPCs are
+    /// marked to explode, the scene removes them along with the ones killed by bullets.
+    pub fn area_damage(&self, x: i32, y: i32, radius: i32, damage: u16, state: &mut SharedGameState) {
+        let radius = radius as i64;
+
+        for npc in self.iter_alive() {
+            // already dead, its death event or explosion is pending
+            if !npc.npc_flags.shootable() || npc.npc_flags.interactable() || npc.life == 0 {
+                continue;
+            }
+
+            let (dx, dy) = ((npc.x - x) as i64, (npc.y - y) as i64);
+            if dx * dx + dy * dy > radius * radius {
+                continue;
+            }
+
+            npc.life = npc.life.saturating_sub(damage);
+
+            if npc.npc_flags.show_damage() {
+                npc.popup.add_value(-(damage as i16));
+            }
+
+            if npc.life == 0 {
+                state.event_bus.emit(GameEvent::NpcDied {
+                    id: npc.id,
+                    npc_type: npc.npc_type,
+                    x: npc.x as isize,
+                    y: npc.y as isize,
+                });
+
+                if npc.npc_flags.event_when_killed() {
+                    state.control_flags.set_tick_world(true);
+                    state.control_flags.set_interactions_disabled(true);
+                    state.textscript_vm.start_script(npc.event_num);
+                } else {
+                    npc.cond.set_explode_die(true);
+                }
+            } else if npc.shock < 14 {
+                if let Some(table_entry) = state.npc_table.get_entry(npc.npc_type) {
+                    state.sound_manager.play_sfx(table_entry.hurt_sound);
+                }
+
+                npc.shock = 16;
+            }
+        }
+    }
+
+    /// Counts down dead NPCs with respawning enabled while all players are away from their spawn point,
+    /// bringing them back to their initial state once the timer runs out.
+    pub fn tick_respawns(&self, state: &SharedGameState, players: [&Player; 2]) {
+        for npc in self.iter() {
+            if npc.respawn_ticks == 0 {
+                continue;
+            }
+
+            if npc.cond.alive() {
+                npc.respawn_timer = npc.respawn_ticks;
+                continue;
+            }
+
+            if npc.respawn_timer == 0 {
+                continue;
+            }
+
+            let player_nearby = players.iter().any(|player| {
+                let (dx, dy) = ((player.x - npc.spawn_x) as i64, (player.y - npc.spawn_y) as i64);
+
+                player.cond.alive() && dx * dx + dy * dy <= RESPAWN_DISTANCE * RESPAWN_DISTANCE
+            });
+
+            if player_nearby {
+                continue;
+            }
+
+            npc.respawn_timer -= 1;
+            if npc.respawn_timer != 0 {
+                continue;
+            }
+
+            if (npc.npc_flags.appear_when_flag_set() && !state.get_flag(npc.flag_num as usize))
+                || (npc.npc_flags.hide_unless_flag_set() && state.get_flag(npc.flag_num as usize))
+            {
+                continue;
+            }
+
+            let mut respawned = NPC::create(npc.npc_type, &state.npc_table);
+            respawned.cond.set_alive(true);
+            respawned.id = npc.id;
+            respawned.x = npc.spawn_x;
+            respawned.y = npc.spawn_y;
+            respawned.spawn_x = npc.spawn_x;
+            respawned.spawn_y = npc.spawn_y;
+            respawned.flag_num = npc.flag_num;
+            respawned.event_num = npc.event_num;
+            respawned.npc_flags = npc.npc_flags;
+            respawned.direction =
+                if npc.npc_flags.spawn_facing_right() { Direction::Right } else { Direction::Left };
+            respawned.tsc_direction = respawned.direction as u16;
+            respawned.respawn_ticks = npc.respawn_ticks;
+            respawned.respawn_timer = npc.respawn_ticks;
+            respawned.rng = npc.rng.clone();
+
+            *npc = respawned;
+        }
+    }
+
+    /// Spawns a drop picked from the drop table of NPC's type, returns false if the type has no drop table.
+    /// Hearts, missiles and experience get their amount from the NPC's experience like the built-in drops.
+    pub fn drop_from_table(&self, npc: &NPC, state: &SharedGameState) -> bool {
+        let drop_table = if let Some(drop_table) = state.npc_table.get_drop_table(npc.npc_type) {
+            drop_table
+        } else {
+            return false;
+        };
+
+        let roll = state.effect_rng.range(0..drop_table.total_weight() as i32 - 1) as u32;
+        let npc_type = if let Some(npc_type) = drop_table.pick(roll) {
+            npc_type
+        } else {
+            return true;
+        };
+
+        if npc_type == 1 {
+            npc.create_xp_drop(state, self);
+            return true;
+        }
+
+        let mut drop = NPC::create(npc_type, &state.npc_table);
+        drop.cond.set_alive(true);
+        drop.direction = Direction::Left;
+        drop.x = npc.x;
+        drop.y = npc.y;
+        drop.exp = match npc_type {
+            86 if npc.exp > 6 => 3,
+            86 => 1,
+            87 if npc.exp > 6 => 6,
+            87 => 2,
+            _ => npc.exp,
+        };
+
+        let _ = self.spawn(0x100, drop);
+
+        true
+    }
+
     /// Called once NPC is killed, creates smoke and drops.
     pub fn kill_npc(&self, id: usize, vanish: bool, can_drop_missile: bool, state: &mut SharedGameState) {
         if let Some(npc) = self.get_npc(id) {
@@ -300,7 +648,7 @@ impl NPCList {
                 _ => {}
             };
 
-            if npc.exp != 0 {
+            if npc.exp != 0 && !self.drop_from_table(npc, state) {
                 let rng = npc.rng.range(0..4);
                 match rng {
                     0 => {
@@ -349,11 +697,9 @@ impl NPCList {
 
     /// Removes NPCs whose event number matches the provided one.
     pub fn kill_npcs_by_event(&self, event_num: u16, state: &mut SharedGameState) {
-        for npc in self.iter_alive() {
-            if npc.event_num == event_num {
-                npc.cond.set_alive(false);
-                state.set_flag(npc.flag_num as usize, true);
-            }
+        for npc in self.iter_alive_by_event(event_num) {
+            npc.cond.set_alive(false);
+            state.set_flag(npc.flag_num as usize, true);
         }
     }
 
@@ -1,8 +1,10 @@
 use crate::common::{Condition, Direction, Flag, Rect};
+use crate::engine_constants::WorldConsts;
 use crate::game::caret::CaretType;
 use crate::game::npc::list::NPCList;
 use crate::game::shared_game_state::{SharedGameState, TileSize};
 use crate::game::stage::Stage;
+use crate::game::tile_props::TileProperty;
 
 //      -3 -2 -1  0  1  2  3  4
 //    +------------------------
@@ -15,6 +17,13 @@ use crate::game::stage::Stage;
 //  3 | 43 31 22 23 24 25 17 56
 //  4 | 57 58 59 60 61 62 63 64
 
+/// Speed of entities moving along ladders.
+pub const CLIMB_SPEED: i32 = 0x300;
+/// Upwards speed of the hop done when climbing off the top of a ladder.
+pub const CLIMB_HOP: i32 = 0x400;
+/// Terminal speed of entities pushed by wind and water currents, same as the player's in air.
+const FORCE_MAX_SPEED: i32 = 0x5ff;
+
 pub const OFFSETS: [(i32, i32); 64] = [
     (0, 0),
     (1, 0),
@@ -82,6 +91,23 @@ pub const OFFSETS: [(i32, i32); 64] = [
     (4, 4),
 ];
 
+/// Blends the velocity an entity wants to move at with its current one while it's on ice,
+/// so it speeds up slowly and slides when trying to stop.
+pub fn ice_velocity(current: i32, target: i32, world: &WorldConsts) -> i32 {
+    let slowing_down = target == 0 || (target.signum() == current.signum() && target.abs() < current.abs());
+
+    if slowing_down {
+        let slid = (current as f32 * world.ice_friction) as i32;
+        if current > 0 {
+            slid.max(target)
+        } else {
+            slid.min(target)
+        }
+    } else {
+        current + ((target - current) as f32 * world.ice_accel_factor) as i32
+    }
+}
+
 pub trait PhysicalEntity {
     fn x(&self) -> i32;
     fn y(&self) -> i32;
@@ -112,12 +138,163 @@ pub trait PhysicalEntity {
     fn ignore_tile_44(&self) -> bool {
         true
     }
+    /// Returns true if tiles solid only to NPCs (0x03, 0x04, 0x64) should be passed through, while tiles solid only
+    /// to the player (0x46) block the entity instead.
+    fn ignores_npc_solidity(&self) -> bool {
+        self.is_player()
+    }
     fn player_left_pressed(&self) -> bool {
         false
     }
     fn player_right_pressed(&self) -> bool {
         false
     }
+    /// Returns true if force tiles should push the entity around, players apply them in their own tick instead.
+    fn blown_by_forces(&self) -> bool {
+        false
+    }
+
+    /// Returns the tile under the entity's feet if it's standing on the ground.
+    fn floor_tile(&mut self, state: &SharedGameState, stage: &Stage) -> Option<u16> {
+        if !self.flags().hit_bottom_wall() {
+            return None;
+        }
+
+        let tile_size = state.tile_size.as_int() * 0x200;
+        let half_tile_size = tile_size / 2;
+        let x = (self.x() + half_tile_size) / tile_size;
+        let y = (self.y() + self.hit_bounds().bottom as i32 + half_tile_size) / tile_size;
+        if x < 0 || y < 0 {
+            return None;
+        }
+
+        Some(stage.tile_at(x as usize, y as usize) as u16)
+    }
+
+    /// Returns true if the entity is standing on an ice tile, see `TileProperties::is_ice`.
+    fn stands_on_ice(&mut self, state: &SharedGameState, stage: &Stage) -> bool {
+        if state.tile_props.is_empty() && state.constants.ice_tile_ids.is_empty() {
+            return false;
+        }
+
+        match self.floor_tile(state, stage) {
+            Some(tile) => state.tile_props.is_ice(&stage.data.tileset.name, tile, &state.constants.ice_tile_ids),
+            None => false,
+        }
+    }
+
+    /// Returns the speed of the conveyor belt the entity is standing on, or 0 if it's not on one.
+    fn conveyor_speed(&mut self, state: &SharedGameState, stage: &Stage) -> i32 {
+        if state.tile_props.is_empty() {
+            return 0;
+        }
+
+        match self.floor_tile(state, stage).and_then(|tile| state.tile_props.get(&stage.data.tileset.name, tile)) {
+            Some(TileProperty::Conveyor { vel_x }) => vel_x as i32,
+            _ => 0,
+        }
+    }
+
+    /// Returns the tile at the entity's center, offset by given amount of tiles vertically.
+    fn center_tile(&self, state: &SharedGameState, stage: &Stage, offset_y: i32) -> Option<u16> {
+        let tile_size = state.tile_size.as_int() * 0x200;
+        let half_tile_size = tile_size / 2;
+        let x = (self.x() + half_tile_size) / tile_size;
+        let y = (self.y() + half_tile_size) / tile_size + offset_y;
+        if x < 0 || y < 0 {
+            return None;
+        }
+
+        Some(stage.tile_at(x as usize, y as usize) as u16)
+    }
+
+    /// Returns drag and buoyancy of the water zone tile the entity's center is in, see `TileProperty::Water`.
+    fn water_zone(&mut self, state: &SharedGameState, stage: &Stage) -> Option<(f32, i32)> {
+        if state.tile_props.is_empty() {
+            return None;
+        }
+
+        match self.center_tile(state, stage, 0).and_then(|tile| state.tile_props.get(&stage.data.tileset.name, tile)) {
+            Some(TileProperty::Water { drag, buoyancy_accel }) => Some((drag, buoyancy_accel as i32)),
+            _ => None,
+        }
+    }
+
+    /// Returns damage and interval of the lava tile at the entity's center or under its feet.
+    fn lava_zone(&mut self, state: &SharedGameState, stage: &Stage) -> Option<(u8, u16)> {
+        if state.tile_props.is_empty() && state.constants.lava_tile_ids.is_empty() {
+            return None;
+        }
+
+        let world = &state.constants.world;
+        let default = (world.lava_damage, world.lava_tick_interval);
+        let tileset = &stage.data.tileset.name;
+
+        [self.center_tile(state, stage, 0), self.floor_tile(state, stage)]
+            .into_iter()
+            .flatten()
+            .find_map(|tile| state.tile_props.lava(tileset, tile, &state.constants.lava_tile_ids, default))
+    }
+
+    /// Returns whether the spike tile the entity is touching kills instantly, see `TileProperty::Spike`.
+    fn touching_spike(&mut self, state: &SharedGameState, stage: &Stage) -> Option<bool> {
+        if state.tile_props.is_empty() && state.constants.spike_tile_ids.is_empty() {
+            return None;
+        }
+
+        let tile_size = state.tile_size.as_int() * 0x200;
+        let to_tile = |v: i32| (v + tile_size / 2).div_euclid(tile_size);
+        let flags = *self.flags();
+        let bounds = *self.hit_bounds();
+        let (x, y) = (self.x(), self.y());
+
+        // point to test, whether the entity is touching it and which way spikes there must point to hurt
+        let contacts = [
+            (x, y, true, None),
+            (x, y + bounds.bottom as i32 + 0x200, flags.hit_bottom_wall(), Some(Direction::Up)),
+            (x, y - bounds.top as i32 - 0x200, flags.hit_top_wall(), Some(Direction::Bottom)),
+            (x - bounds.left as i32 - 0x200, y, flags.hit_left_wall(), Some(Direction::Right)),
+            (x + bounds.right as i32 + 0x200, y, flags.hit_right_wall(), Some(Direction::Left)),
+        ];
+
+        let tileset = &stage.data.tileset.name;
+        for (px, py, touching, side) in contacts {
+            let (tx, ty) = (to_tile(px), to_tile(py));
+            if !touching || tx < 0 || ty < 0 {
+                continue;
+            }
+
+            let tile = stage.tile_at(tx as usize, ty as usize) as u16;
+            if let Some((instant_kill, direction)) =
+                state.tile_props.spike(tileset, tile, &state.constants.spike_tile_ids)
+            {
+                if direction.is_none() || side.is_none() || direction == side {
+                    return Some(instant_kill);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns true if there's a ladder tile at the entity's center, offset by given amount of tiles vertically.
+    fn test_ladder(&self, state: &SharedGameState, stage: &Stage, offset_y: i32) -> bool {
+        if state.tile_props.is_empty() {
+            return false;
+        }
+
+        let tile = self.center_tile(state, stage, offset_y);
+        tile.and_then(|tile| state.tile_props.get(&stage.data.tileset.name, tile)) == Some(TileProperty::Ladder)
+    }
+
+    /// Applies buoyancy and drag of a water zone to the entity's velocity.
+    fn apply_water_zone(&mut self, drag: f32, buoyancy_accel: i32, horizontal: bool) {
+        if horizontal {
+            self.set_vel_x((self.vel_x() as f32 * drag) as i32);
+        }
+
+        self.set_vel_y(((self.vel_y() + buoyancy_accel) as f32 * drag) as i32);
+    }
 
     fn test_block_hit(&mut self, state: &mut SharedGameState, x: i32, y: i32) {
         let bounds_x = if self.is_player() { 0x600 } else { 0x600 };
@@ -229,12 +406,18 @@ pub trait PhysicalEntity {
     fn test_platform_hit(&mut self, state: &mut SharedGameState, x: i32, y: i32) {
         let half_tile_size = state.tile_size.as_int() * 0x100;
 
+        let top = (y * 2 - 1) * half_tile_size;
+        let bottom = self.y() + self.hit_bounds().bottom as i32;
+        // compare with the position from before this tick's movement, so falling faster than the
+        // snapping range doesn't skip the platform
+        let crossed_top = self.vel_y() > 0 && bottom - self.vel_y() <= top;
+
         if ((self.x() - self.hit_bounds().right as i32) < (x * 2 + 1) * half_tile_size)
             && ((self.x() + self.hit_bounds().right as i32) > (x * 2 - 1) * half_tile_size)
-            && ((self.y() + self.hit_bounds().bottom as i32) > ((y * 2 - 1) * half_tile_size))
-            && ((self.y() + self.hit_bounds().bottom as i32) < (y * 2 - 1) * half_tile_size + 0x400)
+            && bottom > top
+            && (bottom < top + 0x400 || crossed_top)
         {
-            self.set_y(((y * 2 - 1) * half_tile_size) - self.hit_bounds().bottom as i32);
+            self.set_y(top - self.hit_bounds().bottom as i32);
 
             if self.is_player() {
                 if self.vel_y() > 0x400 {
@@ -734,6 +917,33 @@ pub trait PhysicalEntity {
         }
     }
 
+    /// Accelerates the entity along force tiles it overlaps, the same way the player is pushed by them.
+    fn apply_forces(&mut self) {
+        let flags = *self.flags();
+        if !(flags.force_left() || flags.force_up() || flags.force_right() || flags.force_down()) {
+            return;
+        }
+
+        let mut vel_x = self.vel_x();
+        let mut vel_y = self.vel_y();
+
+        if flags.force_left() {
+            vel_x -= 0x88;
+        }
+        if flags.force_up() {
+            vel_y -= 0x80;
+        }
+        if flags.force_right() {
+            vel_x += 0x88;
+        }
+        if flags.force_down() {
+            vel_y += 0x55;
+        }
+
+        self.set_vel_x(vel_x.clamp(-FORCE_MAX_SPEED, FORCE_MAX_SPEED));
+        self.set_vel_y(vel_y.clamp(-FORCE_MAX_SPEED, FORCE_MAX_SPEED));
+    }
+
     fn tick_map_collisions(&mut self, state: &mut SharedGameState, _npc_list: &NPCList, stage: &mut Stage) {
         let hit_rect_size = self.hit_rect_size().clamp(1, 4);
         let hit_rect_size = if state.tile_size == TileSize::Tile8x8 {
@@ -770,14 +980,17 @@ pub trait PhysicalEntity {
                     self.test_block_hit(state, x + ox, y + oy);
                     self.test_hit_water(state, x + ox, y + oy);
                 }
-                0x04 | 0x64 if !self.is_player() => {
+                0x04 | 0x64 if !self.ignores_npc_solidity() => {
                     self.test_block_hit(state, x + ox, y + oy);
                     self.test_hit_water(state, x + ox, y + oy);
                 }
-                0x05 | 0x41 | 0x43 | 0x46 if self.is_player() => {
+                0x05 | 0x41 | 0x43 => {
                     self.test_block_hit(state, x + ox, y + oy);
                 }
-                0x03 | 0x05 | 0x41 | 0x43 if !self.is_player() => {
+                0x46 if self.ignores_npc_solidity() => {
+                    self.test_block_hit(state, x + ox, y + oy);
+                }
+                0x03 if !self.ignores_npc_solidity() => {
                     self.test_block_hit(state, x + ox, y + oy);
                 }
                 0x44 => {
@@ -907,5 +1120,9 @@ pub trait PhysicalEntity {
         if (self.y() - 0x800) > state.water_level {
             self.flags().set_in_water(true);
         }
+
+        if self.blown_by_forces() {
+            self.apply_forces();
+        }
     }
 }
@@ -1,32 +1,50 @@
 use std::clone::Clone;
+use std::f32::consts::PI;
 
 use num_derive::FromPrimitive;
 use num_traits::clamp;
 
-use crate::common::{interpolate_fix9_scale, Condition, Direction, Equipment, Flag, Rect};
+use crate::common::{interpolate_fix9_scale, Condition, Direction, Equipment, Flag, PlayerAbility, Rect};
 use crate::components::number_popup::NumberPopup;
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::game::caret::CaretType;
+use crate::game::events::{DamageSource, GameEvent};
 use crate::game::frame::Frame;
 use crate::game::npc::list::NPCList;
 use crate::game::npc::NPC;
+use crate::game::physics::{CLIMB_HOP, CLIMB_SPEED};
 use crate::game::player::skin::basic::BasicPlayerSkin;
 use crate::game::player::skin::{PlayerAnimationState, PlayerAppearanceState, PlayerSkin};
-use crate::game::shared_game_state::SharedGameState;
+use crate::game::player::status_effects::StatusEffects;
+use crate::game::shared_game_state::{Player2JoinState, SharedGameState};
+use crate::game::tile_props::SPIKE_DAMAGE;
 use crate::input::dummy_player_controller::DummyPlayerController;
 use crate::input::player_controller::PlayerController;
 use crate::util::rng::RNG;
 
 mod player_hit;
 pub mod skin;
+pub mod status_effects;
+
+/// Horizontal speed the player is pushed away from damage sources with, see `Player::knock_back_from`.
+const DAMAGE_KNOCKBACK: i32 = 0x300;
+/// Acceleration pulling a ceiling walking player towards the ceiling.
+const CEILING_GRAVITY: i32 = 0x50;
+const CEILING_MAX_SPEED: i32 = 0x5ff;
+/// Speed of the jump off a ladder.
+const CLIMB_JUMP: (i32, i32) = (0x300, 0x400);
+/// Sound played on top of the hurt sound each time lava damages the player.
+const LAVA_BURN_SFX: u8 = 34;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
 #[repr(u8)]
 pub enum ControlMode {
     Normal = 0,
     IronHead,
+    /// Normal physics, but the player doesn't respond to input.
+    Locked,
 }
 
 #[derive(PartialEq, Eq, Copy, Clone)]
@@ -91,6 +109,7 @@ pub struct Player {
     pub cond: Condition,
     pub flags: Flag,
     pub equip: Equipment,
+    pub abilities: PlayerAbility,
     pub direction: Direction,
     pub display_bounds: Rect<u32>,
     pub hit_bounds: Rect<u32>,
@@ -110,6 +129,8 @@ pub struct Player {
     pub controller: Box<dyn PlayerController>,
     pub damage_popup: NumberPopup,
     pub exp_popup: NumberPopup,
+    /// Shows recovered health if `Settings::heal_popups` is on.
+    pub heal_popup: NumberPopup,
     strafe_up: bool,
     weapon_offset_y: i8,
     splash: bool,
@@ -122,6 +143,31 @@ pub struct Player {
     dog_stack: Vec<DogStack>,
     pub has_dog: bool,
     pub teleport_counter: u16,
+    /// Extra jumps left before landing, refilled from `PlayerConsts::air_jumps` with the double jump ability.
+    pub air_jumps_remaining: u8,
+    /// Set while falling in a ground slam, cleared on landing.
+    pub slam_active: bool,
+    /// Point where a ground slam landed this tick, the scene damages NPCs and bosses around it.
+    pub slam_landing: Option<(i32, i32)>,
+    pub is_gliding: bool,
+    pub is_crouching: bool,
+    /// Set while gravity is inverted by the ceiling walk ability, ceilings act as floors.
+    pub is_ceiling_walking: bool,
+    /// Set while standing on a slippery tile, see [Player::tick_surface].
+    pub on_ice: bool,
+    /// Speed of the conveyor belt the player stands on, added to their position each tick.
+    pub conveyor_vel_x: i32,
+    /// Set while the player's center is in front of a ladder tile, see [Player::tick_surface].
+    pub on_ladder: bool,
+    pub is_climbing: bool,
+    /// Damage and interval of the lava tile the player is touching, see [Player::tick_surface].
+    pub in_lava: Option<(u8, u16)>,
+    pub lava_damage_timer: u16,
+    /// Set while touching a spike tile, to whether it kills instantly. See [Player::tick_surface].
+    pub spike_contact: Option<bool>,
+    pub status_effects: StatusEffects,
+    /// Set for the co-op partner, who becomes a ghost on death instead of causing a game over.
+    pub(crate) spectate_on_death: bool,
 }
 
 impl Player {
@@ -145,6 +191,7 @@ impl Player {
             cond: Condition(0),
             flags: Flag(0),
             equip: Equipment(0),
+            abilities: PlayerAbility(0),
             direction: Direction::Right,
             display_bounds: skin.get_display_bounds(),
             hit_bounds: skin.get_hit_bounds(),
@@ -168,6 +215,7 @@ impl Player {
             controller: Box::new(DummyPlayerController::new()),
             damage_popup: NumberPopup::new(),
             exp_popup: NumberPopup::new(),
+            heal_popup: NumberPopup::with_color((128, 255, 160, 255)),
             strafe_up: false,
             anim_num: 0,
             anim_counter: 0,
@@ -176,6 +224,21 @@ impl Player {
             dog_stack: Vec::new(),
             has_dog: false,
             teleport_counter: 0,
+            air_jumps_remaining: 0,
+            slam_active: false,
+            slam_landing: None,
+            is_gliding: false,
+            is_ceiling_walking: false,
+            is_crouching: false,
+            on_ice: false,
+            conveyor_vel_x: 0,
+            on_ladder: false,
+            is_climbing: false,
+            in_lava: None,
+            lava_damage_timer: 0,
+            spike_contact: None,
+            status_effects: StatusEffects::default(),
+            spectate_on_death: false,
         }
     }
 
@@ -193,6 +256,24 @@ impl Player {
         self.hit_bounds = self.skin.get_hit_bounds();
     }
 
+    fn land_slam(&mut self, state: &mut SharedGameState) {
+        let feet_y = self.y + self.hit_bounds.bottom as i32;
+
+        self.slam_landing = Some((self.x, feet_y));
+
+        state.quake_counter = 20;
+        state.sound_manager.play_sfx(26);
+
+        for _ in 0..4 {
+            state.create_caret(
+                self.x + state.game_rng.range(-12..12) * 0x200,
+                feet_y,
+                CaretType::LittleParticles,
+                Direction::Left,
+            );
+        }
+    }
+
     fn tick_normal(&mut self, state: &mut SharedGameState, npc_list: &NPCList) -> GameResult {
         if !state.control_flags.interactions_disabled() && state.control_flags.control_enabled() {
             if self.equip.has_air_tank() {
@@ -210,7 +291,7 @@ impl Player {
                         self.cond.set_hidden(true);
                         state.create_caret(self.x, self.y, CaretType::DrownedQuote, self.direction);
                     }
-                    state.textscript_vm.start_script(41);
+                    state.textscript_vm.interrupt_script(41);
                 }
             } else {
                 self.air = 1000;
@@ -249,8 +330,50 @@ impl Player {
             self.strafe_up = false;
         }
 
+        if !self.abilities.has_ceiling_walk() {
+            self.is_ceiling_walking = false;
+        } else if !self.is_ceiling_walking
+            && state.control_flags.control_enabled()
+            && self.flags.hit_top_wall()
+            && self.controller.jump()
+        {
+            self.is_ceiling_walking = true;
+            self.slam_active = false;
+            self.booster_switch = BoosterSwitch::None;
+        }
+
+        if !self.is_climbing
+            && !self.is_ceiling_walking
+            && self.on_ladder
+            && state.control_flags.control_enabled()
+            && self.controller.move_up()
+            && !self.controller.shoot()
+        {
+            self.is_climbing = true;
+            self.is_gliding = false;
+            self.slam_active = false;
+            self.booster_switch = BoosterSwitch::None;
+        }
+
+        self.tick_lava(state, npc_list);
+        self.tick_spikes(state, npc_list);
+
+        if !self.cond.alive() {
+            return Ok(());
+        }
+
+        if self.is_climbing {
+            return self.tick_climbing(state);
+        }
+
+        let on_ground = if self.is_ceiling_walking {
+            self.flags.hit_top_wall()
+        } else {
+            self.flags.hit_bottom_wall() || self.flags.hit_right_slope() || self.flags.hit_left_slope()
+        };
+
         // ground movement
-        if self.flags.hit_bottom_wall() || self.flags.hit_right_slope() || self.flags.hit_left_slope() {
+        if on_ground {
             self.booster_switch = BoosterSwitch::None;
 
             if state.settings.infinite_booster {
@@ -261,6 +384,14 @@ impl Player {
                 self.booster_fuel = 0;
             }
 
+            self.air_jumps_remaining =
+                if self.abilities.has_double_jump() { state.constants.player.air_jumps } else { 0 };
+
+            if self.slam_active {
+                self.slam_active = false;
+                self.land_slam(state);
+            }
+
             if state.control_flags.control_enabled() {
                 let only_down = self.controller.move_down()
                     && !self.controller.move_up()
@@ -283,12 +414,18 @@ impl Player {
                     self.cond.set_interacted(true);
                     self.question = true;
                 } else {
+                    let dash_ground = if self.on_ice {
+                        (physics.dash_ground as f32 * state.constants.world.ice_accel_factor) as i32
+                    } else {
+                        physics.dash_ground
+                    };
+
                     if self.controller.move_left() && self.vel_x > -physics.max_dash {
-                        self.vel_x -= physics.dash_ground;
+                        self.vel_x -= dash_ground;
                     }
 
                     if self.controller.move_right() && self.vel_x < physics.max_dash {
-                        self.vel_x += physics.dash_ground;
+                        self.vel_x += dash_ground;
                     }
 
                     if !self.controller.strafe() {
@@ -303,7 +440,16 @@ impl Player {
                 }
             }
 
-            if !self.cond.increase_acceleration() {
+            if self.is_crouching {
+                let max_dash = (physics.max_dash as f32 * state.constants.player.crouch_speed_factor) as i32;
+                self.vel_x = self.vel_x.clamp(-max_dash, max_dash);
+            }
+
+            if self.on_ice {
+                if !self.controller.move_left() && !self.controller.move_right() {
+                    self.vel_x = (self.vel_x as f32 * state.constants.world.ice_friction) as i32;
+                }
+            } else if !self.cond.increase_acceleration() {
                 if self.vel_x < 0 {
                     if self.vel_x > -physics.resist {
                         self.vel_x = 0;
@@ -322,7 +468,18 @@ impl Player {
         } else {
             // air movement
             if state.control_flags.control_enabled() {
-                if self.controller.trigger_jump() && self.booster_fuel != 0 {
+                if self.equip.has_ground_slam()
+                    && !self.slam_active
+                    && self.controller.move_down()
+                    && self.controller.trigger_jump()
+                {
+                    self.slam_active = true;
+                    self.booster_switch = BoosterSwitch::None;
+                    self.vel_x = 0;
+                } else if self.controller.trigger_jump()
+                    && self.booster_fuel != 0
+                    && (self.air_jumps_remaining == 0 || !self.abilities.has_double_jump())
+                {
                     if self.equip.has_booster_0_8() {
                         self.booster_switch = BoosterSwitch::Up;
 
@@ -395,15 +552,43 @@ impl Player {
             self.up = self.controller.move_up() || self.strafe_up;
             self.down = self.controller.move_down() && !self.flags.hit_bottom_wall();
 
-            if self.controller.trigger_jump()
-                && (self.flags.hit_bottom_wall() || self.flags.hit_right_slope() || self.flags.hit_left_slope())
+            if self.controller.trigger_jump() && self.is_ceiling_walking && on_ground {
+                // jumping off the ceiling restores normal gravity
+                self.is_ceiling_walking = false;
+                self.vel_y = physics.jump;
+                state.sound_manager.play_sfx(15);
+            } else if self.controller.trigger_jump() && on_ground && !self.flags.force_up() {
+                self.vel_y = -physics.jump;
+                state.sound_manager.play_sfx(15);
+            } else if self.controller.trigger_jump()
+                && self.abilities.has_double_jump()
+                && self.air_jumps_remaining > 0
+                && !self.slam_active
+                && self.booster_switch == BoosterSwitch::None
+                && !self.is_ceiling_walking
+                && !self.flags.hit_bottom_wall()
                 && !self.flags.force_up()
             {
+                self.air_jumps_remaining -= 1;
                 self.vel_y = -physics.jump;
-                state.sound_manager.play_sfx(15);
+                state.sound_manager.play_sfx(30);
+                state.create_caret(
+                    self.x,
+                    self.y + self.hit_bounds.bottom as i32,
+                    CaretType::LittleParticles,
+                    Direction::Left,
+                );
             }
         }
 
+        self.is_gliding = self.equip.has_cape()
+            && state.control_flags.control_enabled()
+            && self.controller.jump()
+            && self.vel_y > 0
+            && !self.slam_active
+            && !self.flags.hit_bottom_wall()
+            && !self.flags.hit_top_wall();
+
         // stop interacting when moved
         if state.control_flags.control_enabled()
             && (self.controller.move_left()
@@ -435,7 +620,9 @@ impl Player {
             self.vel_y += 0x55;
         }
 
-        if (state.settings.infinite_booster || self.equip.has_booster_2_0())
+        if self.is_ceiling_walking {
+            self.vel_y -= CEILING_GRAVITY;
+        } else if (state.settings.infinite_booster || self.equip.has_booster_2_0())
             && self.booster_switch != BoosterSwitch::None
         {
             match self.booster_switch {
@@ -513,6 +700,8 @@ impl Player {
             }
         } else if self.vel_y < 0 && state.control_flags.control_enabled() && self.controller.jump() {
             self.vel_y += physics.gravity_air;
+        } else if self.is_gliding {
+            self.vel_y += (physics.gravity_ground as f32 * state.constants.player.glide_gravity_factor) as i32;
         } else {
             self.vel_y += physics.gravity_ground;
         }
@@ -550,6 +739,14 @@ impl Player {
         self.vel_x = self.vel_x.clamp(-max_move, max_move);
         self.vel_y = self.vel_y.clamp(-max_move, max_move);
 
+        if self.is_ceiling_walking {
+            self.vel_y = self.vel_y.max(-CEILING_MAX_SPEED);
+        } else if self.slam_active {
+            self.vel_y = state.constants.player.slam_velocity;
+        } else if self.is_gliding && self.vel_y > state.constants.player.glide_max_fall {
+            self.vel_y = state.constants.player.glide_max_fall;
+        }
+
         if !self.splash && self.flags.in_water() {
             let vertical_splash = !self.flags.hit_bottom_wall() && self.vel_y > 0x200;
             let horizontal_splash = self.vel_x > 0x200 || self.vel_x < -0x200;
@@ -584,9 +781,15 @@ impl Player {
             self.splash = false;
         }
 
+        // air bubbles rising from the mouth
+        if state.settings.water_bubbles && self.flags.in_water() && self.tick % 64 == 0 {
+            let mouth_x = self.x + self.direction.vector_x() * 0x600;
+            state.create_caret(mouth_x, self.y - 0x600, CaretType::Bubble, Direction::Up);
+        }
+
         // spike damage
         if self.flags.hit_by_spike() {
-            self.damage(10, state, npc_list);
+            self.damage_from(10, DamageSource::Tile, state, npc_list);
         }
 
         // camera
@@ -621,6 +824,66 @@ impl Player {
             self.x += self.vel_x;
         }
 
+        self.x += self.conveyor_vel_x;
+        self.y += self.vel_y;
+
+        Ok(())
+    }
+
+    /// Moves the player along a ladder, without gravity.
+    fn tick_climbing(&mut self, state: &mut SharedGameState) -> GameResult {
+        let control_enabled = state.control_flags.control_enabled();
+
+        if !self.on_ladder {
+            // climbed past the top or fell off the bottom end
+            self.is_climbing = false;
+            if self.vel_y < 0 {
+                self.vel_y = -CLIMB_HOP;
+            }
+
+            return Ok(());
+        }
+
+        if control_enabled && self.controller.trigger_jump() {
+            self.is_climbing = false;
+            self.vel_x = if self.controller.move_left() {
+                -CLIMB_JUMP.0
+            } else if self.controller.move_right() {
+                CLIMB_JUMP.0
+            } else {
+                0
+            };
+            self.vel_y = -CLIMB_JUMP.1;
+            state.sound_manager.play_sfx(15);
+
+            return Ok(());
+        }
+
+        self.vel_x = 0;
+        self.vel_y = 0;
+
+        if control_enabled {
+            if self.controller.move_left() {
+                self.direction = Direction::Left;
+            } else if self.controller.move_right() {
+                self.direction = Direction::Right;
+            }
+
+            if self.controller.move_up() {
+                self.vel_y = -CLIMB_SPEED;
+            } else if self.controller.move_down() {
+                if self.flags.hit_bottom_wall() {
+                    // reached the ground
+                    self.is_climbing = false;
+                    return Ok(());
+                }
+
+                self.vel_y = CLIMB_SPEED;
+            }
+        }
+
+        self.target_x = self.x + self.camera_target_x;
+        self.target_y = self.y + self.camera_target_y;
         self.y += self.vel_y;
 
         Ok(())
@@ -742,8 +1005,20 @@ impl Player {
             return;
         }
 
-        if self.flags.hit_bottom_wall() {
-            if self.cond.interacted() {
+        if self.is_climbing {
+            self.skin.set_state(if self.vel_y != 0 {
+                PlayerAnimationState::Climbing
+            } else {
+                PlayerAnimationState::LookingUp
+            });
+            self.anim_num = 5;
+            self.anim_counter = 0;
+        } else if self.flags.hit_bottom_wall() {
+            if self.is_crouching {
+                self.skin.set_state(PlayerAnimationState::Crouching);
+                self.anim_num = 0;
+                self.anim_counter = 0;
+            } else if self.cond.interacted() {
                 self.skin.set_state(PlayerAnimationState::Examining);
                 self.anim_num = 11;
                 self.anim_counter = 0;
@@ -815,6 +1090,10 @@ impl Player {
             self.skin.set_state(PlayerAnimationState::FallingLookingDown);
             self.anim_num = 10;
             self.anim_counter = 0;
+        } else if self.is_gliding {
+            self.skin.set_state(PlayerAnimationState::Gliding);
+            self.anim_num = 3;
+            self.anim_counter = 0;
         } else {
             if self.vel_y > 0 {
                 self.skin.set_state(PlayerAnimationState::Falling);
@@ -851,6 +1130,10 @@ impl Player {
             self.weapon_rect.top += 1;
         }
 
+        if self.is_crouching {
+            self.weapon_offset_y += (state.constants.player.crouch_barrel_offset_y / 0x200) as i8;
+        }
+
         self.skin.tick();
         self.skin.set_direction(self.direction);
         self.skin.set_appearance(if self.equip.has_mimiga_mask() {
@@ -870,18 +1153,108 @@ impl Player {
     }
 
     pub fn damage(&mut self, hp: i32, state: &mut SharedGameState, npc_list: &NPCList) {
+        self.damage_from(hp, DamageSource::Other, state, npc_list);
+    }
+
+    pub fn damage_from(&mut self, hp: i32, source: DamageSource, state: &mut SharedGameState, npc_list: &NPCList) {
         if self.life == 0 || hp <= 0 || state.settings.god_mode || self.shock_counter > 0 {
             return;
         }
 
         state.sound_manager.play_sfx(16);
-        self.shock_counter = 128;
+        state.event_bus.emit(GameEvent::PlayerDamaged { amount: hp.min(u16::MAX as i32) as u16, source });
+        self.shock_counter = self.invincible_ticks(state);
         self.cond.set_interacted(false);
 
-        if self.control_mode == ControlMode::Normal {
+        if self.control_mode != ControlMode::IronHead {
             self.vel_y = -0x400; // -2.0fix9
         }
 
+        self.lose_life(hp, state, npc_list);
+    }
+
+    /// Burns the player every few ticks while they're touching lava.
+    fn tick_lava(&mut self, state: &mut SharedGameState, npc_list: &NPCList) {
+        let (damage, tick_interval) = match self.in_lava {
+            Some(lava) => lava,
+            None => {
+                self.lava_damage_timer = 0;
+                return;
+            }
+        };
+
+        self.lava_damage_timer += 1;
+        if self.lava_damage_timer < tick_interval.max(1) {
+            return;
+        }
+        self.lava_damage_timer = 0;
+
+        // lava only respects the part of invincibility frames it doesn't bypass
+        let invincible_ticks = self.invincible_ticks(state);
+        let bypass_fraction = state.constants.lava_iframe_bypass_fraction.clamp(0.0, 1.0);
+        let bypassed = (invincible_ticks as f32 * bypass_fraction) as u8;
+        if self.shock_counter > invincible_ticks.saturating_sub(bypassed) {
+            return;
+        }
+
+        let shock_counter = self.shock_counter;
+        self.shock_counter = 0;
+        self.damage_from(damage as i32, DamageSource::Lava, state, npc_list);
+
+        if self.shock_counter == 0 {
+            // ignored, eg. in god mode
+            self.shock_counter = shock_counter;
+        } else {
+            state.sound_manager.play_sfx(LAVA_BURN_SFX);
+        }
+    }
+
+    /// Hurts or kills the player touching a spike tile, also while climbing.
+    fn tick_spikes(&mut self, state: &mut SharedGameState, npc_list: &NPCList) {
+        match self.spike_contact {
+            Some(true) => self.kill(state, npc_list),
+            Some(false) => self.damage_from(SPIKE_DAMAGE as i32, DamageSource::Tile, state, npc_list),
+            None => (),
+        }
+    }
+
+    /// Returns for how long the player is invincible after taking damage.
+    pub fn invincible_ticks(&self, state: &SharedGameState) -> u8 {
+        let consts = &state.constants.player;
+        let ticks = if self.equip.has_guardian_charm() {
+            consts.guardian_charm_invincible_ticks
+        } else {
+            consts.invincible_ticks
+        };
+
+        if self.equip.has_whimsical_star() {
+            (ticks as f32 * consts.spirit_invincible_multiplier).clamp(0.0, u8::MAX as f32) as u8
+        } else {
+            ticks
+        }
+    }
+
+    /// Pushes the player away from a damage source at given x position, if enabled in settings.
+    pub fn knock_back_from(&mut self, source_x: i32, state: &SharedGameState) {
+        if !state.settings.damage_knockback || self.control_mode == ControlMode::IronHead {
+            return;
+        }
+
+        self.vel_x = if self.x < source_x { -DAMAGE_KNOCKBACK } else { DAMAGE_KNOCKBACK };
+    }
+
+    /// Returns true if the player sprite is hidden in the current frame of the invincibility blink.
+    pub fn is_shock_blink_hidden(&self, state: &SharedGameState) -> bool {
+        if !state.settings.variable_shock_blink {
+            return self.shock_counter / 2 % 2 != 0;
+        }
+
+        let period = 1 + self.shock_counter / 32;
+        self.shock_counter / period % 2 != 0
+    }
+
+    /// Takes away health without playing the hurt sound, knockback or invincibility frames, kills the player at 0.
+    fn lose_life(&mut self, hp: i32, state: &mut SharedGameState, npc_list: &NPCList) {
         let final_hp = state.get_damage(hp);
 
         self.life = self.life.saturating_sub(final_hp as u16);
@@ -899,27 +1272,87 @@ impl Player {
         self.damage_popup.update_displayed_value();
 
         if self.life == 0 {
-            state.sound_manager.play_sfx(17);
-            self.cond.0 = 0;
-            state.control_flags.set_tick_world(true);
-            state.control_flags.set_interactions_disabled(true);
-            state.textscript_vm.start_script(40);
+            self.die(state, npc_list);
+        }
 
-            state.create_caret(self.x, self.y, CaretType::Explosion, Direction::Left);
-            let mut npc = NPC::create(4, &state.npc_table);
-            npc.cond.set_alive(true);
-            for _ in 0..0x40 {
-                npc.x = self.x + state.game_rng.range(-10..10) as i32 * 0x200;
-                npc.y = self.y + state.game_rng.range(-10..10) as i32 * 0x200;
+        #[cfg(feature = "discord-rpc")]
+        let _ = state.discord_rpc.update_hp(&self);
+    }
 
-                let _ = npc_list.spawn(0x100, npc.clone());
-            }
+    /// Kills the player right away, regardless of their health and damage modifiers.
+    pub fn kill(&mut self, state: &mut SharedGameState, npc_list: &NPCList) {
+        if self.life == 0 || state.settings.god_mode {
+            return;
         }
 
+        self.life = 0;
+        self.controller.set_rumble(0x6000, 0x6000, 20);
+        self.die(state, npc_list);
+
         #[cfg(feature = "discord-rpc")]
         let _ = state.discord_rpc.update_hp(&self);
     }
 
+    fn die(&mut self, state: &mut SharedGameState, npc_list: &NPCList) {
+        state.sound_manager.play_sfx(17);
+        self.cond.0 = 0;
+
+        if self.spectate_on_death && state.player2_join_state == Player2JoinState::Active {
+            state.player2_join_state = Player2JoinState::Ghost;
+        } else {
+            state.control_flags.set_tick_world(true);
+            state.control_flags.set_interactions_disabled(true);
+            // death can happen in the middle of any event, boss fights included
+            state.textscript_vm.interrupt_script(40);
+        }
+
+        state.create_caret(self.x, self.y, CaretType::Explosion, Direction::Left);
+        let mut npc = NPC::create(4, &state.npc_table);
+        npc.cond.set_alive(true);
+        for _ in 0..0x40 {
+            npc.x = self.x + state.game_rng.range(-10..10) as i32 * 0x200;
+            npc.y = self.y + state.game_rng.range(-10..10) as i32 * 0x200;
+
+            let _ = npc_list.spawn(0x100, npc.clone());
+        }
+    }
+
+    /// Shows health recovered by the player above them, if enabled in settings.
+    pub fn show_heal_popup(&mut self, state: &SharedGameState, amount: u16) {
+        if state.settings.heal_popups && amount > 0 {
+            self.heal_popup.add_value(amount.min(i16::MAX as u16) as i16);
+            self.heal_popup.update_displayed_value();
+        }
+    }
+
+    /// Brings the player back to life at given position with full health, used by checkpoints.
+    pub fn respawn(&mut self, x: i32, y: i32) {
+        self.cond.0 = 0x80;
+        self.flags.0 = 0;
+        self.life = self.max_life;
+        self.x = x;
+        self.y = y;
+        self.prev_x = x;
+        self.prev_y = y;
+        self.vel_x = 0;
+        self.vel_y = 0;
+        self.shock_counter = 0;
+        self.is_ceiling_walking = false;
+        self.is_climbing = false;
+        self.in_lava = None;
+        self.lava_damage_timer = 0;
+        self.spike_contact = None;
+        self.damage = 0;
+        self.damage_popup = NumberPopup::new();
+        self.cure_status_effects();
+    }
+
+    /// Raises max life by given amount, which is also added to current life unless `full_heal` is set.
+    pub fn add_max_life(&mut self, amount: u16, full_heal: bool) {
+        self.max_life = self.max_life.saturating_add(amount);
+        self.life = if full_heal { self.max_life } else { self.life.saturating_add(amount) };
+    }
+
     pub fn update_teleport_counter(&mut self, state: &SharedGameState) {
         self.teleport_counter += 1;
 
@@ -955,10 +1388,19 @@ impl GameEntity<&NPCList> for Player {
             self.exp_popup.update_displayed_value();
         }
 
+        self.apply_status_effects(state, npc_list);
+
         match (self.control_mode, state.settings.noclip) {
             (_, true) => self.tick_ironhead(state)?,
             (ControlMode::Normal, _) => self.tick_normal(state, npc_list)?,
             (ControlMode::IronHead, _) => self.tick_ironhead(state)?,
+            (ControlMode::Locked, _) => {
+                let control_enabled = state.control_flags.control_enabled();
+
+                state.control_flags.set_control_enabled(false);
+                self.tick_normal(state, npc_list)?;
+                state.control_flags.set_control_enabled(control_enabled);
+            }
         }
 
         self.damage_popup.x = self.x;
@@ -967,6 +1409,9 @@ impl GameEntity<&NPCList> for Player {
         self.exp_popup.x = self.x;
         self.exp_popup.y = self.y - self.display_bounds.top as i32 + 0x1000;
         self.exp_popup.tick(state, ())?;
+        self.heal_popup.x = self.x;
+        self.heal_popup.y = self.y - self.display_bounds.top as i32 + 0x1000;
+        self.heal_popup.tick(state, ())?;
 
         self.cond.set_increase_acceleration(false);
         self.tick_animation(state);
@@ -1034,30 +1479,37 @@ impl GameEntity<&NPCList> for Player {
             }
         }
 
-        if self.shock_counter / 2 % 2 != 0 {
+        if self.is_shock_blink_hidden(state) {
             return Ok(());
         }
 
+        let sprite_x = interpolate_fix9_scale(
+            self.prev_x - self.display_bounds.left as i32,
+            self.x - self.display_bounds.left as i32,
+            state.frame_time,
+        ) - frame_x;
+        let sprite_y = interpolate_fix9_scale(
+            self.prev_y - self.display_bounds.top as i32,
+            self.y - self.display_bounds.top as i32,
+            state.frame_time,
+        ) - frame_y;
+        // ceiling walking player is drawn upside down, rotated around their center
+        let (center_x, center_y) =
+            (sprite_x + self.display_bounds.left as f32 / 512.0, sprite_y + self.display_bounds.top as f32 / 512.0);
+
         if self.current_weapon != 0 {
             let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "Arms")?;
             let (gun_off_x, gun_off_y) = self.skin.get_gun_offset();
 
-            batch.add_rect(
-                interpolate_fix9_scale(
-                    self.prev_x - self.display_bounds.left as i32,
-                    self.x - self.display_bounds.left as i32,
-                    state.frame_time,
-                ) + if self.direction == Direction::Left { -8.0 - gun_off_x as f32 } else { gun_off_x as f32 }
-                    - frame_x,
-                interpolate_fix9_scale(
-                    self.prev_y - self.display_bounds.top as i32,
-                    self.y - self.display_bounds.top as i32,
-                    state.frame_time,
-                ) + self.weapon_offset_y as f32
-                    + gun_off_y as f32
-                    - frame_y,
-                &self.weapon_rect,
-            );
+            let gun_x =
+                sprite_x + if self.direction == Direction::Left { -8.0 - gun_off_x as f32 } else { gun_off_x as f32 };
+            let gun_y = sprite_y + self.weapon_offset_y as f32 + gun_off_y as f32;
+
+            if self.is_ceiling_walking {
+                batch.add_rect_rotated(gun_x, gun_y, PI, (center_x - gun_x, center_y - gun_y), &self.weapon_rect);
+            } else {
+                batch.add_rect(gun_x, gun_y, &self.weapon_rect);
+            }
 
             batch.draw(ctx)?;
         }
@@ -1065,19 +1517,18 @@ impl GameEntity<&NPCList> for Player {
         {
             let batch =
                 state.texture_set.get_or_load_batch(ctx, &state.constants, self.skin.get_skin_texture_name())?;
-            batch.add_rect(
-                interpolate_fix9_scale(
-                    self.prev_x - self.display_bounds.left as i32,
-                    self.x - self.display_bounds.left as i32,
-                    state.frame_time,
-                ) - frame_x,
-                interpolate_fix9_scale(
-                    self.prev_y - self.display_bounds.top as i32,
-                    self.y - self.display_bounds.top as i32,
-                    state.frame_time,
-                ) - frame_y,
-                &self.anim_rect,
-            );
+
+            if self.is_ceiling_walking {
+                batch.add_rect_rotated(
+                    sprite_x,
+                    sprite_y,
+                    PI,
+                    (center_x - sprite_x, center_y - sprite_y),
+                    &self.anim_rect,
+                );
+            } else {
+                batch.add_rect(sprite_x, sprite_y, &self.anim_rect);
+            }
             batch.draw(ctx)?;
         }
 
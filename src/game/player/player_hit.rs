@@ -4,6 +4,7 @@ use num_traits::abs;
 
 use crate::common::{Condition, Direction, Flag, Rect};
 use crate::game::caret::CaretType;
+use crate::game::events::DamageSource;
 use crate::game::inventory::Inventory;
 use crate::game::npc::boss::BossNPC;
 use crate::game::npc::list::NPCList;
@@ -11,6 +12,7 @@ use crate::game::npc::NPC;
 use crate::game::physics::PhysicalEntity;
 use crate::game::player::{ControlMode, Player, TargetPlayer};
 use crate::game::shared_game_state::SharedGameState;
+use crate::game::stage::Stage;
 use crate::game::weapon::WeaponType;
 
 impl PhysicalEntity for Player {
@@ -102,6 +104,89 @@ impl PhysicalEntity for Player {
 }
 
 impl Player {
+    /// Enters or leaves crouch depending on input, shrinking the hitbox while crouched.
+    /// Stays crouched as long as standing up would put the player inside a ceiling.
+    pub fn tick_crouch(&mut self, state: &SharedGameState, stage: &Stage) {
+        let has_input = self.control_mode != ControlMode::Locked && state.control_flags.control_enabled();
+        let wants_crouch = self.equip.has_crouch()
+            && self.cond.alive()
+            && self.control_mode != ControlMode::IronHead
+            && self.flags.hit_bottom_wall()
+            && has_input
+            && self.controller.move_down()
+            && !self.controller.move_up()
+            && !self.controller.jump();
+
+        let stand_top = self.skin.get_hit_bounds().top;
+        if wants_crouch {
+            self.is_crouching = true;
+        } else if self.is_crouching && self.can_stand_up(state, stage, stand_top) {
+            self.is_crouching = false;
+        }
+
+        self.hit_bounds.top = if self.is_crouching { state.constants.player.crouch_hit_top } else { stand_top };
+    }
+
+    /// Checks the floor the player is standing on for ice and conveyor belts, emitting ice chips when landing on ice,
+    /// looks for ladders and applies water zones.
+    pub fn tick_surface(&mut self, state: &mut SharedGameState, stage: &Stage) {
+        let affected = self.cond.alive() && self.control_mode != ControlMode::IronHead;
+        let on_ice = affected && self.stands_on_ice(state, stage);
+        self.conveyor_vel_x = if affected { self.conveyor_speed(state, stage) } else { 0 };
+
+        self.on_ladder = affected && self.test_ladder(state, stage, 0);
+        self.in_lava = if affected { self.lava_zone(state, stage) } else { None };
+
+        let spike_contact = if affected { self.touching_spike(state, stage) } else { None };
+        if spike_contact.is_some() && self.spike_contact.is_none() {
+            // stepping onto spikes hurts even right after taking damage elsewhere
+            self.shock_counter = 0;
+        }
+        self.spike_contact = spike_contact;
+        if !affected {
+            self.is_climbing = false;
+        }
+
+        if let Some((drag, buoyancy_accel)) = self.water_zone(state, stage) {
+            // lets the regular water code handle the air meter, drowning and splashes
+            self.flags.set_in_water(true);
+
+            if affected {
+                self.apply_water_zone(drag, buoyancy_accel, true);
+            }
+        }
+
+        if on_ice && !self.on_ice {
+            let feet_y = self.y + self.hit_bounds.bottom as i32;
+            state.create_caret(self.x, feet_y, CaretType::LittleParticles, Direction::Left);
+        }
+
+        self.on_ice = on_ice;
+    }
+
+    fn can_stand_up(&self, state: &SharedGameState, stage: &Stage, stand_top: u32) -> bool {
+        let tile_size = state.tile_size.as_int() * 0x200;
+        let to_tile = |v: i32| (v + tile_size / 2).div_euclid(tile_size);
+
+        let (left, right) =
+            (to_tile(self.x - self.hit_bounds.left as i32 + 1), to_tile(self.x + self.hit_bounds.right as i32 - 1));
+        let (top, bottom) = (to_tile(self.y - stand_top as i32), to_tile(self.y - self.hit_bounds.top as i32));
+
+        for y in top..=bottom {
+            for x in left..=right {
+                if x < 0 || y < 0 {
+                    continue;
+                }
+
+                if matches!(stage.map.get_attribute(x as usize, y as usize), 0x05 | 0x41 | 0x43 | 0x46) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
     fn test_hit_npc_solid_soft(&mut self, npc: &NPC) -> Flag {
         let mut flags = Flag(0);
 
@@ -259,6 +344,26 @@ impl Player {
         flags
     }
 
+    /// True if the player overlaps the hitbox of a non-solid NPC.
+    pub fn touches_npc(&mut self, npc: &NPC) -> bool {
+        self.test_hit_npc_non_solid(npc).0 != 0
+    }
+
+    fn damage_by_npc(&mut self, npc: &NPC, state: &mut SharedGameState, npc_list: &NPCList) {
+        let shock_counter = self.shock_counter;
+
+        self.damage_from(npc.damage as i32, DamageSource::Npc(npc.npc_type), state, npc_list);
+
+        // only poison or knock back if the hit went through invincibility frames
+        if shock_counter == 0 && self.shock_counter != 0 {
+            self.knock_back_from(npc.x, state);
+
+            if state.npc_table.poisons_on_hit(npc.npc_type) {
+                self.apply_poison(state.constants.world.poison_damage, state.constants.world.poison_duration);
+            }
+        }
+    }
+
     fn tick_npc_collision(
         &mut self,
         id: TargetPlayer,
@@ -309,7 +414,9 @@ impl Player {
                 }
                 // heart pickup
                 87 => {
+                    let prev_life = self.life;
                     self.life = self.max_life.min(self.life.saturating_add(npc.exp));
+                    self.show_heal_popup(state, self.life.saturating_sub(prev_life));
                     npc.cond.set_alive(false);
 
                     state.sound_manager.play_sfx(20);
@@ -317,6 +424,13 @@ impl Player {
                     #[cfg(feature = "discord-rpc")]
                     let _ = state.discord_rpc.update_hp(&self);
                 }
+                // antidote pickup
+                373 => {
+                    self.cure_status_effects();
+                    npc.cond.set_alive(false);
+
+                    state.sound_manager.play_sfx(20);
+                }
                 _ => {}
             }
         }
@@ -340,10 +454,10 @@ impl Player {
                     || flags.hit_top_wall() && npc.vel_y > 0
                     || flags.hit_bottom_wall() && npc.vel_y < 0
                 {
-                    self.damage(npc.damage as i32, state, npc_list);
+                    self.damage_by_npc(npc, state, npc_list);
                 }
             } else if flags.0 != 0 && npc.damage != 0 && !state.control_flags.interactions_disabled() {
-                self.damage(npc.damage as i32, state, npc_list);
+                self.damage_by_npc(npc, state, npc_list);
             }
         }
 
@@ -373,7 +487,14 @@ impl Player {
             return;
         }
 
-        for npc in npc_list.iter_alive() {
+        let bounds = Rect {
+            left: self.x - self.hit_bounds.left as i32,
+            top: self.y - self.hit_bounds.top as i32,
+            right: self.x + self.hit_bounds.right as i32,
+            bottom: self.y + self.hit_bounds.bottom as i32,
+        };
+
+        for npc in npc_list.iter_alive_near(&bounds) {
             self.tick_npc_collision(id, state, npc, npc_list, inventory);
         }
 
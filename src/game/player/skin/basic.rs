@@ -147,7 +147,16 @@ impl PlayerSkin for BasicPlayerSkin {
             PlayerAnimationState::FallingLookingUp => 4,
             PlayerAnimationState::FallingLookingDown => 6,
             PlayerAnimationState::FallingUpsideDown => 10,
+            // vanilla spritesheet has no dedicated frame
+            PlayerAnimationState::Gliding => 2,
+            PlayerAnimationState::Crouching => 8,
             PlayerAnimationState::Drowned => 11,
+            // vanilla spritesheet has no dedicated frames, reuse the ones of walking while looking up
+            PlayerAnimationState::Climbing => {
+                const CLIMB_INDEXES: [u16; 4] = [4, 3, 5, 3];
+
+                CLIMB_INDEXES[(tick as usize / 5) % 4]
+            }
         };
 
         let y_offset = if direction == Direction::Left { 0 } else { self.metadata.frame_size_height }
@@ -174,7 +183,10 @@ impl PlayerSkin for BasicPlayerSkin {
     }
 
     fn tick(&mut self) {
-        if self.state == PlayerAnimationState::Walking || self.state == PlayerAnimationState::WalkingUp {
+        if matches!(
+            self.state,
+            PlayerAnimationState::Walking | PlayerAnimationState::WalkingUp | PlayerAnimationState::Climbing
+        ) {
             self.tick = self.tick.wrapping_add(1);
         }
     }
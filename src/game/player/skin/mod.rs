@@ -28,7 +28,10 @@ pub enum PlayerAnimationState {
     FallingLookingUp,
     FallingLookingDown,
     FallingUpsideDown,
+    Gliding,
+    Crouching,
     Drowned,
+    Climbing,
 }
 
 /// Represents an alternative appearance of player eg. wearing a Mimiga Mask
@@ -0,0 +1,64 @@
+use crate::game::npc::list::NPCList;
+use crate::game::player::Player;
+use crate::game::shared_game_state::SharedGameState;
+
+/// Longest possible poison, also the duration at which the screen tint is the strongest.
+pub const MAX_POISON_TICKS: u16 = 1500;
+/// Default amount of ticks between poison damage.
+const POISON_INTERVAL: u16 = 50;
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct StatusEffects {
+    pub poison_ticks: u16,
+    pub poison_damage_per_interval: u8,
+    pub poison_interval: u16,
+}
+
+impl StatusEffects {
+    /// Returns how strong the poison currently is, from 0.0 to 1.0.
+    pub fn poison_strength(&self) -> f32 {
+        self.poison_ticks as f32 / MAX_POISON_TICKS as f32
+    }
+}
+
+impl Player {
+    /// Poisons the player for given amount of ticks, stronger poison replaces weaker one.
+    pub fn apply_poison(&mut self, damage: u8, duration: u16) {
+        let effects = &mut self.status_effects;
+
+        effects.poison_ticks = effects.poison_ticks.max(duration.min(MAX_POISON_TICKS));
+        effects.poison_damage_per_interval = effects.poison_damage_per_interval.max(damage);
+        effects.poison_interval = POISON_INTERVAL;
+    }
+
+    pub fn cure_status_effects(&mut self) {
+        self.status_effects = StatusEffects::default();
+    }
+
+    pub(crate) fn apply_status_effects(&mut self, state: &mut SharedGameState, npc_list: &NPCList) {
+        // don't hurt the player during cutscenes
+        if !state.control_flags.control_enabled() {
+            return;
+        }
+
+        let effects = &mut self.status_effects;
+
+        if effects.poison_ticks > 0 {
+            effects.poison_ticks -= 1;
+
+            if effects.poison_interval != 0 && effects.poison_ticks % effects.poison_interval == 0 {
+                let damage = effects.poison_damage_per_interval as i32;
+
+                if effects.poison_ticks == 0 {
+                    self.cure_status_effects();
+                }
+
+                // poison ignores invincibility frames and doesn't knock the player back
+                if !state.settings.god_mode && self.life != 0 && damage > 0 {
+                    state.sound_manager.play_sfx(16);
+                    self.lose_life(damage, state, npc_list);
+                }
+            }
+        }
+    }
+}
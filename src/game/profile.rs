@@ -1,4 +1,5 @@
 use std::io;
+use std::io::SeekFrom;
 
 use byteorder::{BE, LE, ReadBytesExt, WriteBytesExt};
 use num_traits::{clamp, FromPrimitive};
@@ -7,6 +8,7 @@ use crate::common::{Direction, FadeState, get_timestamp};
 use crate::framework::context::Context;
 use crate::framework::error::GameError::ResourceLoadError;
 use crate::framework::error::GameResult;
+use crate::game::journal::Journal;
 use crate::game::player::ControlMode;
 use crate::game::shared_game_state::{GameDifficulty, SharedGameState};
 use crate::game::weapon::{WeaponLevel, WeaponType};
@@ -25,6 +27,55 @@ pub struct TeleporterSlotData {
     pub event_num: u32,
 }
 
+/// Checkpoint set with the checkpoint NPC or `<CKP`, stored after the journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointData {
+    pub map: u32,
+    pub x: i32,
+    pub y: i32,
+    pub song: u32,
+}
+
+impl CheckpointData {
+    fn write_to<W: io::Write>(checkpoint: Option<CheckpointData>, mut data: W) -> GameResult {
+        if let Some(checkpoint) = checkpoint {
+            data.write_u8(1)?;
+            data.write_u32::<LE>(checkpoint.map)?;
+            data.write_i32::<LE>(checkpoint.x)?;
+            data.write_i32::<LE>(checkpoint.y)?;
+            data.write_u32::<LE>(checkpoint.song)?;
+        } else {
+            data.write_u8(0)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_from<R: io::Read>(mut data: R) -> GameResult<Option<CheckpointData>> {
+        if data.read_u8()? == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(CheckpointData {
+            map: data.read_u32::<LE>()?,
+            x: data.read_i32::<LE>()?,
+            y: data.read_i32::<LE>()?,
+            song: data.read_u32::<LE>()?,
+        }))
+    }
+}
+
+/// Part of a profile shown in the save slot picker, see `GameProfile::peek_summary`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProfileSummary {
+    pub current_map: u32,
+    pub max_life: u16,
+    pub life: u16,
+    pub weapon_ids: [u32; 8],
+    pub difficulty: u8,
+    pub play_time: u64,
+}
+
 pub struct GameProfile {
     pub current_map: u32,
     pub current_song: u32,
@@ -46,6 +97,10 @@ pub struct GameProfile {
     pub flags: [u8; 1000],
     pub timestamp: u64,
     pub difficulty: u8,
+    pub player_name: String,
+    pub play_time: u64,
+    pub journal: Journal,
+    pub checkpoint: Option<CheckpointData>,
 }
 
 impl GameProfile {
@@ -135,12 +190,12 @@ impl GameProfile {
         state.textscript_vm.start_script(0);
 
         game_scene.player1.equip.0 = self.equipment as u16;
+        game_scene.player1.abilities.0 = (self.equipment >> 16) as u16;
 
         game_scene.player1.x = self.pos_x;
         game_scene.player1.y = self.pos_y;
 
-        game_scene.player1.control_mode =
-            if self.control_mode == 1 { ControlMode::IronHead } else { ControlMode::Normal };
+        game_scene.player1.control_mode = ControlMode::from_u32(self.control_mode).unwrap_or(ControlMode::Normal);
         game_scene.player1.direction = self.direction;
         game_scene.player1.life = self.life;
         game_scene.player1.max_life = self.max_life;
@@ -152,6 +207,16 @@ impl GameProfile {
         game_scene.player1.cond.0 = 0x80;
 
         state.difficulty = GameDifficulty::from_primitive(self.difficulty);
+        state.player_name = self.player_name.clone();
+        state.play_time = self.play_time;
+        state.journal = self.journal.clone();
+
+        state.clear_checkpoint();
+        if let Some(checkpoint) = self.checkpoint {
+            state.checkpoint = Some((checkpoint.x as isize, checkpoint.y as isize));
+            state.checkpoint_map = Some(checkpoint.map as usize);
+            state.checkpoint_song = checkpoint.song as usize;
+        }
 
         game_scene.player1.skin.apply_gamestate(state);
         game_scene.player2.skin.apply_gamestate(state);
@@ -168,7 +233,7 @@ impl GameProfile {
         let life = game_scene.player1.life;
         let current_weapon = game_scene.inventory_player1.current_weapon as u32;
         let current_item = game_scene.inventory_player1.current_item as u32;
-        let equipment = game_scene.player1.equip.0 as u32;
+        let equipment = game_scene.player1.equip.0 as u32 | (game_scene.player1.abilities.0 as u32) << 16;
         let control_mode = game_scene.player1.control_mode as u32;
         let counter = 0; // TODO
         let mut weapon_data = [
@@ -230,6 +295,15 @@ impl GameProfile {
 
         let timestamp = get_timestamp();
         let difficulty = state.difficulty as u8;
+        let player_name = state.player_name.clone();
+        let play_time = state.play_time;
+        let journal = state.journal.clone();
+        let checkpoint = match (state.checkpoint, state.checkpoint_map) {
+            (Some((x, y)), Some(map)) => {
+                Some(CheckpointData { map: map as u32, x: x as i32, y: y as i32, song: state.checkpoint_song as u32 })
+            }
+            _ => None,
+        };
 
         GameProfile {
             current_map,
@@ -252,6 +326,10 @@ impl GameProfile {
             flags,
             timestamp,
             difficulty,
+            player_name,
+            play_time,
+            journal,
+            checkpoint,
         }
     }
 
@@ -301,6 +379,15 @@ impl GameProfile {
         data.write_u64::<LE>(self.timestamp)?;
         data.write_u8(self.difficulty)?;
 
+        let player_name = self.player_name.as_bytes();
+        let player_name = &player_name[..player_name.len().min(u8::MAX as usize)];
+        data.write_u8(player_name.len() as u8)?;
+        data.write_all(player_name)?;
+
+        data.write_u64::<LE>(self.play_time)?;
+        self.journal.write_to(&mut data)?;
+        CheckpointData::write_to(self.checkpoint, &mut data)?;
+
         Ok(())
     }
 
@@ -378,6 +465,17 @@ impl GameProfile {
         let timestamp = data.read_u64::<LE>().unwrap_or(0);
         let difficulty = data.read_u8().unwrap_or(0);
 
+        let mut player_name = vec![0u8; data.read_u8().unwrap_or(0) as usize];
+        let player_name = if data.read_exact(&mut player_name).is_ok() {
+            String::from_utf8_lossy(&player_name).into_owned()
+        } else {
+            String::new()
+        };
+
+        let play_time = data.read_u64::<LE>().unwrap_or(0);
+        let journal = Journal::read_from(&mut data).unwrap_or_default();
+        let checkpoint = CheckpointData::read_from(&mut data).unwrap_or(None);
+
         Ok(GameProfile {
             current_map,
             current_song,
@@ -399,6 +497,91 @@ impl GameProfile {
             flags,
             timestamp,
             difficulty,
+            player_name,
+            play_time,
+            journal,
+            checkpoint,
         })
     }
+
+    /// Reads only the fields shown in the save slot picker, seeking over flags and inventory.
+    pub fn peek_summary<R: io::Read + io::Seek>(mut data: R) -> GameResult<ProfileSummary> {
+        if data.read_u64::<BE>()? != 0x446f303431323230 {
+            return Err(ResourceLoadError("Invalid magic".to_owned()));
+        }
+
+        let current_map = data.read_u32::<LE>()?;
+        data.seek(SeekFrom::Current(16))?; // song, position, direction
+        let max_life = data.read_u16::<LE>()?;
+        data.seek(SeekFrom::Current(2))?; // stars
+        let life = data.read_u16::<LE>()?;
+        data.seek(SeekFrom::Current(22))?; // ???, current weapon and item, equipment, control mode, counter
+
+        let mut weapon_ids = [0u32; 8];
+        for weapon_id in &mut weapon_ids {
+            *weapon_id = data.read_u32::<LE>()?;
+            data.seek(SeekFrom::Current(16))?; // level, exp, ammo
+        }
+
+        data.seek(SeekFrom::Current(128 + 64 + 0x80))?; // items, teleporter slots, map flags
+
+        if data.read_u32::<BE>()? != 0x464c4147 {
+            return Err(ResourceLoadError("Invalid FLAG signature".to_owned()));
+        }
+
+        data.seek(SeekFrom::Current(1000 + 4 + 8))?; // flags, CS+ space, timestamp
+        let difficulty = data.read_u8().unwrap_or(0);
+        let name_len = data.read_u8().unwrap_or(0);
+        data.seek(SeekFrom::Current(name_len as i64))?;
+        let play_time = data.read_u64::<LE>().unwrap_or(0);
+
+        Ok(ProfileSummary { current_map, max_life, life, weapon_ids, difficulty, play_time })
+    }
+}
+
+#[test]
+fn test_peek_summary() {
+    let mut save = vec![0u8; 1565];
+    let mut write = |offset: usize, bytes: &[u8]| save[offset..offset + bytes.len()].copy_from_slice(bytes);
+
+    write(0, b"Do041220");
+    write(8, &12u32.to_le_bytes());
+    write(28, &50u16.to_le_bytes());
+    write(32, &37u16.to_le_bytes());
+    write(56, &2u32.to_le_bytes());
+    write(76, &5u32.to_le_bytes());
+    write(536, b"FLAG");
+    write(1552, &[4, 3]);
+    write(1554, b"Quo");
+    write(1557, &12345u64.to_le_bytes());
+
+    let summary = GameProfile::peek_summary(io::Cursor::new(&save)).unwrap();
+    let profile = GameProfile::load_from_save(io::Cursor::new(&save)).unwrap();
+
+    assert_eq!(summary.current_map, 12);
+    assert_eq!((summary.max_life, summary.life), (50, 37));
+    assert_eq!(summary.weapon_ids, profile.weapon_data.map(|weapon| weapon.weapon_id));
+    assert_eq!(summary.weapon_ids[..2], [2, 5]);
+    assert_eq!(summary.difficulty, 4);
+    assert_eq!(profile.player_name, "Quo");
+    assert_eq!((summary.play_time, profile.play_time), (12345, 12345));
+}
+
+#[test]
+fn test_checkpoint_roundtrip() {
+    let mut save = vec![0u8; 1565];
+    save[..8].copy_from_slice(b"Do041220");
+    save[536..540].copy_from_slice(b"FLAG");
+
+    let mut profile = GameProfile::load_from_save(io::Cursor::new(&save)).unwrap();
+    assert_eq!(profile.checkpoint, None);
+
+    let checkpoint = CheckpointData { map: 13, x: 42, y: -1, song: 8 };
+    profile.checkpoint = Some(checkpoint);
+
+    let mut data = Vec::new();
+    profile.write_save(&mut data).unwrap();
+
+    let profile = GameProfile::load_from_save(io::Cursor::new(&data)).unwrap();
+    assert_eq!(profile.checkpoint, Some(checkpoint));
 }
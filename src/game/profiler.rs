@@ -0,0 +1,188 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::common::{Color, Rect};
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::graphics;
+use crate::game::shared_game_state::SharedGameState;
+use crate::graphics::font::Font;
+
+/// Amount of frames used to compute the rolling average of each section.
+const AVERAGE_FRAMES: usize = 60;
+/// Amount of frame times displayed on the sparkline.
+const SPARKLINE_FRAMES: usize = 120;
+
+const SECTION_COLORS: [(u8, u8, u8); 8] = [
+    (0xe0, 0x40, 0x40),
+    (0x40, 0xc0, 0x40),
+    (0x40, 0x80, 0xf0),
+    (0xf0, 0xc0, 0x30),
+    (0xc0, 0x50, 0xe0),
+    (0x30, 0xd0, 0xd0),
+    (0xf0, 0x80, 0x30),
+    (0xa0, 0xa0, 0xa0),
+];
+
+struct ProfilerSection {
+    name: &'static str,
+    top_level: bool,
+    current: Duration,
+    samples: VecDeque<Duration>,
+}
+
+impl ProfilerSection {
+    fn new(name: &'static str, top_level: bool) -> ProfilerSection {
+        ProfilerSection { name, top_level, current: Duration::ZERO, samples: VecDeque::with_capacity(AVERAGE_FRAMES) }
+    }
+
+    fn average(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+}
+
+/// Measures time spent in named sections of update and draw code.
+///
+/// Sections are opened with [`Profiler::begin`] and closed with [`Profiler::end`], they can be nested
+/// and a single section can be entered multiple times per frame, in which case the times are summed.
+pub struct Profiler {
+    sections: Vec<ProfilerSection>,
+    frame_ring: VecDeque<Duration>,
+    stack: Vec<(usize, Instant)>,
+    last_frame: Option<Instant>,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler {
+            sections: Vec::new(),
+            frame_ring: VecDeque::with_capacity(SPARKLINE_FRAMES),
+            stack: Vec::new(),
+            last_frame: None,
+        }
+    }
+
+    pub fn begin(&mut self, name: &'static str) {
+        let idx = match self.sections.iter().position(|s| s.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.sections.push(ProfilerSection::new(name, self.stack.is_empty()));
+                self.sections.len() - 1
+            }
+        };
+
+        self.stack.push((idx, Instant::now()));
+    }
+
+    pub fn end(&mut self) {
+        if let Some((idx, start)) = self.stack.pop() {
+            self.sections[idx].current += start.elapsed();
+        } else {
+            log::warn!("Profiler::end() called without a matching begin().");
+        }
+    }
+
+    /// Commits the times measured since the last call into the rolling averages.
+    pub fn end_frame(&mut self) {
+        let now = Instant::now();
+
+        if let Some(last_frame) = self.last_frame {
+            if self.frame_ring.len() == SPARKLINE_FRAMES {
+                self.frame_ring.pop_front();
+            }
+            self.frame_ring.push_back(now - last_frame);
+        }
+        self.last_frame = Some(now);
+
+        for section in &mut self.sections {
+            if section.samples.len() == AVERAGE_FRAMES {
+                section.samples.pop_front();
+            }
+            section.samples.push_back(section.current);
+            section.current = Duration::ZERO;
+        }
+    }
+
+    pub fn draw(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let budget = match state.settings.timing_mode.get_delta_millis() {
+            delta if delta > 0.0 => delta as f32,
+            _ => 1000.0 / 60.0,
+        };
+
+        let scale = state.scale;
+        let fill = |ctx: &mut Context, x: f32, y: f32, width: f32, height: f32, color: Color| {
+            let rect = Rect::new_size(
+                (x * scale) as isize,
+                (y * scale) as isize,
+                (width * scale).max(1.0) as isize,
+                (height * scale).max(1.0) as isize,
+            );
+
+            graphics::draw_rect(ctx, rect, color)
+        };
+
+        let x = 8.0;
+        let mut y = 56.0;
+        let width = SPARKLINE_FRAMES as f32;
+
+        // sparkline, the top of the box is two frame budgets
+        let spark_height = 16.0;
+        fill(ctx, x, y, width, spark_height, Color::from_rgba(0, 0, 0, 160))?;
+        for (i, frame) in self.frame_ring.iter().enumerate() {
+            let ms = frame.as_secs_f32() * 1000.0;
+            let height = (ms / (budget * 2.0) * spark_height).min(spark_height);
+            let color = if ms > budget { Color::from_rgb(0xe0, 0x40, 0x40) } else { Color::from_rgb(0x40, 0xc0, 0x40) };
+
+            fill(ctx, x + i as f32, y + spark_height - height, 1.0, height, color)?;
+        }
+        y += spark_height + 2.0;
+
+        // stacked bar of the outermost sections, full width is one frame budget
+        let averages: Vec<(&'static str, bool, f32)> =
+            self.sections.iter().map(|s| (s.name, s.top_level, s.average().as_secs_f32() * 1000.0)).collect();
+
+        fill(ctx, x, y, width, 6.0, Color::from_rgba(0, 0, 0, 160))?;
+        let mut bar_x = x;
+        for (i, (_, top_level, ms)) in averages.iter().enumerate() {
+            if !top_level {
+                continue;
+            }
+
+            let (r, g, b) = SECTION_COLORS[i % SECTION_COLORS.len()];
+            let bar_width = (ms / budget * width).min(x + width - bar_x);
+            if bar_width <= 0.0 {
+                continue;
+            }
+
+            fill(ctx, bar_x, y, bar_width, 6.0, Color::from_rgb(r, g, b))?;
+            bar_x += bar_width;
+        }
+        y += 8.0;
+
+        let line_height = state.font.line_height();
+        for (i, (name, _, ms)) in averages.iter().enumerate() {
+            let (r, g, b) = SECTION_COLORS[i % SECTION_COLORS.len()];
+            fill(ctx, x, y + (line_height - 6.0) / 2.0, 6.0, 6.0, Color::from_rgb(r, g, b))?;
+
+            state.font.builder().position(x + 10.0, y).shadow(true).draw(
+                &format!("{} {:.2}ms", name, ms),
+                ctx,
+                &state.constants,
+                &mut state.texture_set,
+            )?;
+            y += line_height;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Profiler::new()
+    }
+}
@@ -10,7 +10,7 @@ use crate::game::scripting::tsc::bytecode_utils::{put_string, put_varint};
 use crate::game::scripting::tsc::credit_script::CreditScript;
 use crate::game::scripting::tsc::opcodes::{CreditOpCode, TSCOpCode};
 use crate::game::scripting::tsc::parse_utils::{expect_char, read_number, skip_until};
-use crate::game::scripting::tsc::text_script::{TextScript, TextScriptEncoding};
+use crate::game::scripting::tsc::text_script::{TextScript, TextScriptEncoding, MAX_CHOICES};
 
 impl TextScript {
     /// Compiles a decrypted text script data into internal bytecode.
@@ -104,7 +104,7 @@ impl TextScript {
 
                     let code = String::from_utf8_lossy(&n);
 
-                    TextScript::compile_code(&code, strict, iter, &mut bytecode)?;
+                    TextScript::compile_code(&code, strict, encoding, iter, &mut bytecode)?;
                 }
                 b'\r' => {
                     iter.next();
@@ -131,9 +131,10 @@ impl TextScript {
         Ok(bytecode)
     }
 
-    fn compile_code<I: Iterator<Item=u8>>(
+    pub(super) fn compile_code<I: Iterator<Item=u8>>(
         code: &str,
         strict: bool,
+        encoding: TextScriptEncoding,
         iter: &mut Peekable<I>,
         out: &mut Vec<u8>,
     ) -> GameResult {
@@ -179,7 +180,8 @@ impl TextScript {
             | TSCOpCode::HM2
             | TSCOpCode::POP
             | TSCOpCode::KE2
-            | TSCOpCode::FR2 => {
+            | TSCOpCode::FR2
+            | TSCOpCode::NAM => {
                 put_varint(instr as i32, out);
             }
             // One operand codes
@@ -205,6 +207,8 @@ impl TextScript {
             | TSCOpCode::SKp
             | TSCOpCode::EQp
             | TSCOpCode::EQm
+            | TSCOpCode::ABp
+            | TSCOpCode::ABm
             | TSCOpCode::MLp
             | TSCOpCode::ITp
             | TSCOpCode::ITm
@@ -221,7 +225,10 @@ impl TextScript {
             | TSCOpCode::ACH
             | TSCOpCode::S2MV
             | TSCOpCode::S2PJ
-            | TSCOpCode::PSH => {
+            | TSCOpCode::PSH
+            | TSCOpCode::SPD
+            | TSCOpCode::BSP
+            | TSCOpCode::VAR => {
                 let operand = read_number(iter)?;
                 put_varint(instr as i32, out);
                 put_varint(operand as i32, out);
@@ -241,7 +248,8 @@ impl TextScript {
             | TSCOpCode::SMP
             | TSCOpCode::PSp
             | TSCOpCode::IpN
-            | TSCOpCode::FFm => {
+            | TSCOpCode::FFm
+            | TSCOpCode::CKP => {
                 let operand_a = read_number(iter)?;
                 if strict {
                     expect_char(b':', iter)?;
@@ -303,6 +311,41 @@ impl TextScript {
                 put_varint(operand_c as i32, out);
                 put_varint(operand_d as i32, out);
             }
+            // Option count followed by text and event number of each option
+            TSCOpCode::MSC => {
+                let count = read_number(iter)?;
+                if !(1..=MAX_CHOICES as i32).contains(&count) {
+                    return Err(ParseError(format!("Invalid amount of choices: {}", count)));
+                }
+
+                put_varint(instr as i32, out);
+                put_varint(count, out);
+
+                let mut char_buf = Vec::with_capacity(16);
+                for _ in 0..count {
+                    if strict {
+                        expect_char(b':', iter)?;
+                    } else {
+                        iter.next().ok_or_else(|| ParseError("Script unexpectedly ended.".to_owned()))?;
+                    }
+
+                    loop {
+                        match iter.next() {
+                            Some(b':') => break,
+                            Some(chr) => char_buf.push(chr),
+                            None => return Err(ParseError("Script unexpectedly ended.".to_owned())),
+                        }
+                    }
+                    let event_num = read_number(iter)?;
+
+                    if char_buf.is_empty() {
+                        put_varint(0, out);
+                    } else {
+                        put_string(&mut char_buf, out, encoding);
+                    }
+                    put_varint(event_num, out);
+                }
+            }
             TSCOpCode::_NOP | TSCOpCode::_UNI | TSCOpCode::_STR | TSCOpCode::_END => {
                 unreachable!()
             }
@@ -409,3 +452,12 @@ impl CreditScript {
         Ok(CreditScript { labels, bytecode })
     }
 }
+
+#[test]
+fn test_compile_choices() {
+    let script = TextScript::compile(b"#0100\n<MSC0002:Yes:0200:No:0300<END", true, TextScriptEncoding::UTF8).unwrap();
+    let decompiled = script.decompile_event(100).unwrap();
+
+    assert!(decompiled.contains("MSC(2, \"Yes\" => 200, \"No\" => 300)"));
+    assert!(TextScript::compile(b"#0100\n<MSC0009", true, TextScriptEncoding::UTF8).is_err());
+}
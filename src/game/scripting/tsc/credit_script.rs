@@ -87,10 +87,22 @@ impl CreditScriptVM {
         self.state = CreditScriptExecutionState::Ended;
     }
 
+    /// Credit.tsc is timed for 50 ticks per second, returns the tick rate the game is actually running at.
+    fn tick_rate(state: &SharedGameState) -> f32 {
+        match state.settings.timing_mode.get_tps() {
+            0 => 50.0,
+            tps => tps as f32,
+        }
+    }
+
     pub fn run(state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let tick_rate = Self::tick_rate(state);
+
         if state.creditscript_vm.state != CreditScriptExecutionState::Ended {
+            let scroll_speed = 0.5 * 50.0 / tick_rate;
+
             for line in &mut state.creditscript_vm.lines {
-                line.pos_y -= 0.5;
+                line.pos_y -= scroll_speed;
             }
         }
 
@@ -144,7 +156,10 @@ impl CreditScriptVM {
                             state.creditscript_vm.state = CreditScriptExecutionState::Running(cursor.position() as u32);
                         }
                         CreditOpCode::Wait => {
-                            let ticks = read_cur_varint(&mut cursor)? as u16;
+                            let mut ticks = read_cur_varint(&mut cursor)? as u16;
+                            if ticks != 9999 {
+                                ticks = (ticks as f32 * tick_rate / 50.0).round().min(9998.0) as u16;
+                            }
 
                             state.creditscript_vm.state =
                                 CreditScriptExecutionState::WaitTicks(cursor.position() as u32, ticks);
@@ -163,7 +178,7 @@ impl CreditScriptVM {
                             state.creditscript_vm.state = CreditScriptExecutionState::Running(cursor.position() as u32);
                         }
                         CreditOpCode::FadeMusic => {
-                            // todo
+                            state.sound_manager.fade_song(1.0)?;
 
                             state.creditscript_vm.state = CreditScriptExecutionState::Running(cursor.position() as u32);
                         }
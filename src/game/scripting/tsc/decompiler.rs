@@ -59,7 +59,8 @@ impl TextScript {
                         | TSCOpCode::HM2
                         | TSCOpCode::POP
                         | TSCOpCode::KE2
-                        | TSCOpCode::FR2 => {
+                        | TSCOpCode::FR2
+                        | TSCOpCode::NAM => {
                             writeln!(&mut result, "{:?}()", op).unwrap();
                         }
                         // One operand codes
@@ -85,6 +86,8 @@ impl TextScript {
                         | TSCOpCode::SKp
                         | TSCOpCode::EQp
                         | TSCOpCode::EQm
+                        | TSCOpCode::ABp
+                        | TSCOpCode::ABm
                         | TSCOpCode::MLp
                         | TSCOpCode::ITp
                         | TSCOpCode::ITm
@@ -102,7 +105,10 @@ impl TextScript {
                         | TSCOpCode::ACH
                         | TSCOpCode::S2MV
                         | TSCOpCode::S2PJ
-                        | TSCOpCode::PSH => {
+                        | TSCOpCode::PSH
+                        | TSCOpCode::SPD
+                        | TSCOpCode::BSP
+                        | TSCOpCode::VAR => {
                             let par_a = read_cur_varint(&mut cursor)?;
 
                             writeln!(&mut result, "{:?}({})", op, par_a).unwrap();
@@ -121,7 +127,8 @@ impl TextScript {
                         | TSCOpCode::SMP
                         | TSCOpCode::PSp
                         | TSCOpCode::IpN
-                        | TSCOpCode::FFm => {
+                        | TSCOpCode::FFm
+                        | TSCOpCode::CKP => {
                             let par_a = read_cur_varint(&mut cursor)?;
                             let par_b = read_cur_varint(&mut cursor)?;
 
@@ -144,6 +151,22 @@ impl TextScript {
 
                             writeln!(&mut result, "{:?}({}, {}, {}, {})", op, par_a, par_b, par_c, par_d).unwrap();
                         }
+                        TSCOpCode::MSC => {
+                            let count = read_cur_varint(&mut cursor)?;
+
+                            write!(&mut result, "{:?}({}", op, count).unwrap();
+                            for _ in 0..count {
+                                let len = read_cur_varint(&mut cursor)?;
+                                let mut text = String::new();
+                                for _ in 0..len {
+                                    text.push(std::char::from_u32(read_cur_varint(&mut cursor)? as u32).unwrap_or('?'));
+                                }
+                                let event_num = read_cur_varint(&mut cursor)?;
+
+                                write!(&mut result, ", {:?} => {}", text, event_num).unwrap();
+                            }
+                            result.push_str(")\n");
+                        }
                         TSCOpCode::_STR => {
                             let len = read_cur_varint(&mut cursor)?;
 
@@ -265,6 +265,24 @@ pub enum TSCOpCode {
     /// <FRE related to player 2?
     FR2,
     // ---- Custom opcodes, for use by modders ----
+    /// <SPDxxxx, Prints xxxx characters at once until the message box is closed
+    SPD,
+    /// <BSPxxxx, Plays sound xxxx while printing text until the message box is closed
+    BSP,
+    /// <MSCnnnn:text:eeee:...:text:eeee, Shows a menu with nnnn (up to 8) options, jumps to event eeee of the selected one
+    MSC,
+    /// <NAM, Prints the name entered by the player
+    NAM,
+    /// <VARxxxx, Prints variable xxxx: 0 - player name, 1 - map name, 2 - life, 3 - max life
+    VAR,
+    /// <CKPxxxx:yyyy, Sets the checkpoint players respawn at after death to tile xxxx:yyyy of the current map
+    CKP,
+    /// <AB+xxxx, Sets specified bits in player ability bitfield
+    #[strum(serialize = "AB+")]
+    ABp,
+    /// <AB-xxxx, Unsets specified bits in player ability bitfield
+    #[strum(serialize = "AB-")]
+    ABm,
 }
 
 #[derive(FromPrimitive, PartialEq, Copy, Clone)]
@@ -1,6 +1,6 @@
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io;
 use std::io::Cursor;
 use std::io::Seek;
@@ -13,16 +13,20 @@ use num_traits::{clamp, FromPrimitive};
 use crate::bitfield;
 use crate::common::Direction::{Left, Right};
 use crate::common::{Direction, FadeDirection, FadeState, Rect};
+use crate::components::number_popup::NumberPopup;
+use crate::components::stage_select::StageSelect;
 use crate::engine_constants::EngineConstants;
 use crate::entity::GameEntity;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::game::frame::UpdateTarget;
+use crate::game::journal::JournalEntry;
 use crate::game::npc::NPC;
 use crate::game::player::{ControlMode, TargetPlayer};
 use crate::game::scripting::tsc::bytecode_utils::read_cur_varint;
 use crate::game::scripting::tsc::encryption::decrypt_tsc;
 use crate::game::scripting::tsc::opcodes::TSCOpCode;
+use crate::game::scripting::tsc::validator::ScriptDiagnostic;
 use crate::game::shared_game_state::ReplayState;
 use crate::game::shared_game_state::SharedGameState;
 use crate::game::weapon::WeaponType;
@@ -31,6 +35,12 @@ use crate::input::touch_controls::TouchControlType;
 use crate::scene::game_scene::GameScene;
 
 const TSC_SUBSTITUTION_MAP_SIZE: usize = 1;
+/// Sound played while printing message text.
+const DEFAULT_TEXT_BLIP_SFX: u8 = 2;
+/// Maximum amount of options in a `<MSC` menu.
+pub const MAX_CHOICES: usize = 8;
+/// Amount of `<MSC` options shown at once, the rest is scrolled into view.
+pub const VISIBLE_CHOICES: usize = 4;
 
 bitfield! {
     pub struct TextScriptFlags(u16);
@@ -113,10 +123,12 @@ pub enum TextScriptExecutionState {
     Running(u16, u32),
     Msg(u16, u32, u32, u8),
     MsgNewLine(u16, u32, u32, u8, u8),
+    WaitPage(u16, u32, u32, u16),
     WaitTicks(u16, u32, u16),
     WaitInput(u16, u32, u16),
     WaitStanding(u16, u32),
     WaitConfirmation(u16, u32, u16, u8, ConfirmSelection),
+    WaitChoice(u16, u32, u8, u8),
     WaitFade(u16, u32),
     FallingIsland(u16, u32, i32, i32, u16, bool),
     MapSystem,
@@ -158,6 +170,18 @@ pub struct TextScriptVM {
     pub current_illustration: Option<String>,
     pub illustration_state: IllustrationState,
     prev_char: char,
+    /// Amount of characters printed at once, see `<SPD`.
+    pub chars_per_tick: u8,
+    chars_printed: u8,
+    /// Sound played while printing message text, see `<BSP`.
+    pub text_blip_sfx: u8,
+    blip_pending: bool,
+    /// Text and target event of `<MSC` options.
+    pub choices: Vec<(Vec<char>, u16)>,
+    /// Text inserted by `<NAM` and `<VAR`, printed before continuing with the script.
+    pending_text: VecDeque<char>,
+    /// Map script text printed since the last journal entry.
+    journal_text: String,
     pub substitution_rect_map: [(char, Rect<u16>); TSC_SUBSTITUTION_MAP_SIZE],
 }
 
@@ -234,6 +258,13 @@ impl TextScriptVM {
             current_illustration: None,
             illustration_state: IllustrationState::Hidden,
             prev_char: '\x00',
+            chars_per_tick: 1,
+            chars_printed: 0,
+            text_blip_sfx: DEFAULT_TEXT_BLIP_SFX,
+            blip_pending: false,
+            choices: Vec::new(),
+            pending_text: VecDeque::new(),
+            journal_text: String::new(),
             substitution_rect_map: [('=', Rect::new(0, 0, 0, 0))],
         }
     }
@@ -285,9 +316,38 @@ impl TextScriptVM {
         self.current_illustration = None;
         self.illustration_state = IllustrationState::Hidden;
         self.face = 0;
+        self.choices.clear();
+        self.pending_text.clear();
+        self.journal_text.clear();
         self.clear_text_box();
     }
 
+    /// Moves the message text printed so far into the dialogue journal.
+    fn flush_journal(state: &mut SharedGameState, map_id: usize) {
+        if state.textscript_vm.journal_text.is_empty() {
+            return;
+        }
+
+        let text = std::mem::take(&mut state.textscript_vm.journal_text);
+        state.journal.push(JournalEntry { face: state.textscript_vm.face, text, map_id, tick: state.play_time });
+    }
+
+    /// Returns the top left corner of the `<MSC` menu, placed above the message box or below it if the box is on top.
+    pub fn choice_box_pos(state: &SharedGameState, ctx: &mut Context) -> (f32, f32) {
+        let (off_left, off_top, off_right, off_bottom) =
+            crate::framework::graphics::screen_insets_scaled(ctx, state.scale);
+
+        let center = ((state.canvas_size.0 - off_left - off_right) / 2.0).floor();
+        let height = 16.0 + state.textscript_vm.choices.len().min(VISIBLE_CHOICES) as f32 * 16.0;
+        let top_pos = if state.textscript_vm.flags.position_top() {
+            32.0 + off_top + 68.0
+        } else {
+            state.canvas_size.1 as f32 - off_bottom - 66.0 - 4.0 - height
+        };
+
+        (off_left + center - 122.0, top_pos)
+    }
+
     pub fn clear_text_box(&mut self) {
         self.item = 0;
         self.current_line = TextScriptLine::Line1;
@@ -296,6 +356,20 @@ impl TextScriptVM {
         self.line_3.clear();
     }
 
+    /// Width at which message lines wrap, narrower when a face portrait takes up the left part of the box.
+    pub fn text_wrap_width(&self) -> f32 {
+        if self.face == 0 {
+            284.0
+        } else {
+            228.0
+        }
+    }
+
+    /// Restores the amount of characters printed at once to the one picked in settings.
+    pub fn reset_text_speed(&mut self, constants: &EngineConstants, text_speed: u8) {
+        self.chars_per_tick = constants.textscript.text_chars_per_tick.saturating_mul(text_speed).max(1);
+    }
+
     pub fn set_mode(&mut self, mode: ScriptMode) {
         self.reset();
         self.mode = mode;
@@ -309,6 +383,13 @@ impl TextScriptVM {
         log::info!("Started script: #{:04}", event_num);
     }
 
+    /// Starts an event, also dropping events saved with `<PSH` by the interrupted script,
+    /// which would otherwise be resumed by a later `<POP`.
+    pub fn interrupt_script(&mut self, event_num: u16) {
+        self.stack.clear();
+        self.start_script(event_num);
+    }
+
     pub fn run(state: &mut SharedGameState, game_scene: &mut GameScene, ctx: &mut Context) -> GameResult {
         let scripts_ref = state.textscript_vm.scripts.clone();
         let scripts = scripts_ref.borrow();
@@ -340,8 +421,16 @@ impl TextScriptVM {
                     }
 
                     state.textscript_vm.state = if let Some((_, bytecode)) = cached_event {
-                        TextScriptVM::execute(bytecode, event, ip, state, game_scene, ctx)?
+                        match TextScriptVM::execute(bytecode, event, ip, state, game_scene, ctx) {
+                            Ok(exec_state) => exec_state,
+                            Err(err) => {
+                                // malformed bytecode, end the event instead of bringing the game down
+                                log::warn!("Error in event #{:04} at {}, ending script: {}", event, ip, err);
+                                TextScriptExecutionState::Ended
+                            }
+                        }
                     } else {
+                        log::warn!("Tried to run missing event #{:04}, ending script.", event);
                         TextScriptExecutionState::Ended
                     };
 
@@ -375,7 +464,15 @@ impl TextScriptVM {
                         let mut new_line = false;
                         cursor.seek(SeekFrom::Start(ip as u64))?;
 
-                        let chr = std::char::from_u32(read_cur_varint(&mut cursor)? as u32).unwrap_or('\u{fffd}');
+                        let chr = if let Some(chr) = state.textscript_vm.pending_text.pop_front() {
+                            chr
+                        } else {
+                            std::char::from_u32(read_cur_varint(&mut cursor)? as u32).unwrap_or('\u{fffd}')
+                        };
+                        if state.textscript_vm.mode == ScriptMode::Map {
+                            state.textscript_vm.journal_text.push(chr);
+                        }
+
                         let builder = state.font.builder().with_symbols(Some(Symbols {
                             symbols: &state.textscript_vm.substitution_rect_map,
                             texture: "",
@@ -397,7 +494,7 @@ impl TextScriptVM {
                                 state.textscript_vm.line_1.push(chr);
 
                                 let text_len = builder.compute_width_iter(state.textscript_vm.line_1.iter().copied());
-                                if text_len >= 284.0 {
+                                if text_len >= state.textscript_vm.text_wrap_width() {
                                     state.textscript_vm.current_line = TextScriptLine::Line2;
                                 }
                             }
@@ -406,7 +503,7 @@ impl TextScriptVM {
                                 state.textscript_vm.line_2.push(chr);
 
                                 let text_len = builder.compute_width_iter(state.textscript_vm.line_2.iter().copied());
-                                if text_len >= 284.0 {
+                                if text_len >= state.textscript_vm.text_wrap_width() {
                                     state.textscript_vm.current_line = TextScriptLine::Line3;
                                 }
                             }
@@ -415,7 +512,7 @@ impl TextScriptVM {
                                 state.textscript_vm.line_3.push(chr);
 
                                 let text_len = builder.compute_width_iter(state.textscript_vm.line_3.iter().copied());
-                                if text_len >= 284.0 {
+                                if text_len >= state.textscript_vm.text_wrap_width() {
                                     new_line = true;
                                 }
                             }
@@ -423,26 +520,47 @@ impl TextScriptVM {
                         }
 
                         if remaining > 1 {
-                            let ticks = if state.textscript_vm.flags.fast() || state.textscript_vm.flags.cutscene_skip()
-                            {
-                                0
-                            } else if remaining != 2
+                            let hurry = remaining != 2
                                 && (game_scene.player1.controller.jump()
                                     || game_scene.player1.controller.shoot()
                                     || game_scene.player2.controller.jump()
-                                    || game_scene.player2.controller.shoot())
-                            {
-                                state.constants.textscript.text_speed_fast
+                                    || game_scene.player2.controller.shoot());
+
+                            let (ticks, chars_per_tick) =
+                                if state.textscript_vm.flags.fast() || state.textscript_vm.flags.cutscene_skip() {
+                                    (0, 1)
+                                } else if hurry {
+                                    (
+                                        state.constants.textscript.text_speed_fast,
+                                        state.constants.textscript.text_chars_per_tick_fast,
+                                    )
+                                } else {
+                                    (state.constants.textscript.text_speed_normal, state.textscript_vm.chars_per_tick)
+                                };
+
+                            // print whole batch of characters before waiting
+                            state.textscript_vm.chars_printed = state.textscript_vm.chars_printed.saturating_add(1);
+                            let ticks = if ticks > 0 && state.textscript_vm.chars_printed < chars_per_tick {
+                                0
                             } else {
-                                state.constants.textscript.text_speed_normal
+                                state.textscript_vm.chars_printed = 0;
+                                ticks
                             };
 
-                            if ticks > 0 {
-                                state.sound_manager.play_sfx(2);
+                            // blip once per printed batch, unless it's only whitespace
+                            if !chr.is_whitespace() {
+                                state.textscript_vm.blip_pending = true;
+                            }
+
+                            if ticks > 0 && state.textscript_vm.blip_pending {
+                                state.textscript_vm.blip_pending = false;
+                                state.sound_manager.play_sfx(state.textscript_vm.text_blip_sfx);
                             }
 
                             state.textscript_vm.state = if !new_line {
                                 TextScriptExecutionState::Msg(event, cursor.position() as u32, remaining - 1, ticks)
+                            } else if state.constants.textscript.paginate_messages || state.settings.paginate_messages {
+                                TextScriptExecutionState::WaitPage(event, cursor.position() as u32, remaining - 1, 0)
                             } else {
                                 TextScriptExecutionState::MsgNewLine(
                                     event,
@@ -453,6 +571,8 @@ impl TextScriptVM {
                                 )
                             };
                         } else {
+                            state.textscript_vm.chars_printed = 0;
+
                             let ticks = if state.textscript_vm.flags.fast() || state.textscript_vm.flags.cutscene_skip()
                             {
                                 0
@@ -460,6 +580,7 @@ impl TextScriptVM {
                                 state.constants.textscript.text_speed_fast
                             };
                             
+                            // not paginated, the command ending the text already waits for a key press if it needs one
                             state.textscript_vm.state = if new_line {
                                 TextScriptExecutionState::MsgNewLine(
                                     event,
@@ -498,6 +619,33 @@ impl TextScriptVM {
                     }
                     break;
                 }
+                TextScriptExecutionState::WaitPage(event, ip, remaining, blink) => {
+                    state.textscript_vm.state =
+                        TextScriptExecutionState::WaitPage(event, ip, remaining, (blink + 1) % 20);
+
+                    if !state.control_flags.control_enabled() {
+                        state.touch_controls.control_type = TouchControlType::Dialog;
+                    }
+
+                    if state.textscript_vm.flags.fast()
+                        || state.textscript_vm.flags.cutscene_skip()
+                        || game_scene.player1.controller.trigger_jump()
+                        || game_scene.player1.controller.trigger_shoot()
+                        || game_scene.player2.controller.trigger_jump()
+                        || game_scene.player2.controller.trigger_shoot()
+                    {
+                        state.textscript_vm.line_1.clear();
+                        state.textscript_vm.line_2.clear();
+                        state.textscript_vm.line_3.clear();
+                        state.textscript_vm.current_line = TextScriptLine::Line1;
+                        state.textscript_vm.state = if remaining < 2 {
+                            TextScriptExecutionState::Running(event, ip)
+                        } else {
+                            TextScriptExecutionState::Msg(event, ip, remaining, 0)
+                        };
+                    }
+                    break;
+                }
                 TextScriptExecutionState::WaitTicks(event, ip, ticks) => {
                     if ticks == 0 {
                         state.textscript_vm.state = TextScriptExecutionState::Running(event, ip);
@@ -568,9 +716,12 @@ impl TextScriptVM {
                         break;
                     }
 
-                    if confirm {
+                    let cancel =
+                        game_scene.player1.controller.trigger_shoot() || game_scene.player2.controller.trigger_shoot();
+
+                    if confirm || cancel {
                         state.sound_manager.play_sfx(18);
-                        match selection {
+                        match if cancel { ConfirmSelection::No } else { selection } {
                             ConfirmSelection::Yes => {
                                 state.textscript_vm.state = TextScriptExecutionState::Running(event, ip);
                             }
@@ -583,6 +734,63 @@ impl TextScriptVM {
 
                     break;
                 }
+                TextScriptExecutionState::WaitChoice(event, ip, mut selected, mut scroll) => {
+                    let count = state.textscript_vm.choices.len() as u8;
+                    if count == 0 {
+                        state.textscript_vm.state = TextScriptExecutionState::Running(event, ip);
+                        continue;
+                    }
+
+                    let mut confirm =
+                        game_scene.player1.controller.trigger_jump() || game_scene.player2.controller.trigger_jump();
+
+                    if state.settings.touch_controls && !state.control_flags.control_enabled() {
+                        state.touch_controls.control_type = TouchControlType::None;
+
+                        let (box_x, box_y) = TextScriptVM::choice_box_pos(state, ctx);
+                        for row in 0..count.min(VISIBLE_CHOICES as u8) {
+                            let row_rect =
+                                Rect::new_size(box_x as isize, (box_y + 8.0 + row as f32 * 16.0) as isize, 244, 16);
+
+                            if state.touch_controls.consume_click_in(row_rect) {
+                                selected = scroll + row;
+                                confirm = true;
+                                break;
+                            }
+                        }
+                    }
+
+                    if !confirm {
+                        if game_scene.player1.controller.trigger_up() || game_scene.player2.controller.trigger_up() {
+                            state.sound_manager.play_sfx(1);
+                            selected = (selected + count - 1) % count;
+                        } else if game_scene.player1.controller.trigger_down()
+                            || game_scene.player2.controller.trigger_down()
+                        {
+                            state.sound_manager.play_sfx(1);
+                            selected = (selected + 1) % count;
+                        }
+
+                        if selected < scroll {
+                            scroll = selected;
+                        } else if selected >= scroll + VISIBLE_CHOICES as u8 {
+                            scroll = selected + 1 - VISIBLE_CHOICES as u8;
+                        }
+                    }
+
+                    state.textscript_vm.state = TextScriptExecutionState::WaitChoice(event, ip, selected, scroll);
+
+                    if confirm {
+                        state.sound_manager.play_sfx(18);
+
+                        let event_num = state.textscript_vm.choices[selected as usize].1;
+                        state.textscript_vm.choices.clear();
+                        state.textscript_vm.clear_text_box();
+                        state.textscript_vm.state = TextScriptExecutionState::Running(event_num, 0);
+                    }
+
+                    break;
+                }
                 TextScriptExecutionState::WaitStanding(event, ip) => {
                     if game_scene.player1.flags.hit_bottom_wall() || game_scene.player2.flags.hit_bottom_wall() {
                         state.textscript_vm.state = TextScriptExecutionState::Running(event, ip);
@@ -644,7 +852,10 @@ impl TextScriptVM {
                     break;
                 }
                 TextScriptExecutionState::LoadProfile => {
-                    state.load_or_start_game(ctx)?;
+                    // retrying after death goes back to the last checkpoint, if there is one
+                    if game_scene.player1.life != 0 || !game_scene.respawn_at_checkpoint(state, ctx)? {
+                        state.load_or_start_game(ctx)?;
+                    }
                     break;
                 }
                 TextScriptExecutionState::Reset => {
@@ -717,9 +928,14 @@ impl TextScriptVM {
                 state.control_flags.set_tick_world(true);
                 state.control_flags.set_control_enabled(true);
 
+                TextScriptVM::flush_journal(state, game_scene.stage_id);
+
                 state.textscript_vm.flags.set_render(false);
                 state.textscript_vm.flags.set_background_visible(false);
                 state.textscript_vm.stack.clear();
+                state.textscript_vm.face = 0;
+                state.textscript_vm.reset_text_speed(&state.constants, state.settings.get_text_speed(state.save_slot));
+                state.textscript_vm.text_blip_sfx = DEFAULT_TEXT_BLIP_SFX;
 
                 if state.textscript_vm.mode == ScriptMode::Debug {
                     state.textscript_vm.set_mode(ScriptMode::Map);
@@ -734,7 +950,7 @@ impl TextScriptVM {
                 state.textscript_vm.set_mode(ScriptMode::StageSelect);
 
                 let event_num = if let Some(slot) =
-                    state.teleporter_slots.get(game_scene.stage_select.current_teleport_slot as usize)
+                    StageSelect::visible_slots(state).get(game_scene.stage_select.current_teleport_slot as usize)
                 {
                     1000 + slot.0
                 } else {
@@ -1079,10 +1295,10 @@ impl TextScriptVM {
             }
             TSCOpCode::MLp => {
                 let life = read_cur_varint(&mut cursor)? as u16;
-                game_scene.player1.life += life;
-                game_scene.player1.max_life += life;
-                game_scene.player2.life += life;
-                game_scene.player2.max_life += life;
+                let heal = state.constants.textscript.max_life_up_heals || state.settings.max_life_up_heals;
+
+                game_scene.player1.add_max_life(life, heal);
+                game_scene.player2.add_max_life(life, heal);
 
                 #[cfg(feature = "discord-rpc")]
                 state.discord_rpc.update_hp(&game_scene.player1)?;
@@ -1094,12 +1310,17 @@ impl TextScriptVM {
                 // Switch uses xx00 for face animation states
                 if face % 100 != state.textscript_vm.face % 100 {
                     game_scene.text_boxes.slide_in = 7;
+                    TextScriptVM::flush_journal(state, game_scene.stage_id);
                 }
                 state.textscript_vm.face = face;
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
             TSCOpCode::CLR => {
+                if !state.textscript_vm.journal_text.is_empty() && !state.textscript_vm.journal_text.ends_with('\n') {
+                    state.textscript_vm.journal_text.push('\n');
+                }
+
                 state.textscript_vm.current_line = TextScriptLine::Line1;
                 state.textscript_vm.line_1.clear();
                 state.textscript_vm.line_2.clear();
@@ -1117,6 +1338,7 @@ impl TextScriptVM {
                 state.textscript_vm.flags.set_fast(state.textscript_vm.flags.perma_fast());
                 state.textscript_vm.flags.set_position_top(op != TSCOpCode::MSG);
                 if op == TSCOpCode::MS2 {
+                    TextScriptVM::flush_journal(state, game_scene.stage_id);
                     state.textscript_vm.face = 0;
                 }
 
@@ -1134,10 +1356,14 @@ impl TextScriptVM {
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
             TSCOpCode::CLO => {
+                TextScriptVM::flush_journal(state, game_scene.stage_id);
+
                 state.textscript_vm.flags.set_render(false);
                 state.textscript_vm.flags.set_background_visible(false);
                 state.textscript_vm.flags.set_fast(false);
                 state.textscript_vm.flags.set_position_top(false);
+                state.textscript_vm.reset_text_speed(&state.constants, state.settings.get_text_speed(state.save_slot));
+                state.textscript_vm.text_blip_sfx = DEFAULT_TEXT_BLIP_SFX;
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
@@ -1167,13 +1393,31 @@ impl TextScriptVM {
             TSCOpCode::NUM => {
                 let index = read_cur_varint(&mut cursor)? as usize;
 
-                if let Some(num) = state.textscript_vm.numbers.get(index) {
-                    let mut str = num.to_string().chars().collect();
+                if let Some(&num) = state.textscript_vm.numbers.get(index) {
+                    let wrap_width = state.textscript_vm.text_wrap_width();
+
+                    // digits go through the same line wrapping as regular text
+                    for chr in num.to_string().chars() {
+                        let vm = &mut state.textscript_vm;
+                        let line = match vm.current_line {
+                            TextScriptLine::Line1 => &mut vm.line_1,
+                            TextScriptLine::Line2 => &mut vm.line_2,
+                            TextScriptLine::Line3 => &mut vm.line_3,
+                        };
+
+                        line.push(chr);
+                        vm.prev_char = chr;
+
+                        if state.font.builder().compute_width_iter(line.iter().copied()) >= wrap_width {
+                            vm.current_line = match vm.current_line {
+                                TextScriptLine::Line1 => TextScriptLine::Line2,
+                                _ => TextScriptLine::Line3,
+                            };
+                        }
+                    }
 
-                    match state.textscript_vm.current_line {
-                        TextScriptLine::Line1 => state.textscript_vm.line_1.append(&mut str),
-                        TextScriptLine::Line2 => state.textscript_vm.line_2.append(&mut str),
-                        TextScriptLine::Line3 => state.textscript_vm.line_3.append(&mut str),
+                    if !state.textscript_vm.flags.fast() && !state.textscript_vm.flags.cutscene_skip() {
+                        state.sound_manager.play_sfx(state.textscript_vm.text_blip_sfx);
                     }
                 }
 
@@ -1182,6 +1426,7 @@ impl TextScriptVM {
             TSCOpCode::GIT => {
                 let item = read_cur_varint(&mut cursor)? as u16;
                 state.textscript_vm.item = item;
+                game_scene.text_boxes.item_slide_in = 8;
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
@@ -1214,6 +1459,18 @@ impl TextScriptVM {
                 // Reset ground collision for WAS / WaitStanding
                 new_scene.player1.flags.set_hit_bottom_wall(false);
                 new_scene.player2.flags.set_hit_bottom_wall(false);
+                new_scene.player1.is_ceiling_walking = false;
+                new_scene.player2.is_ceiling_walking = false;
+                new_scene.player1.is_climbing = false;
+                new_scene.player2.is_climbing = false;
+                // Clear damage/experience popups and quakes like the original does on stage transfer
+                for player in [&mut new_scene.player1, &mut new_scene.player2] {
+                    player.damage_popup = NumberPopup::new();
+                    player.exp_popup = NumberPopup::new();
+                    player.heal_popup = NumberPopup::with_color(player.heal_popup.color);
+                }
+                state.quake_counter = 0;
+                state.super_quake_counter = 0;
                 new_scene.frame.wait = game_scene.frame.wait;
                 new_scene.nikumaru = game_scene.nikumaru;
                 new_scene.replay = game_scene.replay.clone();
@@ -1316,7 +1573,7 @@ impl TextScriptVM {
                 let fade_type = read_cur_varint(&mut cursor)? as usize;
 
                 if let Some(direction) = FadeDirection::from_int(fade_type) {
-                    state.fade_state = FadeState::FadeIn(15, direction);
+                    state.fade_state = FadeState::FadeIn(state.constants.textscript.fade_ticks, direction);
                 }
 
                 exec_state = TextScriptExecutionState::WaitFade(event, cursor.position() as u32);
@@ -1325,7 +1582,7 @@ impl TextScriptVM {
                 let fade_type = read_cur_varint(&mut cursor)? as usize;
 
                 if let Some(direction) = FadeDirection::from_int(fade_type) {
-                    state.fade_state = FadeState::FadeOut(-15, direction.opposite());
+                    state.fade_state = FadeState::FadeOut(-state.constants.textscript.fade_ticks, direction.opposite());
                 }
 
                 exec_state = TextScriptExecutionState::WaitFade(event, cursor.position() as u32);
@@ -1350,7 +1607,7 @@ impl TextScriptVM {
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
             TSCOpCode::FMU => {
-                state.sound_manager.play_song(0, &state.constants, &state.settings, ctx)?;
+                state.sound_manager.fade_song(1.0)?;
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
@@ -1399,10 +1656,11 @@ impl TextScriptVM {
             TSCOpCode::FON => {
                 let event_num = read_cur_varint(&mut cursor)? as u16;
                 let ticks = read_cur_varint(&mut cursor)? as i32;
-                game_scene.frame.wait = ticks;
 
+                // the original leaves the camera untouched (including the wait) if there's no such NPC
                 for npc in game_scene.npc_list.iter() {
                     if event_num == npc.event_num {
+                        game_scene.frame.wait = ticks;
                         game_scene.frame.update_target = UpdateTarget::NPC(npc.id);
                         break;
                     }
@@ -1415,13 +1673,8 @@ impl TextScriptVM {
 
                 if event_num == 0 {
                     game_scene.boss_life_bar.set_boss_target(&game_scene.boss);
-                } else {
-                    for npc in game_scene.npc_list.iter_alive() {
-                        if event_num == npc.event_num {
-                            game_scene.boss_life_bar.set_npc_target(npc.id, &game_scene.npc_list);
-                            break;
-                        }
-                    }
+                } else if let Some(npc) = game_scene.npc_list.iter_alive_by_event(event_num).next() {
+                    game_scene.boss_life_bar.set_npc_target(npc.id, &game_scene.npc_list);
                 }
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
@@ -1436,25 +1689,15 @@ impl TextScriptVM {
             TSCOpCode::ANP => {
                 let event_num = read_cur_varint(&mut cursor)? as u16;
                 let action_num = read_cur_varint(&mut cursor)? as u16;
-                let tsc_direction = read_cur_varint(&mut cursor)? as usize;
-                let direction = Direction::from_int_facing(tsc_direction).unwrap_or(Direction::Left);
-
-                for npc in game_scene.npc_list.iter_alive() {
-                    if npc.event_num == event_num {
-                        npc.action_num = action_num;
-                        npc.tsc_direction = tsc_direction as u16;
-
-                        if direction == Direction::FacingPlayer {
-                            let player = match state.textscript_vm.executor_player {
-                                TargetPlayer::Player1 => &game_scene.player1,
-                                TargetPlayer::Player2 => &game_scene.player2,
-                            };
+                let tsc_direction = read_cur_varint(&mut cursor)? as u16;
+                let player = match state.textscript_vm.executor_player {
+                    TargetPlayer::Player1 => &game_scene.player1,
+                    TargetPlayer::Player2 => &game_scene.player2,
+                };
 
-                            npc.direction = if player.x < npc.x { Direction::Left } else { Direction::Right };
-                        } else if tsc_direction != 5 {
-                            npc.direction = direction;
-                        }
-                    }
+                for npc in game_scene.npc_list.iter_alive_by_event(event_num) {
+                    npc.action_num = action_num;
+                    npc.set_tsc_direction(tsc_direction, player);
                 }
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
@@ -1462,68 +1705,60 @@ impl TextScriptVM {
             TSCOpCode::CNP | TSCOpCode::INP => {
                 let event_num = read_cur_varint(&mut cursor)? as u16;
                 let new_type = read_cur_varint(&mut cursor)? as u16;
-                let tsc_direction = read_cur_varint(&mut cursor)? as usize;
-                let direction = Direction::from_int_facing(tsc_direction).unwrap_or(Direction::Left);
-
-                for npc in game_scene.npc_list.iter_alive() {
-                    if npc.event_num == event_num {
-                        npc.npc_flags.set_solid_soft(false);
-                        npc.npc_flags.set_ignore_tile_44(false);
-                        npc.npc_flags.set_invulnerable(false);
-                        npc.npc_flags.set_ignore_solidity(false);
-                        npc.npc_flags.set_bouncy(false);
-                        npc.npc_flags.set_shootable(false);
-                        npc.npc_flags.set_solid_hard(false);
-                        npc.npc_flags.set_rear_and_top_not_hurt(false);
-                        npc.npc_flags.set_show_damage(false);
-
-                        if op == TSCOpCode::INP {
-                            npc.npc_flags.set_event_when_touched(true);
-                        }
+                let tsc_direction = read_cur_varint(&mut cursor)? as u16;
+
+                for npc in game_scene.npc_list.iter_alive_by_event(event_num) {
+                    npc.npc_flags.set_solid_soft(false);
+                    npc.npc_flags.set_ignore_tile_44(false);
+                    npc.npc_flags.set_invulnerable(false);
+                    npc.npc_flags.set_ignore_solidity(false);
+                    npc.npc_flags.set_bouncy(false);
+                    npc.npc_flags.set_shootable(false);
+                    npc.npc_flags.set_solid_hard(false);
+                    npc.npc_flags.set_rear_and_top_not_hurt(false);
+                    npc.npc_flags.set_show_damage(false);
+
+                    if op == TSCOpCode::INP {
+                        npc.npc_flags.set_event_when_touched(true);
+                    }
 
-                        npc.npc_type = new_type;
-                        npc.display_bounds = state.npc_table.get_display_bounds(new_type);
-                        npc.hit_bounds = state.npc_table.get_hit_bounds(new_type);
-                        let entry = state.npc_table.get_entry(new_type).unwrap().to_owned();
+                    npc.npc_type = new_type;
+                    npc.display_bounds = state.npc_table.get_display_bounds(new_type);
+                    npc.hit_bounds = state.npc_table.get_hit_bounds(new_type);
+                    if let Some(entry) = state.npc_table.get_entry(new_type) {
                         npc.npc_flags.0 |= entry.npc_flags.0;
                         npc.life = entry.life;
                         npc.size = entry.size;
                         npc.exp = entry.experience as u16;
                         npc.damage = entry.damage as u16;
                         npc.spritesheet_id = entry.spritesheet_id as u16;
+                    }
 
-                        npc.cond.set_alive(true);
-                        npc.action_num = 0;
-                        npc.action_counter = 0;
-                        npc.anim_num = 0;
-                        npc.anim_counter = 0;
-                        npc.vel_x = 0;
-                        npc.vel_y = 0;
-                        npc.tsc_direction = tsc_direction as u16;
-
-                        if direction == Direction::FacingPlayer {
-                            let player = match state.textscript_vm.executor_player {
-                                TargetPlayer::Player1 => &game_scene.player1,
-                                TargetPlayer::Player2 => &game_scene.player2,
-                            };
-
-                            npc.direction = if player.x < npc.x { Direction::Left } else { Direction::Right };
-                        } else if tsc_direction != 5 {
-                            npc.direction = direction;
-                        }
+                    npc.cond.set_alive(true);
+                    npc.action_num = 0;
+                    npc.action_counter = 0;
+                    npc.anim_num = 0;
+                    npc.anim_counter = 0;
+                    npc.vel_x = 0;
+                    npc.vel_y = 0;
 
-                        npc.tick(
-                            state,
-                            (
-                                [&mut game_scene.player1, &mut game_scene.player2],
-                                &game_scene.npc_list,
-                                &mut game_scene.stage,
-                                &mut game_scene.bullet_manager,
-                                &mut game_scene.flash,
-                                &mut game_scene.boss,
-                            ),
-                        )?;
-                    }
+                    let player = match state.textscript_vm.executor_player {
+                        TargetPlayer::Player1 => &game_scene.player1,
+                        TargetPlayer::Player2 => &game_scene.player2,
+                    };
+                    npc.set_tsc_direction(tsc_direction, player);
+
+                    npc.tick(
+                        state,
+                        (
+                            [&mut game_scene.player1, &mut game_scene.player2],
+                            &game_scene.npc_list,
+                            &mut game_scene.stage,
+                            &mut game_scene.bullet_manager,
+                            &mut game_scene.flash,
+                            &mut game_scene.boss,
+                        ),
+                    )?;
                 }
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
@@ -1532,29 +1767,17 @@ impl TextScriptVM {
                 let event_num = read_cur_varint(&mut cursor)? as u16;
                 let x = read_cur_varint(&mut cursor)? as i32;
                 let y = read_cur_varint(&mut cursor)? as i32;
-                let tsc_direction = read_cur_varint(&mut cursor)? as usize;
-                let direction = Direction::from_int_facing(tsc_direction).unwrap_or(Direction::Left);
+                let tsc_direction = read_cur_varint(&mut cursor)? as u16;
                 let block_size = state.tile_size.as_int() * 0x200;
+                let player = match state.textscript_vm.executor_player {
+                    TargetPlayer::Player1 => &game_scene.player1,
+                    TargetPlayer::Player2 => &game_scene.player2,
+                };
 
-                for npc in game_scene.npc_list.iter_alive() {
-                    if npc.event_num == event_num {
-                        npc.x = x * block_size;
-                        npc.y = y * block_size;
-                        npc.tsc_direction = tsc_direction as u16;
-
-                        if direction == Direction::FacingPlayer {
-                            let player = match state.textscript_vm.executor_player {
-                                TargetPlayer::Player1 => &game_scene.player1,
-                                TargetPlayer::Player2 => &game_scene.player2,
-                            };
-
-                            npc.direction = if player.x < npc.x { Direction::Left } else { Direction::Right };
-                        } else if tsc_direction != 5 {
-                            npc.direction = direction;
-                        }
-
-                        break;
-                    }
+                if let Some(npc) = game_scene.npc_list.iter_alive_by_event(event_num).next() {
+                    npc.x = x * block_size;
+                    npc.y = y * block_size;
+                    npc.set_tsc_direction(tsc_direction, player);
                 }
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
@@ -1591,8 +1814,11 @@ impl TextScriptVM {
             TSCOpCode::LIp => {
                 let life = read_cur_varint(&mut cursor)? as u16;
 
-                game_scene.player1.life = clamp(game_scene.player1.life + life, 0, game_scene.player1.max_life);
-                game_scene.player2.life = clamp(game_scene.player2.life + life, 0, game_scene.player2.max_life);
+                for player in [&mut game_scene.player1, &mut game_scene.player2] {
+                    let prev_life = player.life;
+                    player.life = clamp(player.life + life, 0, player.max_life);
+                    player.show_heal_popup(state, player.life.saturating_sub(prev_life));
+                }
 
                 #[cfg(feature = "discord-rpc")]
                 state.discord_rpc.update_hp(&game_scene.player1)?;
@@ -1685,9 +1911,12 @@ impl TextScriptVM {
                 let old_weapon_type: Option<WeaponType> = FromPrimitive::from_u8(old_weapon_id);
                 let new_weapon_type: Option<WeaponType> = FromPrimitive::from_u8(new_weapon_id);
 
+                let keep_level =
+                    state.constants.textscript.trade_keeps_weapon_level || state.settings.trade_keeps_weapon_level;
+
                 if let Some(wtype) = new_weapon_type {
-                    game_scene.inventory_player1.trade_weapon(old_weapon_type, wtype, max_ammo);
-                    game_scene.inventory_player2.trade_weapon(old_weapon_type, wtype, max_ammo);
+                    game_scene.inventory_player1.trade_weapon(old_weapon_type, wtype, max_ammo, keep_level);
+                    game_scene.inventory_player2.trade_weapon(old_weapon_type, wtype, max_ammo, keep_level);
                 }
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
@@ -1714,6 +1943,22 @@ impl TextScriptVM {
 
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
+            TSCOpCode::ABp => {
+                let mask = read_cur_varint(&mut cursor)? as u16;
+
+                game_scene.player1.abilities.0 |= mask;
+                game_scene.player2.abilities.0 |= mask;
+
+                exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
+            }
+            TSCOpCode::ABm => {
+                let mask = read_cur_varint(&mut cursor)? as u16;
+
+                game_scene.player1.abilities.0 &= !mask;
+                game_scene.player2.abilities.0 &= !mask;
+
+                exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
+            }
             TSCOpCode::FLA => {
                 game_scene.flash.set_blink();
 
@@ -1751,6 +1996,14 @@ impl TextScriptVM {
 
                 exec_state = TextScriptExecutionState::LoadProfile;
             }
+            TSCOpCode::CKP => {
+                let pos_x = read_cur_varint(&mut cursor)? as isize;
+                let pos_y = read_cur_varint(&mut cursor)? as isize;
+
+                state.set_checkpoint(game_scene.stage_id, pos_x, pos_y);
+
+                exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
+            }
             TSCOpCode::MPp => {
                 let stage_id = read_cur_varint(&mut cursor)? as u16;
 
@@ -1855,6 +2108,66 @@ impl TextScriptVM {
                 state.control_flags.set_ok_button_disabled(false);
                 exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
             }
+            TSCOpCode::SPD => {
+                let chars_per_tick = read_cur_varint(&mut cursor)?.clamp(1, u8::MAX as i32) as u8;
+
+                state.textscript_vm.chars_per_tick = chars_per_tick;
+                state.textscript_vm.chars_printed = 0;
+
+                exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
+            }
+            TSCOpCode::BSP => {
+                let sfx = read_cur_varint(&mut cursor)? as u8;
+
+                state.textscript_vm.text_blip_sfx = sfx;
+
+                exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
+            }
+            TSCOpCode::MSC => {
+                let count = read_cur_varint(&mut cursor)? as usize;
+
+                state.textscript_vm.choices.clear();
+                for _ in 0..count.min(MAX_CHOICES) {
+                    let len = read_cur_varint(&mut cursor)? as usize;
+                    let mut text = Vec::with_capacity(len);
+                    for _ in 0..len {
+                        text.push(std::char::from_u32(read_cur_varint(&mut cursor)? as u32).unwrap_or('\u{fffd}'));
+                    }
+                    let event_num = read_cur_varint(&mut cursor)? as u16;
+
+                    state.textscript_vm.choices.push((text, event_num));
+                }
+
+                state.sound_manager.play_sfx(5);
+
+                exec_state = TextScriptExecutionState::WaitChoice(event, cursor.position() as u32, 0, 0);
+            }
+            TSCOpCode::NAM | TSCOpCode::VAR => {
+                let variable = if op == TSCOpCode::NAM { 0 } else { read_cur_varint(&mut cursor)? };
+
+                let text = match variable {
+                    0 => state.player_name.clone(),
+                    1 if state.constants.is_cs_plus && state.settings.locale == "jp" => {
+                        game_scene.stage.data.name_jp.clone()
+                    }
+                    1 => game_scene.stage.data.name.clone(),
+                    2 => game_scene.player1.life.to_string(),
+                    3 => game_scene.player1.max_life.to_string(),
+                    _ => {
+                        log::warn!("Unknown text variable: {}", variable);
+                        String::new()
+                    }
+                };
+
+                if state.textscript_vm.flags.render() && !text.is_empty() {
+                    state.textscript_vm.pending_text.extend(text.chars());
+
+                    let len = state.textscript_vm.pending_text.len() as u32;
+                    exec_state = TextScriptExecutionState::Msg(event, cursor.position() as u32, len, 0);
+                } else {
+                    exec_state = TextScriptExecutionState::Running(event, cursor.position() as u32);
+                }
+            }
             TSCOpCode::ACH => {
                 // todo: any idea for any practical purpose of that opcode?
                 let idx = read_cur_varint(&mut cursor)?;
@@ -1902,6 +2215,29 @@ impl TextScript {
         TextScript::compile(&buf, false, constants.textscript.encoding)
     }
 
+    /// Same as `load_from`, but also logs and returns problems found in the script by `TextScript::validate`.
+    pub fn load_validated<R: io::Read>(
+        mut data: R,
+        constants: &EngineConstants,
+        known_events: &TextScript,
+    ) -> GameResult<(TextScript, Vec<ScriptDiagnostic>)> {
+        let mut buf = Vec::new();
+        data.read_to_end(&mut buf)?;
+
+        if constants.textscript.encrypted {
+            decrypt_tsc(&mut buf);
+        }
+
+        let diagnostics = TextScript::validate(&buf, constants.textscript.encoding, known_events);
+        for diagnostic in &diagnostics {
+            log::warn!("TSC: {}", diagnostic);
+        }
+
+        let script = TextScript::compile(&buf, false, constants.textscript.encoding)?;
+
+        Ok((script, diagnostics))
+    }
+
     pub fn get_event_ids(&self) -> Vec<u16> {
         let mut vec: Vec<u16> = self.event_map.keys().copied().collect();
         vec.sort();
@@ -0,0 +1,275 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Cursor;
+use std::str::FromStr;
+
+use crate::framework::error::GameError;
+use crate::framework::error::GameResult;
+use crate::game::scripting::tsc::bytecode_utils::read_cur_varint;
+use crate::game::scripting::tsc::opcodes::TSCOpCode;
+use crate::game::scripting::tsc::parse_utils::read_number;
+use crate::game::scripting::tsc::text_script::{TextScript, TextScriptEncoding};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptProblem {
+    /// Opcode isn't known to the engine.
+    UnknownOpcode,
+    /// Operands are missing, truncated or not separated with `:`.
+    InvalidOperands(String),
+    /// Jump to an event which isn't defined anywhere.
+    MissingEvent(u16),
+    /// A message box is opened, but the event ends without closing it or jumping elsewhere.
+    UnterminatedMessage,
+}
+
+/// A problem found in a text script, see `TextScript::validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptDiagnostic {
+    pub event: u16,
+    /// Byte offset of the offending opcode in the decrypted script.
+    pub offset: usize,
+    /// Opcode as written in the script, for example `<FLJ`.
+    pub code: String,
+    pub problem: ScriptProblem,
+}
+
+impl fmt::Display for ScriptDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Event #{:04}, offset {}, {}: ", self.event, self.offset, self.code)?;
+
+        match &self.problem {
+            ScriptProblem::UnknownOpcode => write!(f, "unknown opcode"),
+            ScriptProblem::InvalidOperands(reason) => write!(f, "invalid operands ({})", reason),
+            ScriptProblem::MissingEvent(target) => write!(f, "jump to missing event #{:04}", target),
+            ScriptProblem::UnterminatedMessage => write!(f, "message is never closed"),
+        }
+    }
+}
+
+/// Returns indices of operands holding event numbers.
+fn event_operands(op: TSCOpCode) -> &'static [usize] {
+    match op {
+        TSCOpCode::EVE | TSCOpCode::YNJ | TSCOpCode::MPJ | TSCOpCode::PSH => &[0],
+        TSCOpCode::FLJ
+        | TSCOpCode::ITJ
+        | TSCOpCode::SKJ
+        | TSCOpCode::AMJ
+        | TSCOpCode::NCJ
+        | TSCOpCode::ECJ
+        | TSCOpCode::UNJ => &[1],
+        TSCOpCode::INJ => &[2],
+        _ => &[],
+    }
+}
+
+/// Reads event numbers an opcode can jump to from its compiled bytecode.
+fn jump_targets(op: TSCOpCode, bytecode: &[u8]) -> GameResult<Vec<u16>> {
+    let mut cursor: Cursor<&[u8]> = Cursor::new(bytecode);
+    let mut targets = Vec::new();
+    read_cur_varint(&mut cursor)?;
+
+    if op == TSCOpCode::MSC {
+        let count = read_cur_varint(&mut cursor)?;
+
+        for _ in 0..count {
+            let len = read_cur_varint(&mut cursor)?;
+            for _ in 0..len {
+                read_cur_varint(&mut cursor)?;
+            }
+
+            targets.push(read_cur_varint(&mut cursor)? as u16);
+        }
+
+        return Ok(targets);
+    }
+
+    let operands = event_operands(op);
+    for idx in 0..operands.iter().max().map_or(0, |&max| max + 1) {
+        let operand = read_cur_varint(&mut cursor)?;
+
+        if operands.contains(&idx) {
+            targets.push(operand as u16);
+        }
+    }
+
+    Ok(targets)
+}
+
+fn is_event_label(data: &[u8], offset: usize) -> bool {
+    data[offset] == b'#' && (offset == 0 || data[offset - 1] == b'\n')
+}
+
+fn read_event_label(data: &[u8], offset: usize) -> Option<u16> {
+    read_number(&mut data[offset + 1..].iter().copied().peekable()).ok().map(|num| num as u16)
+}
+
+impl TextScript {
+    /// Checks decrypted script data for problems which would otherwise only show up once the broken event runs.
+    /// Events defined in `known_events` (usually Head.tsc) are considered valid jump targets.
+    pub fn validate(data: &[u8], encoding: TextScriptEncoding, known_events: &TextScript) -> Vec<ScriptDiagnostic> {
+        let defined_events: HashSet<u16> = (0..data.len())
+            .filter(|&offset| is_event_label(data, offset))
+            .filter_map(|offset| read_event_label(data, offset))
+            .collect();
+
+        let mut diagnostics = Vec::new();
+        let mut bytecode = Vec::new();
+        let mut event = None;
+        // position and text of the opcode which opened a message box
+        let mut open_message: Option<(usize, String)> = None;
+        let mut offset = 0;
+
+        while offset < data.len() {
+            if is_event_label(data, offset) {
+                if let (Some(event), Some((offset, code))) = (event, open_message.take()) {
+                    diagnostics.push(ScriptDiagnostic {
+                        event,
+                        offset,
+                        code,
+                        problem: ScriptProblem::UnterminatedMessage,
+                    });
+                }
+
+                event = read_event_label(data, offset);
+                offset += 1;
+                continue;
+            }
+
+            let event = match event {
+                Some(event) if data[offset] == b'<' => event,
+                _ => {
+                    offset += 1;
+                    continue;
+                }
+            };
+
+            let code_end = (offset + 4).min(data.len());
+            let code = String::from_utf8_lossy(&data[offset + 1..code_end]).into_owned();
+            let diagnostic = |problem| ScriptDiagnostic { event, offset, code: format!("<{}", code), problem };
+
+            let op = match TSCOpCode::from_str(&code) {
+                Ok(op) => op,
+                Err(_) if code.len() < 3 => {
+                    diagnostics
+                        .push(diagnostic(ScriptProblem::InvalidOperands("script unexpectedly ended".to_owned())));
+                    break;
+                }
+                Err(_) => {
+                    diagnostics.push(diagnostic(ScriptProblem::UnknownOpcode));
+                    offset = code_end;
+                    continue;
+                }
+            };
+
+            let mut iter = data[code_end..].iter().copied().peekable();
+            let remaining = iter.len();
+            bytecode.clear();
+
+            match TextScript::compile_code(&code, true, encoding, &mut iter, &mut bytecode) {
+                Ok(()) => {
+                    for target in jump_targets(op, &bytecode).unwrap_or_default() {
+                        if !defined_events.contains(&target) && !known_events.has_event(target) {
+                            diagnostics.push(diagnostic(ScriptProblem::MissingEvent(target)));
+                        }
+                    }
+
+                    match op {
+                        TSCOpCode::MSG | TSCOpCode::MS2 | TSCOpCode::MS3 => {
+                            open_message = Some((offset, format!("<{}", code)));
+                        }
+                        TSCOpCode::CLO
+                        | TSCOpCode::END
+                        | TSCOpCode::EVE
+                        | TSCOpCode::TRA
+                        | TSCOpCode::ESC
+                        | TSCOpCode::INI
+                        | TSCOpCode::LDP => {
+                            open_message = None;
+                        }
+                        _ => (),
+                    }
+
+                    offset = code_end + (remaining - iter.len());
+                }
+                Err(err) => {
+                    let reason = match err {
+                        GameError::ParseError(reason) => reason,
+                        err => err.to_string(),
+                    };
+
+                    diagnostics.push(diagnostic(ScriptProblem::InvalidOperands(reason)));
+                    offset = code_end;
+                }
+            }
+        }
+
+        if let (Some(event), Some((offset, code))) = (event, open_message) {
+            diagnostics.push(ScriptDiagnostic { event, offset, code, problem: ScriptProblem::UnterminatedMessage });
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(script: &str) -> Vec<ScriptDiagnostic> {
+        TextScript::validate(script.as_bytes(), TextScriptEncoding::UTF8, &TextScript::new())
+    }
+
+    #[test]
+    fn test_valid_script() {
+        let script = "#0100\r\n<PRI<MSGHello!<NOD<CLO<FLJ0001:0200<END\r\n#0200\r\n<KEY<MSG#1<NOD<EVE0100\r\n";
+
+        assert_eq!(validate(script), vec![]);
+    }
+
+    #[test]
+    fn test_unknown_opcode() {
+        let diagnostics = validate("#0100\n<PRI<XYZ<END\n");
+
+        assert_eq!(
+            diagnostics,
+            vec![ScriptDiagnostic {
+                event: 100,
+                offset: 10,
+                code: "<XYZ".to_owned(),
+                problem: ScriptProblem::UnknownOpcode
+            }]
+        );
+    }
+
+    #[test]
+    fn test_invalid_operands() {
+        let diagnostics = validate("#0100\n<TRA0012:0094:0001<END\n#0101\n<FL+00");
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!((diagnostics[0].event, diagnostics[0].offset), (100, 6));
+        assert_eq!((diagnostics[1].event, diagnostics[1].offset), (101, 35));
+        assert!(diagnostics.iter().all(|d| matches!(d.problem, ScriptProblem::InvalidOperands(_))));
+    }
+
+    #[test]
+    fn test_missing_event() {
+        let script = "#0100\n<FLJ0001:0300<MSC0002:Yes:0100:No:0400<END\n";
+        let head = TextScript::compile(b"#0300\n<END\n", false, TextScriptEncoding::UTF8).unwrap();
+
+        let diagnostics = TextScript::validate(script.as_bytes(), TextScriptEncoding::UTF8, &head);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "<MSC");
+        assert_eq!(diagnostics[0].problem, ScriptProblem::MissingEvent(400));
+    }
+
+    #[test]
+    fn test_unterminated_message() {
+        let diagnostics = validate("#0100\n<MSGHello<NOD\n#0200\n<MSGBye<NOD<END\n#0300\n<MS2...");
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!((diagnostics[0].event, diagnostics[0].offset), (100, 6));
+        assert_eq!((diagnostics[1].event, diagnostics[1].code.as_str()), (300, "<MS2"));
+        assert!(diagnostics.iter().all(|d| d.problem == ScriptProblem::UnterminatedMessage));
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::filesystem::{user_create, user_open};
@@ -5,14 +8,65 @@ use crate::framework::gamepad::{Axis, AxisDirection, Button, PlayerControllerInp
 use crate::framework::graphics::VSyncMode;
 use crate::framework::keyboard::ScanCode;
 use crate::game::player::TargetPlayer;
-use crate::game::shared_game_state::{CutsceneSkipMode, ScreenShakeIntensity, TimingMode, WindowMode};
+use crate::game::shared_game_state::{CutsceneSkipMode, GameDifficulty, ScreenShakeIntensity, TimingMode, WindowMode};
 use crate::input::combined_player_controller::CombinedPlayerController;
 use crate::input::gamepad_player_controller::GamepadController;
 use crate::input::keyboard_player_controller::KeyboardController;
 use crate::input::player_controller::PlayerController;
+use crate::input::touch_controls::TouchLayout;
 use crate::input::touch_player_controller::TouchPlayerController;
 use crate::sound::InterpolationMode;
 
+const SETTINGS_PATH: &str = "/settings.toml";
+/// Settings file used before the switch to TOML, only read when migrating.
+const LEGACY_SETTINGS_PATH: &str = "/settings.json";
+
+/// Written at the top of the settings file, documents values which can't be told from their names alone.
+const SETTINGS_HEADER: &str = "\
+# doukutsu-rs settings, edit while the game is not running.
+# Removing a line resets the option to its default value.
+#
+# bgm_volume, sfx_volume: 0.0 - 1.0
+# player1_controller_axis_sensitivity, player2_controller_axis_sensitivity: 0.0 - 1.0
+# text_speed: 1 - 10
+# fps_cap: frames per second, remove the line for no limit
+# locale: \"en\", \"jp\" or the name of a locale file in data/locale
+# soundtrack: \"Organya\" or the name of a soundtrack from the data directory
+# timing_mode: \"_50Hz\", \"_60Hz\", \"FrameSynchronized\"
+# window_mode: \"Windowed\", \"Fullscreen\"
+# vsync_mode: \"Uncapped\", \"VSync\", \"VRRTickSync1x\", \"VRRTickSync2x\", \"VRRTickSync3x\"
+# organya_interpolation: \"Nearest\", \"Linear\", \"Cosine\", \"Cubic\", \"Polyphase\"
+# screen_shake_intensity: \"Full\", \"Half\", \"Off\"
+# cutscene_skip_mode: \"Hold\", \"FastForward\"
+# player1_controller_type, player2_controller_type: \"Keyboard\" or { Gamepad = <index> }
+# player1_gamepad_id, player2_gamepad_id: name and GUID of the assigned gamepad, found again after reconnecting
+# show_hud: true, false
+# minimal_hud: true to only show the status bars for a few seconds after they change
+# heal_popups: true to show recovered health above the player
+# reverb_enabled: true to add reverb to sound effects in enclosed rooms
+# booster_gauge: true to show remaining Booster fuel below the health bar
+# boss_weak_points: true to make boss parts blink when they become vulnerable
+# water_bubbles: true to have air bubbles occasionally rise from the player underwater
+# attack_telegraphs: true to show a warning above some enemies right before they attack
+# damage_knockback: true to push the player away from enemies hurting them
+# current_particles: true to show streaks flowing inside wind and water currents
+# variable_shock_blink: true to make the player blink slowly right after a hit and faster as invincibility runs out
+# name_entry: true to ask for the player's name before a new game starts
+# paginate_messages: true to make long messages wait for a key press and continue on a clear box instead of scrolling
+# trade_keeps_weapon_level: true to keep the level and experience of a weapon traded with <TAM
+# max_life_up_heals: true to fully heal the player when their max life is raised
+# boss_phases: true to let bosses supporting it enter an enraged phase at low health, like Balfrog
+# touch_layout: on-screen controls, x and y are measured from the anchor corner, size: 0.5 - 2.0, opacity: 0.0 - 1.0
+# profile_overrides: per save slot values replacing the ones above, for example:
+#   [profile_overrides.2]
+#   difficulty = \"Easy\" (\"Normal\", \"Easy\", \"Hard\")
+#   text_speed = 3
+#   show_hud = false
+# best_stage_times: fastest stage completions in milliseconds, keyed by stage number
+# version: used to upgrade old settings, do not change
+
+";
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Settings {
     #[serde(default = "current_version")]
@@ -43,6 +97,10 @@ pub struct Settings {
     pub player1_controller_type: ControllerType,
     #[serde(default = "default_p2_controller_type")]
     pub player2_controller_type: ControllerType,
+    #[serde(default)]
+    pub player1_gamepad_id: Option<String>,
+    #[serde(default)]
+    pub player2_gamepad_id: Option<String>,
     #[serde(default = "p1_default_keymap")]
     pub player1_key_map: PlayerKeyMap,
     #[serde(default = "p2_default_keymap")]
@@ -67,12 +125,16 @@ pub struct Settings {
     pub infinite_booster: bool,
     #[serde(skip)]
     pub debug_outlines: bool,
+    #[serde(skip)]
+    pub show_profiler: bool,
     pub fps_counter: bool,
     pub locale: String,
     #[serde(default = "default_window_mode")]
     pub window_mode: WindowMode,
     #[serde(default = "default_vsync")]
     pub vsync_mode: VSyncMode,
+    #[serde(default = "default_fps_cap")]
+    pub fps_cap: Option<u32>,
     #[serde(default = "default_screen_shake_intensity")]
     pub screen_shake_intensity: ScreenShakeIntensity,
     pub debug_mode: bool,
@@ -81,8 +143,124 @@ pub struct Settings {
     pub more_rust: bool,
     #[serde(default = "default_cutscene_skip_mode")]
     pub cutscene_skip_mode: CutsceneSkipMode,
+    #[serde(default = "default_text_speed")]
+    pub text_speed: u8,
     #[serde(default = "default_true")]
     pub discord_rpc: bool,
+    #[serde(default = "default_true")]
+    pub show_hud: bool,
+    #[serde(default)]
+    pub minimal_hud: bool,
+    #[serde(default)]
+    pub heal_popups: bool,
+    #[serde(default = "default_true")]
+    pub reverb_enabled: bool,
+    #[serde(default)]
+    pub booster_gauge: bool,
+    #[serde(default)]
+    pub boss_weak_points: bool,
+    #[serde(default)]
+    pub water_bubbles: bool,
+    #[serde(default)]
+    pub attack_telegraphs: bool,
+    #[serde(default = "default_true")]
+    pub damage_knockback: bool,
+    #[serde(default)]
+    pub current_particles: bool,
+    #[serde(default)]
+    pub variable_shock_blink: bool,
+    #[serde(default)]
+    pub name_entry: bool,
+    #[serde(default)]
+    pub paginate_messages: bool,
+    #[serde(default)]
+    pub trade_keeps_weapon_level: bool,
+    #[serde(default)]
+    pub max_life_up_heals: bool,
+    #[serde(default)]
+    pub boss_phases: bool,
+    #[serde(default)]
+    pub touch_layout: TouchLayout,
+    #[serde(default, with = "profile_overrides_map")]
+    pub profile_overrides: HashMap<u8, ProfileOverrides>,
+    /// Fastest completion of each stage in milliseconds, keyed by stage id.
+    #[serde(default, with = "string_keyed_map")]
+    pub best_stage_times: HashMap<usize, u64>,
+}
+
+/// Settings which can differ between save slots, unset ones fall back to the global value.
+#[derive(serde::Serialize, serde::Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProfileOverrides {
+    #[serde(default)]
+    pub difficulty: Option<GameDifficulty>,
+    #[serde(default)]
+    pub text_speed: Option<u8>,
+    #[serde(default)]
+    pub show_hud: Option<bool>,
+}
+
+impl ProfileOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == ProfileOverrides::default()
+    }
+}
+
+/// Save slots without any override are left out of the settings file.
+mod profile_overrides_map {
+    use std::collections::HashMap;
+
+    use serde::{Deserializer, Serializer};
+
+    use super::{string_keyed_map, ProfileOverrides};
+
+    pub fn serialize<S: Serializer>(map: &HashMap<u8, ProfileOverrides>, serializer: S) -> Result<S::Ok, S::Error> {
+        let map: HashMap<u8, ProfileOverrides> = map
+            .iter()
+            .filter(|(_, overrides)| !overrides.is_empty())
+            .map(|(slot, overrides)| (*slot, *overrides))
+            .collect();
+
+        string_keyed_map::serialize(&map, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<u8, ProfileOverrides>, D::Error> {
+        string_keyed_map::deserialize(deserializer)
+    }
+}
+
+/// TOML only allows string keys, so numeric ones are stored as strings.
+mod string_keyed_map {
+    use std::collections::HashMap;
+    use std::fmt::Display;
+    use std::hash::Hash;
+    use std::str::FromStr;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: ToString,
+        V: Serialize,
+        S: Serializer,
+    {
+        let map: HashMap<String, &V> = map.iter().map(|(key, value)| (key.to_string(), value)).collect();
+
+        map.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: FromStr + Eq + Hash,
+        K::Err: Display,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let map = HashMap::<String, V>::deserialize(deserializer)?;
+
+        map.into_iter()
+            .map(|(key, value)| key.parse::<K>().map(|key| (key, value)).map_err(serde::de::Error::custom))
+            .collect()
+    }
 }
 
 fn default_true() -> bool {
@@ -91,7 +269,7 @@ fn default_true() -> bool {
 
 #[inline(always)]
 fn current_version() -> u32 {
-    22
+    28
 }
 
 #[inline(always)]
@@ -114,6 +292,11 @@ fn default_speed() -> f64 {
     1.0
 }
 
+#[inline(always)]
+fn default_text_speed() -> u8 {
+    1
+}
+
 #[inline(always)]
 fn default_vol() -> f32 {
     1.0
@@ -129,6 +312,11 @@ fn default_vsync() -> VSyncMode {
     VSyncMode::VSync
 }
 
+#[inline(always)]
+fn default_fps_cap() -> Option<u32> {
+    None
+}
+
 #[inline(always)]
 fn default_screen_shake_intensity() -> ScreenShakeIntensity {
     ScreenShakeIntensity::Full
@@ -169,11 +357,27 @@ fn default_cutscene_skip_mode() -> CutsceneSkipMode {
 
 impl Settings {
     pub fn load(ctx: &Context) -> GameResult<Settings> {
-        if let Ok(file) = user_open(ctx, "/settings.json") {
-            match serde_json::from_reader::<_, Settings>(file) {
+        if let Ok(mut file) = user_open(ctx, SETTINGS_PATH) {
+            let mut data = String::new();
+            file.read_to_string(&mut data)?;
+
+            match toml::from_str::<Settings>(&data) {
                 Ok(settings) => return Ok(settings.upgrade()),
                 Err(err) => log::warn!("Failed to deserialize settings: {}", err),
             }
+        } else if let Ok(file) = user_open(ctx, LEGACY_SETTINGS_PATH) {
+            // settings from older versions, converted to TOML once
+            match serde_json::from_reader::<_, Settings>(file) {
+                Ok(settings) => {
+                    let settings = settings.upgrade();
+                    if let Err(err) = settings.save(ctx) {
+                        log::warn!("Failed to migrate settings: {}", err);
+                    }
+
+                    return Ok(settings);
+                }
+                Err(err) => log::warn!("Failed to deserialize legacy settings: {}", err),
+            }
         }
 
         Ok(Settings::default())
@@ -334,6 +538,40 @@ impl Settings {
             self.discord_rpc = true;
         }
 
+        if self.version == 22 {
+            self.version = 23;
+            self.fps_cap = default_fps_cap();
+        }
+
+        if self.version == 23 {
+            self.version = 24;
+            self.text_speed = default_text_speed();
+        }
+
+        if self.version == 24 {
+            self.version = 25;
+            self.show_hud = true;
+            self.profile_overrides = HashMap::new();
+        }
+
+        if self.version == 25 {
+            self.version = 26;
+            self.player1_key_map.join = p1_default_keymap().join;
+            self.player2_key_map.join = p2_default_keymap().join;
+            self.player1_controller_button_map.join = default_controller_join();
+            self.player2_controller_button_map.join = default_controller_join();
+        }
+
+        if self.version == 26 {
+            self.version = 27;
+            self.reverb_enabled = true;
+        }
+
+        if self.version == 27 {
+            self.version = 28;
+            self.damage_knockback = true;
+        }
+
         if self.version != initial_version {
             log::info!("Upgraded configuration file from version {} to {}.", initial_version, self.version);
         }
@@ -341,9 +579,17 @@ impl Settings {
         self
     }
 
+    pub fn to_toml(&self) -> GameResult<String> {
+        let mut data = SETTINGS_HEADER.to_owned();
+        data.push_str(&toml::to_string_pretty(self)?);
+
+        Ok(data)
+    }
+
     pub fn save(&self, ctx: &Context) -> GameResult {
-        let file = user_create(ctx, "/settings.json")?;
-        serde_json::to_writer_pretty(file, self)?;
+        let data = self.to_toml()?;
+        let mut file = user_create(ctx, SETTINGS_PATH)?;
+        file.write_all(data.as_bytes())?;
 
         Ok(())
     }
@@ -388,6 +634,52 @@ impl Settings {
         }
     }
 
+    /// Returns the stable id of the gamepad assigned to given player, see `GamepadData::get_stable_id`.
+    pub fn get_gamepad_id(&self, target: TargetPlayer) -> Option<&str> {
+        match target {
+            TargetPlayer::Player1 => self.player1_gamepad_id.as_deref(),
+            TargetPlayer::Player2 => self.player2_gamepad_id.as_deref(),
+        }
+    }
+
+    pub fn is_assigned_gamepad(&self, stable_id: &str) -> bool {
+        self.get_gamepad_id(TargetPlayer::Player1) == Some(stable_id)
+            || self.get_gamepad_id(TargetPlayer::Player2) == Some(stable_id)
+    }
+
+    /// Returns settings overridden for given save slot, empty if there are none.
+    pub fn get_profile_overrides(&self, slot: usize) -> ProfileOverrides {
+        self.profile_overrides.get(&(slot as u8)).copied().unwrap_or_default()
+    }
+
+    pub fn get_text_speed(&self, slot: usize) -> u8 {
+        self.get_profile_overrides(slot).text_speed.unwrap_or(self.text_speed)
+    }
+
+    pub fn get_show_hud(&self, slot: usize) -> bool {
+        self.get_profile_overrides(slot).show_hud.unwrap_or(self.show_hud)
+    }
+
+    /// Resets all preferences to their defaults.
+    /// Controls, language and data which isn't a preference (per slot overrides, best times) are kept.
+    pub fn restore_defaults(&mut self) {
+        let old = std::mem::take(self);
+
+        self.player1_controller_type = old.player1_controller_type;
+        self.player2_controller_type = old.player2_controller_type;
+        self.player1_key_map = old.player1_key_map;
+        self.player2_key_map = old.player2_key_map;
+        self.player1_controller_button_map = old.player1_controller_button_map;
+        self.player2_controller_button_map = old.player2_controller_button_map;
+        self.player1_controller_axis_sensitivity = old.player1_controller_axis_sensitivity;
+        self.player2_controller_axis_sensitivity = old.player2_controller_axis_sensitivity;
+        self.player1_rumble = old.player1_rumble;
+        self.player2_rumble = old.player2_rumble;
+        self.locale = old.locale;
+        self.profile_overrides = old.profile_overrides;
+        self.best_stage_times = old.best_stage_times;
+    }
+
     pub fn get_gamepad_axis_sensitivity(&self, id: u32) -> f64 {
         if self.player1_controller_type == ControllerType::Gamepad(id) {
             self.player1_controller_axis_sensitivity
@@ -418,6 +710,8 @@ impl Default for Settings {
             organya_interpolation: InterpolationMode::Linear,
             player1_controller_type: default_p1_controller_type(),
             player2_controller_type: default_p2_controller_type(),
+            player1_gamepad_id: None,
+            player2_gamepad_id: None,
             player1_key_map: p1_default_keymap(),
             player2_key_map: p2_default_keymap(),
             player1_controller_button_map: player_default_controller_button_map(),
@@ -430,16 +724,38 @@ impl Default for Settings {
             god_mode: false,
             infinite_booster: false,
             debug_outlines: false,
+            show_profiler: false,
             fps_counter: false,
             locale: default_locale(),
             window_mode: WindowMode::Windowed,
             vsync_mode: VSyncMode::VSync,
+            fps_cap: default_fps_cap(),
             screen_shake_intensity: ScreenShakeIntensity::Full,
             debug_mode: false,
             noclip: false,
             more_rust: false,
             cutscene_skip_mode: CutsceneSkipMode::Hold,
+            text_speed: default_text_speed(),
             discord_rpc: true,
+            show_hud: true,
+            minimal_hud: false,
+            heal_popups: false,
+            reverb_enabled: true,
+            booster_gauge: false,
+            boss_weak_points: false,
+            water_bubbles: false,
+            attack_telegraphs: false,
+            damage_knockback: true,
+            current_particles: false,
+            variable_shock_blink: false,
+            name_entry: false,
+            paginate_messages: false,
+            trade_keeps_weapon_level: false,
+            max_life_up_heals: false,
+            boss_phases: false,
+            touch_layout: TouchLayout::default(),
+            profile_overrides: HashMap::new(),
+            best_stage_times: HashMap::new(),
         }
     }
 }
@@ -460,6 +776,13 @@ pub struct PlayerKeyMap {
     pub strafe: ScanCode,
     pub menu_ok: ScanCode,
     pub menu_back: ScanCode,
+    #[serde(default = "default_keyboard_join")]
+    pub join: ScanCode,
+}
+
+#[inline(always)]
+fn default_keyboard_join() -> ScanCode {
+    p2_default_keymap().join
 }
 
 #[inline(always)]
@@ -479,6 +802,7 @@ pub fn p1_default_keymap() -> PlayerKeyMap {
         strafe: ScanCode::LShift,
         menu_ok: ScanCode::Z,
         menu_back: ScanCode::X,
+        join: ScanCode::E,
     }
 }
 
@@ -499,6 +823,7 @@ pub fn p2_default_keymap() -> PlayerKeyMap {
         strafe: ScanCode::RShift,
         menu_ok: ScanCode::B,
         menu_back: ScanCode::N,
+        join: ScanCode::U,
     }
 }
 
@@ -524,6 +849,8 @@ pub struct PlayerControllerButtonMap {
     pub strafe: PlayerControllerInputType,
     pub menu_ok: PlayerControllerInputType,
     pub menu_back: PlayerControllerInputType,
+    #[serde(default = "default_controller_join")]
+    pub join: PlayerControllerInputType,
 }
 
 #[inline(always)]
@@ -543,10 +870,73 @@ pub fn player_default_controller_button_map() -> PlayerControllerButtonMap {
         map: PlayerControllerInputType::ButtonInput(Button::North),
         menu_ok: PlayerControllerInputType::ButtonInput(Button::South),
         menu_back: PlayerControllerInputType::ButtonInput(Button::East),
+        join: default_controller_join(),
     }
 }
 
+#[inline(always)]
+fn default_controller_join() -> PlayerControllerInputType {
+    PlayerControllerInputType::ButtonInput(Button::Start)
+}
+
 #[inline(always)]
 pub fn default_controller_axis_sensitivity() -> f64 {
     0.3
 }
+
+#[test]
+fn test_settings_toml_roundtrip() {
+    let mut settings = Settings::default();
+    settings.player2_controller_type = ControllerType::Gamepad(1);
+    settings.player2_gamepad_id = Some("Xbox Controller 030000005e0400008e02000014010000".to_owned());
+    settings.fps_cap = Some(144);
+    settings.text_speed = 7;
+
+    let data = settings.to_toml().unwrap();
+    assert!(data.starts_with(SETTINGS_HEADER));
+
+    let loaded = toml::from_str::<Settings>(&data).unwrap();
+    assert!(loaded.player2_controller_type == ControllerType::Gamepad(1));
+    assert!(loaded.is_assigned_gamepad("Xbox Controller 030000005e0400008e02000014010000"));
+    assert_eq!(loaded.player1_gamepad_id, None);
+    assert_eq!(loaded.fps_cap, Some(144));
+    assert_eq!(loaded.text_speed, 7);
+    assert_eq!(loaded.player1_controller_button_map.strafe, settings.player1_controller_button_map.strafe);
+}
+
+#[test]
+fn test_profile_overrides() {
+    let mut settings = Settings::default();
+    settings.text_speed = 4;
+    settings.profile_overrides.insert(2, ProfileOverrides { text_speed: Some(9), ..Default::default() });
+    settings.profile_overrides.insert(3, ProfileOverrides::default());
+
+    let data = settings.to_toml().unwrap();
+    let loaded = toml::from_str::<Settings>(&data).unwrap();
+
+    assert_eq!(loaded.get_text_speed(1), 4);
+    assert_eq!(loaded.get_text_speed(2), 9);
+    assert!(loaded.get_show_hud(2));
+    // empty overrides aren't written
+    assert!(!loaded.profile_overrides.contains_key(&3));
+}
+
+#[test]
+fn test_restore_defaults() {
+    let mut settings = Settings::default();
+    settings.bgm_volume = 0.2;
+    settings.timing_mode = TimingMode::FrameSynchronized;
+    settings.locale = "jp".to_owned();
+    settings.player1_key_map.jump = ScanCode::Space;
+    settings.best_stage_times.insert(12, 90000);
+    settings.window_mode = WindowMode::Fullscreen;
+
+    settings.restore_defaults();
+
+    assert_eq!(settings.bgm_volume, Settings::default().bgm_volume);
+    assert!(settings.timing_mode == default_timing());
+    assert!(settings.window_mode == default_window_mode());
+    assert_eq!(settings.locale, "jp");
+    assert!(settings.player1_key_map.jump == ScanCode::Space);
+    assert_eq!(settings.best_stage_times.get(&12), Some(&90000));
+}
@@ -15,14 +15,20 @@ use crate::framework::graphics::{create_texture_mutable, set_render_target};
 use crate::framework::vfs::OpenOptions;
 use crate::framework::{filesystem, graphics};
 use crate::game::caret::{Caret, CaretType};
+use crate::game::events::{EventBus, GameEvent};
+use crate::game::journal::Journal;
 use crate::game::npc::NPCTable;
 use crate::game::profile::GameProfile;
+#[cfg(feature = "profiler")]
+use crate::game::profiler::Profiler;
 #[cfg(feature = "scripting-lua")]
 use crate::game::scripting::lua::LuaScriptingState;
 use crate::game::scripting::tsc::credit_script::{CreditScript, CreditScriptVM};
 use crate::game::scripting::tsc::text_script::{ScriptMode, TextScript, TextScriptEncoding, TextScriptExecutionState, TextScriptVM};
 use crate::game::settings::Settings;
 use crate::game::stage::StageData;
+use crate::game::stats::GameStats;
+use crate::game::tile_props::TileProperties;
 use crate::graphics::bmfont::BMFont;
 use crate::graphics::texture_set::TextureSet;
 use crate::i18n::Locale;
@@ -30,6 +36,7 @@ use crate::input::touch_controls::TouchControls;
 use crate::mod_list::ModList;
 use crate::mod_requirements::ModRequirements;
 use crate::scene::game_scene::GameScene;
+use crate::scene::name_entry_scene::NameEntryScene;
 use crate::scene::title_scene::TitleScene;
 use crate::scene::Scene;
 use crate::sound::SoundManager;
@@ -102,7 +109,7 @@ impl WindowMode {
     }
 }
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug, num_derive::FromPrimitive)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug, num_derive::FromPrimitive, serde::Serialize, serde::Deserialize)]
 pub enum GameDifficulty {
     Normal = 0,
     Easy = 2,
@@ -115,6 +122,17 @@ pub enum PlayerCount {
     Two,
 }
 
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Player2JoinState {
+    /// Player 2 isn't playing.
+    Inactive,
+    /// Player 2 pressed pause at given game tick and will be spawned once player 1 is in control.
+    Joining(u64),
+    Active,
+    /// Player 2 has died and follows player 1 invisibly until revived.
+    Ghost,
+}
+
 #[derive(PartialEq, Eq, Copy, Clone, serde::Serialize, serde::Deserialize)]
 pub enum CutsceneSkipMode {
     Hold,
@@ -287,9 +305,22 @@ impl Default for PlayerSkinLocation {
     }
 }
 
+/// Callback run by `SharedGameState::set_flag` when a game flag changes, see `SharedGameState::add_flag_listener`.
+pub struct FlagListener {
+    pub id: usize,
+    pub flag: usize,
+    /// Whether the listener fires when the flag gets set or when it gets cleared.
+    pub on_set: bool,
+    pub callback: Box<dyn Fn(&mut SharedGameState)>,
+}
+
 pub struct SharedGameState {
     pub control_flags: ControlFlags,
     pub game_flags: BitVec,
+    flag_listeners: Vec<FlagListener>,
+    next_flag_listener_id: usize,
+    /// Listeners removed while `set_flag` was running them, `None` outside of it.
+    removed_flag_listeners: Option<Vec<usize>>,
     pub skip_flags: BitVec,
     pub map_flags: BitVec,
     pub fade_state: FadeState,
@@ -308,6 +339,7 @@ pub struct SharedGameState {
     pub mod_path: Option<String>,
     pub mod_list: ModList,
     pub npc_table: NPCTable,
+    pub tile_props: TileProperties,
     pub npc_super_pos: (i32, i32),
     pub npc_curly_target: (i32, i32),
     pub npc_curly_counter: u16,
@@ -332,12 +364,29 @@ pub struct SharedGameState {
     pub texture_set: TextureSet,
     #[cfg(feature = "scripting-lua")]
     pub lua: LuaScriptingState,
+    #[cfg(feature = "profiler")]
+    pub profiler: Profiler,
     pub sound_manager: SoundManager,
     pub settings: Settings,
     pub save_slot: usize,
     pub difficulty: GameDifficulty,
     pub player_count: PlayerCount,
     pub player_count_modified_in_game: bool,
+    pub player2_join_state: Player2JoinState,
+    /// Name entered by the player when starting a new game, see `<NAM`.
+    pub player_name: String,
+    /// Ticks spent in game since the save file was created.
+    pub play_time: u64,
+    pub journal: Journal,
+    pub stats: GameStats,
+    pub event_bus: EventBus,
+    /// Id of the stage the game scene was last initialized with.
+    pub current_stage: usize,
+    /// Tile position players respawn at after dying, instead of reloading the save.
+    pub checkpoint: Option<(isize, isize)>,
+    pub checkpoint_map: Option<usize>,
+    /// Song playing when the checkpoint was set, resumed on respawn.
+    pub checkpoint_song: usize,
     pub player2_skin_location: PlayerSkinLocation,
     pub replay_state: ReplayState,
     pub mod_requirements: ModRequirements,
@@ -459,6 +508,9 @@ impl SharedGameState {
         Ok(SharedGameState {
             control_flags: ControlFlags(0),
             game_flags: BitVec::with_size(8000),
+            flag_listeners: Vec::new(),
+            next_flag_listener_id: 0,
+            removed_flag_listeners: None,
             skip_flags: BitVec::with_size(64),
             map_flags: BitVec::with_size(128),
             fade_state: FadeState::Hidden,
@@ -475,6 +527,7 @@ impl SharedGameState {
             mod_path: None,
             mod_list,
             npc_table: NPCTable::new(),
+            tile_props: TileProperties::new(),
             npc_super_pos: (0, 0),
             npc_curly_target: (0, 0),
             npc_curly_counter: 0,
@@ -499,12 +552,24 @@ impl SharedGameState {
             texture_set: TextureSet::new(),
             #[cfg(feature = "scripting-lua")]
             lua: LuaScriptingState::new(),
+            #[cfg(feature = "profiler")]
+            profiler: Profiler::new(),
             sound_manager,
             settings,
             save_slot: 1,
             difficulty: GameDifficulty::Normal,
             player_count: PlayerCount::One,
             player_count_modified_in_game: false,
+            player2_join_state: Player2JoinState::Inactive,
+            player_name: String::new(),
+            play_time: 0,
+            journal: Journal::new(),
+            stats: GameStats::new(),
+            event_bus: EventBus::new(),
+            current_stage: 0,
+            checkpoint: None,
+            checkpoint_map: None,
+            checkpoint_song: 0,
             player2_skin_location: PlayerSkinLocation::default(),
             replay_state: ReplayState::None,
             mod_requirements,
@@ -526,12 +591,55 @@ impl SharedGameState {
         self.constants.load_csplus_tables(ctx)?;
         self.constants.load_animated_faces(ctx)?;
         self.constants.load_texture_size_hints(ctx)?;
+        self.constants.load_completion_flags(ctx)?;
+        self.constants.load_weapon_heat(ctx)?;
         let stages = StageData::load_stage_table(ctx, &self.constants.base_paths, self.constants.is_switch)?;
         self.stages = stages;
 
         let npc_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc.tbl")?;
-        let npc_table = NPCTable::load_from(npc_tbl)?;
+        let mut npc_table = NPCTable::load_from(npc_tbl)?;
+        if filesystem::exists_find(ctx, &self.constants.base_paths, "npc_drops.tbl") {
+            let npc_drops_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc_drops.tbl")?;
+            npc_table.load_drop_tables(npc_drops_tbl)?;
+        }
+        if filesystem::exists_find(ctx, &self.constants.base_paths, "npc_factions.tbl") {
+            let npc_factions_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc_factions.tbl")?;
+            npc_table.load_factions(npc_factions_tbl)?;
+        }
+        if filesystem::exists_find(ctx, &self.constants.base_paths, "npc_sight.tbl") {
+            let npc_sight_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc_sight.tbl")?;
+            npc_table.load_sight_ranges(npc_sight_tbl)?;
+        }
+        if filesystem::exists_find(ctx, &self.constants.base_paths, "npc_respawn.tbl") {
+            let npc_respawn_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc_respawn.tbl")?;
+            npc_table.load_respawn_ticks(npc_respawn_tbl)?;
+        }
+        if filesystem::exists_find(ctx, &self.constants.base_paths, "npc_barrels.tbl") {
+            let npc_barrels_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc_barrels.tbl")?;
+            npc_table.load_barrels(npc_barrels_tbl)?;
+        }
+        if filesystem::exists_find(ctx, &self.constants.base_paths, "npc_tile_aware.tbl") {
+            let npc_tile_aware_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc_tile_aware.tbl")?;
+            npc_table.load_tile_aware(npc_tile_aware_tbl)?;
+        }
+        if filesystem::exists_find(ctx, &self.constants.base_paths, "npc_swimmers.tbl") {
+            let npc_swimmers_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc_swimmers.tbl")?;
+            npc_table.load_swimmers(npc_swimmers_tbl)?;
+        }
+        if filesystem::exists_find(ctx, &self.constants.base_paths, "npc_climbers.tbl") {
+            let npc_climbers_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc_climbers.tbl")?;
+            npc_table.load_climbers(npc_climbers_tbl)?;
+        }
+        if filesystem::exists_find(ctx, &self.constants.base_paths, "npc_tile_damage.tbl") {
+            let npc_tile_damage_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc_tile_damage.tbl")?;
+            npc_table.load_tile_damage_takers(npc_tile_damage_tbl)?;
+        }
+        if filesystem::exists_find(ctx, &self.constants.base_paths, "npc_poison.tbl") {
+            let npc_poison_tbl = filesystem::open_find(ctx, &self.constants.base_paths, "npc_poison.tbl")?;
+            npc_table.load_poisoners(npc_poison_tbl)?;
+        }
         self.npc_table = npc_table;
+        self.tile_props = TileProperties::load(ctx, &self.constants.base_paths);
 
         let head_tsc = filesystem::open_find(ctx, &self.constants.base_paths, "Head.tsc")?;
         let head_script = TextScript::load_from(head_tsc, &self.constants)?;
@@ -547,6 +655,7 @@ impl SharedGameState {
 
         let substitution_rect_map = [('=', self.constants.textscript.textbox_item_marker_rect)];
         self.textscript_vm.set_substitution_rect_map(substitution_rect_map);
+        self.textscript_vm.reset_text_speed(&self.constants, self.settings.get_text_speed(self.save_slot));
 
         if filesystem::exists_find(ctx, &self.constants.base_paths, "Credit.tsc") {
             let credit_tsc = filesystem::open_find(ctx, &self.constants.base_paths, "Credit.tsc")?;
@@ -592,6 +701,7 @@ impl SharedGameState {
 
     pub fn start_new_game(&mut self, ctx: &mut Context) -> GameResult {
         self.reset();
+        self.textscript_vm.reset_text_speed(&self.constants, self.settings.get_text_speed(self.save_slot));
         #[cfg(feature = "scripting-lua")]
         self.lua.reload_scripts(ctx)?;
 
@@ -611,7 +721,11 @@ impl SharedGameState {
         self.textscript_vm.state = TextScriptExecutionState::Running(self.constants.game.new_game_event, 0);
         self.tutorial_counter = 300;
 
-        self.next_scene = Some(Box::new(next_scene));
+        self.next_scene = if self.constants.game.name_entry || self.settings.name_entry {
+            Some(Box::new(NameEntryScene::new(Box::new(next_scene))))
+        } else {
+            Some(Box::new(next_scene))
+        };
 
         Ok(())
     }
@@ -644,6 +758,17 @@ impl SharedGameState {
         Ok(())
     }
 
+    /// Applies settings overridden for the current save slot on top of the global ones.
+    pub fn apply_profile_overrides(&mut self) {
+        let overrides = self.settings.get_profile_overrides(self.save_slot);
+
+        if let Some(difficulty) = overrides.difficulty {
+            self.difficulty = difficulty;
+        }
+
+        self.textscript_vm.reset_text_speed(&self.constants, self.settings.get_text_speed(self.save_slot));
+    }
+
     pub fn save_game(&mut self, game_scene: &mut GameScene, ctx: &mut Context) -> GameResult {
         if let Some(save_path) = self.get_save_filename(self.save_slot) {
             if let Ok(data) = filesystem::open_options(ctx, save_path, OpenOptions::new().write(true).create(true)) {
@@ -668,6 +793,7 @@ impl SharedGameState {
                         let mut next_scene = GameScene::new(self, ctx, profile.current_map as usize)?;
 
                         profile.apply(self, &mut next_scene, ctx);
+                        self.apply_profile_overrides();
 
                         #[cfg(feature = "scripting-lua")]
                         self.lua.reload_scripts(ctx)?;
@@ -700,10 +826,40 @@ impl SharedGameState {
         self.teleporter_slots.clear();
         self.quake_counter = 0;
         self.carets.clear();
+        self.player2_join_state = Player2JoinState::Inactive;
+        self.player_name.clear();
+        self.play_time = 0;
+        self.journal.clear();
+        if let Some(id) = self.stats.completion_listener.take() {
+            self.remove_flag_listener(id);
+        }
+        self.stats.clear();
+        self.event_bus.clear();
+        self.clear_checkpoint();
         self.textscript_vm.set_mode(ScriptMode::Map);
         self.textscript_vm.suspend = true;
     }
 
+    /// Dispatches events emitted since the last call, called once per tick by the game scene.
+    pub fn drain_events(&mut self) {
+        for event in self.event_bus.take() {
+            self.stats.handle_event(&event);
+        }
+    }
+
+    /// Sets the checkpoint players respawn at, see [GameScene::respawn_at_checkpoint].
+    pub fn set_checkpoint(&mut self, map: usize, x: isize, y: isize) {
+        self.checkpoint = Some((x, y));
+        self.checkpoint_map = Some(map);
+        self.checkpoint_song = self.sound_manager.current_song();
+    }
+
+    pub fn clear_checkpoint(&mut self) {
+        self.checkpoint = None;
+        self.checkpoint_map = None;
+        self.checkpoint_song = 0;
+    }
+
     pub fn handle_resize(&mut self, ctx: &mut Context) -> GameResult {
         self.screen_size = graphics::screen_size(ctx);
         let scale_x = self.screen_size.1.div(self.preferred_viewport_size.1).floor().max(1.0);
@@ -756,11 +912,64 @@ impl SharedGameState {
         self.sound_manager.stop_sfx(58);
     }
 
+    /// Sets a game flag, running listeners registered for it if its value changed.
+    ///
+    /// Flags replaced as a whole (on reset or profile load) don't notify listeners, neither do flags changed
+    /// by another listener's callback.
     pub fn set_flag(&mut self, id: usize, value: bool) {
-        if id < self.game_flags.len() {
-            self.game_flags.set(id, value);
-        } else {
+        if id >= self.game_flags.len() {
             log::warn!("Attempted to set an out-of-bounds flag: {} to {}.", id, value);
+            return;
+        }
+
+        if self.game_flags.get(id) == Some(value) {
+            return;
+        }
+
+        self.game_flags.set(id, value);
+        self.event_bus.emit(GameEvent::FlagChanged { id, val: value });
+
+        if !self.flag_listeners.iter().any(|listener| listener.flag == id && listener.on_set == value) {
+            return;
+        }
+
+        // listeners get mutable access to the state, so they're moved out while running
+        let mut listeners = std::mem::take(&mut self.flag_listeners);
+        let outer_removed = self.removed_flag_listeners.replace(Vec::new());
+        for listener in &listeners {
+            if listener.flag == id && listener.on_set == value {
+                (listener.callback)(self);
+            }
+        }
+
+        let removed = std::mem::replace(&mut self.removed_flag_listeners, outer_removed).unwrap_or_default();
+        listeners.retain(|listener| !removed.contains(&listener.id));
+        if let Some(outer_removed) = &mut self.removed_flag_listeners {
+            outer_removed.extend(removed);
+        }
+        listeners.append(&mut self.flag_listeners);
+        self.flag_listeners = listeners;
+    }
+
+    /// Registers a callback run whenever `flag` gets set (or cleared, if `on_set` is false) through `set_flag`.
+    /// Returns an id which can be passed to `remove_flag_listener`.
+    pub fn add_flag_listener(
+        &mut self,
+        flag: usize,
+        on_set: bool,
+        callback: impl Fn(&mut SharedGameState) + 'static,
+    ) -> usize {
+        let id = self.next_flag_listener_id;
+        self.next_flag_listener_id += 1;
+        self.flag_listeners.push(FlagListener { id, flag, on_set, callback: Box::new(callback) });
+
+        id
+    }
+
+    pub fn remove_flag_listener(&mut self, id: usize) {
+        self.flag_listeners.retain(|listener| listener.id != id);
+        if let Some(removed) = &mut self.removed_flag_listeners {
+            removed.push(id);
         }
     }
 
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::io::{Cursor, Read};
 use std::str::from_utf8;
 
@@ -13,6 +14,7 @@ use crate::framework::error::GameError::ResourceLoadError;
 use crate::framework::filesystem;
 use crate::game::map::{Map, NPCData};
 use crate::game::scripting::tsc::text_script::TextScript;
+use crate::game::scripting::tsc::validator::ScriptDiagnostic;
 use crate::util::encoding::read_cur_shift_jis;
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -91,6 +93,9 @@ impl Background {
     }
 }
 
+/// Reverb amount used by stages which don't take place outside.
+const CAVE_ROOM_REVERB: f32 = 0.6;
+
 #[derive(Debug, EnumIter, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum BackgroundType {
     TiledStatic,
@@ -110,6 +115,19 @@ pub enum BackgroundType {
     Waterway,
 }
 
+impl BackgroundType {
+    /// Returns the default reverb amount of stages using this background, see `StageData::room_reverb`.
+    pub fn room_reverb(self) -> f32 {
+        match self {
+            BackgroundType::Scrolling
+            | BackgroundType::OutsideWind
+            | BackgroundType::Outside
+            | BackgroundType::OutsideUnknown => 0.0,
+            _ => CAVE_ROOM_REVERB,
+        }
+    }
+}
+
 impl From<u8> for BackgroundType {
     fn from(val: u8) -> Self {
         match val {
@@ -208,6 +226,8 @@ pub struct StageData {
     pub background_color: Color,
     pub npc1: NpcType,
     pub npc2: NpcType,
+    /// Dry/wet mix of the reverb applied to sound effects, from 0.0 to 1.0.
+    pub room_reverb: f32,
 }
 
 impl Clone for StageData {
@@ -224,6 +244,7 @@ impl Clone for StageData {
             background_color: self.background_color,
             npc1: self.npc1.clone(),
             npc2: self.npc2.clone(),
+            room_reverb: self.room_reverb,
         }
     }
 }
@@ -345,6 +366,7 @@ impl StageData {
                             background_color: Color::from_rgb(0, 0, 32),
                             npc1: NpcType::new(&npc1),
                             npc2: NpcType::new(&npc2),
+                            room_reverb: BackgroundType::from(bg_type).room_reverb(),
                         };
                         new_stages.push(stage);
                     }
@@ -410,6 +432,7 @@ impl StageData {
                     background_color: Color::from_rgb(0, 0, 32),
                     npc1: NpcType::new(&npc1),
                     npc2: NpcType::new(&npc2),
+                    room_reverb: BackgroundType::from(bg_type).room_reverb(),
                 };
                 stages.push(stage);
             }
@@ -469,6 +492,7 @@ impl StageData {
                     background_color: Color::from_rgb(0, 0, 32),
                     npc1: NpcType::new(&npc1),
                     npc2: NpcType::new(&npc2),
+                    room_reverb: BackgroundType::from(bg_type).room_reverb(),
                 };
                 stages.push(stage);
             }
@@ -526,6 +550,7 @@ impl StageData {
                     background_color: Color::from_rgb(0, 0, 32),
                     npc1: NpcType::new(NXENGINE_NPCS.get(npc1).unwrap_or(&"0")),
                     npc2: NpcType::new(NXENGINE_NPCS.get(npc2).unwrap_or(&"0")),
+                    room_reverb: BackgroundType::from(bg_type).room_reverb(),
                 };
                 stages.push(stage);
             }
@@ -541,6 +566,9 @@ impl StageData {
 pub struct Stage {
     pub map: Map,
     pub data: StageData,
+    /// Hits left on destructible tiles that were already shot, keyed by tile position. Not saved, so
+    /// damaged tiles are back to full health once the stage is loaded again.
+    pub tile_damage: HashMap<(u16, u16), u8>,
 }
 
 impl Stage {
@@ -549,7 +577,7 @@ impl Stage {
 
         if let Ok(pxpack_file) = filesystem::open_find(ctx, roots, ["Stage/", &data.map, ".pxpack"].join("")) {
             let map = Map::load_pxpack(pxpack_file, roots, &mut data, ctx)?;
-            let stage = Self { map, data };
+            let stage = Self { map, data, tile_damage: HashMap::new() };
 
             return Ok(stage);
         } else if let Ok(map_file) = filesystem::open_find(ctx, roots, ["Stage/", &data.map, ".pxm"].join("")) {
@@ -557,7 +585,7 @@ impl Stage {
 
             let map = Map::load_pxm(map_file, attrib_file)?;
 
-            let stage = Self { map, data };
+            let stage = Self { map, data, tile_damage: HashMap::new() };
 
             return Ok(stage);
         }
@@ -565,16 +593,17 @@ impl Stage {
         Err(GameError::ResourceLoadError(format!("Stage {} not found", data.map)))
     }
 
+    /// Loads the map script, along with problems found in it. `global_script` is used to resolve jumps to Head.tsc.
     pub fn load_text_script(
         &self,
         roots: &Vec<String>,
         constants: &EngineConstants,
+        global_script: &TextScript,
         ctx: &mut Context,
-    ) -> GameResult<TextScript> {
+    ) -> GameResult<(TextScript, Vec<ScriptDiagnostic>)> {
         let tsc_file = filesystem::open_find(ctx, roots, ["Stage/", &self.data.map, ".tsc"].join(""))?;
-        let text_script = TextScript::load_from(tsc_file, constants)?;
 
-        Ok(text_script)
+        TextScript::load_validated(tsc_file, constants, global_script)
     }
 
     pub fn load_npcs(&self, roots: &Vec<String>, ctx: &mut Context) -> GameResult<Vec<NPCData>> {
@@ -604,6 +633,20 @@ impl Stage {
 
         false
     }
+
+    /// Registers a weapon hit on a destructible tile, returns true if it ran out of hits and got replaced.
+    pub fn damage_tile(&mut self, x: usize, y: usize, hp: u8, replacement_tile: u8) -> bool {
+        let key = (x as u16, y as u16);
+        let hits_left = self.tile_damage.get(&key).copied().unwrap_or(hp).saturating_sub(1);
+
+        if hits_left > 0 {
+            self.tile_damage.insert(key, hits_left);
+            return false;
+        }
+
+        self.tile_damage.remove(&key);
+        self.change_tile(x, y, replacement_tile)
+    }
 }
 
 pub struct StageTexturePaths {
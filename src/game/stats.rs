@@ -0,0 +1,62 @@
+use crate::game::events::GameEvent;
+
+/// Statistics of the current playthrough, shown on the stage summary.
+#[derive(Debug, Clone, Default)]
+pub struct GameStats {
+    /// Play time at which the current stage was entered, in ticks.
+    pub stage_start_tick: u64,
+    pub enemies_defeated: u32,
+    pub damage_taken: u32,
+    /// Flag listener waiting for the completion flag of the current stage, if it has one.
+    pub completion_listener: Option<usize>,
+    /// Set by the completion listener, the game scene shows the summary once it sees it.
+    pub completion_reached: bool,
+    pub total_enemies_defeated: u32,
+    pub total_damage_taken: u32,
+    pub stages_completed: u32,
+}
+
+impl GameStats {
+    pub fn new() -> GameStats {
+        GameStats::default()
+    }
+
+    pub fn begin_stage(&mut self, play_time: u64) {
+        self.stage_start_tick = play_time;
+        self.enemies_defeated = 0;
+        self.damage_taken = 0;
+        self.completion_reached = false;
+    }
+
+    pub fn complete_stage(&mut self) {
+        self.completion_reached = false;
+        self.stages_completed += 1;
+    }
+
+    pub fn add_enemy_defeated(&mut self) {
+        self.enemies_defeated = self.enemies_defeated.saturating_add(1);
+        self.total_enemies_defeated = self.total_enemies_defeated.saturating_add(1);
+    }
+
+    pub fn add_damage_taken(&mut self, hp: u32) {
+        self.damage_taken = self.damage_taken.saturating_add(hp);
+        self.total_damage_taken = self.total_damage_taken.saturating_add(hp);
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::NpcDied { .. } => self.add_enemy_defeated(),
+            GameEvent::PlayerDamaged { amount, .. } => self.add_damage_taken(amount as u32),
+            _ => (),
+        }
+    }
+
+    /// Returns the amount of ticks spent in the current stage.
+    pub fn stage_time(&self, play_time: u64) -> u64 {
+        play_time.saturating_sub(self.stage_start_tick)
+    }
+
+    pub fn clear(&mut self) {
+        *self = GameStats::default();
+    }
+}
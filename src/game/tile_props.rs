@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::common::Direction;
+use crate::framework::context::Context;
+use crate::framework::filesystem;
+use crate::framework::filesystem::File;
+
+const TILE_PROPS_PATH: &str = "Stage/tile_props.json";
+/// Damage dealt by spike tiles that don't kill instantly, same as the spike tile attribute.
+pub const SPIKE_DAMAGE: u16 = 10;
+
+/// Extra behavior attached to a tile of a tileset, on top of its .pxa attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TileProperty {
+    /// Solid tile that takes `hp` weapon hits before being replaced with `replacement_tile`.
+    Destructible { hp: u8, replacement_tile: u8 },
+    /// Runs `activate_event` when the player steps on it and `deactivate_event` once they've been off it for
+    /// `reset_ticks`. Switches with `reset_ticks` of 0 stay pressed forever. The game flag `flag` is set
+    /// while the switch is pressed, switches meant to work independently need their own tile and flag.
+    PressureSwitch { flag: u16, activate_event: u16, deactivate_event: u16, reset_ticks: u16 },
+    /// Slippery floor, see `WorldConsts::ice_friction`.
+    IceSurface,
+    /// Moves entities standing on it by `vel_x` each tick, negative values move them to the left.
+    Conveyor { vel_x: isize },
+    /// Water zone, entities with their center inside of it have `buoyancy_accel` added to their vertical speed
+    /// and both velocities multiplied by `drag` each tick. Counts as water for the air meter and splashes.
+    Water { drag: f32, buoyancy_accel: isize },
+    /// Can be climbed by holding up while standing in front of it.
+    Ladder,
+    /// Hurts the player for `damage` every `tick_interval` ticks they spend touching it.
+    Lava { damage: u8, tick_interval: u16 },
+    /// Deals [SPIKE_DAMAGE] or kills entities touching it. If `direction` is set, only touching the side the spikes
+    /// are pointing to hurts, eg. `up` spikes only hurt when landed on.
+    Spike {
+        instant_kill: bool,
+        #[serde(default)]
+        direction: Option<Direction>,
+    },
+}
+
+/// Tile properties defined by the optional `Stage/tile_props.json`, keyed by tileset name and tile index.
+///
+/// ```json
+/// { "Cave": { "32": { "type": "destructible", "hp": 3, "replacement_tile": 0 } } }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct TileProperties {
+    tilesets: HashMap<String, HashMap<u16, TileProperty>>,
+}
+
+impl TileProperties {
+    pub fn new() -> TileProperties {
+        TileProperties::default()
+    }
+
+    pub fn load(ctx: &mut Context, roots: &Vec<String>) -> TileProperties {
+        if let Ok(file) = filesystem::open_find(ctx, roots, TILE_PROPS_PATH) {
+            match serde_json::from_reader::<File, TileProperties>(file) {
+                Ok(props) => return props,
+                Err(err) => log::warn!("Failed to load tile properties: {:?}", err),
+            }
+        }
+
+        TileProperties::new()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tilesets.is_empty()
+    }
+
+    pub fn get(&self, tileset: &str, tile: u16) -> Option<TileProperty> {
+        self.tilesets.get(tileset).and_then(|tiles| tiles.get(&tile)).copied()
+    }
+
+    /// Returns true if the tile is marked as ice, either here or in `ice_tile_ids` shared by all tilesets.
+    pub fn is_ice(&self, tileset: &str, tile: u16, ice_tile_ids: &[u16]) -> bool {
+        ice_tile_ids.contains(&tile) || self.get(tileset, tile) == Some(TileProperty::IceSurface)
+    }
+
+    /// Returns instant kill flag and dangerous side of a spike tile, tiles in `spike_tile_ids` hurt from every side.
+    pub fn spike(&self, tileset: &str, tile: u16, spike_tile_ids: &[u16]) -> Option<(bool, Option<Direction>)> {
+        match self.get(tileset, tile) {
+            Some(TileProperty::Spike { instant_kill, direction }) => Some((instant_kill, direction)),
+            _ if spike_tile_ids.contains(&tile) => Some((false, None)),
+            _ => None,
+        }
+    }
+
+    /// Returns damage and interval of a lava tile, tiles in `lava_tile_ids` use given defaults.
+    pub fn lava(&self, tileset: &str, tile: u16, lava_tile_ids: &[u16], default: (u8, u16)) -> Option<(u8, u16)> {
+        match self.get(tileset, tile) {
+            Some(TileProperty::Lava { damage, tick_interval }) => Some((damage, tick_interval)),
+            _ if lava_tile_ids.contains(&tile) => Some(default),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_tile_props_parse() {
+    let json = r#"{ "Cave": {
+        "32": { "type": "destructible", "hp": 3, "replacement_tile": 0 },
+        "48": { "type": "pressure_switch", "flag": 7000, "activate_event": 300, "deactivate_event": 301, "reset_ticks": 50 },
+        "64": { "type": "ice_surface" },
+        "80": { "type": "conveyor", "vel_x": -256 },
+        "96": { "type": "water", "drag": 0.9, "buoyancy_accel": -32 },
+        "112": { "type": "ladder" },
+        "128": { "type": "lava", "damage": 4, "tick_interval": 20 },
+        "144": { "type": "spike", "instant_kill": true, "direction": "up" },
+        "145": { "type": "spike", "instant_kill": false }
+    } }"#;
+    let props: TileProperties = serde_json::from_str(json).unwrap();
+
+    assert_eq!(props.get("Cave", 32), Some(TileProperty::Destructible { hp: 3, replacement_tile: 0 }));
+    assert_eq!(
+        props.get("Cave", 48),
+        Some(TileProperty::PressureSwitch { flag: 7000, activate_event: 300, deactivate_event: 301, reset_ticks: 50 })
+    );
+    assert_eq!(props.get("Cave", 80), Some(TileProperty::Conveyor { vel_x: -256 }));
+    assert_eq!(props.get("Cave", 96), Some(TileProperty::Water { drag: 0.9, buoyancy_accel: -32 }));
+    assert_eq!(props.get("Cave", 112), Some(TileProperty::Ladder));
+    assert_eq!(props.lava("Cave", 128, &[], (2, 30)), Some((4, 20)));
+    assert_eq!(props.lava("Cave", 129, &[129], (2, 30)), Some((2, 30)));
+    assert_eq!(props.lava("Cave", 112, &[], (2, 30)), None);
+    assert_eq!(props.spike("Cave", 144, &[]), Some((true, Some(Direction::Up))));
+    assert_eq!(props.spike("Cave", 145, &[]), Some((false, None)));
+    assert_eq!(props.spike("Cave", 146, &[146]), Some((false, None)));
+    assert!(props.is_ice("Cave", 64, &[]));
+    assert!(props.is_ice("Sand", 65, &[65]));
+    assert!(!props.is_ice("Cave", 32, &[]));
+    assert_eq!(props.get("Cave", 33), None);
+    assert_eq!(props.get("Sand", 32), None);
+
+    let json = r#"{ "Cave": { "32": { "type": "destructible", "hp": 3, "replacement_tile": 256 } } }"#;
+    assert!(serde_json::from_str::<TileProperties>(json).is_err());
+}
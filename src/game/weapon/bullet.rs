@@ -3,12 +3,14 @@ use num_traits::clamp;
 use crate::common::{BulletFlag, Condition, Direction, Flag, Rect};
 use crate::engine_constants::{BulletData, EngineConstants};
 use crate::game::caret::CaretType;
+use crate::game::npc::faction::{FACTION_ENEMY, FACTION_NEUTRAL};
 use crate::game::npc::list::NPCList;
 use crate::game::npc::NPC;
 use crate::game::physics::{OFFSETS, PhysicalEntity};
 use crate::game::player::{Player, TargetPlayer};
 use crate::game::shared_game_state::{SharedGameState, TileSize};
 use crate::game::stage::Stage;
+use crate::game::tile_props::TileProperty;
 use crate::util::rng::{RNG, Xoroshiro32PlusPlus, XorShift};
 
 pub struct BulletManager {
@@ -79,8 +81,42 @@ impl BulletManager {
         }
     }
 
+    /// Destroys player bullets and bullets shot by enemy faction NPCs that hit each other.
+    pub fn tick_bullet_collisions(&mut self, state: &mut SharedGameState) {
+        let can_deflect = |b: &Bullet| b.cond.alive() && !b.weapon_flags.deflect_immune();
+
+        for i in 0..self.bullets.len() {
+            if self.bullets[i].source_faction != FACTION_NEUTRAL || !can_deflect(&self.bullets[i]) {
+                continue;
+            }
+
+            for j in 0..self.bullets.len() {
+                let (player_bullet, enemy_bullet) = (&self.bullets[i], &self.bullets[j]);
+
+                if enemy_bullet.source_faction != FACTION_ENEMY || !can_deflect(enemy_bullet) {
+                    continue;
+                }
+
+                if (player_bullet.x - enemy_bullet.x).abs()
+                    >= (player_bullet.enemy_hit_width + enemy_bullet.enemy_hit_width) as i32
+                    || (player_bullet.y - enemy_bullet.y).abs()
+                        >= (player_bullet.enemy_hit_height + enemy_bullet.enemy_hit_height) as i32
+                {
+                    continue;
+                }
+
+                let (x, y) = ((player_bullet.x + enemy_bullet.x) / 2, (player_bullet.y + enemy_bullet.y) / 2);
+                state.create_caret(x, y, CaretType::Explosion, Direction::Left);
+
+                self.bullets[i].cond.set_alive(false);
+                self.bullets[j].cond.set_alive(false);
+                break;
+            }
+        }
+    }
+
     pub fn count_bullets(&self, btype: u16, player_id: TargetPlayer) -> usize {
-        self.bullets.iter().filter(|b| b.owner == player_id && b.btype == btype).count()
+        self.bullets.iter().filter(|b| b.owner == player_id && b.npc_owner.is_none() && b.btype == btype).count()
     }
 
     pub fn count_bullets_type_idx_all(&self, type_idx: u16) -> usize {
@@ -88,7 +124,10 @@ impl BulletManager {
     }
 
     pub fn count_bullets_multi(&self, btypes: &[u16], player_id: TargetPlayer) -> usize {
-        self.bullets.iter().filter(|b| b.owner == player_id && btypes.contains(&b.btype)).count()
+        self.bullets
+            .iter()
+            .filter(|b| b.owner == player_id && b.npc_owner.is_none() && btypes.contains(&b.btype))
+            .count()
     }
 }
 
@@ -109,7 +148,12 @@ pub struct Bullet {
     pub counter1: u16,
     pub counter2: u16,
     pub rng: Xoroshiro32PlusPlus,
+    /// Player that shot this bullet, meaningless if `npc_owner` is set.
     pub owner: TargetPlayer,
+    /// Id of the NPC that shot this bullet, these don't count towards the bullet limits of players.
+    pub npc_owner: Option<u16>,
+    /// Faction of the NPC that shot this bullet, bullets shot by players are neutral.
+    pub source_faction: u8,
     pub cond: Condition,
     pub weapon_flags: BulletFlag,
     pub flags: Flag,
@@ -163,6 +207,8 @@ impl Bullet {
             counter2: 0,
             rng: Xoroshiro32PlusPlus::new(1),
             owner,
+            npc_owner: None,
+            source_faction: 0,
             cond: Condition(0x80),
             weapon_flags: bullet.flags,
             flags: Flag(0),
@@ -189,6 +235,15 @@ impl Bullet {
         }
     }
 
+    /// Creates a bullet shot by given NPC, hitting NPCs of factions hostile to its own.
+    pub fn new_npc(npc: &NPC, btype: u16, direction: Direction, constants: &EngineConstants) -> Bullet {
+        let mut bullet = Bullet::new(npc.x, npc.y, btype, TargetPlayer::Player1, direction, constants);
+        bullet.npc_owner = Some(npc.id);
+        bullet.source_faction = npc.faction;
+
+        bullet
+    }
+
     #[inline]
     pub fn is_dead(&self) -> bool {
         !self.cond.alive()
@@ -1816,6 +1871,43 @@ impl Bullet {
             self.vanish(state);
         }
     }
+
+    fn spawn_tile_debris(state: &SharedGameState, npc_list: &NPCList, x: i32, y: i32) {
+        let mut npc = NPC::create(4, &state.npc_table);
+        npc.cond.set_alive(true);
+        npc.direction = Direction::Left;
+        npc.x = x;
+        npc.y = y;
+
+        for _ in 0..4 {
+            npc.vel_x = npc.rng.range(-0x200..0x200) as i32;
+            npc.vel_y = npc.rng.range(-0x200..0x200) as i32;
+
+            let _ = npc_list.spawn(0x100, npc.clone());
+        }
+    }
+
+    /// Damages the tile if it's marked as destructible in tile properties, breaking it once it runs out of hits.
+    fn hit_destructible_tile(
+        state: &mut SharedGameState,
+        npc_list: &NPCList,
+        stage: &mut Stage,
+        tile_x: usize,
+        tile_y: usize,
+    ) {
+        let tile = stage.tile_at(tile_x, tile_y) as u16;
+
+        if let Some(TileProperty::Destructible { hp, replacement_tile }) =
+            state.tile_props.get(&stage.data.tileset.name, tile)
+        {
+            if stage.damage_tile(tile_x, tile_y, hp, replacement_tile) {
+                let tile_size = state.tile_size.as_int() * 0x200;
+
+                state.sound_manager.play_sfx(12);
+                Self::spawn_tile_debris(state, npc_list, tile_x as i32 * tile_size, tile_y as i32 * tile_size);
+            }
+        }
+    }
 }
 
 impl PhysicalEntity for Bullet {
@@ -1933,7 +2025,16 @@ impl PhysicalEntity for Bullet {
             match attrib {
                 // Blocks
                 0x41 | 0x44 | 0x61 | 0x64 => {
+                    let old_hit = self.flags;
+                    self.flags.0 = 0;
                     self.test_block_hit(state, x + ox, y + oy);
+
+                    if self.flags.weapon_hit_block() && !state.tile_props.is_empty() {
+                        let (tile_x, tile_y) = ((x + ox) as usize, (y + oy) as usize);
+                        Self::hit_destructible_tile(state, npc_list, stage, tile_x, tile_y);
+                    }
+
+                    self.flags.0 |= old_hit.0;
                 }
                 0x43 => {
                     let old_hit = self.flags;
@@ -1950,24 +2051,10 @@ impl PhysicalEntity for Bullet {
                         state.create_caret(self.x, self.y, CaretType::ProjectileDissipation, Direction::Left);
                         state.sound_manager.play_sfx(12);
 
-                        let mut npc = NPC::create(4, &state.npc_table);
-                        npc.cond.set_alive(true);
-                        npc.direction = Direction::Left;
-                        npc.x = (x + ox) * tile_size;
-                        npc.y = (y + oy) * tile_size;
-
-                        for _ in 0..4 {
-                            npc.vel_x = npc.rng.range(-0x200..0x200) as i32;
-                            npc.vel_y = npc.rng.range(-0x200..0x200) as i32;
+                        Self::spawn_tile_debris(state, npc_list, (x + ox) * tile_size, (y + oy) * tile_size);
 
-                            let _ = npc_list.spawn(0x100, npc.clone());
-                        }
-
-                        if let Some(tile) =
-                        stage.map.tiles.get_mut(stage.map.width as usize * (y + oy) as usize + (x + ox) as usize)
-                        {
-                            *tile = tile.wrapping_sub(1);
-                        }
+                        let (tile_x, tile_y) = ((x + ox) as usize, (y + oy) as usize);
+                        stage.change_tile(tile_x, tile_y, stage.tile_at(tile_x, tile_y).wrapping_sub(1));
                     }
 
                     self.flags.0 |= old_hit.0;
@@ -75,11 +75,25 @@ pub struct Weapon {
     empty_counter: u16,
     counter1: u16,
     counter2: u16,
+    pub heat: f32,
+    pub overheated: bool,
 }
 
 impl Weapon {
     pub fn new(wtype: WeaponType, level: WeaponLevel, experience: u16, ammo: u16, max_ammo: u16) -> Weapon {
-        Weapon { wtype, level, experience, ammo, max_ammo, refire_timer: 0, empty_counter: 0, counter1: 0, counter2: 0 }
+        Weapon {
+            wtype,
+            level,
+            experience,
+            ammo,
+            max_ammo,
+            refire_timer: 0,
+            empty_counter: 0,
+            counter1: 0,
+            counter2: 0,
+            heat: 0.0,
+            overheated: false,
+        }
     }
 
     /// Consume a specified amount of bullets, returns true if there was enough ammo.
@@ -157,6 +171,42 @@ impl Weapon {
         player.xp_counter = if self.wtype != WeaponType::Spur { 30 } else { 10 };
     }
 
+    /// Returns true if this weapon is a Spur that is currently being charged.
+    pub fn is_charging(&self) -> bool {
+        self.wtype == WeaponType::Spur && self.counter1 > 0
+    }
+
+    /// Returns the fraction of maximum heat, or None if this weapon type doesn't overheat.
+    pub fn get_heat(&self, constants: &EngineConstants) -> Option<f32> {
+        let heat = &constants.weapon.heat_table[self.wtype as usize];
+
+        if heat.heat_per_shot == 0.0 || heat.max_heat <= 0.0 {
+            return None;
+        }
+
+        Some((self.heat / heat.max_heat).clamp(0.0, 1.0))
+    }
+
+    fn tick_heat(&mut self, state: &SharedGameState, shot: bool) {
+        let heat = state.constants.weapon.heat_table[self.wtype as usize];
+
+        if heat.heat_per_shot == 0.0 {
+            return;
+        }
+
+        if shot {
+            self.heat += heat.heat_per_shot;
+        }
+
+        self.heat = (self.heat - heat.cool_rate).max(0.0);
+
+        if self.heat >= heat.max_heat {
+            self.overheated = true;
+        } else if self.overheated && self.heat <= heat.cool_threshold {
+            self.overheated = false;
+        }
+    }
+
     pub fn reset_xp(&mut self) {
         self.level = WeaponLevel::Level1;
         self.experience = 0;
@@ -185,6 +235,13 @@ impl Weapon {
 
         // todo lua hook
 
+        let first_new_bullet = bullet_manager.bullets.len();
+
+        if self.overheated {
+            self.tick_heat(state, false);
+            return;
+        }
+
         match self.wtype {
             WeaponType::None => {}
             WeaponType::Snake => self.tick_snake(player, player_id, bullet_manager, state),
@@ -200,5 +257,13 @@ impl Weapon {
             WeaponType::Nemesis => self.tick_nemesis(player, player_id, bullet_manager, state),
             WeaponType::Spur => self.tick_spur(player, player_id, bullet_manager, state),
         }
+
+        self.tick_heat(state, bullet_manager.bullets.len() > first_new_bullet);
+
+        if player.is_crouching {
+            for bullet in bullet_manager.bullets.iter_mut().skip(first_new_bullet) {
+                bullet.y += state.constants.player.crouch_barrel_offset_y;
+            }
+        }
     }
 }
@@ -74,6 +74,9 @@ pub trait SpriteBatch {
         rect: &common::Rect<u16>,
     );
 
+    /// Adds a sprite rotated by `angle` radians (clockwise) around `pivot`, relative to its top left corner.
+    fn add_rect_rotated(&mut self, x: f32, y: f32, angle: f32, pivot: (f32, f32), rect: &common::Rect<u16>);
+
     fn draw(&mut self, ctx: &mut Context) -> GameResult;
 
     fn draw_filtered(&mut self, _filter: FilterMode, _ctx: &mut Context) -> GameResult;
@@ -148,6 +151,8 @@ impl SpriteBatch for DummyBatch {
         _rect: &Rect<u16>,
     ) {}
 
+    fn add_rect_rotated(&mut self, _x: f32, _y: f32, _angle: f32, _pivot: (f32, f32), _rect: &Rect<u16>) {}
+
     fn draw(&mut self, _ctx: &mut Context) -> GameResult {
         Ok(())
     }
@@ -360,6 +365,31 @@ impl SpriteBatch for SubBatch {
         ));
     }
 
+    fn add_rect_rotated(&mut self, x: f32, y: f32, angle: f32, pivot: (f32, f32), rect: &common::Rect<u16>) {
+        if (rect.right.saturating_sub(rect.left)) == 0 || (rect.bottom.saturating_sub(rect.top)) == 0 {
+            return;
+        }
+
+        let mag = unsafe { I_MAG };
+
+        self.batch.add(SpriteBatchCommand::DrawRectRotated(
+            Rect {
+                left: rect.left as f32 / self.scale_x,
+                top: rect.top as f32 / self.scale_y,
+                right: rect.right as f32 / self.scale_x,
+                bottom: rect.bottom as f32 / self.scale_y,
+            },
+            Rect {
+                left: x * mag,
+                top: y * mag,
+                right: (x + rect.width() as f32) * mag,
+                bottom: (y + rect.height() as f32) * mag,
+            },
+            angle,
+            ((x + pivot.0) * mag, (y + pivot.1) * mag),
+        ));
+    }
+
     #[inline(always)]
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         self.draw_filtered(FilterMode::Nearest, ctx)
@@ -462,6 +492,10 @@ impl SpriteBatch for CombinedBatch {
         self.main_batch.add_rect_scaled_tinted(x, y, color, scale_x, scale_y, rect)
     }
 
+    fn add_rect_rotated(&mut self, x: f32, y: f32, angle: f32, pivot: (f32, f32), rect: &Rect<u16>) {
+        self.main_batch.add_rect_rotated(x, y, angle, pivot, rect)
+    }
+
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         self.main_batch.draw(ctx)
     }
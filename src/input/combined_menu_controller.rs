@@ -90,6 +90,26 @@ impl CombinedMenuController {
         false
     }
 
+    pub fn shift_left(&self) -> bool {
+        for cont in &self.controllers {
+            if cont.prev_weapon() {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn shift_right(&self) -> bool {
+        for cont in &self.controllers {
+            if cont.next_weapon() {
+                return true;
+            }
+        }
+
+        false
+    }
+
     pub fn trigger_shift_left(&self) -> bool {
         for cont in &self.controllers {
             if cont.trigger_prev_weapon() {
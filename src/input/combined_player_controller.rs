@@ -77,6 +77,14 @@ impl PlayerController for CombinedPlayerController {
         self.controllers.iter().any(|cont| cont.strafe())
     }
 
+    fn menu_pause(&self) -> bool {
+        self.controllers.iter().any(|cont| cont.menu_pause())
+    }
+
+    fn join(&self) -> bool {
+        self.controllers.iter().any(|cont| cont.join())
+    }
+
     fn trigger_up(&self) -> bool {
         self.controllers.iter().any(|cont| cont.trigger_up())
     }
@@ -137,6 +145,10 @@ impl PlayerController for CombinedPlayerController {
         self.controllers.iter().any(|cont| cont.trigger_menu_pause())
     }
 
+    fn trigger_join(&self) -> bool {
+        self.controllers.iter().any(|cont| cont.trigger_join())
+    }
+
     fn look_up(&self) -> bool {
         self.controllers.iter().any(|cont| cont.look_up())
     }
@@ -68,6 +68,14 @@ impl PlayerController for DummyPlayerController {
         false
     }
 
+    fn menu_pause(&self) -> bool {
+        false
+    }
+
+    fn join(&self) -> bool {
+        false
+    }
+
     fn trigger_up(&self) -> bool {
         false
     }
@@ -128,6 +136,10 @@ impl PlayerController for DummyPlayerController {
         false
     }
 
+    fn trigger_join(&self) -> bool {
+        false
+    }
+
     fn look_up(&self) -> bool {
         false
     }
@@ -2,6 +2,7 @@ use crate::bitfield;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::gamepad::{self, Button, PlayerControllerInputType};
+use crate::game::settings::ControllerType;
 use crate::game::shared_game_state::SharedGameState;
 use crate::input::player_controller::PlayerController;
 use crate::game::player::TargetPlayer;
@@ -26,6 +27,7 @@ bitfield! {
     pub strafe, set_strafe: 12;
     pub menu_ok, set_menu_ok: 13;
     pub menu_back, set_menu_back: 14;
+    pub join, set_join: 15;
 }
 
 #[derive(Clone)]
@@ -66,6 +68,44 @@ impl GamepadController {
 
 impl PlayerController for GamepadController {
     fn update(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let found_index = match state.settings.get_gamepad_id(self.target) {
+            Some(stable_id) if gamepad::get_stable_id(ctx, self.gamepad_id) != Some(stable_id) => {
+                // the gamepad moved to another index, don't take the one used by the other player
+                let other_type = match self.target {
+                    TargetPlayer::Player1 => state.settings.player2_controller_type,
+                    TargetPlayer::Player2 => state.settings.player1_controller_type,
+                };
+                let claimed_index = match other_type {
+                    ControllerType::Gamepad(index) => Some(index as usize),
+                    ControllerType::Keyboard => None,
+                };
+
+                Some(gamepad::find_gamepad_by_stable_id(ctx, stable_id, claimed_index))
+            }
+            _ => None,
+        };
+
+        match found_index {
+            Some(Some(index)) => {
+                self.gamepad_id = index as u32;
+
+                match self.target {
+                    TargetPlayer::Player1 => {
+                        state.settings.player1_controller_type = ControllerType::Gamepad(self.gamepad_id)
+                    }
+                    TargetPlayer::Player2 => {
+                        state.settings.player2_controller_type = ControllerType::Gamepad(self.gamepad_id)
+                    }
+                }
+            }
+            Some(None) => {
+                // disconnected, the keyboard controller this one is combined with keeps working
+                self.state = KeyState(0);
+                return Ok(());
+            }
+            None => {}
+        }
+
         let button_map = match self.target {
             TargetPlayer::Player1 => &state.settings.player1_controller_button_map,
             TargetPlayer::Player2 => &state.settings.player2_controller_button_map,
@@ -90,6 +130,7 @@ impl PlayerController for GamepadController {
         self.state.set_strafe(gamepad::is_active(ctx, self.gamepad_id, &button_map.strafe));
         self.state.set_menu_ok(gamepad::is_active(ctx, self.gamepad_id, &button_map.menu_ok));
         self.state.set_menu_back(gamepad::is_active(ctx, self.gamepad_id, &button_map.menu_back));
+        self.state.set_join(gamepad::is_active(ctx, self.gamepad_id, &button_map.join));
 
         if let Some(rumble_data) = &self.rumble_state {
             gamepad::set_rumble(
@@ -161,6 +202,14 @@ impl PlayerController for GamepadController {
         self.state.strafe()
     }
 
+    fn menu_pause(&self) -> bool {
+        self.state.escape()
+    }
+
+    fn join(&self) -> bool {
+        self.state.join()
+    }
+
     fn trigger_up(&self) -> bool {
         self.trigger.up()
     }
@@ -221,6 +270,10 @@ impl PlayerController for GamepadController {
         self.trigger.escape()
     }
 
+    fn trigger_join(&self) -> bool {
+        self.trigger.join()
+    }
+
     fn look_up(&self) -> bool {
         self.state.up()
     }
@@ -9,7 +9,7 @@ use crate::game::player::TargetPlayer;
 
 bitfield! {
   #[derive(Clone, Copy)]
-  pub struct KeyState(u16);
+  pub struct KeyState(u32);
   impl Debug;
 
   pub left, set_left: 0;
@@ -28,6 +28,7 @@ bitfield! {
   pub strafe, set_strafe: 13;
   pub menu_ok, set_menu_ok: 14;
   pub menu_back, set_menu_back: 15;
+  pub join, set_join: 16;
 }
 
 #[derive(Clone)]
@@ -67,6 +68,7 @@ impl PlayerController for KeyboardController {
         self.state.set_strafe(keyboard::is_key_pressed(ctx, keymap.strafe));
         self.state.set_menu_ok(keyboard::is_key_pressed(ctx, keymap.menu_ok));
         self.state.set_menu_back(keyboard::is_key_pressed(ctx, keymap.menu_back));
+        self.state.set_join(keyboard::is_key_pressed(ctx, keymap.join));
 
         Ok(())
     }
@@ -126,6 +128,14 @@ impl PlayerController for KeyboardController {
         self.state.strafe()
     }
 
+    fn menu_pause(&self) -> bool {
+        self.state.escape()
+    }
+
+    fn join(&self) -> bool {
+        self.state.join()
+    }
+
     fn trigger_up(&self) -> bool {
         self.trigger.up()
     }
@@ -186,6 +196,10 @@ impl PlayerController for KeyboardController {
         self.trigger.escape()
     }
 
+    fn trigger_join(&self) -> bool {
+        self.trigger.join()
+    }
+
     fn look_up(&self) -> bool {
         self.state.up()
     }
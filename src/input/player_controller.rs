@@ -43,6 +43,12 @@ pub trait PlayerController: PlayerControllerClone {
     /// True if "strafe" button is down.
     fn strafe(&self) -> bool;
 
+    /// True if "pause" button is down.
+    fn menu_pause(&self) -> bool;
+
+    /// True if "join" button is down.
+    fn join(&self) -> bool;
+
     fn trigger_up(&self) -> bool;
 
     fn trigger_left(&self) -> bool;
@@ -73,6 +79,8 @@ pub trait PlayerController: PlayerControllerClone {
 
     fn trigger_menu_pause(&self) -> bool;
 
+    fn trigger_join(&self) -> bool;
+
     /// Optional, useful for controllers with two analog sticks.
     /// Returns true if player looks towards upper direction.
     fn look_up(&self) -> bool;
@@ -107,6 +107,14 @@ impl PlayerController for ReplayController {
         self.state.strafe()
     }
 
+    fn menu_pause(&self) -> bool {
+        self.state.escape()
+    }
+
+    fn join(&self) -> bool {
+        false
+    }
+
     fn trigger_up(&self) -> bool {
         self.trigger.up()
     }
@@ -167,6 +175,10 @@ impl PlayerController for ReplayController {
         self.trigger.escape()
     }
 
+    fn trigger_join(&self) -> bool {
+        false
+    }
+
     fn look_up(&self) -> bool {
         self.state.up()
     }
@@ -1,9 +1,238 @@
+use serde::{Deserialize, Serialize};
+
 use crate::common::Rect;
 use crate::engine_constants::EngineConstants;
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::graphics::screen_insets_scaled;
+use crate::graphics::bmfont::BMFont;
+use crate::graphics::font::Font;
 use crate::graphics::texture_set::TextureSet;
+use crate::i18n::Locale;
+
+/// Size of a button at 1.0 size, the d-pad is 3 buttons wide.
+pub const TOUCH_BUTTON_SIZE: f32 = 48.0;
+/// Smallest and largest size a button can be resized to.
+pub const TOUCH_BUTTON_SIZE_RANGE: (f32, f32) = (0.5, 2.0);
+/// Area in the top left corner taken by the HUD, buttons are kept out of it.
+const HUD_AREA: (f32, f32) = (112.0, 56.0);
+const DEFAULT_OPACITY: f32 = 160.0 / 255.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TouchButton {
+    DPad,
+    Jump,
+    Shoot,
+    Inventory,
+    Map,
+    PrevWeapon,
+    NextWeapon,
+    Pause,
+}
+
+impl TouchButton {
+    pub const ALL: [TouchButton; 8] = [
+        TouchButton::DPad,
+        TouchButton::Jump,
+        TouchButton::Shoot,
+        TouchButton::Inventory,
+        TouchButton::Map,
+        TouchButton::PrevWeapon,
+        TouchButton::NextWeapon,
+        TouchButton::Pause,
+    ];
+
+    /// Returns the icon from `builtin/touch`, buttons without one are drawn with [TouchButton::label].
+    fn icon(self) -> Option<Rect<u16>> {
+        match self {
+            TouchButton::Jump => Some(Rect::new_size(3 * 32, 32, 32, 32)),
+            TouchButton::Shoot => Some(Rect::new_size(3 * 32, 0, 32, 32)),
+            TouchButton::Inventory => Some(Rect::new_size(0, 3 * 32, 32, 32)),
+            TouchButton::Pause => Some(Rect::new_size(32, 3 * 32, 32, 32)),
+            _ => None,
+        }
+    }
+
+    fn label(self, loc: &Locale) -> &str {
+        match self {
+            TouchButton::Map => loc.t("game.touch_controls.map"),
+            TouchButton::PrevWeapon => loc.t("game.touch_controls.prev_weapon"),
+            TouchButton::NextWeapon => loc.t("game.touch_controls.next_weapon"),
+            _ => "",
+        }
+    }
+}
+
+/// Screen corner a button is positioned relative to, so layouts carry over between screen sizes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TouchAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl TouchAnchor {
+    fn is_left(self) -> bool {
+        matches!(self, TouchAnchor::TopLeft | TouchAnchor::BottomLeft)
+    }
+
+    fn is_top(self) -> bool {
+        matches!(self, TouchAnchor::TopLeft | TouchAnchor::TopRight)
+    }
+}
+
+/// Placement of an on-screen button. `x` and `y` are the distances between the anchor corner and the closest edges
+/// of the button, in game pixels so they scale along with everything else.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TouchButtonLayout {
+    pub anchor: TouchAnchor,
+    pub x: f32,
+    pub y: f32,
+    /// Multiplier of [TOUCH_BUTTON_SIZE].
+    pub size: f32,
+    /// 0.0 - 1.0
+    pub opacity: f32,
+}
+
+impl TouchButtonLayout {
+    const fn new(anchor: TouchAnchor, x: f32, y: f32, size: f32) -> TouchButtonLayout {
+        TouchButtonLayout { anchor, x, y, size, opacity: DEFAULT_OPACITY }
+    }
+}
+
+/// Positions of the on-screen controls, changed in the touch layout editor.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TouchLayout {
+    pub dpad: TouchButtonLayout,
+    pub jump: TouchButtonLayout,
+    pub shoot: TouchButtonLayout,
+    pub inventory: TouchButtonLayout,
+    pub map: TouchButtonLayout,
+    pub prev_weapon: TouchButtonLayout,
+    pub next_weapon: TouchButtonLayout,
+    pub pause: TouchButtonLayout,
+}
+
+impl Default for TouchLayout {
+    fn default() -> TouchLayout {
+        TouchLayout {
+            dpad: TouchButtonLayout::new(TouchAnchor::BottomLeft, 4.0, 4.0, 1.0),
+            jump: TouchButtonLayout::new(TouchAnchor::BottomRight, 4.0, 4.0, 1.0),
+            shoot: TouchButtonLayout::new(TouchAnchor::BottomRight, 4.0, 56.0, 1.0),
+            inventory: TouchButtonLayout::new(TouchAnchor::TopRight, 4.0, 4.0, 1.0),
+            map: TouchButtonLayout::new(TouchAnchor::TopRight, 56.0, 4.0, 1.0),
+            prev_weapon: TouchButtonLayout::new(TouchAnchor::BottomRight, 96.0, 56.0, 0.75),
+            next_weapon: TouchButtonLayout::new(TouchAnchor::BottomRight, 56.0, 56.0, 0.75),
+            pause: TouchButtonLayout::new(TouchAnchor::TopRight, 108.0, 4.0, 1.0),
+        }
+    }
+}
+
+impl TouchLayout {
+    pub fn get(&self, button: TouchButton) -> &TouchButtonLayout {
+        match button {
+            TouchButton::DPad => &self.dpad,
+            TouchButton::Jump => &self.jump,
+            TouchButton::Shoot => &self.shoot,
+            TouchButton::Inventory => &self.inventory,
+            TouchButton::Map => &self.map,
+            TouchButton::PrevWeapon => &self.prev_weapon,
+            TouchButton::NextWeapon => &self.next_weapon,
+            TouchButton::Pause => &self.pause,
+        }
+    }
+
+    pub fn get_mut(&mut self, button: TouchButton) -> &mut TouchButtonLayout {
+        match button {
+            TouchButton::DPad => &mut self.dpad,
+            TouchButton::Jump => &mut self.jump,
+            TouchButton::Shoot => &mut self.shoot,
+            TouchButton::Inventory => &mut self.inventory,
+            TouchButton::Map => &mut self.map,
+            TouchButton::PrevWeapon => &mut self.prev_weapon,
+            TouchButton::NextWeapon => &mut self.next_weapon,
+            TouchButton::Pause => &mut self.pause,
+        }
+    }
+
+    fn size_px(&self, button: TouchButton) -> f32 {
+        let size = TOUCH_BUTTON_SIZE * self.get(button).size;
+
+        if button == TouchButton::DPad {
+            size * 3.0
+        } else {
+            size
+        }
+    }
+
+    /// Returns the on-screen bounds of given button, `insets` are the scaled screen insets.
+    pub fn bounds(&self, button: TouchButton, canvas_size: (f32, f32), insets: (f32, f32, f32, f32)) -> Rect {
+        let layout = self.get(button);
+        let (left, top, right, bottom) = insets;
+        let size = self.size_px(button);
+
+        let x = if layout.anchor.is_left() { left + layout.x } else { canvas_size.0 - right - layout.x - size };
+        let y = if layout.anchor.is_top() { top + layout.y } else { canvas_size.1 - bottom - layout.y - size };
+
+        // keeps buttons placed on a bigger screen reachable
+        let x = x.min(canvas_size.0 - right - size).max(left);
+        let y = y.min(canvas_size.1 - bottom - size).max(top);
+
+        Rect::new_size(x as isize, y as isize, size as isize, size as isize)
+    }
+
+    /// Moves given button's top left corner to given position, anchoring it to the closest screen corner.
+    pub fn move_button(
+        &mut self,
+        button: TouchButton,
+        x: f32,
+        y: f32,
+        canvas_size: (f32, f32),
+        insets: (f32, f32, f32, f32),
+    ) {
+        let (left, top, right, bottom) = insets;
+        let size = self.size_px(button);
+
+        let x = x.min(canvas_size.0 - right - size).max(left);
+        let mut y = y.min(canvas_size.1 - bottom - size).max(top);
+
+        if x < left + HUD_AREA.0 && y < top + HUD_AREA.1 {
+            y = top + HUD_AREA.1;
+        }
+
+        let anchor = match (x + size / 2.0 < canvas_size.0 / 2.0, y + size / 2.0 < canvas_size.1 / 2.0) {
+            (true, true) => TouchAnchor::TopLeft,
+            (false, true) => TouchAnchor::TopRight,
+            (true, false) => TouchAnchor::BottomLeft,
+            (false, false) => TouchAnchor::BottomRight,
+        };
+
+        let layout = self.get_mut(button);
+        layout.anchor = anchor;
+        layout.x = if anchor.is_left() { x - left } else { canvas_size.0 - right - size - x };
+        layout.y = if anchor.is_top() { y - top } else { canvas_size.1 - bottom - size - y };
+    }
+
+    /// Changes the size of given button, keeping its center in place.
+    pub fn resize_button(
+        &mut self,
+        button: TouchButton,
+        size: f32,
+        canvas_size: (f32, f32),
+        insets: (f32, f32, f32, f32),
+    ) {
+        let bounds = self.bounds(button, canvas_size, insets);
+        let (center_x, center_y) =
+            ((bounds.left + bounds.right) as f32 / 2.0, (bounds.top + bounds.bottom) as f32 / 2.0);
+
+        self.get_mut(button).size = size.clamp(TOUCH_BUTTON_SIZE_RANGE.0, TOUCH_BUTTON_SIZE_RANGE.1);
+
+        let new_size = self.size_px(button);
+        self.move_button(button, center_x - new_size / 2.0, center_y - new_size / 2.0, canvas_size, insets);
+    }
+}
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum TouchControlType {
@@ -26,6 +255,8 @@ pub struct TouchControls {
     pub interact_icon: bool,
     pub touch_id_counter: u64,
     pub clicks: Vec<TouchPoint>,
+    /// Set while the layout editor is open, it draws the buttons itself.
+    pub editing: bool,
 }
 
 impl TouchControls {
@@ -36,6 +267,7 @@ impl TouchControls {
             interact_icon: false,
             touch_id_counter: 0,
             clicks: Vec::with_capacity(8),
+            editing: false,
         }
     }
 
@@ -53,6 +285,13 @@ impl TouchControls {
         None
     }
 
+    /// Returns position of the first touch inside given bounds.
+    pub fn point_pos_in(&self, bounds: Rect) -> Option<(f64, f64)> {
+        let touch_id = self.point_in(bounds)?;
+
+        self.points.iter().find(|p| p.touch_id == touch_id).map(|p| p.position)
+    }
+
     pub fn consume_click_in(&mut self, bounds: Rect) -> bool {
         self.clicks.retain(|p| p.touch_id != 0);
 
@@ -71,84 +310,139 @@ impl TouchControls {
         false
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn draw(
         &self,
         canvas_size: (f32, f32),
         scale: f32,
+        layout: &TouchLayout,
         constants: &EngineConstants,
         texture_set: &mut TextureSet,
+        font: &BMFont,
+        loc: &Locale,
         ctx: &mut Context,
     ) -> GameResult {
-        let color = (255, 255, 255, 160);
+        if self.editing {
+            return Ok(());
+        }
 
-        let (left, top, right, bottom) = screen_insets_scaled(ctx, scale);
+        let insets = screen_insets_scaled(ctx, scale);
 
         match self.control_type {
             TouchControlType::None => {}
             TouchControlType::Dialog => {
-                let batch = texture_set.get_or_load_batch(ctx, constants, "builtin/touch")?;
                 // Fast-Forward
-                batch.add_rect_tinted(
-                    canvas_size.0 - (4.0 + 48.0) + 8.0 - right,
-                    4.0 + 8.0 + top,
-                    color,
+                let batch = texture_set.get_or_load_batch(ctx, constants, "builtin/touch")?;
+                let bounds = layout.bounds(TouchButton::Inventory, canvas_size, insets);
+                let icon_scale = bounds.width() as f32 / TOUCH_BUTTON_SIZE;
+
+                batch.add_rect_scaled_tinted(
+                    bounds.left as f32 + 8.0 * icon_scale,
+                    bounds.top as f32 + 8.0 * icon_scale,
+                    button_color(&layout.inventory),
+                    icon_scale,
+                    icon_scale,
                     &Rect::new_size(2 * 32, 3 * 32, 32, 32),
                 );
 
                 batch.draw(ctx)?;
             }
             TouchControlType::Controls => {
-                let batch = texture_set.get_or_load_batch(ctx, constants, "builtin/touch")?;
-                // Movement
-                for x in 0..3 {
-                    for y in 0..3 {
-                        let mut icon_x = x;
-                        let icon_y = y;
-
-                        if self.interact_icon && x == 1 && y == 2 {
-                            icon_x = 3;
-                        }
-
-                        batch.add_rect_tinted(
-                            4.0 + 48.0 * x as f32 + 8.0 + left,
-                            (canvas_size.1 - 4.0 - 48.0 * 3.0) + 48.0 * y as f32 + 8.0 - bottom,
-                            color,
-                            &Rect::new_size(icon_x * 32, icon_y * 32, 32, 32),
-                        );
-                    }
+                for button in TouchButton::ALL {
+                    self.draw_button(button, canvas_size, layout, insets, constants, texture_set, font, loc, ctx)?;
                 }
+            }
+        }
 
-                // Jump
-                batch.add_rect_tinted(
-                    canvas_size.0 - (4.0 + 48.0) + 8.0 - right,
-                    canvas_size.1 - (4.0 + 48.0) + 8.0 - bottom,
-                    color,
-                    &Rect::new_size(3 * 32, 32, 32, 32),
-                );
+        Ok(())
+    }
 
-                // Shoot
-                batch.add_rect_tinted(
-                    canvas_size.0 - (4.0 + 48.0) + 8.0 - right,
-                    canvas_size.1 - (4.0 + 48.0) * 2.0 + 8.0 - bottom,
-                    color,
-                    &Rect::new_size(3 * 32, 0, 32, 32),
-                );
+    /// Draws a single button at its place in the layout.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_button(
+        &self,
+        button: TouchButton,
+        canvas_size: (f32, f32),
+        layout: &TouchLayout,
+        insets: (f32, f32, f32, f32),
+        constants: &EngineConstants,
+        texture_set: &mut TextureSet,
+        font: &BMFont,
+        loc: &Locale,
+        ctx: &mut Context,
+    ) -> GameResult {
+        let bounds = layout.bounds(button, canvas_size, insets);
+        let color = button_color(layout.get(button));
+        let (x, y) = (bounds.left as f32, bounds.top as f32);
 
-                // Inventory
-                batch.add_rect_tinted(
-                    canvas_size.0 - (4.0 + 48.0) + 8.0 - right,
-                    4.0 + 8.0 + top,
-                    color,
-                    &Rect::new_size(0, 3 * 32, 32, 32),
-                );
+        if button == TouchButton::DPad {
+            let batch = texture_set.get_or_load_batch(ctx, constants, "builtin/touch")?;
+            let cell = bounds.width() as f32 / 3.0;
+            let icon_scale = cell / TOUCH_BUTTON_SIZE;
 
-                // Pause
-                batch.add_rect_tinted(4.0, 4.0, color, &Rect::new_size(32, 3 * 32, 32, 32));
+            for cx in 0..3 {
+                for cy in 0..3 {
+                    let icon_x = if self.interact_icon && cx == 1 && cy == 2 { 3 } else { cx };
 
-                batch.draw(ctx)?;
+                    batch.add_rect_scaled_tinted(
+                        x + cell * cx as f32 + 8.0 * icon_scale,
+                        y + cell * cy as f32 + 8.0 * icon_scale,
+                        color,
+                        icon_scale,
+                        icon_scale,
+                        &Rect::new_size(icon_x * 32, cy * 32, 32, 32),
+                    );
+                }
             }
+
+            return batch.draw(ctx);
         }
 
-        Ok(())
+        let icon_scale = bounds.width() as f32 / TOUCH_BUTTON_SIZE;
+
+        if let Some(icon) = button.icon() {
+            let batch = texture_set.get_or_load_batch(ctx, constants, "builtin/touch")?;
+            batch.add_rect_scaled_tinted(
+                x + 8.0 * icon_scale,
+                y + 8.0 * icon_scale,
+                color,
+                icon_scale,
+                icon_scale,
+                &icon,
+            );
+
+            return batch.draw(ctx);
+        }
+
+        let label = button.label(loc);
+        let builder = font.builder().color(color).scale(icon_scale);
+        let text_x = x + (bounds.width() as f32 - builder.compute_width(label) * icon_scale) / 2.0;
+        let text_y = y + (bounds.height() as f32 - font.line_height() * icon_scale) / 2.0;
+
+        builder.position(text_x, text_y).draw(label, ctx, constants, texture_set)
     }
 }
+
+fn button_color(layout: &TouchButtonLayout) -> (u8, u8, u8, u8) {
+    (255, 255, 255, (layout.opacity.clamp(0.0, 1.0) * 255.0) as u8)
+}
+
+#[test]
+fn test_touch_layout_move() {
+    let canvas_size = (400.0, 240.0);
+    let insets = (0.0, 0.0, 0.0, 0.0);
+    let mut layout = TouchLayout::default();
+
+    assert_eq!(layout.bounds(TouchButton::Jump, canvas_size, insets).left, 400 - 4 - 48);
+
+    layout.move_button(TouchButton::Jump, 10.0, 60.0, canvas_size, insets);
+    assert_eq!(layout.jump.anchor, TouchAnchor::TopLeft);
+    assert_eq!(layout.bounds(TouchButton::Jump, canvas_size, insets).top, 60);
+
+    // kept out of the HUD
+    layout.move_button(TouchButton::Jump, 10.0, 0.0, canvas_size, insets);
+    assert_eq!(layout.bounds(TouchButton::Jump, canvas_size, insets).top, 56);
+
+    layout.resize_button(TouchButton::Jump, 10.0, canvas_size, insets);
+    assert_eq!(layout.jump.size, TOUCH_BUTTON_SIZE_RANGE.1);
+}
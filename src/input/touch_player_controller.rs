@@ -5,7 +5,7 @@ use crate::framework::error::GameResult;
 use crate::framework::graphics::screen_insets_scaled;
 use crate::game::shared_game_state::SharedGameState;
 use crate::input::player_controller::PlayerController;
-use crate::input::touch_controls::TouchControlType;
+use crate::input::touch_controls::{TouchButton, TouchControlType};
 
 /// A no-op implementation of player controller.
 #[derive(Clone)]
@@ -26,12 +26,12 @@ bitfield! {
     pub right, set_right: 1;
     pub up, set_up: 2;
     pub down, set_down: 3;
-    pub map, _: 4;
+    pub map, set_map: 4;
     pub inventory, set_inventory: 5;
     pub jump, set_jump: 6;
     pub shoot, set_shoot: 7;
-    pub next_weapon, _: 8;
-    pub prev_weapon, _: 9;
+    pub next_weapon, set_next_weapon: 8;
+    pub prev_weapon, set_prev_weapon: 9;
     pub pause, set_pause: 10;
 }
 
@@ -43,6 +43,15 @@ impl TouchPlayerController {
 
 impl PlayerController for TouchPlayerController {
     fn update(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let insets = screen_insets_scaled(ctx, state.scale);
+        let (layout, canvas_size) = (state.settings.touch_layout, state.canvas_size);
+        let bounds = |button: TouchButton| layout.bounds(button, canvas_size, insets);
+
+        if state.touch_controls.editing {
+            self.state.0 = 0;
+            return Ok(());
+        }
+
         match state.touch_controls.control_type {
             TouchControlType::None => {}
             TouchControlType::Dialog => {
@@ -63,180 +72,34 @@ impl PlayerController for TouchPlayerController {
                     self.old_state.set_jump(false);
                 }
 
-                let (_, top, right, _) = screen_insets_scaled(ctx, state.scale);
-
-                let top = 4 + top as isize;
-                let right = 4 + right as isize;
-
-                self.state.set_inventory(
-                    state
-                        .touch_controls
-                        .point_in(Rect::new_size(state.canvas_size.0 as isize - 48 - right, top, 48, 48))
-                        .is_some(),
-                );
+                self.state.set_inventory(state.touch_controls.point_in(bounds(TouchButton::Inventory)).is_some());
             }
             TouchControlType::Controls => {
-                let (left, _top, right, bottom) = screen_insets_scaled(ctx, state.scale);
-
-                let left = 4 + left as isize;
-                let top = 4 + bottom as isize;
-                let bottom = 4 + bottom as isize;
-                let right = 4 + right as isize;
-
                 self.state.0 = 0;
-                // left
-                self.state.set_left(
-                    self.state.left()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(left, state.canvas_size.1 as isize - bottom - 48 * 2, 48, 48))
-                        .is_some(),
-                );
-
-                // up
-                self.state.set_up(
-                    self.state.up()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(48 + left, state.canvas_size.1 as isize - bottom - 48 * 3, 48, 48))
-                        .is_some(),
-                );
-
-                // right
-                self.state.set_right(
-                    self.state.right()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(
-                            48 * 2 + left,
-                            state.canvas_size.1 as isize - bottom - 48 * 2,
-                            48,
-                            48,
-                        ))
-                        .is_some(),
-                );
 
-                // down
-                self.state.set_down(
-                    self.state.down()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(48 + left, state.canvas_size.1 as isize - bottom - 48, 48, 48))
-                        .is_some(),
-                );
-
-                // left+up
-                self.state.set_left(
-                    self.state.left()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(left, state.canvas_size.1 as isize - bottom - 48 * 3, 48, 48))
-                        .is_some(),
-                );
-                self.state.set_up(
-                    self.state.up()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(left, state.canvas_size.1 as isize - bottom - 48 * 3, 48, 48))
-                        .is_some(),
-                );
-
-                // right+up
-                self.state.set_right(
-                    self.state.right()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(
-                            48 * 2 + left,
-                            state.canvas_size.1 as isize - bottom - 48 * 3,
-                            48,
-                            48,
-                        ))
-                        .is_some(),
-                );
-                self.state.set_up(
-                    self.state.up()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(
-                            48 * 2 + left,
-                            state.canvas_size.1 as isize - bottom - 48 * 3,
-                            48,
-                            48,
-                        ))
-                        .is_some(),
-                );
-
-                // left+down
-                self.state.set_left(
-                    self.state.left()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(left, state.canvas_size.1 as isize - 48 - bottom, 48, 48))
-                        .is_some(),
-                );
-                self.state.set_down(
-                    self.state.down()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(left, state.canvas_size.1 as isize - 48 - bottom, 48, 48))
-                        .is_some(),
-                );
-
-                // right+down
-                self.state.set_right(
-                    self.state.right()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(48 * 2 + left, state.canvas_size.1 as isize - 48 - bottom, 48, 48))
-                        .is_some(),
-                );
-                self.state.set_down(
-                    self.state.down()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(48 * 2 + left, state.canvas_size.1 as isize - 48 - bottom, 48, 48))
-                        .is_some(),
-                );
-
-                self.state.set_inventory(
-                    self.state.inventory()
-                        || state.touch_controls.consume_click_in(Rect::new_size(
-                        state.canvas_size.0 as isize - 48 - right,
-                        top,
-                        48,
-                        48,
-                    )),
-                );
-
-                self.state.set_jump(
-                    self.state.jump()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(
-                            state.canvas_size.0 as isize - 48 - right,
-                            state.canvas_size.1 as isize - (48 + 4) - bottom,
-                            48,
-                            48,
-                        ))
-                        .is_some(),
-                );
-                self.state.set_shoot(
-                    self.state.shoot()
-                        || state
-                        .touch_controls
-                        .point_in(Rect::new_size(
-                            state.canvas_size.0 as isize - 48 - right,
-                            state.canvas_size.1 as isize - (48 + 4) * 2 - bottom,
-                            48,
-                            48,
-                        ))
-                        .is_some(),
-                );
+                // every finger on the d-pad counts, so diagonals can be held with two
+                let dpad = bounds(TouchButton::DPad);
+                let cell = (dpad.width() / 3).max(1);
+                for point in &state.touch_controls.points {
+                    let (x, y) = (point.position.0 as isize, point.position.1 as isize);
+                    if x <= dpad.left || x >= dpad.right || y <= dpad.top || y >= dpad.bottom {
+                        continue;
+                    }
+
+                    let (cell_x, cell_y) = ((x - dpad.left) / cell, (y - dpad.top) / cell);
+                    self.state.set_left(self.state.left() || cell_x == 0);
+                    self.state.set_right(self.state.right() || cell_x >= 2);
+                    self.state.set_up(self.state.up() || cell_y == 0);
+                    self.state.set_down(self.state.down() || cell_y >= 2);
+                }
 
-                self.state.set_pause(
-                    self.state.pause() || state.touch_controls.point_in(Rect::new_size(0, 0, 40, 40)).is_some(),
-                );
+                self.state.set_jump(state.touch_controls.point_in(bounds(TouchButton::Jump)).is_some());
+                self.state.set_shoot(state.touch_controls.point_in(bounds(TouchButton::Shoot)).is_some());
+                self.state.set_pause(state.touch_controls.point_in(bounds(TouchButton::Pause)).is_some());
+                self.state.set_inventory(state.touch_controls.consume_click_in(bounds(TouchButton::Inventory)));
+                self.state.set_map(state.touch_controls.consume_click_in(bounds(TouchButton::Map)));
+                self.state.set_prev_weapon(state.touch_controls.consume_click_in(bounds(TouchButton::PrevWeapon)));
+                self.state.set_next_weapon(state.touch_controls.consume_click_in(bounds(TouchButton::NextWeapon)));
             }
         }
 
@@ -299,6 +162,14 @@ impl PlayerController for TouchPlayerController {
         false
     }
 
+    fn menu_pause(&self) -> bool {
+        self.state.pause()
+    }
+
+    fn join(&self) -> bool {
+        false
+    }
+
     fn trigger_up(&self) -> bool {
         self.trigger.up()
     }
@@ -360,6 +231,10 @@ impl PlayerController for TouchPlayerController {
         self.trigger.pause()
     }
 
+    fn trigger_join(&self) -> bool {
+        false
+    }
+
     fn look_up(&self) -> bool {
         self.state.up()
     }
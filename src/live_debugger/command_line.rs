@@ -1,5 +1,8 @@
+use std::collections::VecDeque;
+
 use num_traits::FromPrimitive;
 
+use crate::framework::context::Context;
 use crate::framework::error::{GameError::CommandLineError, GameResult};
 use crate::game::npc::NPC;
 use crate::game::scripting::tsc::text_script::{ScriptMode, TextScript, TextScriptEncoding};
@@ -22,6 +25,10 @@ pub enum CommandLineCommand {
     SetMaxHP(u16),
     SpawnNPC(u16),
     TeleportPlayer(f32, f32),
+    SetFlag(u16, bool),
+    SetLife(u16),
+    SetSpeed(f64),
+    ReloadScripts,
     TSC(String),
 }
 
@@ -155,6 +162,45 @@ impl CommandLineCommand {
                     return Some(CommandLineCommand::TeleportPlayer(x, y));
                 }
             }
+            "set_flag" => {
+                if components.len() < 3 {
+                    return None;
+                }
+
+                let flag_id = components[1].parse::<u16>();
+                let value = match components[2] {
+                    "1" | "true" | "on" => Some(true),
+                    "0" | "false" | "off" => Some(false),
+                    _ => None,
+                };
+
+                if let (Ok(flag_id), Some(value)) = (flag_id, value) {
+                    return Some(CommandLineCommand::SetFlag(flag_id, value));
+                }
+            }
+            "set_life" => {
+                if components.len() < 2 {
+                    return None;
+                }
+
+                let life = components[1].parse::<u16>();
+                if let Ok(life) = life {
+                    return Some(CommandLineCommand::SetLife(life));
+                }
+            }
+            "set_speed" => {
+                if components.len() < 2 {
+                    return None;
+                }
+
+                let speed = components[1].parse::<f64>();
+                if let Ok(speed) = speed {
+                    return Some(CommandLineCommand::SetSpeed(speed));
+                }
+            }
+            "reload_scripts" => {
+                return Some(CommandLineCommand::ReloadScripts);
+            }
             "tsc" => {
                 if components.len() < 2 {
                     return None;
@@ -169,7 +215,12 @@ impl CommandLineCommand {
         None
     }
 
-    pub fn execute(&mut self, game_scene: &mut GameScene, state: &mut SharedGameState) -> GameResult {
+    pub fn execute(
+        &mut self,
+        game_scene: &mut GameScene,
+        state: &mut SharedGameState,
+        ctx: &mut Context,
+    ) -> GameResult {
         match self.clone() {
             CommandLineCommand::AddItem(item_id) => {
                 game_scene.inventory_player1.add_item(item_id);
@@ -247,6 +298,47 @@ impl CommandLineCommand {
                 game_scene.player2.x = game_scene.player1.x;
                 game_scene.player2.y = game_scene.player1.y;
             }
+            CommandLineCommand::SetFlag(flag_id, value) => {
+                state.set_flag(flag_id as usize, value);
+            }
+            CommandLineCommand::SetLife(life) => {
+                if life > game_scene.player1.max_life {
+                    return Err(CommandLineError(format!(
+                        "Life can't exceed max HP of {}",
+                        game_scene.player1.max_life
+                    )));
+                }
+
+                game_scene.player1.life = life;
+            }
+            CommandLineCommand::SetSpeed(speed) => {
+                if !(0.1..=3.0).contains(&speed) {
+                    return Err(CommandLineError("Speed must be between 0.1 and 3.0".to_string()));
+                }
+
+                state.set_speed(speed);
+            }
+            CommandLineCommand::ReloadScripts => {
+                let (scene_script, diagnostics) = {
+                    let scripts = state.textscript_vm.scripts.borrow();
+                    game_scene.stage.load_text_script(
+                        &state.constants.base_paths,
+                        &state.constants,
+                        &scripts.global_script,
+                        ctx,
+                    )?
+                };
+
+                state.textscript_vm.set_scene_script(scene_script);
+
+                if !diagnostics.is_empty() {
+                    return Err(CommandLineError(format!(
+                        "Reloaded with {} problem(s), first: {}",
+                        diagnostics.len(),
+                        diagnostics[0]
+                    )));
+                }
+            }
             CommandLineCommand::TSC(script) => {
                 log::info!("Executing TSC script: {}", format!("#9999\n{}", script));
                 match TextScript::compile(format!("#9999\n{}", script).as_bytes(), true, TextScriptEncoding::UTF8) {
@@ -281,6 +373,10 @@ impl CommandLineCommand {
             CommandLineCommand::SetMaxHP(hp_count) => format!("/set_max_hp {}", hp_count),
             CommandLineCommand::SpawnNPC(npc_id) => format!("/spawn_npc {}", npc_id),
             CommandLineCommand::TeleportPlayer(x, y) => format!("/teleport_player {} {}", x, y),
+            CommandLineCommand::SetFlag(flag_id, value) => format!("/set_flag {} {}", flag_id, *value as u8),
+            CommandLineCommand::SetLife(life) => format!("/set_life {}", life),
+            CommandLineCommand::SetSpeed(speed) => format!("/set_speed {}", speed),
+            CommandLineCommand::ReloadScripts => "/reload_scripts".to_string(),
             CommandLineCommand::TSC(script) => format!("/tsc {}", script.replace("\n", "\\n")),
         }
     }
@@ -304,14 +400,23 @@ impl CommandLineCommand {
             CommandLineCommand::SetMaxHP(hp_count) => format!("Set max HP of player to {}.", hp_count),
             CommandLineCommand::SpawnNPC(npc_id) => format!("Spawned NPC ID {} in front of player.", npc_id),
             CommandLineCommand::TeleportPlayer(x, y) => format!("Teleported players to ({}, {}).", x, y),
+            CommandLineCommand::SetFlag(flag_id, value) => format!("Set flag {} to {}.", flag_id, value),
+            CommandLineCommand::SetLife(life) => format!("Set HP of player to {}.", life),
+            CommandLineCommand::SetSpeed(speed) => format!("Set game speed to {:.1}x.", speed),
+            CommandLineCommand::ReloadScripts => "Reloaded stage scripts.".to_string(),
             CommandLineCommand::TSC(_) => "Executed TSC script.".to_string(),
         }
     }
 }
 
+/// Amount of executed commands and their results kept in the output log.
+const OUTPUT_HISTORY_SIZE: usize = 50;
+
 pub struct CommandLineParser {
     command_history: Vec<CommandLineCommand>,
     cursor: usize,
+    /// Entered commands along with their feedback and whether they succeeded, oldest first.
+    pub output_history: VecDeque<(String, String, bool)>,
     pub last_feedback: String,
     pub last_feedback_color: [f32; 4],
     pub buffer: String,
@@ -324,6 +429,7 @@ impl CommandLineParser {
             last_feedback: "Awaiting command.".to_string(),
             last_feedback_color: [1.0, 1.0, 1.0, 1.0],
             cursor: 0,
+            output_history: VecDeque::with_capacity(OUTPUT_HISTORY_SIZE),
             buffer: String::new(),
         }
     }
@@ -343,6 +449,16 @@ impl CommandLineParser {
         }
     }
 
+    pub fn log_output(&mut self, command: String, feedback: String, success: bool) {
+        while self.output_history.len() >= OUTPUT_HISTORY_SIZE {
+            self.output_history.pop_front();
+        }
+
+        self.last_feedback = feedback.clone();
+        self.last_feedback_color = if success { [0.0, 1.0, 0.0, 1.0] } else { [1.0, 0.0, 0.0, 1.0] };
+        self.output_history.push_back((command, feedback, success));
+    }
+
     #[allow(dead_code)]
     pub fn traverse(&mut self, delta: i16) -> Option<&CommandLineCommand> {
         if self.command_history.is_empty() {
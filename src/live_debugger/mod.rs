@@ -1,4 +1,4 @@
-use imgui::{CollapsingHeader, Condition, ImStr, ImString, Slider, Window};
+use imgui::{ChildWindow, CollapsingHeader, Condition, ImStr, ImString, Slider, Window};
 use itertools::Itertools;
 
 use crate::framework::context::Context;
@@ -74,7 +74,7 @@ impl LiveDebugger {
 
         if state.command_line {
             let width = state.screen_size.0;
-            let height = 85.0;
+            let height = 180.0;
             let x = 0.0 as f32;
             let y = state.screen_size.1 - height;
 
@@ -85,6 +85,19 @@ impl LiveDebugger {
                 .collapsible(false)
                 .movable(false)
                 .build(ui, || {
+                    ChildWindow::new("command_line_history").size([0.0, -50.0]).border(true).build(ui, || {
+                        for (command, feedback, success) in &self.command_line_parser.output_history {
+                            let color = if *success { [0.0, 1.0, 0.0, 1.0] } else { [1.0, 0.0, 0.0, 1.0] };
+
+                            ui.text(format!("> {}", command));
+                            ui.text_colored(color, feedback);
+                        }
+
+                        if ui.scroll_y() >= ui.scroll_max_y() {
+                            ui.set_scroll_here_y_with_ratio(1.0);
+                        }
+                    });
+
                     self.draw_left_label(ui, "Command:");
 
                     let iw = ui.push_item_width(state.screen_size.0 - 150.0);
@@ -106,26 +119,20 @@ impl LiveDebugger {
 
                     ui.same_line();
                     if ui.is_key_released(imgui::Key::Enter) || ui.button("Execute") {
-                        log::info!("Executing command: {}", self.command_line_parser.buffer);
-                        match self.command_line_parser.push(self.command_line_parser.buffer.clone()) {
-                            Some(mut command) => match command.execute(game_scene, state) {
-                                Ok(()) => {
-                                    self.command_line_parser.last_feedback = command.feedback_string();
-                                    self.command_line_parser.last_feedback_color = [0.0, 1.0, 0.0, 1.0];
-                                    state.sound_manager.play_sfx(5);
-                                }
-                                Err(e) => {
-                                    self.command_line_parser.last_feedback = e.to_string();
-                                    self.command_line_parser.last_feedback_color = [1.0, 0.0, 0.0, 1.0];
-                                    state.sound_manager.play_sfx(12);
-                                }
+                        let input = self.command_line_parser.buffer.clone();
+                        log::info!("Executing command: {}", input);
+
+                        let (feedback, success) = match self.command_line_parser.push(input.clone()) {
+                            Some(mut command) => match command.execute(game_scene, state, ctx) {
+                                Ok(()) => (command.feedback_string(), true),
+                                Err(e) => (e.to_string(), false),
                             },
-                            None => {
-                                self.command_line_parser.last_feedback = "Invalid command".to_string();
-                                self.command_line_parser.last_feedback_color = [1.0, 0.0, 0.0, 1.0];
-                                state.sound_manager.play_sfx(12);
-                            }
-                        }
+                            None => ("Invalid command".to_string(), false),
+                        };
+
+                        state.sound_manager.play_sfx(if success { 5 } else { 12 });
+                        self.command_line_parser.log_output(input, feedback, success);
+                        self.command_line_parser.buffer.clear();
                     }
                     ui.text_colored(
                         self.command_line_parser.last_feedback_color,
@@ -211,7 +218,7 @@ impl LiveDebugger {
 
                 if game_scene.player2.cond.alive() {
                     if ui.button("Drop Player 2") {
-                        game_scene.drop_player2();
+                        game_scene.drop_player2(state);
                     }
                 } else if ui.button("Add Player 2") {
                     game_scene.add_player2(state, ctx);
@@ -243,6 +250,8 @@ impl LiveDebugger {
                 ui.checkbox("noclip", &mut state.settings.noclip);
                 ui.same_line();
                 ui.checkbox("more rust", &mut state.more_rust);
+                #[cfg(feature = "profiler")]
+                ui.checkbox("profiler", &mut state.settings.show_profiler);
             });
 
         if self.map_selector_visible {
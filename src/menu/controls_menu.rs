@@ -9,6 +9,7 @@ use crate::game::settings::{
 use crate::game::shared_game_state::SharedGameState;
 use crate::input::combined_menu_controller::CombinedMenuController;
 
+use super::touch_layout_editor::TouchLayoutEditor;
 use super::{ControlMenuData, Menu, MenuEntry, MenuSelectionResult};
 
 const FORBIDDEN_SCANCODES: [ScanCode; 12] = [
@@ -34,6 +35,7 @@ enum CurrentMenu {
     RebindMenu,
     ConfirmRebindMenu,
     ConfirmResetMenu,
+    TouchLayoutEditor,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -42,6 +44,8 @@ enum MainMenuEntry {
     Controller,
     Rebind,
     Rumble,
+    TouchControls,
+    TouchLayout,
     Back,
 }
 
@@ -121,6 +125,7 @@ enum ControlEntry {
     Strafe,
     MenuOk,
     MenuBack,
+    Join,
 }
 
 impl ControlEntry {
@@ -140,6 +145,7 @@ impl ControlEntry {
             ControlEntry::Strafe => state.loc.t("menus.controls_menu.rebind_menu.strafe"),
             ControlEntry::MenuOk => state.loc.t("menus.controls_menu.rebind_menu.menu_ok"),
             ControlEntry::MenuBack => state.loc.t("menus.controls_menu.rebind_menu.menu_back"),
+            ControlEntry::Join => state.loc.t("menus.controls_menu.rebind_menu.join"),
         }
         .to_owned()
     }
@@ -152,6 +158,7 @@ pub struct ControlsMenu {
     rebind: Menu<RebindMenuEntry>,
     confirm_rebind: Menu<usize>,
     confirm_reset: Menu<ConfirmResetMenuEntry>,
+    touch_layout_editor: TouchLayoutEditor,
 
     selected_player: Player,
     selected_controller: ControllerType,
@@ -180,6 +187,7 @@ impl ControlsMenu {
             rebind,
             confirm_rebind,
             confirm_reset,
+            touch_layout_editor: TouchLayoutEditor::new(),
 
             selected_player: Player::Player1,
             selected_controller: ControllerType::Keyboard,
@@ -214,6 +222,15 @@ impl ControlsMenu {
         self.main
             .push_entry(MainMenuEntry::Rebind, MenuEntry::Active(state.loc.t("menus.controls_menu.rebind").to_owned()));
         self.main.push_entry(MainMenuEntry::Rumble, MenuEntry::Hidden);
+        self.main.push_entry(
+            MainMenuEntry::TouchControls,
+            MenuEntry::Toggle(
+                state.loc.t("menus.controls_menu.touch_controls").to_owned(),
+                state.settings.touch_controls,
+            ),
+        );
+        self.main.push_entry(MainMenuEntry::TouchLayout, MenuEntry::Hidden);
+        self.update_touch_layout_entry(state);
         self.main.push_entry(MainMenuEntry::Back, MenuEntry::Active(state.loc.t("common.back").to_owned()));
 
         self.confirm_reset.push_entry(
@@ -242,6 +259,16 @@ impl ControlsMenu {
         Ok(())
     }
 
+    fn update_touch_layout_entry(&mut self, state: &SharedGameState) {
+        let entry = if state.settings.touch_controls {
+            MenuEntry::Active(state.loc.t("menus.controls_menu.touch_layout").to_owned())
+        } else {
+            MenuEntry::Hidden
+        };
+
+        self.main.set_entry(MainMenuEntry::TouchLayout, entry);
+    }
+
     fn update_sizes(&mut self, state: &SharedGameState) {
         self.main.update_width(state);
         self.main.update_height(state);
@@ -287,6 +314,7 @@ impl ControlsMenu {
         map.push((ControlEntry::Map, settings_key_map.map));
         map.push((ControlEntry::Skip, settings_key_map.skip));
         map.push((ControlEntry::Strafe, settings_key_map.strafe));
+        map.push((ControlEntry::Join, settings_key_map.join));
 
         map
     }
@@ -311,6 +339,7 @@ impl ControlsMenu {
         map.push((ControlEntry::Map, settings_controller_button_map.map));
         map.push((ControlEntry::Skip, settings_controller_button_map.skip));
         map.push((ControlEntry::Strafe, settings_controller_button_map.strafe));
+        map.push((ControlEntry::Join, settings_controller_button_map.join));
 
         map
     }
@@ -599,6 +628,10 @@ impl ControlsMenu {
                 Player::Player1 => state.settings.player1_key_map.strafe = scan_code,
                 Player::Player2 => state.settings.player2_key_map.strafe = scan_code,
             },
+            ControlEntry::Join => match self.selected_player {
+                Player::Player1 => state.settings.player1_key_map.join = scan_code,
+                Player::Player2 => state.settings.player2_key_map.join = scan_code,
+            },
             ControlEntry::MenuOk => match self.selected_player {
                 Player::Player1 => {
                     did_swap_controls = self.swap_if_same(
@@ -749,6 +782,10 @@ impl ControlsMenu {
                 Player::Player1 => state.settings.player1_controller_button_map.strafe = input_type,
                 Player::Player2 => state.settings.player2_controller_button_map.strafe = input_type,
             },
+            ControlEntry::Join => match self.selected_player {
+                Player::Player1 => state.settings.player1_controller_button_map.join = input_type,
+                Player::Player2 => state.settings.player2_controller_button_map.join = input_type,
+            },
             ControlEntry::MenuOk => match self.selected_player {
                 Player::Player1 => {
                     did_swap_controls = self.swap_if_same(
@@ -954,15 +991,41 @@ impl ControlsMenu {
                         state.settings.save(ctx)?;
                     }
                 }
+                MenuSelectionResult::Selected(MainMenuEntry::TouchControls, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.touch_controls = !state.settings.touch_controls;
+                        *value = state.settings.touch_controls;
+
+                        self.update_touch_layout_entry(state);
+                        state.settings.save(ctx)?;
+                    }
+                }
+                MenuSelectionResult::Selected(MainMenuEntry::TouchLayout, _) => {
+                    self.touch_layout_editor.init(state);
+                    self.current = CurrentMenu::TouchLayoutEditor;
+                }
                 MenuSelectionResult::Selected(MainMenuEntry::Back, _) | MenuSelectionResult::Canceled => exit_action(),
                 _ => {}
             },
+            CurrentMenu::TouchLayoutEditor => {
+                let cm = &mut self.current;
+                self.touch_layout_editor.tick(
+                    &mut || {
+                        *cm = CurrentMenu::MainMenu;
+                    },
+                    controller,
+                    state,
+                    ctx,
+                );
+            }
             CurrentMenu::SelectControllerMenu => match self.select_controller.tick(controller, state) {
                 MenuSelectionResult::Selected(SelectControllerMenuEntry::Keyboard, _) => {
                     if self.selected_player == Player::Player1 {
                         state.settings.player1_controller_type = ControllerType::Keyboard;
+                        state.settings.player1_gamepad_id = None;
                     } else {
                         state.settings.player2_controller_type = ControllerType::Keyboard;
+                        state.settings.player2_gamepad_id = None;
                     }
 
                     let _ = state.settings.save(ctx);
@@ -980,10 +1043,15 @@ impl ControlsMenu {
                     self.current = CurrentMenu::MainMenu;
                 }
                 MenuSelectionResult::Selected(SelectControllerMenuEntry::Gamepad(idx), _) => {
+                    let gamepad_id =
+                        gamepad::get_gamepads(ctx).get(idx).map(|gamepad| gamepad.get_stable_id().to_owned());
+
                     if self.selected_player == Player::Player1 {
                         state.settings.player1_controller_type = ControllerType::Gamepad(idx as u32);
+                        state.settings.player1_gamepad_id = gamepad_id;
                     } else {
                         state.settings.player2_controller_type = ControllerType::Gamepad(idx as u32);
+                        state.settings.player2_gamepad_id = gamepad_id;
                     }
 
                     let _ = state.settings.save(ctx);
@@ -1172,6 +1240,7 @@ impl ControlsMenu {
             CurrentMenu::RebindMenu => self.rebind.draw(state, ctx)?,
             CurrentMenu::ConfirmRebindMenu => self.confirm_rebind.draw(state, ctx)?,
             CurrentMenu::ConfirmResetMenu => self.confirm_reset.draw(state, ctx)?,
+            CurrentMenu::TouchLayoutEditor => self.touch_layout_editor.draw(state, ctx)?,
         }
 
         Ok(())
@@ -0,0 +1,177 @@
+use crate::common::{Color, Rect};
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::graphics;
+use crate::game::journal::JournalEntry;
+use crate::game::shared_game_state::SharedGameState;
+use crate::graphics::font::Font;
+use crate::input::combined_menu_controller::CombinedMenuController;
+
+const FACE_TEX: &str = "Face";
+const LINE_HEIGHT: f32 = 16.0;
+const FACE_SIZE: f32 = 48.0;
+const MARGIN: f32 = 16.0;
+
+/// Scrollable list of dialogue read so far, newest entries first.
+pub struct JournalMenu {
+    /// Index of the topmost entry shown, counted from the newest one.
+    scroll: usize,
+}
+
+impl JournalMenu {
+    pub fn new() -> JournalMenu {
+        JournalMenu { scroll: 0 }
+    }
+
+    pub fn init(&mut self) {
+        self.scroll = 0;
+    }
+
+    pub fn tick(
+        &mut self,
+        exit_action: &mut dyn FnMut(),
+        controller: &mut CombinedMenuController,
+        state: &mut SharedGameState,
+    ) {
+        let count = state.journal.entries.len();
+
+        if controller.trigger_back() || controller.trigger_ok() {
+            state.sound_manager.play_sfx(5);
+            exit_action();
+        } else if controller.trigger_up() && self.scroll > 0 {
+            state.sound_manager.play_sfx(1);
+            self.scroll -= 1;
+        } else if controller.trigger_down() && self.scroll + 1 < count {
+            state.sound_manager.play_sfx(1);
+            self.scroll += 1;
+        }
+    }
+
+    /// Formats play time in ticks as hours, minutes and seconds.
+    fn format_play_time(state: &SharedGameState, tick: u64) -> String {
+        let tps = match state.settings.timing_mode.get_tps() {
+            0 => 50,
+            tps => tps as u64,
+        };
+        let seconds = tick / tps;
+
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    }
+
+    /// Splits entry text into lines fitting in given width.
+    fn wrap_text(state: &SharedGameState, text: &str, width: f32) -> Vec<String> {
+        let builder = state.font.builder();
+        let mut lines = Vec::new();
+
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+
+            for chr in paragraph.chars() {
+                line.push(chr);
+
+                if builder.compute_width(&line) > width {
+                    line.pop();
+                    lines.push(std::mem::take(&mut line));
+                    line.push(chr);
+                }
+            }
+
+            if !line.trim().is_empty() {
+                lines.push(line);
+            }
+        }
+
+        lines
+    }
+
+    fn draw_entry(entry: &JournalEntry, y: f32, state: &mut SharedGameState, ctx: &mut Context) -> GameResult<f32> {
+        let text_x = if entry.face != 0 { MARGIN + FACE_SIZE + 8.0 } else { MARGIN };
+        let lines = Self::wrap_text(state, &entry.text, state.canvas_size.0 - text_x - MARGIN);
+
+        let map_name = state.stages.get(entry.map_id).map(|stage| stage.name.clone()).unwrap_or_default();
+        let header = format!("{}  {}", Self::format_play_time(state, entry.tick), map_name);
+
+        state.font.builder().position(MARGIN, y).color((160, 160, 176, 255)).draw(
+            &header,
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+
+        let y = y + LINE_HEIGHT;
+
+        if entry.face != 0 {
+            let face_num = entry.face % 100;
+            let rect = Rect::new_size((face_num % 6) * 48, (face_num / 6) * 48, 48, 48);
+
+            let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, FACE_TEX)?;
+            batch.add_rect(MARGIN, y, &rect);
+            batch.draw(ctx)?;
+        }
+
+        for (idx, line) in lines.iter().enumerate() {
+            state.font.builder().position(text_x, y + idx as f32 * LINE_HEIGHT).draw(
+                line,
+                ctx,
+                &state.constants,
+                &mut state.texture_set,
+            )?;
+        }
+
+        let height = lines.len() as f32 * LINE_HEIGHT;
+        let height = if entry.face != 0 { height.max(FACE_SIZE) } else { height };
+
+        Ok(LINE_HEIGHT + height + 8.0)
+    }
+
+    pub fn draw(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        graphics::draw_rect(
+            ctx,
+            Rect::new(0, 0, state.screen_size.0 as isize, state.screen_size.1 as isize),
+            Color::from_rgba(0, 0, 16, 224),
+        )?;
+
+        state.font.builder().center(state.canvas_size.0).y(8.0).draw(
+            state.loc.t("menus.journal.title"),
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+
+        if state.journal.entries.is_empty() {
+            return state.font.builder().center(state.canvas_size.0).y((state.canvas_size.1 / 2.0).floor()).draw(
+                state.loc.t("menus.journal.empty"),
+                ctx,
+                &state.constants,
+                &mut state.texture_set,
+            );
+        }
+
+        let top = 8.0 + LINE_HEIGHT * 2.0;
+        let clip_rect = Rect::new_size(
+            0,
+            (top * state.scale) as isize,
+            state.screen_size.0 as isize,
+            ((state.canvas_size.1 - top) * state.scale) as isize,
+        );
+        graphics::set_clip_rect(ctx, Some(clip_rect))?;
+
+        // every entry takes at least two lines, no need to look further
+        let max_visible = (state.canvas_size.1 / (LINE_HEIGHT * 2.0)) as usize + 1;
+        let entries: Vec<JournalEntry> =
+            state.journal.entries.iter().rev().skip(self.scroll).take(max_visible).cloned().collect();
+        let mut y = top;
+
+        for entry in &entries {
+            if y >= state.canvas_size.1 {
+                break;
+            }
+
+            y += Self::draw_entry(entry, y, state, ctx)?;
+        }
+
+        graphics::set_clip_rect(ctx, None)?;
+
+        Ok(())
+    }
+}
@@ -12,9 +12,11 @@ use crate::menu::save_select_menu::MenuSaveInfo;
 
 pub mod controls_menu;
 pub mod coop_menu;
+pub mod journal_menu;
 pub mod pause_menu;
 pub mod save_select_menu;
 pub mod settings_menu;
+pub mod touch_layout_editor;
 
 const MENU_MIN_PADDING: f32 = 30.0;
 
@@ -658,6 +660,22 @@ impl<T: std::cmp::PartialEq + std::default::Default + Clone> Menu<T> {
                     )?;
 
                     if valid_save {
+                        // Play time
+                        let tps = match state.settings.timing_mode.get_tps() {
+                            0 => 50,
+                            tps => tps as u64,
+                        };
+                        let secs = save.play_time / tps;
+                        let play_time = format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+                        let play_time_width = state.font.builder().compute_width(&play_time);
+
+                        state
+                            .font
+                            .builder()
+                            .position(right_edge - 66.0 - play_time_width, y)
+                            .color((160, 160, 176, 255))
+                            .draw(&play_time, ctx, &state.constants, &mut state.texture_set)?;
+
                         // Lifebar
                         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "TextBox")?;
 
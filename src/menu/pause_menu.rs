@@ -1,16 +1,22 @@
+use crate::common::{Color, Rect};
 use crate::framework::context::Context;
 use crate::framework::error::GameResult;
 use crate::framework::graphics;
 use crate::framework::keyboard::ScanCode;
-use crate::game::shared_game_state::{MenuCharacter, PlayerCount, SharedGameState};
+use crate::game::scripting::tsc::text_script::TextScriptExecutionState;
+use crate::game::shared_game_state::{GameDifficulty, MenuCharacter, PlayerCount, SharedGameState};
 use crate::input::combined_menu_controller::CombinedMenuController;
 use crate::menu::MenuEntry;
 use crate::menu::{Menu, MenuSelectionResult};
 use crate::scene::title_scene::TitleScene;
 
 use super::coop_menu::PlayerCountMenu;
+use super::journal_menu::JournalMenu;
 use super::settings_menu::SettingsMenu;
 
+/// Difficulties in the order they're listed in the difficulty menu.
+const DIFFICULTIES: [GameDifficulty; 3] = [GameDifficulty::Easy, GameDifficulty::Normal, GameDifficulty::Hard];
+
 #[derive(PartialEq, Eq, Copy, Clone)]
 #[repr(u8)]
 #[allow(unused)]
@@ -18,6 +24,7 @@ enum CurrentMenu {
     PauseMenu,
     CoopMenu,
     SettingsMenu,
+    JournalMenu,
     ConfirmMenu,
 }
 
@@ -27,6 +34,9 @@ enum PauseMenuEntry {
     Retry,
     AddPlayer2,
     DropPlayer2,
+    Save,
+    Journal,
+    Difficulty,
     Settings,
     Title,
     Quit,
@@ -56,11 +66,13 @@ pub struct PauseMenu {
     current_menu: CurrentMenu,
     settings_menu: SettingsMenu,
     coop_menu: PlayerCountMenu,
+    journal_menu: JournalMenu,
     controller: CombinedMenuController,
     pause_menu: Menu<PauseMenuEntry>,
     confirm_menu: Menu<ConfirmMenuEntry>,
     tick: u32,
     should_update_coop_menu: bool,
+    save_requested: bool,
 }
 
 impl PauseMenu {
@@ -72,11 +84,13 @@ impl PauseMenu {
             current_menu: CurrentMenu::PauseMenu,
             settings_menu: SettingsMenu::new(),
             coop_menu: PlayerCountMenu::new(),
+            journal_menu: JournalMenu::new(),
             controller: CombinedMenuController::new(),
             pause_menu: main,
             confirm_menu: Menu::new(0, 0, 75, 0),
             tick: 0,
             should_update_coop_menu: false,
+            save_requested: false,
         }
     }
 
@@ -90,6 +104,10 @@ impl PauseMenu {
             .push_entry(PauseMenuEntry::Retry, MenuEntry::Active(state.loc.t("menus.pause_menu.retry").to_owned()));
         self.pause_menu.push_entry(PauseMenuEntry::AddPlayer2, MenuEntry::Hidden);
         self.pause_menu.push_entry(PauseMenuEntry::DropPlayer2, MenuEntry::Hidden);
+        self.pause_menu.push_entry(PauseMenuEntry::Save, MenuEntry::Hidden);
+        self.pause_menu
+            .push_entry(PauseMenuEntry::Journal, MenuEntry::Active(state.loc.t("menus.pause_menu.journal").to_owned()));
+        self.pause_menu.push_entry(PauseMenuEntry::Difficulty, MenuEntry::Hidden);
         self.pause_menu.push_entry(
             PauseMenuEntry::Settings,
             MenuEntry::Active(state.loc.t("menus.pause_menu.options").to_owned()),
@@ -116,6 +134,7 @@ impl PauseMenu {
         state.menu_character = MenuCharacter::Quote;
 
         self.update_coop_menu_items(state);
+        self.update_difficulty_menu_item(state);
 
         Ok(())
     }
@@ -163,11 +182,57 @@ impl PauseMenu {
         }
     }
 
+    fn update_difficulty_menu_item(&mut self, state: &SharedGameState) {
+        if !state.constants.has_difficulty_menu {
+            return;
+        }
+
+        let value = DIFFICULTIES.iter().position(|&d| d == state.difficulty).unwrap_or(0);
+        let options = vec![
+            state.loc.t("menus.difficulty_menu.easy").to_owned(),
+            state.loc.t("menus.difficulty_menu.normal").to_owned(),
+            state.loc.t("menus.difficulty_menu.hard").to_owned(),
+        ];
+
+        self.pause_menu.set_entry(
+            PauseMenuEntry::Difficulty,
+            MenuEntry::Options(state.loc.t("menus.pause_menu.difficulty").to_owned(), value, options),
+        );
+    }
+
+    /// Changes difficulty of the current game, remembered for its save slot.
+    fn set_difficulty(state: &mut SharedGameState, ctx: &mut Context, difficulty: GameDifficulty) -> GameResult {
+        state.difficulty = difficulty;
+        state.settings.profile_overrides.entry(state.save_slot as u8).or_default().difficulty = Some(difficulty);
+        let _ = state.settings.save(ctx);
+
+        #[cfg(feature = "discord-rpc")]
+        state.discord_rpc.update_difficulty(difficulty)?;
+
+        Ok(())
+    }
+
     pub fn pause(&mut self, state: &mut SharedGameState) {
         self.is_paused = true;
+        self.update_save_menu_item(state);
         state.sound_manager.play_sfx(5);
     }
 
+    /// Saving is only possible while the player is in control, so a script can't be interrupted halfway.
+    fn update_save_menu_item(&mut self, state: &SharedGameState) {
+        let label = state.loc.t("menus.pause_menu.save").to_owned();
+        let can_save =
+            state.control_flags.control_enabled() && state.textscript_vm.state == TextScriptExecutionState::Ended;
+        let entry = if can_save { MenuEntry::Active(label) } else { MenuEntry::Disabled(label) };
+
+        self.pause_menu.set_entry(PauseMenuEntry::Save, entry);
+    }
+
+    /// Returns whether the player chose to save the game, the save itself is done by the game scene.
+    pub fn take_save_request(&mut self) -> bool {
+        std::mem::take(&mut self.save_requested)
+    }
+
     pub fn is_paused(&mut self) -> bool {
         self.is_paused
     }
@@ -218,6 +283,28 @@ impl PauseMenu {
                     state.player_count_modified_in_game = true;
                     self.should_update_coop_menu = true;
                 }
+                MenuSelectionResult::Selected(PauseMenuEntry::Save, _) => {
+                    state.sound_manager.play_sfx(18);
+                    self.save_requested = true;
+                    self.is_paused = false;
+                }
+                MenuSelectionResult::Selected(PauseMenuEntry::Journal, _) => {
+                    self.journal_menu.init();
+                    self.current_menu = CurrentMenu::JournalMenu;
+                }
+                MenuSelectionResult::Selected(PauseMenuEntry::Difficulty, toggle)
+                | MenuSelectionResult::Right(PauseMenuEntry::Difficulty, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + 1) % DIFFICULTIES.len();
+                        Self::set_difficulty(state, ctx, DIFFICULTIES[*value])?;
+                    }
+                }
+                MenuSelectionResult::Left(PauseMenuEntry::Difficulty, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + DIFFICULTIES.len() - 1) % DIFFICULTIES.len();
+                        Self::set_difficulty(state, ctx, DIFFICULTIES[*value])?;
+                    }
+                }
                 MenuSelectionResult::Selected(PauseMenuEntry::Settings, _) => {
                     self.current_menu = CurrentMenu::SettingsMenu;
                 }
@@ -262,6 +349,16 @@ impl PauseMenu {
                     ctx,
                 )?;
             }
+            CurrentMenu::JournalMenu => {
+                let cm = &mut self.current_menu;
+                self.journal_menu.tick(
+                    &mut || {
+                        *cm = CurrentMenu::PauseMenu;
+                    },
+                    &mut self.controller,
+                    state,
+                );
+            }
             CurrentMenu::ConfirmMenu => match self.confirm_menu.tick(&mut self.controller, state) {
                 MenuSelectionResult::Selected(ConfirmMenuEntry::Yes, _) => match self.pause_menu.selected {
                     PauseMenuEntry::Title => {
@@ -290,13 +387,20 @@ impl PauseMenu {
         if self.is_paused {
             let clip_y = ((self.tick as f32 + state.frame_time as f32 - 2.0) * state.scale * 10.0)
                 .clamp(0.0, state.screen_size.1) as isize;
-            let clip_rect = crate::common::Rect::new_size(
+            let clip_rect = Rect::new_size(
                 0,
                 (state.screen_size.1 / 2.0) as isize - clip_y,
                 state.screen_size.0 as isize,
                 clip_y * 2,
             );
 
+            // dim the frozen game behind the menu
+            graphics::draw_rect(
+                ctx,
+                Rect::new(0, 0, state.screen_size.0 as isize + 1, state.screen_size.1 as isize + 1),
+                Color::from_rgba(0, 0, 0, 96),
+            )?;
+
             match self.current_menu {
                 CurrentMenu::PauseMenu => {
                     graphics::set_clip_rect(ctx, Some(clip_rect))?;
@@ -309,6 +413,9 @@ impl PauseMenu {
                 CurrentMenu::SettingsMenu => {
                     self.settings_menu.draw(state, ctx)?;
                 }
+                CurrentMenu::JournalMenu => {
+                    self.journal_menu.draw(state, ctx)?;
+                }
                 CurrentMenu::ConfirmMenu => {
                     graphics::set_clip_rect(ctx, Some(clip_rect))?;
                     self.confirm_menu.draw(state, ctx)?;
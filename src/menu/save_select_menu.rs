@@ -16,11 +16,21 @@ pub struct MenuSaveInfo {
     pub weapon_count: usize,
     pub weapon_id: [u32; 8],
     pub difficulty: u8,
+    /// Ticks spent in game, see `SharedGameState::play_time`.
+    pub play_time: u64,
 }
 
 impl Default for MenuSaveInfo {
     fn default() -> Self {
-        MenuSaveInfo { current_map: 0, max_life: 0, life: 0, weapon_count: 0, weapon_id: [0; 8], difficulty: 0 }
+        MenuSaveInfo {
+            current_map: 0,
+            max_life: 0,
+            life: 0,
+            weapon_count: 0,
+            weapon_id: [0; 8],
+            difficulty: 0,
+            play_time: 0,
+        }
     }
 }
 
@@ -128,14 +138,15 @@ impl SaveSelectMenu {
 
         for (iter, save) in self.saves.iter_mut().enumerate() {
             if let Ok(data) = filesystem::user_open(ctx, state.get_save_filename(iter + 1).unwrap_or(String::new())) {
-                let loaded_save = GameProfile::load_from_save(data)?;
-
-                save.current_map = loaded_save.current_map;
-                save.max_life = loaded_save.max_life;
-                save.life = loaded_save.life;
-                save.weapon_count = loaded_save.weapon_data.iter().filter(|weapon| weapon.weapon_id != 0).count();
-                save.weapon_id = loaded_save.weapon_data.map(|weapon| weapon.weapon_id);
-                save.difficulty = loaded_save.difficulty;
+                let summary = GameProfile::peek_summary(data)?;
+
+                save.current_map = summary.current_map;
+                save.max_life = summary.max_life;
+                save.life = summary.life;
+                save.weapon_count = summary.weapon_ids.iter().filter(|&&weapon_id| weapon_id != 0).count();
+                save.weapon_id = summary.weapon_ids;
+                save.difficulty = summary.difficulty;
+                save.play_time = summary.play_time;
 
                 self.save_menu.push_entry(SaveMenuEntry::Load(iter), MenuEntry::SaveData(*save));
 
@@ -29,6 +29,7 @@ enum CurrentMenu {
     LinksMenu,
     AdvancedMenu,
     PortableMenu,
+    ConfirmRestoreMenu,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -40,6 +41,7 @@ enum MainMenuEntry {
     Behavior,
     Links,
     Advanced,
+    RestoreDefaults,
     Back,
 }
 
@@ -49,9 +51,13 @@ impl Default for MainMenuEntry {
     }
 }
 
+const FPS_CAP_PRESETS: [Option<u32>; 4] = [Some(30), Some(60), Some(120), None];
+const MAX_TEXT_SPEED: u8 = 10;
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum GraphicsMenuEntry {
     VSyncMode,
+    FpsCap,
     WindowMode,
     LightingEffects,
     WeaponLightCone,
@@ -60,6 +66,14 @@ enum GraphicsMenuEntry {
     SubpixelScrolling,
     OriginalTextures,
     SeasonalTextures,
+    MinimalHud,
+    HealPopups,
+    BoosterGauge,
+    BossWeakPoints,
+    WaterBubbles,
+    CurrentParticles,
+    AttackTelegraphs,
+    VariableShockBlink,
     Renderer,
     Back,
 }
@@ -75,6 +89,7 @@ enum SoundMenuEntry {
     MusicVolume,
     EffectsVolume,
     BGMInterpolation,
+    Reverb,
     Soundtrack,
     Back,
 }
@@ -115,6 +130,13 @@ enum BehaviorMenuEntry {
     GameTiming,
     PauseOnFocusLoss,
     CutsceneSkipMode,
+    TextSpeed,
+    DamageKnockback,
+    NameEntry,
+    PaginateMessages,
+    TradeKeepsWeaponLevel,
+    MaxLifeUpHeals,
+    BossPhases,
     #[cfg(feature = "discord-rpc")]
     DiscordRPC,
     Back,
@@ -171,6 +193,19 @@ impl Default for PortableMenuEntry {
     }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum ConfirmRestoreMenuEntry {
+    Title,
+    Yes,
+    No,
+}
+
+impl Default for ConfirmRestoreMenuEntry {
+    fn default() -> Self {
+        ConfirmRestoreMenuEntry::No
+    }
+}
+
 pub struct SettingsMenu {
     current: CurrentMenu,
     main: Menu<MainMenuEntry>,
@@ -182,6 +217,7 @@ pub struct SettingsMenu {
     links: Menu<LinksMenuEntry>,
     advanced: Menu<AdvancedMenuEntry>,
     portable: Menu<PortableMenuEntry>,
+    confirm_restore: Menu<ConfirmRestoreMenuEntry>,
     controls_menu: ControlsMenu,
     pub on_title: bool,
 }
@@ -205,6 +241,7 @@ impl SettingsMenu {
         let links = Menu::new(0, 0, 220, 0);
         let advanced = Menu::new(0, 0, 220, 0);
         let portable = Menu::new(0, 0, 220, 0);
+        let confirm_restore = Menu::new(0, 0, 160, 0);
 
         let controls_menu = ControlsMenu::new();
 
@@ -220,6 +257,7 @@ impl SettingsMenu {
             advanced,
             controls_menu,
             portable,
+            confirm_restore,
             on_title: false,
         }
     }
@@ -246,6 +284,20 @@ impl SettingsMenu {
                 ],
             ),
         );
+        self.graphics.push_entry(
+            GraphicsMenuEntry::FpsCap,
+            MenuEntry::Options(
+                state.loc.t("menus.options_menu.graphics_menu.fps_cap.entry").to_owned(),
+                FPS_CAP_PRESETS.iter().position(|&cap| cap == state.settings.fps_cap).unwrap_or(3),
+                FPS_CAP_PRESETS
+                    .iter()
+                    .map(|cap| match cap {
+                        Some(fps) => fps.to_string(),
+                        None => state.loc.t("menus.options_menu.graphics_menu.fps_cap.unlimited").to_owned(),
+                    })
+                    .collect(),
+            ),
+        );
         #[cfg(not(any(target_os = "android", target_os = "horizon")))]
         self.graphics.push_entry(
             GraphicsMenuEntry::WindowMode,
@@ -298,6 +350,62 @@ impl SettingsMenu {
                 state.settings.subpixel_coords,
             ),
         );
+        self.graphics.push_entry(
+            GraphicsMenuEntry::MinimalHud,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.graphics_menu.minimal_hud").to_owned(),
+                state.settings.minimal_hud,
+            ),
+        );
+        self.graphics.push_entry(
+            GraphicsMenuEntry::HealPopups,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.graphics_menu.heal_popups").to_owned(),
+                state.settings.heal_popups,
+            ),
+        );
+        self.graphics.push_entry(
+            GraphicsMenuEntry::BoosterGauge,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.graphics_menu.booster_gauge").to_owned(),
+                state.settings.booster_gauge,
+            ),
+        );
+        self.graphics.push_entry(
+            GraphicsMenuEntry::BossWeakPoints,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.graphics_menu.boss_weak_points").to_owned(),
+                state.settings.boss_weak_points,
+            ),
+        );
+        self.graphics.push_entry(
+            GraphicsMenuEntry::WaterBubbles,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.graphics_menu.water_bubbles").to_owned(),
+                state.settings.water_bubbles,
+            ),
+        );
+        self.graphics.push_entry(
+            GraphicsMenuEntry::CurrentParticles,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.graphics_menu.current_particles").to_owned(),
+                state.settings.current_particles,
+            ),
+        );
+        self.graphics.push_entry(
+            GraphicsMenuEntry::AttackTelegraphs,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.graphics_menu.attack_telegraphs").to_owned(),
+                state.settings.attack_telegraphs,
+            ),
+        );
+        self.graphics.push_entry(
+            GraphicsMenuEntry::VariableShockBlink,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.graphics_menu.variable_shock_blink").to_owned(),
+                state.settings.variable_shock_blink,
+            ),
+        );
 
         // NS version uses two different maps, therefore we can't dynamically switch between graphics presets.
         if state.constants.supports_og_textures {
@@ -460,8 +568,21 @@ impl SettingsMenu {
             MenuEntry::Active(state.loc.t("menus.options_menu.portable_menu.cancel").to_owned()),
         );
 
+        self.main.push_entry(
+            MainMenuEntry::RestoreDefaults,
+            MenuEntry::Active(state.loc.t("menus.options_menu.restore_defaults").to_owned()),
+        );
         self.main.push_entry(MainMenuEntry::Back, MenuEntry::Active(state.loc.t("common.back").to_owned()));
 
+        self.confirm_restore.push_entry(
+            ConfirmRestoreMenuEntry::Title,
+            MenuEntry::Disabled(state.loc.t("menus.options_menu.restore_defaults_confirm_menu_title").to_owned()),
+        );
+        self.confirm_restore
+            .push_entry(ConfirmRestoreMenuEntry::Yes, MenuEntry::Active(state.loc.t("common.yes").to_owned()));
+        self.confirm_restore
+            .push_entry(ConfirmRestoreMenuEntry::No, MenuEntry::Active(state.loc.t("common.no").to_owned()));
+
         self.sound.push_entry(
             SoundMenuEntry::MusicVolume,
             MenuEntry::OptionsBar(
@@ -498,6 +619,13 @@ impl SettingsMenu {
                 ],
             ),
         );
+        self.sound.push_entry(
+            SoundMenuEntry::Reverb,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.sound_menu.reverb").to_owned(),
+                state.settings.reverb_enabled,
+            ),
+        );
         self.sound.push_entry(
             SoundMenuEntry::Soundtrack,
             MenuEntry::Active(
@@ -572,6 +700,63 @@ impl SettingsMenu {
             ),
         );
 
+        self.behavior.push_entry(
+            BehaviorMenuEntry::TextSpeed,
+            MenuEntry::Options(
+                state.loc.t("menus.options_menu.behavior_menu.text_speed").to_owned(),
+                (state.settings.text_speed.clamp(1, MAX_TEXT_SPEED) - 1) as usize,
+                (1..=MAX_TEXT_SPEED).map(|speed| speed.to_string()).collect(),
+            ),
+        );
+
+        self.behavior.push_entry(
+            BehaviorMenuEntry::DamageKnockback,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.behavior_menu.damage_knockback").to_owned(),
+                state.settings.damage_knockback,
+            ),
+        );
+
+        self.behavior.push_entry(
+            BehaviorMenuEntry::NameEntry,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.behavior_menu.name_entry").to_owned(),
+                state.settings.name_entry,
+            ),
+        );
+
+        self.behavior.push_entry(
+            BehaviorMenuEntry::PaginateMessages,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.behavior_menu.paginate_messages").to_owned(),
+                state.settings.paginate_messages,
+            ),
+        );
+
+        self.behavior.push_entry(
+            BehaviorMenuEntry::TradeKeepsWeaponLevel,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.behavior_menu.trade_keeps_weapon_level").to_owned(),
+                state.settings.trade_keeps_weapon_level,
+            ),
+        );
+
+        self.behavior.push_entry(
+            BehaviorMenuEntry::MaxLifeUpHeals,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.behavior_menu.max_life_up_heals").to_owned(),
+                state.settings.max_life_up_heals,
+            ),
+        );
+
+        self.behavior.push_entry(
+            BehaviorMenuEntry::BossPhases,
+            MenuEntry::Toggle(
+                state.loc.t("menus.options_menu.behavior_menu.boss_phases").to_owned(),
+                state.settings.boss_phases,
+            ),
+        );
+
         #[cfg(feature = "discord-rpc")]
         self.behavior.push_entry(
             BehaviorMenuEntry::DiscordRPC,
@@ -637,6 +822,48 @@ impl SettingsMenu {
         self.portable.update_height(state);
         self.portable.x = ((state.canvas_size.0 - self.portable.width as f32) / 2.0).floor() as isize;
         self.portable.y = 30 + ((state.canvas_size.1 - self.portable.height as f32) / 2.0).floor() as isize;
+
+        self.confirm_restore.update_width(state);
+        self.confirm_restore.update_height(state);
+        self.confirm_restore.x = ((state.canvas_size.0 - self.confirm_restore.width as f32) / 2.0).floor() as isize;
+        self.confirm_restore.y = ((state.canvas_size.1 - self.confirm_restore.height as f32) / 2.0).floor() as isize;
+    }
+
+    /// Resets settings to defaults, applies them right away and rebuilds the menus to show restored values.
+    fn restore_defaults(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let textures = (state.settings.original_textures, state.settings.seasonal_textures);
+        let soundtrack = state.settings.soundtrack.clone();
+
+        state.settings.restore_defaults();
+
+        // the backend switches to the restored window mode on the next frame, like after changing it in the graphics menu
+        state.sound_manager.set_song_volume(state.settings.bgm_volume);
+        state.sound_manager.set_sfx_volume(state.settings.sfx_volume);
+        graphics::set_vsync_mode(ctx, state.settings.vsync_mode)?;
+        state.set_speed(state.settings.speed);
+        state.textscript_vm.reset_text_speed(&state.constants, state.settings.get_text_speed(state.save_slot));
+
+        if textures != (state.settings.original_textures, state.settings.seasonal_textures) {
+            if self.on_title {
+                state.reload_resources(ctx)?;
+            } else {
+                state.reload_graphics();
+            }
+        }
+
+        if soundtrack != state.settings.soundtrack {
+            state.sound_manager.reload_songs(&state.constants, &state.settings, ctx)?;
+        }
+
+        let _ = state.settings.save(ctx);
+
+        let on_title = self.on_title;
+        *self = SettingsMenu::new();
+        self.on_title = on_title;
+        self.init(state, ctx)?;
+        self.main.selected = MainMenuEntry::RestoreDefaults;
+
+        Ok(())
     }
 
     pub fn tick(
@@ -671,6 +898,10 @@ impl SettingsMenu {
                 MenuSelectionResult::Selected(MainMenuEntry::Advanced, _) => {
                     self.current = CurrentMenu::AdvancedMenu;
                 }
+                MenuSelectionResult::Selected(MainMenuEntry::RestoreDefaults, _) => {
+                    self.confirm_restore.selected = ConfirmRestoreMenuEntry::default();
+                    self.current = CurrentMenu::ConfirmRestoreMenu;
+                }
                 MenuSelectionResult::Selected(MainMenuEntry::Back, _) | MenuSelectionResult::Canceled => exit_action(),
                 _ => (),
             },
@@ -726,6 +957,23 @@ impl SettingsMenu {
                         let _ = state.settings.save(ctx);
                     }
                 }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::FpsCap, toggle)
+                | MenuSelectionResult::Right(GraphicsMenuEntry::FpsCap, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + 1) % FPS_CAP_PRESETS.len();
+                        state.settings.fps_cap = FPS_CAP_PRESETS[*value];
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Left(GraphicsMenuEntry::FpsCap, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + FPS_CAP_PRESETS.len() - 1) % FPS_CAP_PRESETS.len();
+                        state.settings.fps_cap = FPS_CAP_PRESETS[*value];
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
                 MenuSelectionResult::Selected(GraphicsMenuEntry::LightingEffects, toggle) => {
                     if let MenuEntry::Toggle(_, value) = toggle {
                         state.settings.shader_effects = !state.settings.shader_effects;
@@ -787,6 +1035,70 @@ impl SettingsMenu {
                         *value = state.settings.subpixel_coords;
                     }
                 }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::MinimalHud, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.minimal_hud = !state.settings.minimal_hud;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.minimal_hud;
+                    }
+                }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::HealPopups, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.heal_popups = !state.settings.heal_popups;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.heal_popups;
+                    }
+                }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::BoosterGauge, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.booster_gauge = !state.settings.booster_gauge;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.booster_gauge;
+                    }
+                }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::BossWeakPoints, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.boss_weak_points = !state.settings.boss_weak_points;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.boss_weak_points;
+                    }
+                }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::WaterBubbles, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.water_bubbles = !state.settings.water_bubbles;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.water_bubbles;
+                    }
+                }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::CurrentParticles, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.current_particles = !state.settings.current_particles;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.current_particles;
+                    }
+                }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::AttackTelegraphs, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.attack_telegraphs = !state.settings.attack_telegraphs;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.attack_telegraphs;
+                    }
+                }
+                MenuSelectionResult::Selected(GraphicsMenuEntry::VariableShockBlink, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.variable_shock_blink = !state.settings.variable_shock_blink;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.variable_shock_blink;
+                    }
+                }
                 MenuSelectionResult::Selected(GraphicsMenuEntry::OriginalTextures, toggle) => {
                     if let MenuEntry::Toggle(_, value) = toggle {
                         state.settings.original_textures = !state.settings.original_textures;
@@ -868,6 +1180,22 @@ impl SettingsMenu {
                         let _ = state.settings.save(ctx);
                     }
                 }
+                MenuSelectionResult::Selected(SoundMenuEntry::Reverb, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.reverb_enabled = !state.settings.reverb_enabled;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.reverb_enabled;
+
+                        if !self.on_title {
+                            let room_reverb = match state.stages.get(state.current_stage) {
+                                Some(stage) if state.settings.reverb_enabled => stage.room_reverb,
+                                _ => 0.0,
+                            };
+                            state.sound_manager.set_room_reverb(room_reverb);
+                        }
+                    }
+                }
                 MenuSelectionResult::Selected(SoundMenuEntry::Soundtrack, _) => {
                     let mut active_soundtrack = SoundtrackMenuEntry::Soundtrack(0);
 
@@ -985,6 +1313,75 @@ impl SettingsMenu {
                         let _ = state.settings.save(ctx);
                     }
                 }
+                MenuSelectionResult::Selected(BehaviorMenuEntry::TextSpeed, toggle)
+                | MenuSelectionResult::Right(BehaviorMenuEntry::TextSpeed, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + 1) % MAX_TEXT_SPEED as usize;
+                        state.settings.text_speed = *value as u8 + 1;
+                        let text_speed = state.settings.get_text_speed(state.save_slot);
+                        state.textscript_vm.reset_text_speed(&state.constants, text_speed);
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Left(BehaviorMenuEntry::TextSpeed, toggle, _) => {
+                    if let MenuEntry::Options(_, value, _) = toggle {
+                        *value = (*value + MAX_TEXT_SPEED as usize - 1) % MAX_TEXT_SPEED as usize;
+                        state.settings.text_speed = *value as u8 + 1;
+                        let text_speed = state.settings.get_text_speed(state.save_slot);
+                        state.textscript_vm.reset_text_speed(&state.constants, text_speed);
+
+                        let _ = state.settings.save(ctx);
+                    }
+                }
+                MenuSelectionResult::Selected(BehaviorMenuEntry::DamageKnockback, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.damage_knockback = !state.settings.damage_knockback;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.damage_knockback;
+                    }
+                }
+                MenuSelectionResult::Selected(BehaviorMenuEntry::NameEntry, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.name_entry = !state.settings.name_entry;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.name_entry;
+                    }
+                }
+                MenuSelectionResult::Selected(BehaviorMenuEntry::PaginateMessages, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.paginate_messages = !state.settings.paginate_messages;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.paginate_messages;
+                    }
+                }
+                MenuSelectionResult::Selected(BehaviorMenuEntry::TradeKeepsWeaponLevel, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.trade_keeps_weapon_level = !state.settings.trade_keeps_weapon_level;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.trade_keeps_weapon_level;
+                    }
+                }
+                MenuSelectionResult::Selected(BehaviorMenuEntry::MaxLifeUpHeals, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.max_life_up_heals = !state.settings.max_life_up_heals;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.max_life_up_heals;
+                    }
+                }
+                MenuSelectionResult::Selected(BehaviorMenuEntry::BossPhases, toggle) => {
+                    if let MenuEntry::Toggle(_, value) = toggle {
+                        state.settings.boss_phases = !state.settings.boss_phases;
+                        let _ = state.settings.save(ctx);
+
+                        *value = state.settings.boss_phases;
+                    }
+                }
                 #[cfg(feature = "discord-rpc")]
                 MenuSelectionResult::Selected(BehaviorMenuEntry::DiscordRPC, toggle) => {
                     if let MenuEntry::Toggle(_, value) = toggle {
@@ -1054,6 +1451,15 @@ impl SettingsMenu {
                 }
                 _ => {}
             },
+            CurrentMenu::ConfirmRestoreMenu => match self.confirm_restore.tick(controller, state) {
+                MenuSelectionResult::Selected(ConfirmRestoreMenuEntry::Yes, _) => {
+                    self.restore_defaults(state, ctx)?;
+                }
+                MenuSelectionResult::Selected(ConfirmRestoreMenuEntry::No, _) | MenuSelectionResult::Canceled => {
+                    self.current = CurrentMenu::MainMenu;
+                }
+                _ => {}
+            },
         }
         Ok(())
     }
@@ -1070,6 +1476,7 @@ impl SettingsMenu {
             CurrentMenu::LinksMenu => self.links.draw(state, ctx)?,
             CurrentMenu::AdvancedMenu => self.advanced.draw(state, ctx)?,
             CurrentMenu::PortableMenu => self.portable.draw(state, ctx)?,
+            CurrentMenu::ConfirmRestoreMenu => self.confirm_restore.draw(state, ctx)?,
         }
 
         Ok(())
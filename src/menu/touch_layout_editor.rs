@@ -0,0 +1,213 @@
+use crate::common::{Color, Rect};
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::graphics;
+use crate::framework::graphics::screen_insets_scaled;
+use crate::game::shared_game_state::SharedGameState;
+use crate::graphics::font::Font;
+use crate::input::combined_menu_controller::CombinedMenuController;
+use crate::input::touch_controls::{TouchButton, TouchLayout, TouchPoint};
+
+const OPACITY_PRESETS: [f32; 4] = [0.25, 0.5, 160.0 / 255.0, 1.0];
+const TOOLBAR_BUTTON_SIZE: (isize, isize) = (64, 20);
+
+/// Lets the on-screen controls be moved by dragging them and resized by pinching them with a second finger.
+pub struct TouchLayoutEditor {
+    /// Button being dragged, the touch dragging it and where it was grabbed relative to its top left corner.
+    drag: Option<(TouchButton, u64, (f32, f32))>,
+    /// Distance between the fingers and button size when pinching started.
+    pinch: Option<(f32, f32)>,
+    /// Last touched button, changed by the opacity option.
+    selected: TouchButton,
+}
+
+impl TouchLayoutEditor {
+    pub fn new() -> TouchLayoutEditor {
+        TouchLayoutEditor { drag: None, pinch: None, selected: TouchButton::DPad }
+    }
+
+    pub fn init(&mut self, state: &mut SharedGameState) {
+        self.drag = None;
+        self.pinch = None;
+        state.touch_controls.editing = true;
+    }
+
+    /// Returns bounds of the Done, Reset and Opacity buttons, lined up in the middle of the screen.
+    fn toolbar(state: &SharedGameState) -> [Rect; 3] {
+        let (width, height) = TOOLBAR_BUTTON_SIZE;
+        let x = state.canvas_size.0 as isize / 2 - (width * 3 + 8) / 2;
+        let y = state.canvas_size.1 as isize / 2 - height / 2;
+
+        [0, 1, 2].map(|i| Rect::new_size(x + (width + 4) * i, y, width, height))
+    }
+
+    fn button_at(state: &SharedGameState, point: &TouchPoint, insets: (f32, f32, f32, f32)) -> Option<TouchButton> {
+        let (x, y) = (point.position.0 as isize, point.position.1 as isize);
+        let contains = |bounds: Rect| x > bounds.left && x < bounds.right && y > bounds.top && y < bounds.bottom;
+
+        if Self::toolbar(state).into_iter().any(contains) {
+            return None;
+        }
+
+        // buttons later in the list are drawn over earlier ones
+        TouchButton::ALL
+            .iter()
+            .rev()
+            .copied()
+            .find(|&button| contains(state.settings.touch_layout.bounds(button, state.canvas_size, insets)))
+    }
+
+    /// Converts bounds in game pixels to screen pixels used by rectangle drawing.
+    fn to_screen(bounds: &Rect, scale: f32) -> Rect {
+        let scaled = |v: isize| (v as f32 * scale) as isize;
+
+        Rect::new(scaled(bounds.left), scaled(bounds.top), scaled(bounds.right), scaled(bounds.bottom))
+    }
+
+    fn save(state: &mut SharedGameState, ctx: &mut Context) {
+        let _ = state.settings.save(ctx);
+    }
+
+    pub fn tick(
+        &mut self,
+        exit_action: &mut dyn FnMut(),
+        controller: &mut CombinedMenuController,
+        state: &mut SharedGameState,
+        ctx: &mut Context,
+    ) {
+        let [done, reset, opacity] = Self::toolbar(state);
+
+        if controller.trigger_back() || state.touch_controls.consume_click_in(done) {
+            state.sound_manager.play_sfx(5);
+            state.touch_controls.editing = false;
+            self.drag = None;
+            Self::save(state, ctx);
+            exit_action();
+            return;
+        }
+
+        if state.touch_controls.consume_click_in(reset) {
+            state.sound_manager.play_sfx(1);
+            state.settings.touch_layout = TouchLayout::default();
+            Self::save(state, ctx);
+        }
+
+        if state.touch_controls.consume_click_in(opacity) {
+            state.sound_manager.play_sfx(1);
+            let layout = state.settings.touch_layout.get_mut(self.selected);
+            let next = OPACITY_PRESETS.iter().position(|&o| o > layout.opacity + 0.01).unwrap_or(0);
+            layout.opacity = OPACITY_PRESETS[next];
+            Self::save(state, ctx);
+        }
+
+        let insets = screen_insets_scaled(ctx, state.scale);
+        let canvas_size = state.canvas_size;
+        let points = state.touch_controls.points.clone();
+
+        if let Some((_, touch_id, _)) = self.drag {
+            if !points.iter().any(|p| p.touch_id == touch_id) {
+                self.drag = None;
+                self.pinch = None;
+                Self::save(state, ctx);
+            }
+        }
+
+        if self.drag.is_none() {
+            for point in &points {
+                if let Some(button) = Self::button_at(state, point, insets) {
+                    let bounds = state.settings.touch_layout.bounds(button, canvas_size, insets);
+                    let grab =
+                        (point.position.0 as f32 - bounds.left as f32, point.position.1 as f32 - bounds.top as f32);
+
+                    self.drag = Some((button, point.touch_id, grab));
+                    self.selected = button;
+                    break;
+                }
+            }
+        }
+
+        let (button, touch_id, grab) = match self.drag {
+            Some(drag) => drag,
+            None => return,
+        };
+        let main = match points.iter().find(|p| p.touch_id == touch_id) {
+            Some(point) => point,
+            None => return,
+        };
+        let layout = &mut state.settings.touch_layout;
+
+        if let Some(second) = points.iter().find(|p| p.touch_id != touch_id) {
+            let (dx, dy) = (second.position.0 - main.position.0, second.position.1 - main.position.1);
+            let distance = ((dx * dx + dy * dy).sqrt() as f32).max(1.0);
+
+            match self.pinch {
+                Some((start_distance, start_size)) => {
+                    layout.resize_button(button, start_size * distance / start_distance, canvas_size, insets);
+                }
+                None => self.pinch = Some((distance, layout.get(button).size)),
+            }
+        } else {
+            if self.pinch.take().is_some() {
+                // the button changed size under the finger, grab it again where it is now
+                let bounds = layout.bounds(button, canvas_size, insets);
+                let grab = (main.position.0 as f32 - bounds.left as f32, main.position.1 as f32 - bounds.top as f32);
+                self.drag = Some((button, touch_id, grab));
+                return;
+            }
+
+            let (x, y) = (main.position.0 as f32 - grab.0, main.position.1 as f32 - grab.1);
+            layout.move_button(button, x, y, canvas_size, insets);
+        }
+    }
+
+    pub fn draw(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let insets = screen_insets_scaled(ctx, state.scale);
+        let line_width = state.scale.max(1.0) as usize;
+
+        for button in TouchButton::ALL {
+            state.touch_controls.draw_button(
+                button,
+                state.canvas_size,
+                &state.settings.touch_layout,
+                insets,
+                &state.constants,
+                &mut state.texture_set,
+                &state.font,
+                &state.loc,
+                ctx,
+            )?;
+
+            let bounds = state.settings.touch_layout.bounds(button, state.canvas_size, insets);
+            let color = if button == self.selected {
+                Color::from_rgba(255, 255, 0, 200)
+            } else {
+                Color::from_rgba(255, 255, 255, 100)
+            };
+
+            graphics::draw_outline_rect(ctx, Self::to_screen(&bounds, state.scale), line_width, color)?;
+        }
+
+        let opacity = (state.settings.touch_layout.get(self.selected).opacity * 100.0).round();
+        let labels = [
+            state.loc.t("menus.controls_menu.touch_layout_editor.done").to_owned(),
+            state.loc.t("menus.controls_menu.touch_layout_editor.reset").to_owned(),
+            format!("{}%", opacity),
+        ];
+
+        for (bounds, label) in Self::toolbar(state).iter().zip(labels.iter()) {
+            let rect = Self::to_screen(bounds, state.scale);
+            graphics::draw_rect(ctx, rect, Color::from_rgba(0, 0, 32, 180))?;
+            graphics::draw_outline_rect(ctx, rect, line_width, Color::from_rgba(255, 255, 255, 200))?;
+
+            let y = bounds.top as f32 + (bounds.height() as f32 - state.font.line_height()) / 2.0;
+            state.font.builder().position(bounds.left as f32, y).center(bounds.width() as f32).draw(
+                label,
+                ctx,
+                &state.constants,
+                &mut state.texture_set,
+            )?;
+        }
+
+        Ok(())
+    }
+}
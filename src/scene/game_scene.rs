@@ -1,10 +1,11 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ops::{Deref, Range};
 use std::rc::Rc;
 
 use log::info;
 
-use crate::common::{interpolate_fix9_scale, Color, Direction, Rect};
+use crate::common::{interpolate_fix9_scale, Color, Direction, FadeState, Rect};
 use crate::components::background::Background;
 use crate::components::boss_life_bar::BossLifeBar;
 use crate::components::credits::Credits;
@@ -16,6 +17,7 @@ use crate::components::hud::HUD;
 use crate::components::inventory::InventoryUI;
 use crate::components::map_system::MapSystem;
 use crate::components::nikumaru::NikumaruCounter;
+use crate::components::photo_mode::PhotoMode;
 use crate::components::replay::Replay;
 use crate::components::stage_select::StageSelect;
 use crate::components::text_boxes::TextBoxes;
@@ -31,10 +33,12 @@ use crate::framework::keyboard::ScanCode;
 use crate::framework::ui::Components;
 use crate::framework::{filesystem, gamepad, graphics};
 use crate::game::caret::CaretType;
+use crate::game::events::GameEvent;
 use crate::game::frame::{Frame, UpdateTarget};
-use crate::game::inventory::{Inventory, TakeExperienceResult};
+use crate::game::inventory::Inventory;
 use crate::game::map::WaterParams;
 use crate::game::npc::boss::BossNPC;
+use crate::game::npc::faction::{bullet_can_hit, FACTION_ENEMY};
 use crate::game::npc::list::NPCList;
 use crate::game::npc::{NPCLayer, NPC};
 use crate::game::physics::{PhysicalEntity, OFFSETS};
@@ -42,14 +46,18 @@ use crate::game::player::{ControlMode, Player, TargetPlayer};
 use crate::game::scripting::tsc::credit_script::CreditScriptVM;
 use crate::game::scripting::tsc::text_script::{ScriptMode, TextScriptExecutionState, TextScriptVM};
 use crate::game::settings::ControllerType;
-use crate::game::shared_game_state::{CutsceneSkipMode, PlayerCount, ReplayState, SharedGameState, TileSize};
+use crate::game::shared_game_state::{
+    CutsceneSkipMode, Player2JoinState, PlayerCount, ReplayState, SharedGameState, TileSize,
+};
 use crate::game::stage::{BackgroundType, Stage, StageTexturePaths};
+use crate::game::tile_props::TileProperty;
 use crate::game::weapon::bullet::BulletManager;
 use crate::game::weapon::{Weapon, WeaponType};
 use crate::graphics::font::{Font, Symbols};
 use crate::graphics::texture_set::SpriteBatch;
 use crate::input::touch_controls::TouchControlType;
 use crate::menu::pause_menu::PauseMenu;
+use crate::scene::summary_scene::SummaryScene;
 use crate::scene::title_scene::TitleScene;
 use crate::scene::Scene;
 use crate::util::rng::RNG;
@@ -86,10 +94,17 @@ pub struct GameScene {
     pub lighting_mode: LightingMode,
     pub intro_mode: bool,
     pub pause_menu: PauseMenu,
+    pub summary: Option<SummaryScene>,
+    pub photo_mode: PhotoMode,
+    /// Debug message shown on top of the screen and ticks left until it disappears.
+    pub debug_toast: Option<(String, u16)>,
     pub stage_textures: Rc<RefCell<StageTexturePaths>>,
     pub replay: Replay,
+    /// Pressure switches that are held down, with ticks left until they reset once the player steps off.
+    pub pressure_timers: HashMap<(u16, u16), u16>,
     map_name_counter: u16,
     skip_counter: u16,
+    player2_leave_counter: usize,
     inventory_dim: f32,
 }
 
@@ -102,6 +117,10 @@ pub enum LightingMode {
 
 const P2_OFFSCREEN_TEXT: &'static str = "P2";
 const CUTSCENE_SKIP_WAIT: u16 = 50;
+const PLAYER2_JOIN_DELAY: u64 = 25;
+const PLAYER2_LEAVE_HOLD_SECONDS: usize = 3;
+const DEBUG_TOAST_TICKS: u16 = 300;
+const BOSS_PHASE_CROSSFADE_SECONDS: f32 = 2.0;
 
 impl GameScene {
     pub fn new(state: &mut SharedGameState, ctx: &mut Context, id: usize) -> GameResult<Self> {
@@ -178,11 +197,16 @@ impl GameScene {
             lighting_mode: LightingMode::None,
             intro_mode: false,
             pause_menu: PauseMenu::new(),
+            summary: None,
+            photo_mode: PhotoMode::new(),
+            debug_toast: None,
             stage_textures,
             map_name_counter: 0,
             skip_counter: 0,
+            player2_leave_counter: 0,
             inventory_dim: 0.0,
             replay: Replay::new(),
+            pressure_timers: HashMap::new(),
         })
     }
 
@@ -203,13 +227,149 @@ impl GameScene {
         self.player2.y = self.player1.y;
         self.player2.vel_x = self.player1.vel_x;
         self.player2.vel_y = self.player1.vel_y;
+        self.player2.spectate_on_death = true;
+
+        if state.player2_join_state == Player2JoinState::Ghost {
+            self.player2.cond.set_alive(false);
+        } else {
+            state.player2_join_state = Player2JoinState::Active;
+        }
     }
 
-    pub fn drop_player2(&mut self) {
+    pub fn drop_player2(&mut self, state: &mut SharedGameState) {
         self.player2.cond.set_alive(false);
+        state.player2_join_state = Player2JoinState::Inactive;
+    }
+
+    /// Brings player 2 into a running game at player 1's position with half of their health.
+    pub fn spawn_player2(&mut self, state: &mut SharedGameState, ctx: &mut Context) {
+        state.player_count = PlayerCount::Two;
+        self.add_player2(state, ctx);
+        self.player2.life = (self.player2.max_life / 2).max(1);
+    }
+
+    /// Brings a ghost player 2 back to life next to player 1 with half of their health.
+    pub fn revive_player2(&mut self, state: &mut SharedGameState) {
+        if state.player2_join_state != Player2JoinState::Ghost {
+            return;
+        }
+
+        self.player2.cond.set_alive(true);
+        self.player2.cond.set_hidden(self.player1.cond.hidden());
+        self.player2.x = self.player1.x;
+        self.player2.y = self.player1.y;
+        self.player2.vel_x = 0;
+        self.player2.vel_y = 0;
+        self.player2.shock_counter = 0;
+        self.player2.life = (self.player2.max_life / 2).max(1);
+        state.player2_join_state = Player2JoinState::Active;
+
+        state.create_caret(self.player2.x, self.player2.y, CaretType::LevelUp, Direction::Left);
+    }
+
+    /// Consumes a revive orb (NPC 374) touched by player 1 to bring a ghost player 2 back.
+    fn tick_revive_orbs(&mut self, state: &mut SharedGameState) {
+        if state.player2_join_state != Player2JoinState::Ghost || !self.player1.cond.alive() {
+            return;
+        }
+
+        let player1 = &mut self.player1;
+        let orb = self.npc_list.iter_alive().find(|npc| npc.npc_type == 374 && player1.touches_npc(npc));
+
+        if let Some(orb) = orb {
+            orb.cond.set_alive(false);
+            state.sound_manager.play_sfx(20);
+            self.revive_player2(state);
+        }
+    }
+
+    /// Revives the players at the checkpoint instead of reloading the save, switching to its stage if needed.
+    /// Returns false if no checkpoint is set.
+    pub fn respawn_at_checkpoint(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult<bool> {
+        let ((tile_x, tile_y), map) = match (state.checkpoint, state.checkpoint_map) {
+            (Some(pos), Some(map)) => (pos, map),
+            _ => return Ok(false),
+        };
+
+        state.fade_state = FadeState::Visible;
+        state.control_flags.set_tick_world(true);
+        state.control_flags.set_control_enabled(true);
+        state.control_flags.set_interactions_disabled(false);
+        state.textscript_vm.state = TextScriptExecutionState::Ended;
+        state.textscript_vm.clear_text_box();
+        state.sound_manager.play_song(state.checkpoint_song, &state.constants, &state.settings, ctx)?;
+
+        if map == self.stage_id {
+            self.respawn_players(tile_x, tile_y);
+            self.frame.target_x = self.player1.x;
+            self.frame.target_y = self.player1.y;
+            self.frame.immediate_update(state, &self.stage);
+        } else {
+            let mut new_scene = GameScene::new(state, ctx, map)?;
+            new_scene.inventory_player1 = self.inventory_player1.clone();
+            new_scene.inventory_player2 = self.inventory_player2.clone();
+            new_scene.player1 = self.player1.clone();
+            new_scene.player2 = self.player2.clone();
+            new_scene.respawn_players(tile_x, tile_y);
+
+            state.next_scene = Some(Box::new(new_scene));
+        }
+
+        log::info!("Respawning at checkpoint {}:{}:{}", map, tile_x, tile_y);
+        Ok(true)
+    }
+
+    fn respawn_players(&mut self, tile_x: isize, tile_y: isize) {
+        let block_size = self.stage.map.tile_size.as_int() * 0x200;
+        let (x, y) = (tile_x as i32 * block_size, tile_y as i32 * block_size);
+
+        self.player1.respawn(x, y);
+        if self.player2.cond.alive() {
+            self.player2.respawn(x, y);
+        }
+    }
+
+    fn tick_player2_join(&mut self, state: &mut SharedGameState, ctx: &mut Context) {
+        match state.player2_join_state {
+            Player2JoinState::Inactive => {
+                if state.player_count == PlayerCount::One && self.player2.controller.trigger_join() {
+                    state.player2_join_state = Player2JoinState::Joining(self.tick as u64);
+                    state.sound_manager.play_sfx(18);
+                }
+            }
+            Player2JoinState::Joining(join_tick) => {
+                if state.control_flags.control_enabled()
+                    && self.player1.cond.alive()
+                    && self.tick as u64 >= join_tick + PLAYER2_JOIN_DELAY
+                {
+                    self.spawn_player2(state, ctx);
+                }
+            }
+            Player2JoinState::Active | Player2JoinState::Ghost => {
+                if !self.player2.controller.join() {
+                    self.player2_leave_counter = 0;
+                    return;
+                }
+
+                let tps = match state.settings.timing_mode.get_tps() {
+                    0 => 50,
+                    tps => tps,
+                };
+
+                self.player2_leave_counter += 1;
+                if self.player2_leave_counter >= tps * PLAYER2_LEAVE_HOLD_SECONDS {
+                    self.player2_leave_counter = 0;
+                    state.player_count = PlayerCount::One;
+                    self.drop_player2(state);
+                }
+            }
+        }
     }
 
     fn draw_npc_layer(&self, state: &mut SharedGameState, ctx: &mut Context, layer: NPCLayer) -> GameResult {
+        // consecutive NPCs sharing a spritesheet are drawn in a single batch, without changing the draw order
+        let mut pending_batch: Option<u16> = None;
+
         for npc in self.npc_list.iter_alive() {
             if npc.layer != layer
                 || npc.x < (self.frame.x - 128 * 0x200 - npc.display_bounds.width() as i32 * 0x200)
@@ -226,7 +386,26 @@ impl GameScene {
                 continue;
             }
 
-            npc.draw(state, ctx, &self.frame)?;
+            if let Some(spritesheet_id) = pending_batch {
+                if spritesheet_id != npc.spritesheet_id {
+                    NPC::draw_batch(spritesheet_id, state, ctx)?;
+                    pending_batch = None;
+                }
+            }
+
+            if npc.queue_draw(state, ctx, &self.frame)? {
+                pending_batch = Some(npc.spritesheet_id);
+            } else {
+                if let Some(spritesheet_id) = pending_batch.take() {
+                    NPC::draw_batch(spritesheet_id, state, ctx)?;
+                }
+
+                npc.draw(state, ctx, &self.frame)?;
+            }
+        }
+
+        if let Some(spritesheet_id) = pending_batch {
+            NPC::draw_batch(spritesheet_id, state, ctx)?;
         }
 
         Ok(())
@@ -295,24 +474,79 @@ impl GameScene {
 
     fn draw_carets(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
         let batch = state.texture_set.get_or_load_batch(ctx, &state.constants, "Caret")?;
+        let mut telegraphs = Vec::new();
 
         for caret in state.carets.iter() {
-            batch.add_rect(
-                interpolate_fix9_scale(
-                    caret.prev_x - caret.offset_x - self.frame.prev_x,
-                    caret.x - caret.offset_x - self.frame.x,
-                    state.frame_time,
-                ),
-                interpolate_fix9_scale(
-                    caret.prev_y - caret.offset_y - self.frame.prev_y,
-                    caret.y - caret.offset_y - self.frame.y,
-                    state.frame_time,
-                ),
-                &caret.anim_rect,
+            let x = interpolate_fix9_scale(
+                caret.prev_x - caret.offset_x - self.frame.prev_x,
+                caret.x - caret.offset_x - self.frame.x,
+                state.frame_time,
             );
+            let y = interpolate_fix9_scale(
+                caret.prev_y - caret.offset_y - self.frame.prev_y,
+                caret.y - caret.offset_y - self.frame.y,
+                state.frame_time,
+            );
+
+            if caret.ctype == CaretType::TelegraphWarning {
+                if caret.telegraph_visible() {
+                    telegraphs.push((x, y));
+                }
+            } else {
+                batch.add_rect(x, y, &caret.anim_rect);
+            }
         }
 
         batch.draw(ctx)?;
+
+        // the caret sheet has no exclamation mark, so it's drawn with the font, centered on the caret
+        let half_width = state.font.builder().compute_width("!") / 2.0;
+        let half_height = state.font.line_height() / 2.0;
+        for (x, y) in telegraphs {
+            state
+                .font
+                .builder()
+                .position(x + 8.0 - half_width, y + 8.0 - half_height)
+                .shadow(true)
+                .color((255, 48, 48, 255))
+                .draw("!", ctx, &state.constants, &mut state.texture_set)?;
+        }
+
+        Ok(())
+    }
+
+    /// Tints the screen edges green while a player is poisoned.
+    fn draw_poison_vignette(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let strength =
+            self.player1.status_effects.poison_strength().max(self.player2.status_effects.poison_strength()).min(1.0);
+
+        if strength <= 0.0 {
+            return Ok(());
+        }
+
+        let (width, height) = (state.screen_size.0 as isize, state.screen_size.1 as isize);
+        let band = (4.0 * state.scale) as isize;
+
+        // a few stacked bands fading towards the center
+        for i in 0..4 {
+            let alpha = (strength * 0.25 * (4 - i) as f32 / 4.0 * 255.0) as u8;
+            let color = Color::from_rgba(32, 200, 32, alpha);
+            let offset = i * band;
+
+            graphics::draw_rect(ctx, Rect::new(offset, offset, width - offset, offset + band), color)?;
+            graphics::draw_rect(
+                ctx,
+                Rect::new(offset, height - offset - band, width - offset, height - offset),
+                color,
+            )?;
+            graphics::draw_rect(ctx, Rect::new(offset, offset + band, offset + band, height - offset - band), color)?;
+            graphics::draw_rect(
+                ctx,
+                Rect::new(width - offset - band, offset + band, width - offset, height - offset - band),
+                color,
+            )?;
+        }
+
         Ok(())
     }
 
@@ -1105,6 +1339,108 @@ impl GameScene {
         Ok(())
     }
 
+    /// Photo mode is only available during regular gameplay, not in cutscenes or boss fights.
+    fn can_enter_photo_mode(&self, state: &SharedGameState) -> bool {
+        !self.intro_mode
+            && state.control_flags.control_enabled()
+            && state.textscript_vm.mode == ScriptMode::Map
+            && matches!(state.textscript_vm.state, TextScriptExecutionState::Ended)
+            && !self.boss_life_bar.is_active()
+            && !self.boss.parts[0].cond.alive()
+    }
+
+    /// Spawns a streak in a random visible tile if it's a wind or water current.
+    fn tick_current_particles(&mut self, state: &mut SharedGameState) {
+        let tile_size = state.tile_size.as_int();
+        let tiles_x = state.canvas_size.0 as i32 / tile_size + 2;
+        let tiles_y = state.canvas_size.1 as i32 / tile_size + 2;
+
+        let x = self.frame.x / (tile_size * 0x200) + state.effect_rng.range(0..tiles_x);
+        let y = self.frame.y / (tile_size * 0x200) + state.effect_rng.range(0..tiles_y);
+        if x < 0 || y < 0 {
+            return;
+        }
+
+        let direction = match self.stage.map.get_attribute(x as usize, y as usize) {
+            0x80 | 0xa0 => Direction::Left,
+            0x81 | 0xa1 => Direction::Up,
+            0x82 | 0xa2 => Direction::Right,
+            0x83 | 0xa3 => Direction::Bottom,
+            _ => return,
+        };
+
+        let half_tile = tile_size / 2;
+        let mut particle = NPC::create(199, &state.npc_table);
+        particle.cond.set_alive(true);
+        particle.direction = direction;
+        particle.x = (x * tile_size + state.effect_rng.range(-half_tile..half_tile)) * 0x200;
+        particle.y = (y * tile_size + state.effect_rng.range(-half_tile..half_tile)) * 0x200;
+
+        let _ = self.npc_list.spawn(0x100, particle);
+    }
+
+    /// Presses switch tiles the players are standing on and releases the ones they stepped off of.
+    fn tick_pressure_switches(&mut self, state: &mut SharedGameState) {
+        let tile_size = state.tile_size.as_int() * 0x200;
+        let half_tile_size = tile_size / 2;
+        let mut standing_on = [None; 2];
+
+        for (i, player) in [&self.player1, &self.player2].iter().enumerate() {
+            if !player.cond.alive() || player.cond.hidden() || !player.flags.hit_bottom_wall() {
+                continue;
+            }
+
+            let x = (player.x + half_tile_size) / tile_size;
+            let y = (player.y + player.hit_bounds.bottom as i32 + half_tile_size) / tile_size;
+            if x >= 0 && y >= 0 {
+                standing_on[i] = Some((x as u16, y as u16));
+            }
+        }
+
+        for &(x, y) in standing_on.iter().flatten() {
+            let tile = self.stage.tile_at(x as usize, y as usize) as u16;
+
+            if let Some(TileProperty::PressureSwitch { flag, activate_event, reset_ticks, .. }) =
+                state.tile_props.get(&self.stage.data.tileset.name, tile)
+            {
+                let flag = flag as usize;
+                if reset_ticks == 0 {
+                    if !state.get_flag(flag) {
+                        state.set_flag(flag, true);
+                        state.textscript_vm.start_script(activate_event);
+                    }
+                } else if self.pressure_timers.insert((x, y), reset_ticks).is_none() {
+                    state.set_flag(flag, true);
+                    state.textscript_vm.start_script(activate_event);
+                }
+            }
+        }
+
+        let mut released = Vec::new();
+        for (&pos, timer) in self.pressure_timers.iter_mut() {
+            if standing_on.contains(&Some(pos)) {
+                continue;
+            }
+
+            *timer = timer.saturating_sub(1);
+            if *timer == 0 {
+                released.push(pos);
+            }
+        }
+
+        for (x, y) in released {
+            self.pressure_timers.remove(&(x, y));
+
+            let tile = self.stage.tile_at(x as usize, y as usize) as u16;
+            if let Some(TileProperty::PressureSwitch { flag, deactivate_event, .. }) =
+                state.tile_props.get(&self.stage.data.tileset.name, tile)
+            {
+                state.set_flag(flag as usize, false);
+                state.textscript_vm.start_script(deactivate_event);
+            }
+        }
+    }
+
     fn tick_npc_splash(&mut self, state: &mut SharedGameState) {
         for npc in self.npc_list.iter_alive() {
             // Water Droplet
@@ -1155,7 +1491,7 @@ impl GameScene {
             }
 
             for bullet in self.bullet_manager.bullets.iter_mut() {
-                if !bullet.cond.alive() || bullet.damage < 0 {
+                if !bullet.cond.alive() || bullet.damage < 0 || !bullet_can_hit(bullet.source_faction, npc.faction) {
                     continue;
                 }
 
@@ -1167,6 +1503,13 @@ impl GameScene {
                     npc.life = (npc.life as i32).saturating_sub(bullet.damage as i32).clamp(0, u16::MAX as i32) as u16;
 
                     if npc.life == 0 {
+                        state.event_bus.emit(GameEvent::NpcDied {
+                            id: npc.id,
+                            npc_type: npc.npc_type,
+                            x: npc.x as isize,
+                            y: npc.y as isize,
+                        });
+
                         if npc.npc_flags.show_damage() {
                             npc.popup.add_value(-bullet.damage);
                         }
@@ -1241,22 +1584,11 @@ impl GameScene {
             }
 
             for bullet in self.bullet_manager.bullets.iter_mut() {
-                if !bullet.cond.alive() || bullet.damage < 0 {
+                if !bullet.cond.alive() || bullet.damage < 0 || bullet.source_faction == FACTION_ENEMY {
                     continue;
                 }
 
-                let hit = (npc.npc_flags.shootable()
-                    && (npc.x - npc.hit_bounds.right as i32) < (bullet.x + bullet.enemy_hit_width as i32)
-                    && (npc.x + npc.hit_bounds.right as i32) > (bullet.x - bullet.enemy_hit_width as i32)
-                    && (npc.y - npc.hit_bounds.top as i32) < (bullet.y + bullet.enemy_hit_height as i32)
-                    && (npc.y + npc.hit_bounds.bottom as i32) > (bullet.y - bullet.enemy_hit_height as i32))
-                    || (npc.npc_flags.invulnerable()
-                        && (npc.x - npc.hit_bounds.right as i32) < (bullet.x + bullet.hit_bounds.right as i32)
-                        && (npc.x + npc.hit_bounds.right as i32) > (bullet.x - bullet.hit_bounds.left as i32)
-                        && (npc.y - npc.hit_bounds.top as i32) < (bullet.y + bullet.hit_bounds.bottom as i32)
-                        && (npc.y + npc.hit_bounds.bottom as i32) > (bullet.y - bullet.hit_bounds.top as i32));
-
-                if !hit {
+                if !npc.collides_with_bullet(bullet) {
                     continue;
                 }
 
@@ -1272,6 +1604,10 @@ impl GameScene {
                     if npc.life == 0 {
                         npc.life = npc.id;
 
+                        if idx == 0 {
+                            state.event_bus.emit(GameEvent::BossDefeated { boss_id: self.boss.boss_type as u8 });
+                        }
+
                         if self.player1.cond.alive() && npc.npc_flags.event_when_killed() {
                             state.control_flags.set_tick_world(true);
                             state.control_flags.set_interactions_disabled(true);
@@ -1346,36 +1682,40 @@ impl GameScene {
                 0
             }
         };
+        #[cfg(feature = "profiler")]
+        state.profiler.begin("player_tick");
         self.player1.tick(state, &self.npc_list)?;
         self.player2.tick(state, &self.npc_list)?;
+        #[cfg(feature = "profiler")]
+        state.profiler.end();
+
+        for (x, y) in [self.player1.slam_landing.take(), self.player2.slam_landing.take()].into_iter().flatten() {
+            let (radius, damage) = (state.constants.player.slam_radius, state.constants.player.slam_damage);
+
+            self.npc_list.area_damage(x, y, radius, damage, state);
+            self.boss.area_damage(x, y, radius, damage, state, &self.npc_list);
+        }
+
+        if state.player2_join_state == Player2JoinState::Ghost {
+            self.player2.x = self.player1.x;
+            self.player2.y = self.player1.y;
+        }
         state.textscript_vm.reset_invicibility = false;
 
         self.whimsical_star.tick(state, (&self.player1, &mut self.bullet_manager))?;
 
         if self.player1.damage > 0 {
-            let xp_loss = self.player1.damage * if self.player1.equip.has_arms_barrier() { 1 } else { 2 };
-            match self.inventory_player1.take_xp(xp_loss, state) {
-                TakeExperienceResult::LevelDown if self.player1.life > 0 => {
-                    state.create_caret(self.player1.x, self.player1.y, CaretType::LevelUp, Direction::Right);
-                }
-                _ => {}
-            }
-
+            self.inventory_player1.take_damage_xp(&self.player1, state);
             self.player1.damage = 0;
         }
 
         if self.player2.damage > 0 {
-            let xp_loss = self.player2.damage * if self.player2.equip.has_arms_barrier() { 1 } else { 2 };
-            match self.inventory_player2.take_xp(xp_loss, state) {
-                TakeExperienceResult::LevelDown if self.player2.life > 0 => {
-                    state.create_caret(self.player2.x, self.player2.y, CaretType::LevelUp, Direction::Right);
-                }
-                _ => {}
-            }
-
+            self.inventory_player2.take_damage_xp(&self.player2, state);
             self.player2.damage = 0;
         }
 
+        #[cfg(feature = "profiler")]
+        state.profiler.begin("npc_tick");
         for npc in self.npc_list.iter_alive() {
             npc.tick(
                 state,
@@ -1389,6 +1729,7 @@ impl GameScene {
                 ),
             )?;
         }
+        self.npc_list.tick_respawns(state, [&self.player1, &self.player2]);
         self.boss.tick(
             state,
             (
@@ -1399,11 +1740,26 @@ impl GameScene {
                 &mut self.flash,
             ),
         )?;
+        #[cfg(feature = "profiler")]
+        state.profiler.end();
+
+        #[cfg(feature = "profiler")]
+        state.profiler.begin("collisions");
         //decides if the player is tangible or not
         if !state.settings.noclip {
             self.player1.tick_map_collisions(state, &self.npc_list, &mut self.stage);
             self.player2.tick_map_collisions(state, &self.npc_list, &mut self.stage);
 
+            self.player1.tick_crouch(state, &self.stage);
+            self.player2.tick_crouch(state, &self.stage);
+            self.player1.tick_surface(state, &self.stage);
+            self.player2.tick_surface(state, &self.stage);
+
+            if !state.tile_props.is_empty() {
+                self.tick_pressure_switches(state);
+            }
+
+            self.npc_list.rebuild_grid();
             self.player1.tick_npc_collisions(
                 TargetPlayer::Player1,
                 state,
@@ -1418,11 +1774,33 @@ impl GameScene {
                 &mut self.boss,
                 &mut self.inventory_player2,
             );
+            self.tick_revive_orbs(state);
         }
 
         for npc in self.npc_list.iter_alive() {
             if !npc.npc_flags.ignore_solidity() {
                 npc.tick_map_collisions(state, &self.npc_list, &mut self.stage);
+
+                if state.npc_table.is_tile_aware(npc.npc_type) {
+                    npc.on_ice = npc.stands_on_ice(state, &self.stage);
+                    npc.conveyor_vel_x = npc.conveyor_speed(state, &self.stage);
+                }
+
+                if state.npc_table.can_climb(npc.npc_type) {
+                    npc.tick_climb(state, &self.stage);
+                }
+
+                if state.npc_table.swims(npc.npc_type) {
+                    if let Some((drag, buoyancy_accel)) = npc.water_zone(state, &self.stage) {
+                        npc.apply_water_zone(drag, buoyancy_accel, false);
+                    }
+                }
+
+                if state.npc_table.takes_tile_damage(npc.npc_type) {
+                    if let Some(instant_kill) = npc.touching_spike(state, &self.stage) {
+                        npc.hit_by_spike(instant_kill, state);
+                    }
+                }
             }
         }
         for npc in self.boss.parts.iter_mut() {
@@ -1435,9 +1813,16 @@ impl GameScene {
             self.tick_npc_splash(state);
         }
 
+        if state.settings.current_particles {
+            self.tick_current_particles(state);
+        }
+
         self.bullet_manager.tick_map_collisions(state, &self.npc_list, &mut self.stage);
 
         self.tick_npc_bullet_collissions(state);
+        self.bullet_manager.tick_bullet_collisions(state);
+        #[cfg(feature = "profiler")]
+        state.profiler.end();
 
         if state.control_flags.control_enabled() {
             self.inventory_player1.tick_weapons(
@@ -1454,7 +1839,11 @@ impl GameScene {
             );
         }
 
+        #[cfg(feature = "profiler")]
+        state.profiler.begin("bullet_tick");
         self.bullet_manager.tick_bullets(state, [&self.player1, &self.player2], &self.npc_list);
+        #[cfg(feature = "profiler")]
+        state.profiler.end();
         state.tick_carets();
 
         match self.frame.update_target {
@@ -1634,6 +2023,71 @@ impl GameScene {
         }
 
         self.draw_debug_object(&self.player1, state, ctx)?;
+        self.draw_debug_npc_tooltip(state, ctx)?;
+
+        Ok(())
+    }
+
+    /// Shows state of the NPC under mouse cursor.
+    fn draw_debug_npc_tooltip(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let mouse_pos = graphics::imgui_context(ctx)?.io().mouse_pos;
+        if mouse_pos[0] < 0.0 || mouse_pos[1] < 0.0 {
+            return Ok(());
+        }
+
+        let mouse_x = (mouse_pos[0] / state.scale * 512.0) as i32 + self.frame.x;
+        let mouse_y = (mouse_pos[1] / state.scale * 512.0) as i32 + self.frame.y;
+
+        let hovered = self.npc_list.iter_alive().chain(self.boss.parts.iter().filter(|n| n.cond.alive())).find(|npc| {
+            let bounds = &npc.display_bounds;
+            let off_x = if npc.direction == Direction::Left { bounds.left } else { bounds.right } as i32;
+            let left = npc.x - off_x;
+            let top = npc.y - bounds.top as i32;
+
+            (left..=left + (bounds.left + bounds.right) as i32).contains(&mouse_x)
+                && (top..=top + (bounds.top + bounds.bottom) as i32).contains(&mouse_y)
+        });
+
+        let npc = match hovered {
+            Some(npc) => npc,
+            None => return Ok(()),
+        };
+
+        let rect = &npc.anim_rect;
+        let mut lines = vec![
+            format!("#{} type: {} action: {}", npc.id, npc.npc_type, npc.action_num),
+            format!("life: {} vel: {}, {}", npc.life, npc.vel_x, npc.vel_y),
+            format!("anim_rect: {}, {}, {}, {}", rect.left, rect.top, rect.right, rect.bottom),
+        ];
+        lines.extend(npc.npc_flags.names().chunks(3).map(|names| names.join(", ")));
+
+        const TEXT_SCALE: f32 = 0.5;
+        let line_height = state.font.line_height() * TEXT_SCALE;
+        let width = lines.iter().map(|line| state.font.builder().compute_width(line)).fold(0.0, f32::max) * TEXT_SCALE;
+        let height = line_height * lines.len() as f32;
+
+        let x = (mouse_pos[0] / state.scale + 8.0).min(state.canvas_size.0 - width - 4.0);
+        let y = (mouse_pos[1] / state.scale + 8.0).min(state.canvas_size.1 - height - 4.0);
+
+        graphics::draw_rect(
+            ctx,
+            Rect::new(
+                ((x - 2.0) * state.scale) as isize,
+                ((y - 2.0) * state.scale) as isize,
+                ((x + width + 2.0) * state.scale) as isize,
+                ((y + height + 2.0) * state.scale) as isize,
+            ),
+            Color::from_rgba(0, 0, 0, 192),
+        )?;
+
+        for (idx, line) in lines.iter().enumerate() {
+            state.font.builder().position(x, y + idx as f32 * line_height).scale(TEXT_SCALE).draw(
+                line,
+                ctx,
+                &state.constants,
+                &mut state.texture_set,
+            )?;
+        }
 
         Ok(())
     }
@@ -1647,7 +2101,7 @@ impl Scene for GameScene {
         if state.player_count == PlayerCount::Two {
             self.add_player2(state, ctx);
         } else {
-            self.drop_player2();
+            self.drop_player2(state);
         }
 
         if state.mod_path.is_some() {
@@ -1658,11 +2112,23 @@ impl Scene for GameScene {
 
         self.npc_list.set_rng_seed(state.game_rng.next());
         self.boss.init_rng(state.game_rng.next());
-        state.textscript_vm.set_scene_script(self.stage.load_text_script(
-            &state.constants.base_paths,
-            &state.constants,
-            ctx,
-        )?);
+        let (scene_script, diagnostics) = {
+            let scripts = state.textscript_vm.scripts.borrow();
+            self.stage.load_text_script(&state.constants.base_paths, &state.constants, &scripts.global_script, ctx)?
+        };
+
+        if state.settings.debug_mode {
+            if let Some(diagnostic) = diagnostics.first() {
+                let message = match diagnostics.len() {
+                    1 => diagnostic.to_string(),
+                    count => format!("{} (+{} more, see log)", diagnostic, count - 1),
+                };
+
+                self.debug_toast = Some((message, DEBUG_TOAST_TICKS));
+            }
+        }
+
+        state.textscript_vm.set_scene_script(scene_script);
         state.textscript_vm.suspend = false;
         state.tile_size = self.stage.map.tile_size;
         #[cfg(feature = "scripting-lua")]
@@ -1694,6 +2160,7 @@ impl Scene for GameScene {
         state.npc_table.stage_textures = self.stage_textures.clone();
 
         self.boss.boss_type = self.stage.data.boss_no as u16;
+        self.boss.reset_phases();
         self.player1.target_x = self.player1.x;
         self.player1.target_y = self.player1.y;
         self.player1.camera_target_x = 0;
@@ -1736,9 +2203,24 @@ impl Scene for GameScene {
             _ => LightingMode::None,
         };
 
+        let room_reverb = if state.settings.reverb_enabled { self.stage.data.room_reverb } else { 0.0 };
+        state.sound_manager.set_room_reverb(room_reverb);
+
         self.pause_menu.init(state, ctx)?;
         self.whimsical_star.init(&self.player1);
 
+        state.current_stage = self.stage_id;
+        state.stats.begin_stage(state.play_time);
+        if let Some(id) = state.stats.completion_listener.take() {
+            state.remove_flag_listener(id);
+        }
+        if let Some(&flag) = state.constants.completion_flags.get(&self.stage_id) {
+            if !state.get_flag(flag) {
+                let id = state.add_flag_listener(flag, true, |state| state.stats.completion_reached = true);
+                state.stats.completion_listener = Some(id);
+            }
+        }
+
         #[cfg(feature = "discord-rpc")]
         {
             if self.stage.data.map == state.stages[state.constants.game.intro_stage as usize].map {
@@ -1754,17 +2236,24 @@ impl Scene for GameScene {
     }
 
     fn tick(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
-        if !self.pause_menu.is_paused() {
+        if !self.pause_menu.is_paused() && !self.photo_mode.is_active() {
             if let ReplayState::Playback(_) = state.replay_state {
                 self.replay.tick(state, (ctx, &mut self.player1))?;
             }
         }
 
+        if let Some((_, ticks)) = &mut self.debug_toast {
+            *ticks = ticks.saturating_sub(1);
+        }
+        if matches!(self.debug_toast, Some((_, 0))) {
+            self.debug_toast = None;
+        }
+
         if state.player_count_modified_in_game {
             if state.player_count == PlayerCount::Two {
                 self.add_player2(state, ctx);
             } else {
-                self.drop_player2();
+                self.drop_player2(state);
             }
 
             state.player_count_modified_in_game = false;
@@ -1797,15 +2286,42 @@ impl Scene for GameScene {
             }
         }
 
+        if self.photo_mode.is_active() {
+            state.touch_controls.control_type = TouchControlType::None;
+            return self.photo_mode.tick(state, ctx, &mut self.frame, &*self.player1.controller);
+        }
+
         if self.player1.controller.trigger_menu_pause() {
             self.pause_menu.pause(state);
         }
 
         if self.pause_menu.is_paused() {
             self.pause_menu.tick(state, ctx)?;
+
+            if self.pause_menu.take_save_request() {
+                state.save_game(self, ctx)?;
+            }
+
             return Ok(());
         }
 
+        if let Some(summary) = self.summary.as_mut() {
+            summary.tick(state, ctx)?;
+
+            if summary.is_finished() {
+                self.summary = None;
+            }
+
+            return Ok(());
+        }
+
+        if PhotoMode::toggle_triggered(&*self.player1.controller) && self.can_enter_photo_mode(state) {
+            self.photo_mode.enter(state, &self.frame);
+            return Ok(());
+        }
+
+        self.tick_player2_join(state, ctx);
+
         if state.replay_state == ReplayState::Recording {
             self.replay.tick(state, (ctx, &mut self.player1))?;
         }
@@ -1818,6 +2334,7 @@ impl Scene for GameScene {
             | TextScriptExecutionState::WaitFade(_, _)
             | TextScriptExecutionState::Msg(_, _, _, _)
             | TextScriptExecutionState::MsgNewLine(_, _, _, _, _)
+            | TextScriptExecutionState::WaitPage(_, _, _, _)
             | TextScriptExecutionState::FallingIsland(_, _, _, _, _, _)
                 if !state.control_flags.control_enabled() =>
             {
@@ -1854,6 +2371,7 @@ impl Scene for GameScene {
         match state.textscript_vm.mode {
             ScriptMode::Map | ScriptMode::Debug => {
                 TextScriptVM::run(state, self, ctx)?;
+                self.falling_island.tick(state, ())?;
 
                 match state.textscript_vm.state {
                     TextScriptExecutionState::FallingIsland(_, _, _, _, _, _) => (),
@@ -1878,6 +2396,28 @@ impl Scene for GameScene {
             }
         }
 
+        state.drain_events();
+
+        if state.stats.completion_reached {
+            if let Some(id) = state.stats.completion_listener.take() {
+                state.remove_flag_listener(id);
+            }
+
+            let mut summary = SummaryScene::new(state, ctx, self.stage_id);
+            summary.init(state, ctx)?;
+            self.summary = Some(summary);
+        }
+
+        if let Some(song_id) = self.boss.pending_song.take() {
+            state.sound_manager.play_song_crossfade(
+                song_id as usize,
+                BOSS_PHASE_CROSSFADE_SECONDS,
+                &state.constants,
+                &state.settings,
+                ctx,
+            )?;
+        }
+
         if state.control_flags.credits_running() {
             self.skip_counter = 0;
             CreditScriptVM::run(state, ctx)?;
@@ -1887,6 +2427,8 @@ impl Scene for GameScene {
         self.flash.tick(state, ())?;
         self.text_boxes.tick(state, ())?;
 
+        state.play_time = state.play_time.saturating_add(1);
+
         #[cfg(feature = "scripting-lua")]
         state.lua.scene_tick();
 
@@ -1923,12 +2465,16 @@ impl Scene for GameScene {
         self.player1.damage_popup.prev_y = self.player1.damage_popup.y;
         self.player1.exp_popup.prev_x = self.player1.exp_popup.x;
         self.player1.exp_popup.prev_y = self.player1.exp_popup.y;
+        self.player1.heal_popup.prev_x = self.player1.heal_popup.x;
+        self.player1.heal_popup.prev_y = self.player1.heal_popup.y;
         self.player2.prev_x = self.player2.x;
         self.player2.prev_y = self.player2.y;
         self.player2.damage_popup.prev_x = self.player2.damage_popup.x;
         self.player2.damage_popup.prev_y = self.player2.damage_popup.y;
         self.player2.exp_popup.prev_x = self.player2.exp_popup.x;
         self.player2.exp_popup.prev_y = self.player2.exp_popup.y;
+        self.player2.heal_popup.prev_x = self.player2.heal_popup.x;
+        self.player2.heal_popup.prev_y = self.player2.heal_popup.y;
 
         for npc in self.npc_list.iter_alive() {
             npc.prev_x = npc.x;
@@ -1991,6 +2537,9 @@ impl Scene for GameScene {
         let stage_textures_ref = &*self.stage_textures.deref().borrow();
         self.background.draw(state, ctx, &self.frame, stage_textures_ref, &self.stage)?;
         self.tilemap.draw(state, ctx, &self.frame, TileLayer::Background, stage_textures_ref, &self.stage)?;
+        if state.settings.shader_effects {
+            self.tilemap.draw_heat_haze(state, ctx, &self.frame, stage_textures_ref, &self.stage)?;
+        }
         self.draw_npc_layer(state, ctx, NPCLayer::Background)?;
         self.tilemap.draw(state, ctx, &self.frame, TileLayer::Middleground, stage_textures_ref, &self.stage)?;
 
@@ -2016,8 +2565,10 @@ impl Scene for GameScene {
         self.draw_carets(state, ctx)?;
         self.player1.exp_popup.draw(state, ctx, &self.frame)?;
         self.player1.damage_popup.draw(state, ctx, &self.frame)?;
+        self.player1.heal_popup.draw(state, ctx, &self.frame)?;
         self.player2.exp_popup.draw(state, ctx, &self.frame)?;
         self.player2.damage_popup.draw(state, ctx, &self.frame)?;
+        self.player2.heal_popup.draw(state, ctx, &self.frame)?;
         self.draw_npc_popup(state, ctx)?;
         self.draw_boss_popup(state, ctx)?;
 
@@ -2028,6 +2579,7 @@ impl Scene for GameScene {
             self.draw_light_map(state, ctx)?;
         }
         self.flash.draw(state, ctx, &self.frame)?;
+        self.draw_poison_vignette(state, ctx)?;
 
         self.draw_black_bars(state, ctx)?;
 
@@ -2035,6 +2587,10 @@ impl Scene for GameScene {
             graphics::set_clip_rect(ctx, None)?;
         }
 
+        if self.photo_mode.is_active() {
+            return self.photo_mode.draw(state, ctx);
+        }
+
         if self.inventory_dim > 0.0 {
             let rect = Rect::new(0, 0, state.screen_size.0 as isize + 1, state.screen_size.1 as isize + 1);
             let mut dim_color = state.constants.inventory_dim_color;
@@ -2044,8 +2600,10 @@ impl Scene for GameScene {
 
         match state.textscript_vm.mode {
             ScriptMode::Map | ScriptMode::Debug if state.control_flags.control_enabled() => {
-                self.hud_player1.draw(state, ctx, &self.frame)?;
-                self.hud_player2.draw(state, ctx, &self.frame)?;
+                if state.settings.get_show_hud(state.save_slot) {
+                    self.hud_player1.draw(state, ctx, &self.frame)?;
+                    self.hud_player2.draw(state, ctx, &self.frame)?;
+                }
                 self.boss_life_bar.draw(state, ctx, &self.frame)?;
 
                 if self.player2.cond.alive() && !self.player2.cond.hidden() {
@@ -2288,8 +2846,22 @@ impl Scene for GameScene {
                 .draw(debug_name, ctx, &state.constants, &mut state.texture_set)?;
         }
 
+        if let Some((message, _)) = &self.debug_toast {
+            state
+                .font
+                .builder()
+                .position(4.0, state.canvas_size.1 - 16.0)
+                .shadow(true)
+                .color((255, 96, 96, 255))
+                .draw(message, ctx, &state.constants, &mut state.texture_set)?;
+        }
+
         self.replay.draw(state, ctx, &self.frame)?;
 
+        if let Some(summary) = &self.summary {
+            summary.draw(state, ctx)?;
+        }
+
         self.pause_menu.draw(state, ctx)?;
 
         //draw_number(state.canvas_size.0 - 8.0, 8.0, timer::fps(ctx) as usize, Alignment::Right, state, ctx)?;
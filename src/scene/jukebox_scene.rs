@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use itertools::Itertools;
 
 use crate::common::Color;
@@ -45,7 +47,9 @@ impl JukeboxScene {
                 background_color: Color { r: 0.0, g: 0.0, b: 0.0, a: 0.0 },
                 npc1: NpcType::new("0"),
                 npc2: NpcType::new("0"),
+                room_reverb: 0.0,
             },
+            tile_damage: HashMap::new(),
         };
 
         let mut textures = StageTexturePaths::new();
@@ -9,7 +9,9 @@ pub mod editor_scene;
 pub mod game_scene;
 pub mod jukebox_scene;
 pub mod loading_scene;
+pub mod name_entry_scene;
 pub mod no_data_scene;
+pub mod summary_scene;
 pub mod title_scene;
 
 /// Implement this trait on any object that represents an interactive game screen.
@@ -0,0 +1,183 @@
+use crate::common::Color;
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::graphics;
+use crate::game::shared_game_state::SharedGameState;
+use crate::graphics::font::Font;
+use crate::input::combined_menu_controller::CombinedMenuController;
+use crate::scene::Scene;
+
+/// Longest name the player can enter.
+pub const MAX_PLAYER_NAME_LEN: usize = 8;
+
+const KEYBOARD_CHARS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const KEYBOARD_COLUMNS: usize = 13;
+const KEY_WIDTH: f32 = 16.0;
+const KEY_HEIGHT: f32 = 16.0;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Key {
+    Char(char),
+    Delete,
+    Done,
+}
+
+/// Lets the player type in their name with an on-screen keyboard before switching to the next scene.
+pub struct NameEntryScene {
+    keys: Vec<Key>,
+    selected: usize,
+    name: String,
+    controller: CombinedMenuController,
+    next_scene: Option<Box<dyn Scene>>,
+}
+
+impl NameEntryScene {
+    pub fn new(next_scene: Box<dyn Scene>) -> Self {
+        let mut keys: Vec<Key> = KEYBOARD_CHARS.chars().map(Key::Char).collect();
+        keys.push(Key::Delete);
+        keys.push(Key::Done);
+
+        Self {
+            keys,
+            selected: 0,
+            name: String::new(),
+            controller: CombinedMenuController::new(),
+            next_scene: Some(next_scene),
+        }
+    }
+
+    fn key_label(state: &SharedGameState, key: Key) -> String {
+        match key {
+            Key::Char(chr) => chr.to_string(),
+            Key::Delete => state.loc.t("menus.name_entry.delete").to_owned(),
+            Key::Done => state.loc.t("menus.name_entry.done").to_owned(),
+        }
+    }
+
+    fn confirm(&mut self, state: &mut SharedGameState) {
+        if self.name.is_empty() {
+            return;
+        }
+
+        state.sound_manager.play_sfx(18);
+        state.player_name = self.name.clone();
+        state.next_scene = self.next_scene.take();
+    }
+}
+
+impl Scene for NameEntryScene {
+    fn init(&mut self, state: &mut SharedGameState, _ctx: &mut Context) -> GameResult {
+        self.controller.add(state.settings.create_player1_controller());
+        self.controller.add(state.settings.create_player2_controller());
+
+        self.name = state.player_name.clone();
+
+        Ok(())
+    }
+
+    fn tick(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        self.controller.update(state, ctx)?;
+        self.controller.update_trigger();
+
+        let count = self.keys.len();
+        let old_selected = self.selected;
+
+        if self.controller.trigger_left() {
+            self.selected = (self.selected + count - 1) % count;
+        } else if self.controller.trigger_right() {
+            self.selected = (self.selected + 1) % count;
+        } else if self.controller.trigger_up() {
+            self.selected = (self.selected + count - KEYBOARD_COLUMNS % count) % count;
+        } else if self.controller.trigger_down() {
+            self.selected = (self.selected + KEYBOARD_COLUMNS) % count;
+        }
+
+        if self.selected != old_selected {
+            state.sound_manager.play_sfx(1);
+        }
+
+        if self.controller.trigger_back() {
+            if self.name.pop().is_some() {
+                state.sound_manager.play_sfx(2);
+            }
+        } else if self.controller.trigger_ok() {
+            match self.keys[self.selected] {
+                Key::Char(chr) => {
+                    if self.name.chars().count() < MAX_PLAYER_NAME_LEN {
+                        state.sound_manager.play_sfx(2);
+                        self.name.push(chr);
+                    }
+                }
+                Key::Delete => {
+                    if self.name.pop().is_some() {
+                        state.sound_manager.play_sfx(2);
+                    }
+                }
+                Key::Done => self.confirm(state),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        graphics::clear(ctx, Color::from_rgb(0, 0, 32));
+
+        let keyboard_width = KEYBOARD_COLUMNS as f32 * KEY_WIDTH;
+        let rows = (self.keys.len() + KEYBOARD_COLUMNS - 1) / KEYBOARD_COLUMNS;
+        let left = ((state.canvas_size.0 - keyboard_width) / 2.0).floor();
+        let top = ((state.canvas_size.1 - rows as f32 * KEY_HEIGHT) / 2.0).floor() + 16.0;
+
+        state.font.builder().center(state.canvas_size.0).y(top - 56.0).draw(
+            state.loc.t("menus.name_entry.title"),
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+
+        let mut name_field = self.name.clone();
+        for _ in self.name.chars().count()..MAX_PLAYER_NAME_LEN {
+            name_field.push('_');
+        }
+
+        state.font.builder().center(state.canvas_size.0).y(top - 32.0).draw(
+            &name_field,
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+
+        let mut x = left;
+        let mut y = top;
+
+        for (idx, &key) in self.keys.iter().enumerate() {
+            let label = Self::key_label(state, key);
+            let color = if idx == self.selected { (255, 255, 0, 255) } else { (255, 255, 255, 255) };
+
+            let builder = state.font.builder();
+            let label_width = builder.compute_width(&label);
+
+            // special keys take up more space than a single character
+            let (offset, advance) = if let Key::Char(_) = key {
+                ((KEY_WIDTH - label_width) / 2.0, KEY_WIDTH)
+            } else {
+                (KEY_WIDTH / 2.0, label_width + KEY_WIDTH)
+            };
+
+            builder.position((x + offset).floor(), y).color(color).draw(
+                &label,
+                ctx,
+                &state.constants,
+                &mut state.texture_set,
+            )?;
+
+            x += advance;
+            if (idx + 1) % KEYBOARD_COLUMNS == 0 {
+                x = left;
+                y += KEY_HEIGHT;
+            }
+        }
+
+        Ok(())
+    }
+}
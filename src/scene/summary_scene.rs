@@ -0,0 +1,205 @@
+use crate::common::{Color, Rect};
+use crate::framework::context::Context;
+use crate::framework::error::GameResult;
+use crate::framework::graphics;
+use crate::game::shared_game_state::SharedGameState;
+use crate::graphics::font::Font;
+use crate::input::combined_menu_controller::CombinedMenuController;
+use crate::scene::Scene;
+
+/// How long the summary stays on screen, in seconds.
+const SUMMARY_DURATION: u32 = 5;
+/// Ticks after which the summary can be closed, so a held button doesn't close it right away.
+const SKIP_DELAY: u32 = 30;
+
+/// Statistics shown after completing a stage, see `EngineConstants::completion_flags`.
+pub struct SummaryScene {
+    map_name: String,
+    /// Time spent in the stage, in milliseconds.
+    time_ms: u64,
+    enemies_defeated: u32,
+    damage_taken: u32,
+    grade: char,
+    new_record: bool,
+    tick: u32,
+    finished: bool,
+    controller: CombinedMenuController,
+}
+
+impl SummaryScene {
+    /// Creates the summary for the current stage, recording its time if it's a new best.
+    pub fn new(state: &mut SharedGameState, ctx: &mut Context, stage_id: usize) -> SummaryScene {
+        let time_ms = state.stats.stage_time(state.play_time) * 1000 / Self::tick_rate(state);
+        let time_secs = time_ms / 1000;
+        let enemies_defeated = state.stats.enemies_defeated;
+        let damage_taken = state.stats.damage_taken;
+
+        state.stats.complete_stage();
+
+        let new_record = match state.settings.best_stage_times.get(&stage_id) {
+            Some(&best) => time_ms < best,
+            None => true,
+        };
+
+        if new_record {
+            state.settings.best_stage_times.insert(stage_id, time_ms);
+            let _ = state.settings.save(ctx);
+        }
+
+        SummaryScene {
+            map_name: state.stages.get(stage_id).map(|stage| stage.name.clone()).unwrap_or_default(),
+            time_ms,
+            enemies_defeated,
+            damage_taken,
+            grade: Self::compute_grade(time_secs, enemies_defeated, damage_taken),
+            new_record,
+            tick: 0,
+            finished: false,
+            controller: CombinedMenuController::new(),
+        }
+    }
+
+    fn tick_rate(state: &SharedGameState) -> u64 {
+        match state.settings.timing_mode.get_tps() {
+            0 => 50,
+            tps => tps as u64,
+        }
+    }
+
+    /// Grades the stage clear, from S (best) to D.
+    ///
+    /// Starts at 100 points, adds a point for every enemy defeated (up to 50), subtracts two for every
+    /// point of damage taken and one for every 10 seconds spent after the first two minutes.
+    pub fn compute_grade(time_secs: u64, enemies_defeated: u32, damage_taken: u32) -> char {
+        let score = 100 + enemies_defeated.min(50) as i64
+            - damage_taken as i64 * 2
+            - (time_secs.saturating_sub(120) / 10) as i64;
+
+        match score {
+            100..=i64::MAX => 'S',
+            85..=99 => 'A',
+            70..=84 => 'B',
+            50..=69 => 'C',
+            _ => 'D',
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn format_time(time_ms: u64) -> String {
+        format!("{}:{:02}.{:02}", time_ms / 60000, time_ms / 1000 % 60, time_ms / 10 % 100)
+    }
+
+    fn draw_row(label_key: &str, value: &str, y: f32, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        let center = (state.canvas_size.0 / 2.0).floor();
+        let value_width = state.font.builder().compute_width(value);
+
+        state.font.builder().position(center - 96.0, y).draw(
+            state.loc.t(label_key),
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+        state.font.builder().position(center + 96.0 - value_width, y).draw(
+            value,
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl Scene for SummaryScene {
+    fn init(&mut self, state: &mut SharedGameState, _ctx: &mut Context) -> GameResult {
+        self.controller.add(state.settings.create_player1_controller());
+        self.controller.add(state.settings.create_player2_controller());
+
+        state.sound_manager.play_sfx(18);
+
+        Ok(())
+    }
+
+    fn tick(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        self.controller.update(state, ctx)?;
+        self.controller.update_trigger();
+
+        self.tick += 1;
+
+        let skipped = self.tick > SKIP_DELAY && (self.controller.trigger_ok() || self.controller.trigger_back());
+        if skipped || self.tick as u64 >= SUMMARY_DURATION as u64 * Self::tick_rate(state) {
+            self.finished = true;
+        }
+
+        Ok(())
+    }
+
+    fn draw(&self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
+        graphics::draw_rect(
+            ctx,
+            Rect::new(0, 0, state.screen_size.0 as isize, state.screen_size.1 as isize),
+            Color::from_rgba(0, 0, 16, 192),
+        )?;
+
+        let top = (state.canvas_size.1 / 2.0).floor() - 64.0;
+
+        state.font.builder().center(state.canvas_size.0).y(top).draw(
+            state.loc.t("menus.summary.title"),
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+        state.font.builder().center(state.canvas_size.0).y(top + 16.0).color((160, 160, 176, 255)).draw(
+            &self.map_name,
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+
+        let time = Self::format_time(self.time_ms);
+        Self::draw_row("menus.summary.time", &time, top + 40.0, state, ctx)?;
+        Self::draw_row("menus.summary.enemies", &self.enemies_defeated.to_string(), top + 56.0, state, ctx)?;
+        Self::draw_row("menus.summary.damage", &self.damage_taken.to_string(), top + 72.0, state, ctx)?;
+
+        let center = (state.canvas_size.0 / 2.0).floor();
+        let grade = self.grade.to_string();
+        let grade_width = state.font.builder().compute_width(&grade) * 2.0;
+
+        state.font.builder().position(center - 96.0, top + 100.0).draw(
+            state.loc.t("menus.summary.grade"),
+            ctx,
+            &state.constants,
+            &mut state.texture_set,
+        )?;
+        state
+            .font
+            .builder()
+            .position(center + 96.0 - grade_width, top + 96.0)
+            .scale(2.0)
+            .color((255, 255, 0, 255))
+            .draw(&grade, ctx, &state.constants, &mut state.texture_set)?;
+
+        if self.new_record && (self.tick / 8) % 2 == 0 {
+            state.font.builder().center(state.canvas_size.0).y(top + 128.0).color((255, 255, 0, 255)).draw(
+                state.loc.t("menus.summary.new_record"),
+                ctx,
+                &state.constants,
+                &mut state.texture_set,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_compute_grade() {
+    assert_eq!(SummaryScene::compute_grade(60, 10, 0), 'S');
+    assert_eq!(SummaryScene::compute_grade(60, 0, 5), 'A');
+    assert_eq!(SummaryScene::compute_grade(420, 0, 0), 'B');
+    assert_eq!(SummaryScene::compute_grade(120, 0, 20), 'C');
+    assert_eq!(SummaryScene::compute_grade(600, 0, 30), 'D');
+}
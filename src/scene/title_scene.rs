@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crate::common::{Color, VERSION_BANNER};
 use crate::components::background::Background;
 use crate::components::nikumaru::NikumaruCounter;
@@ -88,6 +90,8 @@ pub struct TitleScene {
     background: Background,
     frame: Frame,
     nikumaru_rec: NikumaruCounter,
+    /// Ticks both weapon switch buttons were held for on the main menu, reveals the jukebox on non-Switch builds.
+    jukebox_unlock_counter: u16,
     stage: Stage,
     textures: StageTexturePaths,
 }
@@ -108,7 +112,9 @@ impl TitleScene {
                 background_color: Color { r: 0.0, g: 0.0, b: 0.0, a: 0.0 },
                 npc1: NpcType::new("0"),
                 npc2: NpcType::new("0"),
+                room_reverb: 0.0,
             },
+            tile_damage: HashMap::new(),
         };
         let mut textures = StageTexturePaths::new();
         textures.update(&fake_stage);
@@ -129,6 +135,7 @@ impl TitleScene {
             background: Background::new(),
             frame: Frame::new(),
             nikumaru_rec: NikumaruCounter::new(),
+            jukebox_unlock_counter: 0,
             stage: fake_stage,
             textures,
         }
@@ -186,6 +193,9 @@ static COPYRIGHT_PIXEL: &str = "2004.12  Studio Pixel";
 // Freeware
 static COPYRIGHT_NICALIS: &str = "@2022 NICALIS INC."; // Nicalis font uses @ for copyright
 
+/// How long both weapon switch buttons need to be held on the main menu to reveal the jukebox.
+const JUKEBOX_UNLOCK_TICKS: u16 = 50;
+
 impl Scene for TitleScene {
     fn init(&mut self, state: &mut SharedGameState, ctx: &mut Context) -> GameResult {
         if !state.mod_path.is_none() {
@@ -193,6 +203,8 @@ impl Scene for TitleScene {
             state.reload_resources(ctx)?;
         }
 
+        state.sound_manager.set_room_reverb(0.0);
+
         self.controller.add(state.settings.create_player1_controller());
         self.controller.add(state.settings.create_player2_controller());
 
@@ -219,6 +231,8 @@ impl Scene for TitleScene {
                 MainMenuEntry::Jukebox,
                 MenuEntry::Active(state.loc.t("menus.main_menu.jukebox").to_owned()),
             );
+        } else {
+            self.main_menu.push_entry(MainMenuEntry::Jukebox, MenuEntry::Hidden);
         }
 
         self.main_menu
@@ -301,6 +315,22 @@ impl Scene for TitleScene {
         self.challenges_menu.y =
             ((state.canvas_size.1 + 30.0 - self.challenges_menu.height as f32) / 2.0).floor() as isize;
 
+        if self.current_menu == CurrentMenu::MainMenu && self.jukebox_unlock_counter < JUKEBOX_UNLOCK_TICKS {
+            if self.controller.shift_left() && self.controller.shift_right() {
+                self.jukebox_unlock_counter += 1;
+
+                if self.jukebox_unlock_counter == JUKEBOX_UNLOCK_TICKS {
+                    state.sound_manager.play_sfx(18);
+                    self.main_menu.set_entry(
+                        MainMenuEntry::Jukebox,
+                        MenuEntry::Active(state.loc.t("menus.main_menu.jukebox").to_owned()),
+                    );
+                }
+            } else {
+                self.jukebox_unlock_counter = 0;
+            }
+        }
+
         match self.current_menu {
             CurrentMenu::MainMenu => match self.main_menu.tick(&mut self.controller, state) {
                 MenuSelectionResult::Selected(MainMenuEntry::Start, _) => {
@@ -21,6 +21,7 @@ use crate::sound::ogg_playback::{OggPlaybackEngine, SavedOggPlaybackState};
 use crate::sound::org_playback::{OrgPlaybackEngine, SavedOrganyaPlaybackState};
 use crate::sound::organya::Song;
 use crate::sound::pixtone::{PixToneParameters, PixTonePlayback};
+use crate::sound::reverb::Reverb;
 use crate::sound::wave_bank::SoundBank;
 
 mod fir;
@@ -30,6 +31,7 @@ mod org_playback;
 mod organya;
 pub mod pixtone;
 mod pixtone_sfx;
+mod reverb;
 mod stuff;
 mod wav;
 mod wave_bank;
@@ -42,6 +44,8 @@ pub struct SoundManager {
     no_audio: bool,
     load_failed: bool,
     stream: Option<cpal::Stream>,
+    /// Stereo position of sounds played with `play_sfx`, from -1.0 (left) to 1.0 (right).
+    sfx_pan: f32,
 }
 
 enum SongFormat {
@@ -76,6 +80,7 @@ impl SoundManager {
                 no_audio: true,
                 load_failed: false,
                 stream: None,
+                sfx_pan: 0.0,
             });
         }
 
@@ -96,6 +101,7 @@ impl SoundManager {
             no_audio: false,
             load_failed: false,
             stream: None,
+            sfx_pan: 0.0,
         };
 
         let host = cpal::default_host();
@@ -190,7 +196,29 @@ impl SoundManager {
             return;
         }
 
-        self.send(PlaybackMessage::PlaySample(id)).unwrap();
+        self.send(PlaybackMessage::PlaySample(id, self.sfx_pan)).unwrap();
+    }
+
+    /// Plays a sound panned by the horizontal distance of its source from the listener,
+    /// reaching either side at `range` (in the same units). Sounds are centered if `range` is 0.
+    pub fn play_sfx_spatial(&mut self, id: u8, source_x: i32, listener_x: i32, range: i32) {
+        let prev_pan = std::mem::replace(&mut self.sfx_pan, Self::pan(source_x, listener_x, range));
+        self.play_sfx(id);
+        self.sfx_pan = prev_pan;
+    }
+
+    /// Sets stereo position of sounds played with `play_sfx` until it's changed again, see `play_sfx_spatial`.
+    pub fn set_sfx_pan(&mut self, pan: f32) {
+        self.sfx_pan = pan.clamp(-1.0, 1.0);
+    }
+
+    /// Returns stereo position of a sound played `source_x - listener_x` units away, see `play_sfx_spatial`.
+    pub fn pan(source_x: i32, listener_x: i32, range: i32) -> f32 {
+        if range <= 0 {
+            return 0.0;
+        }
+
+        ((source_x as f32 - listener_x as f32) / range as f32).clamp(-1.0, 1.0)
     }
 
     pub fn loop_sfx(&self, id: u8) {
@@ -236,6 +264,14 @@ impl SoundManager {
         self.send(PlaybackMessage::SetSampleVolume(volume.powf(3.0))).unwrap();
     }
 
+    /// Sets dry/wet mix of the reverb applied to sound effects, 0.0 disables it.
+    pub fn set_room_reverb(&mut self, mix: f32) {
+        if self.no_audio {
+            return;
+        }
+        self.send(PlaybackMessage::SetReverb(mix)).unwrap();
+    }
+
     pub fn set_sfx_samples(&mut self, id: u8, data: Vec<i16>) {
         if self.no_audio {
             return;
@@ -407,6 +443,40 @@ impl SoundManager {
         Ok(())
     }
 
+    /// Fades out current song over the first half of given amount of seconds and fades in the new one over
+    /// the other half.
+    pub fn play_song_crossfade(
+        &mut self,
+        song_id: usize,
+        seconds: f32,
+        constants: &EngineConstants,
+        settings: &Settings,
+        ctx: &mut Context,
+    ) -> GameResult {
+        if self.current_song_id == song_id || self.no_audio {
+            return Ok(());
+        }
+
+        self.send(PlaybackMessage::Crossfade(seconds)).unwrap();
+        self.play_song(song_id, constants, settings, ctx)
+    }
+
+    /// Fades out current song over given amount of seconds and stops it,
+    /// the song can be resumed from where the fade started with [SoundManager::restore_state].
+    pub fn fade_song(&mut self, seconds: f32) -> GameResult {
+        if self.current_song_id == 0 || self.no_audio {
+            return Ok(());
+        }
+
+        self.prev_song_id = self.current_song_id;
+        self.current_song_id = 0;
+
+        self.send(PlaybackMessage::SaveState).unwrap();
+        self.send(PlaybackMessage::FadeOut(seconds)).unwrap();
+
+        Ok(())
+    }
+
     pub fn restore_state(&mut self) -> GameResult {
         if self.no_audio {
             return Ok(());
@@ -544,12 +614,14 @@ impl SoundManager {
 
 pub(in crate::sound) enum PlaybackMessage {
     Stop,
+    FadeOut(f32),
+    Crossfade(f32),
     PlayOrganyaSong(Box<Song>),
     #[cfg(feature = "ogg-playback")]
     PlayOggSongSinglePart(Box<OggStreamReader<File>>),
     #[cfg(feature = "ogg-playback")]
     PlayOggSongMultiPart(Box<OggStreamReader<File>>, Box<OggStreamReader<File>>),
-    PlaySample(u8),
+    PlaySample(u8, f32),
     LoopSample(u8),
     LoopSampleFreq(u8, f32),
     StopSample(u8),
@@ -561,6 +633,19 @@ pub(in crate::sound) enum PlaybackMessage {
     SetSampleParams(u8, PixToneParameters),
     SetOrgInterpolation(InterpolationMode),
     SetSampleData(u8, Vec<i16>),
+    SetReverb(f32),
+}
+
+impl PlaybackMessage {
+    /// Returns true if the message changes the song, these get delayed until a crossfade fades out.
+    fn changes_song(&self) -> bool {
+        match self {
+            PlaybackMessage::Stop | PlaybackMessage::PlayOrganyaSong(_) => true,
+            #[cfg(feature = "ogg-playback")]
+            PlaybackMessage::PlayOggSongSinglePart(_) | PlaybackMessage::PlayOggSongMultiPart(..) => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(PartialEq, Eq)]
@@ -603,6 +688,7 @@ fn run<T>(
     let mut ogg_engine = Box::new(OggPlaybackEngine::new());
     let mut pixtone = Box::new(PixTonePlayback::new());
     pixtone.create_samples();
+    let mut reverb = Box::new(Reverb::new(sample_rate));
 
     log::info!("Audio format: {} {}", sample_rate, channels);
     org_engine.set_sample_rate(sample_rate as usize);
@@ -614,12 +700,22 @@ fn run<T>(
 
     let buf_size = sample_rate as usize * 10 / 1000;
     let mut bgm_buf = vec![0x8080; buf_size * 2];
-    let mut pxt_buf = vec![0x8000; buf_size];
+    let mut pxt_buf = vec![0x8000; buf_size * 2];
     let mut bgm_index = 0;
     let mut pxt_index = 0;
     let mut samples = 0;
     let mut bgm_vol = 1.0_f32;
     let mut sfx_vol = 1.0_f32;
+    let mut fade_samples = 0;
+    let mut fade_remaining = 0;
+    // faded out songs are resumed from where the fade started instead of being rewound
+    let mut faded_out = false;
+    // length of the fade in of the next song, set when crossfading
+    let mut crossfade_samples = 0;
+    let mut fade_in_samples = 0;
+    let mut fade_in_remaining = 0;
+    // song change waiting for the crossfade to fade out current song
+    let mut delayed_message: Option<PlaybackMessage> = None;
     pixtone.mix(&mut pxt_buf, sample_rate);
 
     let err_fn = |err| eprintln!("an error occurred on stream: {}", err);
@@ -628,8 +724,24 @@ fn run<T>(
         &config,
         move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
             loop {
-                match rx.try_recv() {
+                let message = match delayed_message.take() {
+                    Some(message) if fade_remaining == 0 => Ok(message),
+                    message => {
+                        delayed_message = message;
+                        rx.try_recv()
+                    }
+                };
+
+                match message {
+                    Ok(message) if message.changes_song() && crossfade_samples != 0 && fade_remaining > 0 => {
+                        delayed_message = Some(message);
+                    }
                     Ok(PlaybackMessage::PlayOrganyaSong(song)) => {
+                        fade_remaining = 0;
+                        faded_out = false;
+                        fade_in_samples = std::mem::take(&mut crossfade_samples);
+                        fade_in_remaining = fade_in_samples;
+
                         if state == PlaybackState::Stopped {
                             saved_state = PlaybackStateType::None;
                         }
@@ -646,6 +758,11 @@ fn run<T>(
                     }
                     #[cfg(feature = "ogg-playback")]
                     Ok(PlaybackMessage::PlayOggSongSinglePart(data)) => {
+                        fade_remaining = 0;
+                        faded_out = false;
+                        fade_in_samples = std::mem::take(&mut crossfade_samples);
+                        fade_in_remaining = fade_in_samples;
+
                         if state == PlaybackState::Stopped {
                             saved_state = PlaybackStateType::None;
                         }
@@ -662,6 +779,11 @@ fn run<T>(
                     }
                     #[cfg(feature = "ogg-playback")]
                     Ok(PlaybackMessage::PlayOggSongMultiPart(data_intro, data_loop)) => {
+                        fade_remaining = 0;
+                        faded_out = false;
+                        fade_in_samples = std::mem::take(&mut crossfade_samples);
+                        fade_in_remaining = fade_in_samples;
+
                         if state == PlaybackState::Stopped {
                             saved_state = PlaybackStateType::None;
                         }
@@ -676,8 +798,8 @@ fn run<T>(
 
                         state = PlaybackState::PlayingOgg;
                     }
-                    Ok(PlaybackMessage::PlaySample(id)) => {
-                        pixtone.play_sfx(id);
+                    Ok(PlaybackMessage::PlaySample(id, pan)) => {
+                        pixtone.play_sfx_panned(id, pan);
                     }
 
                     Ok(PlaybackMessage::LoopSample(id)) => {
@@ -690,12 +812,29 @@ fn run<T>(
                         pixtone.stop_sfx(id);
                     }
                     Ok(PlaybackMessage::Stop) => {
+                        fade_remaining = 0;
+                        faded_out = false;
+                        crossfade_samples = 0;
+                        fade_in_remaining = 0;
+
                         if state == PlaybackState::Stopped {
                             saved_state = PlaybackStateType::None;
                         }
 
                         state = PlaybackState::Stopped;
                     }
+                    Ok(PlaybackMessage::FadeOut(seconds)) => {
+                        fade_samples = ((sample_rate * seconds) as usize).max(1);
+                        fade_remaining = fade_samples;
+                    }
+                    Ok(PlaybackMessage::Crossfade(seconds)) => {
+                        crossfade_samples = ((sample_rate * seconds / 2.0) as usize).max(1);
+
+                        if state != PlaybackState::Stopped {
+                            fade_samples = crossfade_samples;
+                            fade_remaining = fade_samples;
+                        }
+                    }
                     Ok(PlaybackMessage::SetSpeed(new_speed)) => {
                         assert!(new_speed > 0.0);
                         speed = new_speed;
@@ -720,6 +859,10 @@ fn run<T>(
                         };
                     }
                     Ok(PlaybackMessage::RestoreState) => {
+                        let rewind = state == PlaybackState::Stopped && !faded_out;
+                        fade_remaining = 0;
+                        faded_out = false;
+
                         let saved_state_loc = std::mem::take(&mut saved_state);
 
                         match saved_state_loc {
@@ -729,7 +872,7 @@ fn run<T>(
                             PlaybackStateType::Organya(playback_state) => {
                                 org_engine.set_state(playback_state, &bank);
 
-                                if state == PlaybackState::Stopped {
+                                if rewind {
                                     org_engine.rewind();
                                 }
 
@@ -745,7 +888,7 @@ fn run<T>(
                             PlaybackStateType::Ogg(playback_state) => {
                                 ogg_engine.set_state(playback_state);
 
-                                if state == PlaybackState::Stopped {
+                                if rewind {
                                     ogg_engine.rewind();
                                 }
 
@@ -768,6 +911,9 @@ fn run<T>(
                     Ok(PlaybackMessage::SetSampleData(id, data)) => {
                         pixtone.set_sample_data(id, data);
                     }
+                    Ok(PlaybackMessage::SetReverb(mix)) => {
+                        reverb.set_mix(mix);
+                    }
                     Err(_) => {
                         break;
                     }
@@ -802,10 +948,29 @@ fn run<T>(
                     }
                 };
 
-                let pxt_sample: u16 = pxt_buf[pxt_index];
+                let (pxt_sample_l, pxt_sample_r) = reverb.process(
+                    ((pxt_buf[pxt_index] ^ 0x8000) as i16) as f32,
+                    ((pxt_buf[pxt_index + 1] ^ 0x8000) as i16) as f32,
+                );
+
+                let bgm_vol = if fade_remaining > 0 {
+                    fade_remaining -= 1;
+                    if fade_remaining == 0 {
+                        state = PlaybackState::Stopped;
+                        faded_out = true;
+                    }
+
+                    bgm_vol * fade_remaining as f32 / fade_samples as f32
+                } else if fade_in_remaining > 0 {
+                    fade_in_remaining -= 1;
+
+                    bgm_vol * (fade_in_samples - fade_in_remaining) as f32 / fade_in_samples as f32
+                } else {
+                    bgm_vol
+                };
 
-                if pxt_index < (pxt_buf.len() - 1) {
-                    pxt_index += 1;
+                if pxt_index < (pxt_buf.len() - 2) {
+                    pxt_index += 2;
                 } else {
                     pxt_index = 0;
                     pxt_buf.fill(0x8000);
@@ -815,14 +980,14 @@ fn run<T>(
                 if frame.len() >= 2 {
                     let sample_l = clamp(
                         (((bgm_sample_l ^ 0x8000) as i16) as f32 * bgm_vol) as isize
-                            + (((pxt_sample ^ 0x8000) as i16) as f32 * sfx_vol) as isize,
+                            + (pxt_sample_l * sfx_vol) as isize,
                         -0x7fff,
                         0x7fff,
                     ) as u16
                         ^ 0x8000;
                     let sample_r = clamp(
                         (((bgm_sample_r ^ 0x8000) as i16) as f32 * bgm_vol) as isize
-                            + (((pxt_sample ^ 0x8000) as i16) as f32 * sfx_vol) as isize,
+                            + (pxt_sample_r * sfx_vol) as isize,
                         -0x7fff,
                         0x7fff,
                     ) as u16
@@ -834,7 +999,7 @@ fn run<T>(
                     let sample = clamp(
                         ((((bgm_sample_l ^ 0x8000) as i16) + ((bgm_sample_r ^ 0x8000) as i16)) as f32 * bgm_vol / 2.0)
                             as isize
-                            + (((pxt_sample ^ 0x8000) as i16) as f32 * sfx_vol) as isize,
+                            + ((pxt_sample_l + pxt_sample_r) * sfx_vol / 2.0) as isize,
                         -0x7fff,
                         0x7fff,
                     ) as u16
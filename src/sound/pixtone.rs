@@ -183,6 +183,8 @@ pub struct PlaybackState {
     pos: f32,
     tag: u32,
     freq: f32,
+    /// Stereo position, from -1.0 (left) to 1.0 (right).
+    pan: f32,
 }
 
 pub struct PixTonePlayback {
@@ -219,15 +221,20 @@ impl PixTonePlayback {
     }
 
     pub fn play_sfx(&mut self, id: u8) {
+        self.play_sfx_panned(id, 0.0);
+    }
+
+    pub fn play_sfx_panned(&mut self, id: u8, pan: f32) {
         for state in &mut self.playback_state {
             if state.id == id && state.tag == 0 {
                 state.pos = 0.0;
                 state.looping = false;
+                state.pan = pan;
                 return;
             }
         }
 
-        self.playback_state.push(PlaybackState { id, pos: 0.0, tag: 0, looping: false, freq: 1.0 });
+        self.playback_state.push(PlaybackState { id, pos: 0.0, tag: 0, looping: false, freq: 1.0, pan });
     }
 
     pub fn loop_sfx(&mut self, id: u8) {
@@ -238,7 +245,7 @@ impl PixTonePlayback {
             }
         }
 
-        self.playback_state.push(PlaybackState { id, pos: 0.0, tag: 0, looping: true, freq: 1.0 });
+        self.playback_state.push(PlaybackState { id, pos: 0.0, tag: 0, looping: true, freq: 1.0, pan: 0.0 });
     }
 
     pub fn loop_sfx_freq(&mut self, id: u8, freq: f32) {
@@ -249,7 +256,7 @@ impl PixTonePlayback {
             }
         }
 
-        self.playback_state.push(PlaybackState { id, pos: 0.0, tag: 0, looping: true, freq });
+        self.playback_state.push(PlaybackState { id, pos: 0.0, tag: 0, looping: true, freq, pan: 0.0 });
     }
 
     pub fn stop_sfx(&mut self, id: u8) {
@@ -259,9 +266,10 @@ impl PixTonePlayback {
     }
 
     pub fn play_concurrent(&mut self, id: u8, tag: u32) {
-        self.playback_state.push(PlaybackState { id, pos: 0.0, tag, looping: false, freq: 1.0 });
+        self.playback_state.push(PlaybackState { id, pos: 0.0, tag, looping: false, freq: 1.0, pan: 0.0 });
     }
 
+    /// Mixes playing samples into an interleaved stereo buffer.
     pub fn mix(&mut self, dst: &mut [u16], sample_rate: f32) {
         let mut scan = VecMutScan::new(&mut self.playback_state);
         let delta = 22050.0 / sample_rate;
@@ -276,7 +284,11 @@ impl PixTonePlayback {
                     continue;
                 };
 
-                for result in dst.iter_mut() {
+                // centered sounds keep full volume on both channels
+                let gain_l = (1.0 - state.pan).min(1.0);
+                let gain_r = (1.0 + state.pan).min(1.0);
+
+                for frame in dst.chunks_exact_mut(2) {
                     if state.pos >= sample.len() as f32 {
                         if state.looping {
                             state.pos = 0.0;
@@ -294,8 +306,10 @@ impl PixTonePlayback {
 
                     let s = cubic_interp(s1, s2, s4, s3, state.pos.fract()) * 32768.0;
                     // let s = sample[pos] as f32;
-                    let sam = (*result ^ 0x8000) as i16;
-                    *result = sam.saturating_add(s as i16) as u16 ^ 0x8000;
+                    for (result, gain) in frame.iter_mut().zip([gain_l, gain_r]) {
+                        let sam = (*result ^ 0x8000) as i16;
+                        *result = sam.saturating_add((s * gain) as i16) as u16 ^ 0x8000;
+                    }
 
                     state.pos += delta * state.freq;
                 }
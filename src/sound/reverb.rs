@@ -0,0 +1,109 @@
+/// Comb filter delays in milliseconds, chosen so their echoes don't line up with each other.
+const COMB_DELAYS: [f32; 4] = [29.7, 37.1, 41.1, 43.7];
+const COMB_FEEDBACK: [f32; 4] = [0.742, 0.733, 0.715, 0.697];
+const ALLPASS_DELAYS: [f32; 2] = [5.0, 1.7];
+const ALLPASS_GAIN: f32 = 0.7;
+
+struct DelayLine {
+    buf: Vec<f32>,
+    pos: usize,
+}
+
+impl DelayLine {
+    fn new(delay_ms: f32, sample_rate: f32) -> DelayLine {
+        let len = ((delay_ms * sample_rate / 1000.0) as usize).max(1);
+
+        DelayLine { buf: vec![0.0; len], pos: 0 }
+    }
+
+    #[inline]
+    fn read(&self) -> f32 {
+        self.buf[self.pos]
+    }
+
+    #[inline]
+    fn write(&mut self, value: f32) {
+        self.buf[self.pos] = value;
+        self.pos = (self.pos + 1) % self.buf.len();
+    }
+
+    fn clear(&mut self) {
+        self.buf.fill(0.0);
+        self.pos = 0;
+    }
+}
+
+/// Schroeder reverberator made of 4 parallel feedback comb filters followed by 2 all-pass filters.
+pub(in crate::sound) struct Reverb {
+    combs: Vec<(DelayLine, f32)>,
+    allpasses: Vec<DelayLine>,
+    /// Dry/wet mix, 0.0 passes the input through untouched.
+    mix: f32,
+}
+
+impl Reverb {
+    pub fn new(sample_rate: f32) -> Reverb {
+        Reverb {
+            combs: COMB_DELAYS
+                .iter()
+                .zip(COMB_FEEDBACK.iter())
+                .map(|(&delay, &feedback)| (DelayLine::new(delay, sample_rate), feedback))
+                .collect(),
+            allpasses: ALLPASS_DELAYS.iter().map(|&delay| DelayLine::new(delay, sample_rate)).collect(),
+            mix: 0.0,
+        }
+    }
+
+    /// Sets the dry/wet mix, dropping the tail left over from the previous room.
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+
+        for (line, _) in &mut self.combs {
+            line.clear();
+        }
+        for line in &mut self.allpasses {
+            line.clear();
+        }
+    }
+
+    /// Processes a single stereo sample, the reverb itself is mono and shared by both channels.
+    pub fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        if self.mix <= 0.0 {
+            return (left, right);
+        }
+
+        let input = (left + right) * 0.5;
+        let mut wet = 0.0;
+
+        for (line, feedback) in &mut self.combs {
+            let delayed = line.read();
+            line.write(input + delayed * *feedback);
+            wet += delayed;
+        }
+        wet /= self.combs.len() as f32;
+
+        for line in &mut self.allpasses {
+            let delayed = line.read();
+            let value = wet + delayed * ALLPASS_GAIN;
+            line.write(value);
+            wet = delayed - value * ALLPASS_GAIN;
+        }
+
+        (left + (wet - left) * self.mix, right + (wet - right) * self.mix)
+    }
+}
+
+#[test]
+fn test_reverb() {
+    let mut reverb = Reverb::new(1000.0);
+    assert_eq!(reverb.process(1.0, -1.0), (1.0, -1.0));
+
+    reverb.set_mix(0.5);
+    let (dry, _) = reverb.process(1.0, 1.0);
+    assert_eq!(dry, 0.5);
+
+    // the impulse comes back once it passes through the shortest comb filter
+    let tail: Vec<f32> = (0..60).map(|_| reverb.process(0.0, 0.0).0).collect();
+    assert!(tail[..28].iter().all(|&s| s == 0.0));
+    assert!(tail.iter().any(|&s| s.abs() > 0.01));
+}